@@ -140,22 +140,25 @@ pub async fn wallet_recovery(
             Ok(UtxoScannerEvent::Progress {
                 current_height,
                 tip_height,
+                outputs_recovered,
             }) => {
                 let percentage_progress = ((current_height as f32) * 100f32 / (tip_height as f32)).round() as u32;
                 debug!(
                     target: LOG_TARGET,
-                    "{}: Recovery process {}% complete (Block {} of {}).",
+                    "{}: Recovery process {}% complete (Block {} of {}, {} outputs recovered).",
                     Local::now(),
                     percentage_progress,
                     current_height,
-                    tip_height
+                    tip_height,
+                    outputs_recovered
                 );
                 println!(
-                    "{}: Recovery process {}% complete (Block {} of {}).",
+                    "{}: Recovery process {}% complete (Block {} of {}, {} outputs recovered).",
                     Local::now(),
                     percentage_progress,
                     current_height,
-                    tip_height
+                    tip_height,
+                    outputs_recovered
                 );
             },
             Ok(UtxoScannerEvent::ScanningRoundFailed {
@@ -119,7 +119,7 @@ where B: BlockchainBackend + 'static
                 self.blockchain_db.get_chain_metadata().await?,
             )),
             NodeCommsRequest::FetchHeaders(range) => {
-                let headers = self.blockchain_db.fetch_chain_headers(range).await?;
+                let headers = self.blockchain_db.fetch_chain_headers_within_limit(range).await?;
                 Ok(NodeCommsResponse::BlockHeaders(headers))
             },
             NodeCommsRequest::FetchHeadersByHashes(block_hashes) => {
@@ -274,11 +274,23 @@ impl<B: BlockchainBackend + 'static> BaseNodeSyncService for BaseNodeSyncRpcServ
             .start_hash
             .try_into()
             .map_err(|_| RpcStatus::bad_request(&"Malformed starting hash received".to_string()))?;
-        let start_header = db
+        let start_header = match db
             .fetch_header_by_block_hash(hash)
             .await
             .rpc_status_internal_error(LOG_TARGET)?
-            .ok_or_else(|| RpcStatus::not_found("Header not found with given hash"))?;
+        {
+            Some(header) => header,
+            None => {
+                let pruned_height = db
+                    .fetch_block_hash_index_pruned_height()
+                    .await
+                    .rpc_status_internal_error(LOG_TARGET)?;
+                if pruned_height > 0 {
+                    return Err(block_hash_index_pruned_status(pruned_height));
+                }
+                return Err(RpcStatus::not_found("Header not found with given hash"));
+            },
+        };
 
         let mut count = message.count;
         if count == 0 {
@@ -414,6 +426,28 @@ impl<B: BlockchainBackend + 'static> BaseNodeSyncService for BaseNodeSyncRpcServ
             .find_headers_after_hash(hashes, message.header_count)
             .await
             .rpc_status_internal_error(LOG_TARGET)?;
+        // Without the block hash index below the pruned height, the peer's hashes can't be resolved there and the
+        // remaining headers can't be streamed from a split below it. Tell the peer so instead of reporting that its
+        // chain does not link to ours.
+        let pruned_height = db
+            .fetch_block_hash_index_pruned_height()
+            .await
+            .rpc_status_internal_error(LOG_TARGET)?;
+        if pruned_height > 0 {
+            let split_height = match &maybe_headers {
+                Some((_, headers)) => headers.first().map(|h| h.height.saturating_sub(1)),
+                None => Some(0),
+            };
+            if split_height.map_or(false, |height| height < pruned_height) {
+                debug!(
+                    target: LOG_TARGET,
+                    "Unable to find a chain split for peer `{}` above the pruned block hash index height {}",
+                    peer,
+                    pruned_height
+                );
+                return Err(block_hash_index_pruned_status(pruned_height));
+            }
+        }
         match maybe_headers {
             Some((idx, headers)) => {
                 debug!(
@@ -599,3 +633,12 @@ impl<B: BlockchainBackend + 'static> BaseNodeSyncService for BaseNodeSyncRpcServ
         Ok(Streaming::new(rx))
     }
 }
+
+/// The status returned to peers whose sync requests can't be served because the block hashes they reference were pruned
+/// from the block hash index. Header sync treats it as an unusable sync peer rather than a peer on a different chain.
+fn block_hash_index_pruned_status(pruned_height: u64) -> RpcStatus {
+    RpcStatus::not_implemented(&format!(
+        "This node has pruned its block hash index below height {} and cannot serve header sync below it",
+        pruned_height
+    ))
+}
@@ -20,7 +20,10 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::{collections::HashMap, sync::Arc};
+
 use futures::StreamExt;
+use tari_common::configuration::Network;
 use tari_comms::protocol::rpc::{mock::RpcRequestMock, RpcStatusCode};
 use tari_service_framework::reply_channel;
 use tari_test_utils::{streams::convert_mpsc_to_stream, unpack_enum};
@@ -30,12 +33,20 @@ use tokio::sync::broadcast;
 use super::BaseNodeSyncRpcService;
 use crate::{
     base_node::{BaseNodeSyncService, LocalNodeCommsInterface},
-    chain_storage::BlockchainDatabase,
-    proto::base_node::{SyncBlocksRequest, SyncUtxosRequest},
+    blocks::{genesis_block::get_genesis_block, ChainBlock},
+    chain_storage::{BlockchainDatabase, BlockchainDatabaseConfig, Validators},
+    consensus::{ConsensusConstantsBuilder, ConsensusManager},
+    proto::base_node::{FindChainSplitRequest, SyncBlocksRequest, SyncHeadersRequest, SyncUtxosRequest},
     test_helpers::{
-        blockchain::{create_main_chain, create_new_blockchain, TempDatabase},
+        blockchain::{
+            create_main_chain,
+            create_new_blockchain,
+            create_store_with_consensus_and_validators_and_config,
+            TempDatabase,
+        },
         create_peer_manager,
     },
+    validation::mocks::MockValidator,
 };
 
 fn setup() -> (
@@ -43,12 +54,45 @@ fn setup() -> (
     BlockchainDatabase<TempDatabase>,
     RpcRequestMock,
     TempDir,
+) {
+    setup_with_db(create_new_blockchain())
+}
+
+fn setup_with_config(
+    config: BlockchainDatabaseConfig,
+) -> (
+    BaseNodeSyncRpcService<TempDatabase>,
+    BlockchainDatabase<TempDatabase>,
+    RpcRequestMock,
+    TempDir,
+) {
+    let network = Network::LocalNet;
+    let rules = ConsensusManager::builder(network)
+        .add_consensus_constants(ConsensusConstantsBuilder::new(network).build())
+        .with_block(get_genesis_block(network))
+        .build();
+    let validators = Validators::new(
+        MockValidator::new(true),
+        MockValidator::new(true),
+        MockValidator::new(true),
+    );
+    setup_with_db(create_store_with_consensus_and_validators_and_config(
+        rules, validators, config,
+    ))
+}
+
+fn setup_with_db(
+    db: BlockchainDatabase<TempDatabase>,
+) -> (
+    BaseNodeSyncRpcService<TempDatabase>,
+    BlockchainDatabase<TempDatabase>,
+    RpcRequestMock,
+    TempDir,
 ) {
     let tmp = tempdir().unwrap();
     let peer_manager = create_peer_manager(&tmp);
     let request_mock = RpcRequestMock::new(peer_manager);
 
-    let db = create_new_blockchain();
     let (req_tx, _) = reply_channel::unbounded();
     let (block_tx, _) = reply_channel::unbounded();
     let (block_event_tx, _) = broadcast::channel(1);
@@ -175,3 +219,85 @@ mod sync_utxos {
         assert!(utxo_indexes.iter().all(|index| (start..=start + 2).contains(index)));
     }
 }
+
+mod pruned_block_hash_index {
+    use super::*;
+
+    fn setup_pruned() -> (
+        BaseNodeSyncRpcService<TempDatabase>,
+        HashMap<String, Arc<ChainBlock>>,
+        RpcRequestMock,
+        TempDir,
+    ) {
+        let (service, db, rpc_request_mock, tmp) = setup_with_config(BlockchainDatabaseConfig {
+            prune_block_hash_index: true,
+            ..Default::default()
+        });
+        let (_, chain) = create_main_chain(&db, block_specs!(["A->GB"], ["B->A"], ["C->B"], ["D->C"], ["E->D"]));
+        db.prune_to_height(3).unwrap();
+        (service, chain, rpc_request_mock, tmp)
+    }
+
+    #[tokio::test]
+    async fn it_reports_a_chain_split_below_the_pruned_height() {
+        let (service, chain, rpc_request_mock, _tmp) = setup_pruned();
+        let msg = FindChainSplitRequest {
+            block_hashes: vec![chain.get("A").unwrap().hash().to_vec()],
+            header_count: 10,
+        };
+        let req = rpc_request_mock.request_with_context(Default::default(), msg);
+        let err = service.find_chain_split(req).await.unwrap_err();
+        // Not `NotFound`, which the syncing peer treats as a peer on another chain
+        unpack_enum!(RpcStatusCode::NotImplemented = err.as_status_code());
+
+        // Splitting from the genesis block is below the pruned height as well
+        let msg = FindChainSplitRequest {
+            block_hashes: vec![chain.get("GB").unwrap().hash().to_vec()],
+            header_count: 10,
+        };
+        let req = rpc_request_mock.request_with_context(Default::default(), msg);
+        let err = service.find_chain_split(req).await.unwrap_err();
+        unpack_enum!(RpcStatusCode::NotImplemented = err.as_status_code());
+    }
+
+    #[tokio::test]
+    async fn it_finds_a_chain_split_from_the_pruned_height() {
+        let (service, chain, rpc_request_mock, _tmp) = setup_pruned();
+        let msg = FindChainSplitRequest {
+            block_hashes: vec![
+                chain.get("C").unwrap().hash().to_vec(),
+                chain.get("B").unwrap().hash().to_vec(),
+            ],
+            header_count: 10,
+        };
+        let req = rpc_request_mock.request_with_context(Default::default(), msg);
+        let resp = service.find_chain_split(req).await.unwrap().into_message();
+        assert_eq!(resp.fork_hash_index, 0);
+        assert_eq!(resp.headers.len(), 2);
+        assert_eq!(resp.headers[0].height, 4);
+    }
+
+    #[tokio::test]
+    async fn it_reports_a_header_sync_from_below_the_pruned_height() {
+        let (service, chain, rpc_request_mock, _tmp) = setup_pruned();
+        let msg = SyncHeadersRequest {
+            start_hash: chain.get("B").unwrap().hash().to_vec(),
+            count: 0,
+        };
+        let req = rpc_request_mock.request_with_context(Default::default(), msg);
+        let err = service.sync_headers(req).await.unwrap_err();
+        unpack_enum!(RpcStatusCode::NotImplemented = err.as_status_code());
+
+        let msg = SyncHeadersRequest {
+            start_hash: chain.get("C").unwrap().hash().to_vec(),
+            count: 0,
+        };
+        let req = rpc_request_mock.request_with_context(Default::default(), msg);
+        let mut streaming = service.sync_headers(req).await.unwrap().into_inner();
+        let headers = convert_mpsc_to_stream(&mut streaming)
+            .map(|header| header.unwrap().height)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(headers, vec![4, 5]);
+    }
+}
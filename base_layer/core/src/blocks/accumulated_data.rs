@@ -22,9 +22,12 @@
 use std::{
     fmt,
     fmt::{Display, Formatter},
+    io,
+    io::Write,
     sync::Arc,
 };
 
+use borsh::{BorshDeserialize, BorshSerialize};
 use croaring::Bitmap;
 use log::*;
 use num_format::{Locale, ToFormattedString};
@@ -436,6 +439,28 @@ impl ChainHeader {
     }
 }
 
+// The accumulated data does not implement borsh, so it is encoded with bincode, the same encoding used to store it in
+// LMDB
+impl BorshSerialize for ChainHeader {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        BorshSerialize::serialize(&self.header, writer)?;
+        let accumulated_data = bincode::serialize(&self.accumulated_data)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        BorshSerialize::serialize(&accumulated_data, writer)
+    }
+}
+
+impl BorshDeserialize for ChainHeader {
+    fn deserialize(buf: &mut &[u8]) -> io::Result<Self> {
+        let header = BlockHeader::deserialize(buf)?;
+        let accumulated_data: Vec<u8> = BorshDeserialize::deserialize(buf)?;
+        let accumulated_data = bincode::deserialize(&accumulated_data)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        Self::try_construct(header, accumulated_data)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Header does not match its accumulated data"))
+    }
+}
+
 /// A block linked to a chain.
 /// A ChainBlock MUST have the same or stronger guarantees than `ChainHeader`
 #[derive(Debug, Clone, PartialEq)]
@@ -26,6 +26,7 @@ use log::*;
 use rand::{rngs::OsRng, RngCore};
 use tari_common_types::{
     chain_metadata::ChainMetadata,
+    epoch::VnEpoch,
     types::{BlockHash, Commitment, HashOutput, PublicKey, Signature},
 };
 use tari_utilities::epoch_time::EpochTime;
@@ -165,11 +166,15 @@ impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
 
     make_async_fn!(fetch_outputs_in_block(hash: HashOutput) -> Vec<PrunedOutput>, "fetch_outputs_in_block");
 
+    make_async_fn!(fetch_outputs_created_between(from_height: u64, to_height: u64) -> Vec<(u64, HashOutput)>, "fetch_outputs_created_between");
+
     make_async_fn!(utxo_count() -> usize, "utxo_count");
 
     //---------------------------------- Kernel --------------------------------------------//
     make_async_fn!(fetch_kernel_by_excess_sig(excess_sig: Signature) -> Option<(TransactionKernel, HashOutput)>, "fetch_kernel_by_excess_sig");
 
+    make_async_fn!(fetch_kernel_by_excess(excess: Commitment) -> Option<(TransactionKernel, HashOutput)>, "fetch_kernel_by_excess");
+
     make_async_fn!(fetch_kernels_in_block(hash: HashOutput) -> Vec<TransactionKernel>, "fetch_kernels_in_block");
 
     //---------------------------------- MMR --------------------------------------------//
@@ -177,6 +182,12 @@ impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
 
     make_async_fn!(fetch_mmr_size(tree: MmrTree) -> u64, "fetch_mmr_size");
 
+    make_async_fn!(fetch_mmr_size_at_height(tree: MmrTree, height: u64) -> u64, "fetch_mmr_size_at_height");
+
+    make_async_fn!(fetch_kernel_mmr_root_at_height(height: u64) -> HashOutput, "fetch_kernel_mmr_root_at_height");
+
+    make_async_fn!(fetch_utxo_set_root_at_height(height: u64) -> HashOutput, "fetch_utxo_set_root_at_height");
+
     make_async_fn!(calculate_mmr_roots(block: Block) -> (Block, MmrRoots), "calculate_mmr_roots");
 
     //---------------------------------- Headers --------------------------------------------//
@@ -186,12 +197,20 @@ impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
 
     make_async_fn!(fetch_chain_headers<T: RangeBounds<u64>>(bounds: T) -> Vec<ChainHeader>, "fetch_chain_headers");
 
+    make_async_fn!(fetch_chain_headers_within_limit<T: RangeBounds<u64>>(bounds: T) -> Vec<ChainHeader>, "fetch_chain_headers_within_limit");
+
     make_async_fn!(fetch_header_accumulated_data(hash: HashOutput) -> Option<BlockHeaderAccumulatedData>, "fetch_header_accumulated_data");
 
+    make_async_fn!(fetch_header_accumulated_data_by_hashes(hashes: Vec<HashOutput>) -> Vec<Option<BlockHeaderAccumulatedData>>, "fetch_header_accumulated_data_by_hashes");
+
+    make_async_fn!(fetch_accumulated_data_range(start: u64, end_inclusive: u64) -> Vec<BlockHeaderAccumulatedData>, "fetch_accumulated_data_range");
+
     make_async_fn!(fetch_headers<T: RangeBounds<u64>>(bounds: T) -> Vec<BlockHeader>, "fetch_headers");
 
     make_async_fn!(fetch_header_by_block_hash(hash: HashOutput) -> Option<BlockHeader>, "fetch_header_by_block_hash");
 
+    make_async_fn!(fetch_block_hash_index_pruned_height() -> u64, "fetch_block_hash_index_pruned_height");
+
     make_async_fn!(fetch_header_containing_kernel_mmr(mmr_position: u64) -> ChainHeader, "fetch_header_containing_kernel_mmr");
 
     make_async_fn!(fetch_header_containing_utxo_mmr(mmr_position: u64) -> ChainHeader, "fetch_header_containing_utxo_mmr");
@@ -204,6 +223,8 @@ impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
         "find_headers_after_hash"
     );
 
+    make_async_fn!(fetch_headers_since_hash(known_hash: HashOutput, max: usize) -> (Vec<BlockHeader>, bool), "fetch_headers_since_hash");
+
     make_async_fn!(fetch_last_header() -> BlockHeader, "fetch_last_header");
 
     make_async_fn!(clear_all_pending_headers() -> usize, "clear_all_pending_headers");
@@ -225,12 +246,20 @@ impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
 
     make_async_fn!(bad_block_exists(block_hash: BlockHash) -> bool, "bad_block_exists");
 
+    make_async_fn!(fetch_all_bad_blocks() -> Vec<(HashOutput, u64)>, "fetch_all_bad_blocks");
+
     make_async_fn!(fetch_block(height: u64, compact: bool) -> HistoricalBlock, "fetch_block");
 
     make_async_fn!(fetch_blocks<T: RangeBounds<u64>>(bounds: T, compact: bool) -> Vec<HistoricalBlock>, "fetch_blocks");
 
+    make_async_fn!(fetch_blocks_in_range(start: u64, end_inclusive: u64) -> Vec<ChainBlock>, "fetch_blocks_in_range");
+
     make_async_fn!(fetch_orphan(hash: HashOutput) -> Block, "fetch_orphan");
 
+    make_async_fn!(fetch_orphan_chain_depth(tip_hash: HashOutput) -> u64, "fetch_orphan_chain_depth");
+
+    make_async_fn!(fetch_orphan_insertion_time(hash: HashOutput) -> Option<u64>, "fetch_orphan_insertion_time");
+
     make_async_fn!(fetch_block_by_hash(hash: HashOutput, compact: bool) -> Option<HistoricalBlock>, "fetch_block_by_hash");
 
     make_async_fn!(fetch_block_with_kernel(excess_sig: Signature) -> Option<HistoricalBlock>, "fetch_block_with_kernel");
@@ -269,6 +298,8 @@ impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
 
     make_async_fn!(fetch_active_validator_nodes(height: u64) -> Vec<(PublicKey, [u8;32])>, "fetch_active_validator_nodes");
 
+    make_async_fn!(fetch_active_validator_nodes_at_epoch(epoch: VnEpoch) -> Vec<(PublicKey, [u8;32])>, "fetch_active_validator_nodes_at_epoch");
+
     make_async_fn!(get_shard_key(height:u64, public_key: PublicKey) -> Option<[u8;32]>, "get_shard_key");
 
     make_async_fn!(fetch_template_registrations<T: RangeBounds<u64>>(range: T) -> Vec<TemplateRegistrationEntry>, "fetch_template_registrations");
@@ -4,6 +4,7 @@
 use croaring::Bitmap;
 use tari_common_types::{
     chain_metadata::ChainMetadata,
+    epoch::VnEpoch,
     types::{Commitment, HashOutput, PublicKey, Signature},
 };
 
@@ -66,6 +67,21 @@ pub trait BlockchainBackend: Send + Sync {
         hash: &HashOutput,
     ) -> Result<Option<BlockHeaderAccumulatedData>, ChainStorageError>;
 
+    /// Fetches the accumulated data of the main chain headers from `start` up to and including `end_inclusive` in a
+    /// single read transaction. The result stops at the first height that does not exist.
+    fn fetch_header_accumulated_data_range(
+        &self,
+        start: u64,
+        end_inclusive: u64,
+    ) -> Result<Vec<BlockHeaderAccumulatedData>, ChainStorageError>;
+
+    /// Fetches the accumulated data for each of the given header hashes in a single read transaction. The result is
+    /// index-aligned with `hashes`, with `None` for hashes that are not in the main chain.
+    fn fetch_header_accumulated_data_by_hashes(
+        &self,
+        hashes: &[HashOutput],
+    ) -> Result<Vec<Option<BlockHeaderAccumulatedData>>, ChainStorageError>;
+
     fn fetch_chain_header_in_all_chains(&self, hash: &HashOutput) -> Result<ChainHeader, ChainStorageError>;
 
     fn fetch_header_containing_kernel_mmr(&self, mmr_position: u64) -> Result<ChainHeader, ChainStorageError>;
@@ -97,6 +113,13 @@ pub trait BlockchainBackend: Send + Sync {
         excess_sig: &Signature,
     ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError>;
 
+    /// Fetch a kernel with this excess commitment and returns a `TransactionKernel` and the hash of the block that it
+    /// is in
+    fn fetch_kernel_by_excess(
+        &self,
+        excess: &Commitment,
+    ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError>;
+
     /// Fetch all UTXOs and spends in the block
     fn fetch_utxos_in_block(
         &self,
@@ -114,6 +137,9 @@ pub trait BlockchainBackend: Send + Sync {
         commitment: &Commitment,
     ) -> Result<Option<HashOutput>, ChainStorageError>;
 
+    /// Returns true if an output with the given commitment exists in the current UTXO set
+    fn is_commitment_unspent(&self, commitment: &Commitment) -> Result<bool, ChainStorageError>;
+
     /// Fetch all outputs in a block
     fn fetch_outputs_in_block(&self, header_hash: &HashOutput) -> Result<Vec<PrunedOutput>, ChainStorageError>;
 
@@ -123,6 +149,9 @@ pub trait BlockchainBackend: Send + Sync {
     /// Fetches the total merkle mountain range node count upto the specified height.
     fn fetch_mmr_size(&self, tree: MmrTree) -> Result<u64, ChainStorageError>;
 
+    /// Calculates the kernel MMR root at the given height from the kernel hash set in the block accumulated data
+    fn fetch_kernel_mmr_root_at_height(&self, height: u64) -> Result<HashOutput, ChainStorageError>;
+
     /// Fetches the leaf index of the provided leaf node hash in the given MMR tree.
     #[allow(clippy::ptr_arg)]
     fn fetch_mmr_leaf_index(&self, tree: MmrTree, hash: &HashOutput) -> Result<Option<u32>, ChainStorageError>;
@@ -152,9 +181,16 @@ pub trait BlockchainBackend: Send + Sync {
     fn fetch_all_orphan_chain_tips(&self) -> Result<Vec<ChainHeader>, ChainStorageError>;
     /// Fetch all orphans that have `hash` as a previous hash
     fn fetch_orphan_children_of(&self, hash: HashOutput) -> Result<Vec<Block>, ChainStorageError>;
+    /// Returns the time at which the orphan was inserted, in milliseconds since the Unix epoch, or None if the orphan
+    /// is not found or was stored before insertion times were recorded
+    fn fetch_orphan_insertion_time(&self, hash: &HashOutput) -> Result<Option<u64>, ChainStorageError>;
 
     fn fetch_orphan_chain_block(&self, hash: HashOutput) -> Result<Option<ChainBlock>, ChainStorageError>;
 
+    /// Returns the number of connected orphans leading up to and including `tip_hash`, walking back until a main chain
+    /// block or a missing parent is reached. Returns 0 if `tip_hash` is not an orphan.
+    fn fetch_orphan_chain_depth(&self, tip_hash: &HashOutput) -> Result<u64, ChainStorageError>;
+
     /// Returns the full deleted bitmap at the current blockchain tip
     fn fetch_deleted_bitmap(&self) -> Result<DeletedBitmap, ChainStorageError>;
 
@@ -186,12 +222,21 @@ pub trait BlockchainBackend: Send + Sync {
     /// Check if a block hash is in the bad block list
     fn bad_block_exists(&self, block_hash: HashOutput) -> Result<bool, ChainStorageError>;
 
+    /// Fetches all block hashes in the bad block list along with the height they were recorded at, ordered by height
+    fn fetch_all_bad_blocks(&self) -> Result<Vec<(HashOutput, u64)>, ChainStorageError>;
+
     /// Fetches all tracked reorgs
     fn fetch_all_reorgs(&self) -> Result<Vec<Reorg>, ChainStorageError>;
 
     /// Fetches the validator node set for the given height ordered according to height of registration and canonical
     /// block body ordering.
     fn fetch_active_validator_nodes(&self, height: u64) -> Result<Vec<(PublicKey, [u8; 32])>, ChainStorageError>;
+    /// Fetches the validator node set that is active in the given epoch. This is the same set returned by
+    /// `fetch_active_validator_nodes` for any height within the epoch.
+    fn fetch_active_validator_nodes_at_epoch(
+        &self,
+        epoch: VnEpoch,
+    ) -> Result<Vec<(PublicKey, [u8; 32])>, ChainStorageError>;
     /// Returns the shard key for the validator node if valid at the given height.
     fn get_shard_key(&self, height: u64, public_key: PublicKey) -> Result<Option<[u8; 32]>, ChainStorageError>;
     /// Returns all template registrations within (inclusive) the given height range.
@@ -36,10 +36,12 @@ use log::*;
 use serde::{Deserialize, Serialize};
 use tari_common_types::{
     chain_metadata::ChainMetadata,
+    epoch::VnEpoch,
     types::{BlockHash, Commitment, FixedHash, HashOutput, PublicKey, Signature},
 };
 use tari_mmr::pruned_hashset::PrunedHashSet;
 use tari_utilities::{epoch_time::EpochTime, hex::Hex, ByteArray};
+use tokio::sync::broadcast;
 
 use super::TemplateRegistrationEntry;
 use crate::{
@@ -58,6 +60,9 @@ use crate::{
     },
     chain_storage::{
         consts::{
+            BLOCKCHAIN_DATABASE_MAX_BLOCK_RANGE,
+            BLOCKCHAIN_DATABASE_MAX_HEADER_BATCH,
+            BLOCKCHAIN_DATABASE_MAX_HEADER_RANGE,
             BLOCKCHAIN_DATABASE_ORPHAN_STORAGE_CAPACITY,
             BLOCKCHAIN_DATABASE_PRUNED_MODE_PRUNING_INTERVAL,
             BLOCKCHAIN_DATABASE_PRUNING_HORIZON,
@@ -74,8 +79,12 @@ use crate::{
         MmrTree,
         Optional,
         OrNotFound,
+        OutputLifecycle,
+        OutputLifecycleEvent,
         Reorg,
+        ReorgEvent,
         TargetDifficulties,
+        TransactionGraph,
     },
     common::rolling_vec::RollingVec,
     consensus::{
@@ -104,16 +113,52 @@ use crate::{
 };
 
 const LOG_TARGET: &str = "c::cs::database";
+const REORG_EVENT_CHANNEL_SIZE: usize = 20;
+const MAX_HEADER_FETCH_ATTEMPTS: usize = 3;
 
 /// Configuration for the BlockchainDatabase.
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct BlockchainDatabaseConfig {
     pub orphan_storage_capacity: usize,
     pub pruning_horizon: u64,
     pub pruning_interval: u64,
     pub track_reorgs: bool,
     pub cleanup_orphans_at_startup: bool,
+    /// The maximum number of headers fetched from the backend under a single read lock. Larger header range requests
+    /// are split into batches of this size.
+    #[serde(default = "default_max_header_batch")]
+    pub max_header_batch: usize,
+    /// The maximum number of headers that a peer may request in a single header range request. Local callers such as
+    /// the base node commands are not limited.
+    #[serde(default = "default_max_header_range")]
+    pub max_header_range: usize,
+    /// The maximum number of blocks that may be requested in a single block range or created outputs range request.
+    #[serde(default = "default_max_block_range")]
+    pub max_block_range: usize,
+    /// If true, pruning also drops the output key from the hash to index entries of pruned outputs, reclaiming index
+    /// space. The output hash and MMR position are kept, so pruned outputs are still rejected as duplicates and can
+    /// still be looked up by hash.
+    #[serde(default)]
+    pub prune_txo_index: bool,
+    /// If true, pruning also removes the block hash to height mapping of blocks below the pruned height, except for
+    /// the genesis block. Those blocks can then no longer be looked up by hash, so the node can no longer serve header
+    /// sync to peers whose chain splits from it below the pruned height and tells them so.
+    #[serde(default)]
+    pub prune_block_hash_index: bool,
+}
+
+fn default_max_header_batch() -> usize {
+    BLOCKCHAIN_DATABASE_MAX_HEADER_BATCH
+}
+
+fn default_max_header_range() -> usize {
+    BLOCKCHAIN_DATABASE_MAX_HEADER_RANGE
+}
+
+fn default_max_block_range() -> usize {
+    BLOCKCHAIN_DATABASE_MAX_BLOCK_RANGE
 }
 
 impl Default for BlockchainDatabaseConfig {
@@ -124,6 +169,11 @@ impl Default for BlockchainDatabaseConfig {
             pruning_interval: BLOCKCHAIN_DATABASE_PRUNED_MODE_PRUNING_INTERVAL,
             track_reorgs: false,
             cleanup_orphans_at_startup: false,
+            max_header_batch: BLOCKCHAIN_DATABASE_MAX_HEADER_BATCH,
+            max_header_range: BLOCKCHAIN_DATABASE_MAX_HEADER_RANGE,
+            max_block_range: BLOCKCHAIN_DATABASE_MAX_BLOCK_RANGE,
+            prune_txo_index: false,
+            prune_block_hash_index: false,
         }
     }
 }
@@ -206,6 +256,9 @@ pub struct BlockchainDatabase<B> {
     consensus_manager: ConsensusManager,
     difficulty_calculator: Arc<DifficultyCalculator>,
     disable_add_block_flag: Arc<AtomicBool>,
+    reorg_event_publisher: broadcast::Sender<ReorgEvent>,
+    /// The best block hash and the UTXO set hash computed for it
+    utxo_set_hash_cache: Arc<RwLock<Option<(HashOutput, HashOutput)>>>,
 }
 
 #[allow(clippy::ptr_arg)]
@@ -229,6 +282,8 @@ where B: BlockchainBackend
             consensus_manager,
             difficulty_calculator: Arc::new(difficulty_calculator),
             disable_add_block_flag: Arc::new(AtomicBool::new(false)),
+            reorg_event_publisher: broadcast::channel(REORG_EVENT_CHANNEL_SIZE).0,
+            utxo_set_hash_cache: Arc::new(RwLock::new(None)),
         };
         let genesis_block = Arc::new(blockchain_db.consensus_manager.get_genesis_block());
         if is_empty {
@@ -292,6 +347,20 @@ where B: BlockchainBackend
         Ok(blockchain_db)
     }
 
+    /// Subscribe to the reorgs of the main chain caused by blocks added to this database
+    pub fn reorg_event_stream(&self) -> broadcast::Receiver<ReorgEvent> {
+        self.reorg_event_publisher.subscribe()
+    }
+
+    fn publish_reorg_event(&self, block_add_result: &BlockAddResult) {
+        if let BlockAddResult::ChainReorg { added, removed } = block_add_result {
+            if let Some(event) = ReorgEvent::from_reorged_blocks(added, removed) {
+                // An error only means that there are no subscribers
+                let _result = self.reorg_event_publisher.send(event);
+            }
+        }
+    }
+
     /// Returns a reference to the consensus cosntants at the current height
     pub fn consensus_constants(&self) -> Result<&ConsensusConstants, ChainStorageError> {
         let height = self.get_height()?;
@@ -374,6 +443,34 @@ where B: BlockchainBackend
         db.fetch_chain_metadata()
     }
 
+    /// Returns a hash committing to the current UTXO set, i.e. the output MMR root of the best block. The hash is
+    /// cached and only recomputed once the best block changes.
+    pub fn current_utxo_set_hash(&self) -> Result<HashOutput, ChainStorageError> {
+        let db = self.db_read_access()?;
+        let best_block = *db.fetch_chain_metadata()?.best_block();
+        let cached = *self
+            .utxo_set_hash_cache
+            .read()
+            .map_err(|_| ChainStorageError::AccessError("Read lock on UTXO set hash cache failed".into()))?;
+        if let Some((cached_block, utxo_set_hash)) = cached {
+            if cached_block == best_block {
+                return Ok(utxo_set_hash);
+            }
+        }
+
+        let header = fetch_header_by_block_hash(&*db, best_block)?.ok_or_else(|| ChainStorageError::ValueNotFound {
+            entity: "BlockHeader",
+            field: "hash",
+            value: best_block.to_hex(),
+        })?;
+        *self
+            .utxo_set_hash_cache
+            .write()
+            .map_err(|_| ChainStorageError::AccessError("Write lock on UTXO set hash cache failed".into()))? =
+            Some((best_block, header.output_mr));
+        Ok(header.output_mr)
+    }
+
     // Fetch the utxo
     pub fn fetch_utxo(&self, hash: HashOutput) -> Result<Option<PrunedOutput>, ChainStorageError> {
         let db = self.db_read_access()?;
@@ -388,6 +485,12 @@ where B: BlockchainBackend
         db.fetch_unspent_output_hash_by_commitment(commitment)
     }
 
+    /// Returns true if an output with the given commitment is in the current UTXO set
+    pub fn is_commitment_unspent(&self, commitment: &Commitment) -> Result<bool, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.is_commitment_unspent(commitment)
+    }
+
     /// Return a list of matching utxos, with each being `None` if not found. If found, the transaction
     /// output, and a boolean indicating if the UTXO was spent as of the current tip.
     pub fn fetch_utxos(&self, hashes: Vec<HashOutput>) -> Result<Vec<Option<(PrunedOutput, bool)>>, ChainStorageError> {
@@ -417,6 +520,13 @@ where B: BlockchainBackend
         Ok(result)
     }
 
+    /// Returns the heights and blocks at which the given output was created and, if it has been spent, spent. This
+    /// works for pruned outputs too, as long as they are still indexed.
+    pub fn fetch_output_lifecycle(&self, output_hash: &HashOutput) -> Result<OutputLifecycle, ChainStorageError> {
+        let db = self.db_read_access()?;
+        fetch_output_lifecycle(&*db, output_hash)
+    }
+
     pub fn fetch_kernel_by_excess_sig(
         &self,
         excess_sig: Signature,
@@ -425,11 +535,36 @@ where B: BlockchainBackend
         db.fetch_kernel_by_excess_sig(&excess_sig)
     }
 
+    pub fn fetch_kernel_by_excess(
+        &self,
+        excess: Commitment,
+    ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_kernel_by_excess(&excess)
+    }
+
     pub fn fetch_kernels_in_block(&self, hash: HashOutput) -> Result<Vec<TransactionKernel>, ChainStorageError> {
         let db = self.db_read_access()?;
         db.fetch_kernels_in_block(&hash)
     }
 
+    /// Returns the sum of the kernel excesses of the block with the given header hash
+    pub fn fetch_kernel_excess_sum_in_block(&self, header_hash: &HashOutput) -> Result<Commitment, ChainStorageError> {
+        let db = self.db_read_access()?;
+        if fetch_header_by_block_hash(&*db, *header_hash)?.is_none() {
+            return Err(ChainStorageError::ValueNotFound {
+                entity: "BlockHeader",
+                field: "hash",
+                value: header_hash.to_hex(),
+            });
+        }
+        Ok(db
+            .fetch_kernels_in_block(header_hash)?
+            .iter()
+            .map(|k| &k.excess)
+            .sum::<Commitment>())
+    }
+
     pub fn fetch_utxos_in_block(
         &self,
         hash: HashOutput,
@@ -444,6 +579,45 @@ where B: BlockchainBackend
         db.fetch_outputs_in_block(&hash)
     }
 
+    /// Returns the hashes of all outputs created in the blocks from `from_height` to `to_height` (inclusive), each
+    /// paired with the height of the block that created it. Requests spanning more than `max_block_range` blocks are
+    /// rejected.
+    pub fn fetch_outputs_created_between(
+        &self,
+        from_height: u64,
+        to_height: u64,
+    ) -> Result<Vec<(u64, HashOutput)>, ChainStorageError> {
+        if from_height > to_height {
+            return Ok(Vec::new());
+        }
+        let requested = (to_height - from_height).saturating_add(1);
+        let max = self.config.max_block_range as u64;
+        if requested > max {
+            return Err(ChainStorageError::RangeTooLarge { requested, max });
+        }
+
+        let db = self.db_read_access()?;
+        let tip_height = db.fetch_chain_metadata()?.height_of_longest_chain();
+        if to_height > tip_height {
+            return Err(ChainStorageError::ValueNotFound {
+                entity: "Block",
+                field: "to_height",
+                value: to_height.to_string(),
+            });
+        }
+
+        let mut outputs = Vec::new();
+        for height in from_height..=to_height {
+            let header = db.fetch_chain_header_by_height(height)?;
+            outputs.extend(
+                db.fetch_outputs_in_block(header.hash())?
+                    .into_iter()
+                    .map(|output| (height, output.hash())),
+            );
+        }
+        Ok(outputs)
+    }
+
     /// Returns the number of UTXOs in the current unspent set
     pub fn utxo_count(&self) -> Result<usize, ChainStorageError> {
         let db = self.db_read_access()?;
@@ -522,6 +696,34 @@ where B: BlockchainBackend
         Ok(None)
     }
 
+    /// Returns up to `max` main chain headers that follow the header with the given hash, along with a flag that is
+    /// `true` if the hash was found in the main chain. An empty set of headers with the flag set means that the hash is
+    /// the current tip, whereas an unset flag means that the hash is unknown or not part of the main chain. At most
+    /// `max_header_range` headers are returned, regardless of `max`.
+    pub fn fetch_headers_since_hash(
+        &self,
+        known_hash: HashOutput,
+        max: usize,
+    ) -> Result<(Vec<BlockHeader>, bool), ChainStorageError> {
+        let db = self.db_read_access()?;
+        let header = match fetch_header_by_block_hash(&*db, known_hash)? {
+            Some(header) => header,
+            None => return Ok((Vec::new(), false)),
+        };
+        if max == 0 {
+            return Ok((Vec::new(), true));
+        }
+
+        let tip_height = db.fetch_last_header()?.height;
+        if header.height >= tip_height {
+            return Ok((Vec::new(), true));
+        }
+        let max = max.min(self.config.max_header_range) as u64;
+        let end_height = header.height.saturating_add(max).min(tip_height);
+        let headers = fetch_headers(&*db, header.height + 1, end_height)?;
+        Ok((headers, true))
+    }
+
     pub fn fetch_block_timestamps(&self, start_hash: HashOutput) -> Result<RollingVec<EpochTime>, ChainStorageError> {
         let start_header =
             self.fetch_header_by_block_hash(start_hash)?
@@ -554,6 +756,37 @@ where B: BlockchainBackend
         db.fetch_header_accumulated_data(&hash)
     }
 
+    /// Returns the accumulated data for each of the given header hashes, read in a single transaction. The result is
+    /// index-aligned with `hashes`, with `None` for hashes that are not in the main chain.
+    pub fn fetch_header_accumulated_data_by_hashes(
+        &self,
+        hashes: Vec<HashOutput>,
+    ) -> Result<Vec<Option<BlockHeaderAccumulatedData>>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_header_accumulated_data_by_hashes(&hashes)
+    }
+
+    /// Returns the accumulated data of the main chain headers from `start` up to and including `end_inclusive`, read in
+    /// a single transaction so that the result is a contiguous chain. The result ends early if the range extends past
+    /// the tip. Requests spanning more than `max_header_range` headers are rejected.
+    pub fn fetch_accumulated_data_range(
+        &self,
+        start: u64,
+        end_inclusive: u64,
+    ) -> Result<Vec<BlockHeaderAccumulatedData>, ChainStorageError> {
+        if start > end_inclusive {
+            return Ok(Vec::new());
+        }
+        let requested = (end_inclusive - start).saturating_add(1);
+        let max = self.config.max_header_range as u64;
+        if requested > max {
+            return Err(ChainStorageError::RangeTooLarge { requested, max });
+        }
+
+        let db = self.db_read_access()?;
+        db.fetch_header_accumulated_data_range(start, end_inclusive)
+    }
+
     /// Store the provided headers. This function does not do any validation and assumes the inserted header has already
     /// been validated.
     pub fn insert_valid_headers(&self, headers: Vec<ChainHeader>) -> Result<(), ChainStorageError> {
@@ -578,17 +811,84 @@ where B: BlockchainBackend
         fetch_headers(&*db, start, end)
     }
 
-    /// Returns the set of block headers between `start` and up to and including `end_inclusive`
+    /// Returns the set of block headers between `start` and up to and including `end_inclusive`. Headers are fetched
+    /// in batches of `max_header_batch` so that the read lock is not held for the entire request. If the chain
+    /// reorgs between batches, the fetch is restarted so that the returned headers always form a contiguous chain.
     pub fn fetch_chain_headers<T: RangeBounds<u64>>(&self, bounds: T) -> Result<Vec<ChainHeader>, ChainStorageError> {
-        let db = self.db_read_access()?;
+        let (start, end) = self.resolve_header_bounds(bounds)?;
+        for _ in 0..MAX_HEADER_FETCH_ATTEMPTS {
+            if let Some(headers) = self.fetch_contiguous_chain_headers(start, end)? {
+                return Ok(headers);
+            }
+            debug!(
+                target: LOG_TARGET,
+                "Chain reorged while fetching headers {} to {}. Restarting the fetch.", start, end
+            );
+        }
+        Err(ChainStorageError::AccessError(format!(
+            "Could not fetch a contiguous chain of headers from {} to {} after {} attempts because the chain kept \
+             reorging",
+            start, end, MAX_HEADER_FETCH_ATTEMPTS
+        )))
+    }
+
+    /// Returns the set of block headers between `start` and up to and including `end_inclusive`, as for
+    /// [fetch_chain_headers](Self::fetch_chain_headers). Requests spanning more than `max_header_range` headers are
+    /// rejected. This should be used for requests that come from peers.
+    pub fn fetch_chain_headers_within_limit<T: RangeBounds<u64>>(
+        &self,
+        bounds: T,
+    ) -> Result<Vec<ChainHeader>, ChainStorageError> {
+        let (start, end) = self.resolve_header_bounds(bounds)?;
+        if start <= end {
+            let requested = (end - start).saturating_add(1);
+            let max = self.config.max_header_range as u64;
+            if requested > max {
+                return Err(ChainStorageError::RangeTooLarge { requested, max });
+            }
+        }
+        self.fetch_chain_headers(start..=end)
+    }
+
+    fn resolve_header_bounds<T: RangeBounds<u64>>(&self, bounds: T) -> Result<(u64, u64), ChainStorageError> {
         let (start, mut end) = convert_to_option_bounds(bounds);
         if end.is_none() {
             // `(n..)` means fetch block headers until this node's tip
-            end = Some(db.fetch_last_header()?.height);
+            end = Some(self.db_read_access()?.fetch_last_header()?.height);
         }
-        let (start, end) = (start.unwrap_or(0), end.unwrap());
+        Ok((start.unwrap_or(0), end.unwrap()))
+    }
 
-        fetch_chain_headers(&*db, start, end)
+    /// Fetches the headers in batches, returning `None` if a batch does not link to the previous batch.
+    fn fetch_contiguous_chain_headers(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> Result<Option<Vec<ChainHeader>>, ChainStorageError> {
+        let batch_size = cmp::max(self.config.max_header_batch, 1) as u64;
+        let mut headers: Vec<ChainHeader> = Vec::new();
+        let mut batch_start = start;
+        loop {
+            let batch_end = cmp::min(end, batch_start.saturating_add(batch_size - 1));
+            let batch = {
+                let db = self.db_read_access()?;
+                fetch_chain_headers(&*db, batch_start, batch_end)?
+            };
+            if let (Some(last), Some(first)) = (headers.last(), batch.first()) {
+                if first.header().prev_hash != *last.hash() {
+                    return Ok(None);
+                }
+            }
+            // A short batch means that we have reached the tip
+            let is_short = (batch.len() as u64) <= batch_end - batch_start;
+            headers.extend(batch);
+            if is_short || batch_end >= end {
+                break;
+            }
+            batch_start = batch_end + 1;
+        }
+
+        Ok(Some(headers))
     }
 
     /// Returns the block header corresponding to the provided BlockHash
@@ -597,6 +897,16 @@ where B: BlockchainBackend
         fetch_header_by_block_hash(&*db, hash)
     }
 
+    /// Returns the height below which blocks, apart from the genesis block, can no longer be looked up by hash because
+    /// their block hash index entries were pruned. Returns 0 if the block hash index is not pruned.
+    pub fn fetch_block_hash_index_pruned_height(&self) -> Result<u64, ChainStorageError> {
+        if !self.config.prune_block_hash_index {
+            return Ok(0);
+        }
+        let db = self.db_read_access()?;
+        Ok(db.fetch_chain_metadata()?.pruned_height())
+    }
+
     /// Returns a connected header in the main chain by block hash
     pub fn fetch_chain_header_by_block_hash(&self, hash: HashOutput) -> Result<Option<ChainHeader>, ChainStorageError> {
         let db = self.db_read_access()?;
@@ -700,6 +1010,49 @@ where B: BlockchainBackend
         fetch_orphan(&*db, hash)
     }
 
+    /// Returns the time at which the orphan was inserted, in milliseconds since the Unix epoch
+    pub fn fetch_orphan_insertion_time(&self, hash: HashOutput) -> Result<Option<u64>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_orphan_insertion_time(&hash)
+    }
+
+    /// Returns the number of connected orphans leading up to and including the given orphan tip
+    pub fn fetch_orphan_chain_depth(&self, tip_hash: HashOutput) -> Result<u64, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_orphan_chain_depth(&tip_hash)
+    }
+
+    /// Returns the height of the block at which the candidate header forks from the main chain, if accepting the
+    /// candidate would cause a reorg. That is the case when the candidate is stronger than the current tip and it
+    /// connects to the main chain below the tip, either directly or through a chain of orphans. Returns `None`
+    /// otherwise, including when the candidate builds on the tip, which merely extends the main chain.
+    pub fn would_reorg(&self, candidate: &ChainHeader) -> Result<Option<u64>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        let tip_header = db.fetch_tip_header()?;
+        if self
+            .consensus_manager
+            .chain_strength_comparer()
+            .compare(candidate, &tip_header) !=
+            Ordering::Greater
+        {
+            return Ok(None);
+        }
+
+        let mut prev_hash = candidate.header().prev_hash;
+        loop {
+            if let Some(header) = fetch_header_by_block_hash(&*db, prev_hash)? {
+                if prev_hash == *tip_header.hash() {
+                    return Ok(None);
+                }
+                return Ok(Some(header.height));
+            }
+            if !db.contains(&DbKey::OrphanBlock(prev_hash))? {
+                return Ok(None);
+            }
+            prev_hash = fetch_orphan(&*db, prev_hash)?.header.prev_hash;
+        }
+    }
+
     pub fn orphan_count(&self) -> Result<usize, ChainStorageError> {
         let db = self.db_read_access()?;
         db.orphan_count()
@@ -845,6 +1198,36 @@ where B: BlockchainBackend
         db.fetch_mmr_size(tree)
     }
 
+    /// Returns the size of the given MMR as it was at the given height, as recorded in the header at that height
+    pub fn fetch_mmr_size_at_height(&self, tree: MmrTree, height: u64) -> Result<u64, ChainStorageError> {
+        let db = self.db_read_access()?;
+        let header = fetch_header(&*db, height)?;
+        match tree {
+            MmrTree::Kernel => Ok(header.kernel_mmr_size),
+            MmrTree::Witness | MmrTree::Utxo => Ok(header.output_mmr_size),
+        }
+    }
+
+    /// Returns the kernel MMR root at the given height
+    pub fn fetch_kernel_mmr_root_at_height(&self, height: u64) -> Result<HashOutput, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_kernel_mmr_root_at_height(height)
+    }
+
+    /// Calculates the output MMR root (the UTXO set commitment) at the given height from the output hash set in the
+    /// block accumulated data and the outputs that had been spent by that height
+    pub fn fetch_utxo_set_root_at_height(&self, height: u64) -> Result<HashOutput, ChainStorageError> {
+        let db = self.db_read_access()?;
+        let hash = fetch_header(&*db, height)?.hash();
+        let outputs = db
+            .fetch_block_accumulated_data(&hash)?
+            .or_not_found("BlockAccumulatedData", "header_hash", hash.to_hex())?
+            .outputs;
+        let deleted = fetch_complete_deleted_bitmap_at(&*db, hash)?.into_bitmap();
+        let output_mmr = MutablePrunedOutputMmr::new(outputs, deleted)?;
+        Ok(FixedHash::try_from(output_mmr.get_merkle_root()?)?)
+    }
+
     pub fn get_shard_key(&self, height: u64, public_key: PublicKey) -> Result<Option<[u8; 32]>, ChainStorageError> {
         let db = self.db_read_access()?;
         db.get_shard_key(height, public_key)
@@ -939,7 +1322,13 @@ where B: BlockchainBackend
                 db.fetch_chain_metadata()?.height_of_longest_chain()
             );
             // If blocks were added and the node is in pruned mode, perform pruning
-            prune_database_if_needed(&mut *db, self.config.pruning_horizon, self.config.pruning_interval)?;
+            prune_database_if_needed(
+                &mut *db,
+                self.config.pruning_horizon,
+                self.config.pruning_interval,
+                self.config.prune_txo_index,
+                self.config.prune_block_hash_index,
+            )?;
         }
 
         if let Err(e) = cleanup_orphans(&mut *db, self.config.orphan_storage_capacity) {
@@ -950,6 +1339,7 @@ where B: BlockchainBackend
             target: LOG_TARGET,
             "Candidate block `add_block` result: {}", block_add_result
         );
+        self.publish_reorg_event(&block_add_result);
 
         trace!(
             target: LOG_TARGET,
@@ -993,7 +1383,12 @@ where B: BlockchainBackend
     /// Prunes the blockchain up to and including the given height
     pub fn prune_to_height(&self, height: u64) -> Result<(), ChainStorageError> {
         let mut db = self.db_write_access()?;
-        prune_to_height(&mut *db, height)
+        prune_to_height(
+            &mut *db,
+            height,
+            self.config.prune_txo_index,
+            self.config.prune_block_hash_index,
+        )
     }
 
     /// Fetch a block from the blockchain database.
@@ -1050,6 +1445,35 @@ where B: BlockchainBackend
         Ok(blocks)
     }
 
+    /// Returns the full blocks from `start` up to and including `end_inclusive` as `ChainBlock`s, reconstructed from
+    /// the stored header, body and accumulated data. Requests spanning more than `max_block_range` blocks are
+    /// rejected. An error is returned if any block in the range has been pruned.
+    pub fn fetch_blocks_in_range(&self, start: u64, end_inclusive: u64) -> Result<Vec<ChainBlock>, ChainStorageError> {
+        if start > end_inclusive {
+            return Ok(Vec::new());
+        }
+        let requested = (end_inclusive - start).saturating_add(1);
+        let max = self.config.max_block_range as u64;
+        if requested > max {
+            return Err(ChainStorageError::RangeTooLarge { requested, max });
+        }
+
+        let db = self.db_read_access()?;
+        let tip_height = db.fetch_chain_metadata()?.height_of_longest_chain();
+        if end_inclusive > tip_height {
+            return Err(ChainStorageError::ValueNotFound {
+                entity: "Block",
+                field: "end height",
+                value: end_inclusive.to_string(),
+            });
+        }
+
+        fetch_blocks(&*db, start, end_inclusive, false)?
+            .into_iter()
+            .map(|block| block.try_into_chain_block().map_err(ChainStorageError::from))
+            .collect()
+    }
+
     /// Attempt to fetch the block corresponding to the provided hash from the main chain
     pub fn fetch_block_by_hash(
         &self,
@@ -1067,6 +1491,16 @@ where B: BlockchainBackend
         fetch_block_by_kernel_signature(&*db, excess_sig)
     }
 
+    /// Fetch the inputs and outputs of the transaction that the kernel with the given excess signature belongs to. See
+    /// [TransactionGraph] for why this is an approximation at the level of the block the kernel was mined in.
+    pub fn fetch_transaction_graph_by_kernel(
+        &self,
+        excess_sig: &Signature,
+    ) -> Result<TransactionGraph, ChainStorageError> {
+        let db = self.db_read_access()?;
+        fetch_transaction_graph_by_kernel(&*db, excess_sig)
+    }
+
     /// Attempt to fetch the block corresponding to the provided utxo hash from the main chain, if the block is past
     /// pruning horizon, it will return Ok<None>
     pub fn fetch_block_with_utxo(&self, commitment: Commitment) -> Result<Option<HistoricalBlock>, ChainStorageError> {
@@ -1086,6 +1520,12 @@ where B: BlockchainBackend
         db.bad_block_exists(hash)
     }
 
+    /// Returns all blocks in the bad block list as (hash, height) pairs, ordered by height.
+    pub fn fetch_all_bad_blocks(&self) -> Result<Vec<(HashOutput, u64)>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_all_bad_blocks()
+    }
+
     /// Atomically commit the provided transaction to the database backend. This function does not update the metadata.
     pub fn commit(&self, txn: DbTransaction) -> Result<(), ChainStorageError> {
         let mut db = self.db_write_access()?;
@@ -1119,12 +1559,13 @@ where B: BlockchainBackend
     /// we are not sure if the new chain is higher than the old one.
     pub fn swap_to_highest_pow_chain(&self) -> Result<(), ChainStorageError> {
         let mut db = self.db_write_access()?;
-        swap_to_highest_pow_chain(
+        let result = swap_to_highest_pow_chain(
             &mut *db,
             &self.config,
             &*self.validators.block,
             self.consensus_manager.chain_strength_comparer(),
         )?;
+        self.publish_reorg_event(&result);
         Ok(())
     }
 
@@ -1138,25 +1579,48 @@ where B: BlockchainBackend
         hash: HashOutput,
     ) -> Result<CompleteDeletedBitmap, ChainStorageError> {
         let db = self.db_read_access()?;
-        let mut deleted = db.fetch_deleted_bitmap()?.into_bitmap();
-
-        let end_header =
-            fetch_header_by_block_hash(&*db, hash).or_not_found("BlockHeader", "start_hash", hash.to_hex())?;
-        let chain_metadata = db.fetch_chain_metadata()?;
-        let height = chain_metadata.height_of_longest_chain();
-        for i in end_header.height..height {
-            // order here does not matter, we dont have to go in reverse
-            deleted.xor_inplace(
-                db.fetch_block_accumulated_data_by_height(i + 1)
-                    .or_not_found("BlockAccumulatedData", "height", height.to_string())?
-                    .deleted(),
-            );
+        fetch_complete_deleted_bitmap_at(&*db, hash)
+    }
+
+    /// Returns the MMR roots committed to in the header of the block at the given height, along with the roots
+    /// calculated from the stored accumulated data of the block. The two sets differ if the stored data has drifted
+    /// from what the header commits to.
+    pub fn fetch_block_roots(&self, height: u64) -> Result<BlockRoots, ChainStorageError> {
+        let db = self.db_read_access()?;
+        let header = fetch_header(&*db, height)?;
+        let hash = header.hash();
+        let BlockAccumulatedData {
+            kernels,
+            outputs,
+            witness,
+            ..
+        } = db.fetch_block_accumulated_data(&hash)?.or_not_found(
+            "BlockAccumulatedData",
+            "header_hash",
+            hash.to_hex(),
+        )?;
+        let deleted = fetch_complete_deleted_bitmap_at(&*db, hash)?.into_bitmap();
+
+        let kernel_mmr = PrunedKernelMmr::new(kernels);
+        let output_mmr = MutablePrunedOutputMmr::new(outputs, deleted)?;
+        let witness_mmr = PrunedWitnessMmr::new(witness);
+        // The input MMR is not accumulated across blocks, so it is rebuilt from the inputs of the block
+        let mut input_mmr = PrunedInputMmr::new(PrunedHashSet::default());
+        for input in db.fetch_inputs_in_block(&hash)? {
+            input_mmr.push(input.canonical_hash().to_vec())?;
         }
-        Ok(CompleteDeletedBitmap::new(
-            deleted,
+
+        Ok(BlockRoots {
             height,
-            *chain_metadata.best_block(),
-        ))
+            header_output_mr: header.output_mr,
+            header_kernel_mr: header.kernel_mr,
+            header_witness_mr: header.witness_mr,
+            header_input_mr: header.input_mr,
+            output_mr: FixedHash::try_from(output_mmr.get_merkle_root()?)?,
+            kernel_mr: FixedHash::try_from(kernel_mmr.get_merkle_root()?)?,
+            witness_mr: FixedHash::try_from(witness_mmr.get_merkle_root()?)?,
+            input_mr: FixedHash::try_from(input_mmr.get_merkle_root()?)?,
+        })
     }
 
     pub fn fetch_deleted_bitmap_at_tip(&self) -> Result<DeletedBitmap, ChainStorageError> {
@@ -1205,6 +1669,14 @@ where B: BlockchainBackend
         db.fetch_active_validator_nodes(height)
     }
 
+    pub fn fetch_active_validator_nodes_at_epoch(
+        &self,
+        epoch: VnEpoch,
+    ) -> Result<Vec<(PublicKey, [u8; 32])>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_active_validator_nodes_at_epoch(epoch)
+    }
+
     pub fn fetch_template_registrations<T: RangeBounds<u64>>(
         &self,
         range: T,
@@ -1229,6 +1701,30 @@ fn unexpected_result<T>(request: DbKey, response: DbValue) -> Result<T, ChainSto
     Err(ChainStorageError::UnexpectedResult(msg))
 }
 
+/// The MMR roots a block header commits to, alongside the roots calculated from the stored accumulated data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockRoots {
+    pub height: u64,
+    pub header_output_mr: FixedHash,
+    pub header_kernel_mr: FixedHash,
+    pub header_witness_mr: FixedHash,
+    pub header_input_mr: FixedHash,
+    pub output_mr: FixedHash,
+    pub kernel_mr: FixedHash,
+    pub witness_mr: FixedHash,
+    pub input_mr: FixedHash,
+}
+
+impl BlockRoots {
+    /// Returns true if every calculated root matches the root in the header
+    pub fn is_consistent(&self) -> bool {
+        self.header_output_mr == self.output_mr &&
+            self.header_kernel_mr == self.kernel_mr &&
+            self.header_witness_mr == self.witness_mr &&
+            self.header_input_mr == self.input_mr
+    }
+}
+
 /// Container struct for MMR roots
 #[derive(Debug, Clone)]
 pub struct MmrRoots {
@@ -1466,6 +1962,30 @@ fn insert_headers<T: BlockchainBackend>(db: &mut T, headers: Vec<ChainHeader>) -
     db.write(txn)
 }
 
+fn fetch_complete_deleted_bitmap_at<T: BlockchainBackend>(
+    db: &T,
+    hash: HashOutput,
+) -> Result<CompleteDeletedBitmap, ChainStorageError> {
+    let mut deleted = db.fetch_deleted_bitmap()?.into_bitmap();
+
+    let end_header = fetch_header_by_block_hash(db, hash).or_not_found("BlockHeader", "start_hash", hash.to_hex())?;
+    let chain_metadata = db.fetch_chain_metadata()?;
+    let height = chain_metadata.height_of_longest_chain();
+    for i in end_header.height..height {
+        // order here does not matter, we dont have to go in reverse
+        deleted.xor_inplace(
+            db.fetch_block_accumulated_data_by_height(i + 1)
+                .or_not_found("BlockAccumulatedData", "height", height.to_string())?
+                .deleted(),
+        );
+    }
+    Ok(CompleteDeletedBitmap::new(
+        deleted,
+        height,
+        *chain_metadata.best_block(),
+    ))
+}
+
 fn fetch_header_by_block_hash<T: BlockchainBackend>(
     db: &T,
     hash: BlockHash,
@@ -1690,6 +2210,68 @@ fn fetch_block_by_kernel_signature<T: BlockchainBackend>(
     }
 }
 
+fn fetch_transaction_graph_by_kernel<T: BlockchainBackend>(
+    db: &T,
+    excess_sig: &Signature,
+) -> Result<TransactionGraph, ChainStorageError> {
+    let (kernel, header_hash) =
+        db.fetch_kernel_by_excess_sig(excess_sig)?
+            .ok_or_else(|| ChainStorageError::ValueNotFound {
+                entity: "Kernel",
+                field: "Excess sig",
+                value: excess_sig.get_signature().to_hex(),
+            })?;
+    let header = fetch_header_by_block_hash(db, header_hash)?.ok_or_else(|| ChainStorageError::ValueNotFound {
+        entity: "BlockHeader",
+        field: "hash",
+        value: header_hash.to_hex(),
+    })?;
+    let inputs = db.fetch_inputs_in_block(&header_hash)?;
+    let mut outputs = db.fetch_outputs_in_block(&header_hash)?;
+    // Coinbase outputs can only belong to a coinbase kernel, so they can safely be excluded for any other kernel
+    if !kernel.is_coinbase() {
+        outputs.retain(|output| !output.as_transaction_output().map_or(false, |o| o.is_coinbase()));
+    }
+
+    Ok(TransactionGraph {
+        kernel,
+        header_hash,
+        height: header.height,
+        inputs,
+        outputs,
+    })
+}
+
+fn fetch_output_lifecycle<T: BlockchainBackend>(
+    db: &T,
+    output_hash: &HashOutput,
+) -> Result<OutputLifecycle, ChainStorageError> {
+    let mined_info = db
+        .fetch_output(output_hash)?
+        .ok_or_else(|| ChainStorageError::ValueNotFound {
+            entity: "TransactionOutput",
+            field: "hash",
+            value: output_hash.to_hex(),
+        })?;
+    let spent = db
+        .fetch_header_hash_by_deleted_mmr_positions(vec![mined_info.mmr_position])?
+        .into_iter()
+        .next()
+        .flatten()
+        .map(|(height, header_hash)| OutputLifecycleEvent { height, header_hash });
+
+    Ok(OutputLifecycle {
+        output_hash: *output_hash,
+        mmr_position: mined_info.mmr_position,
+        created: OutputLifecycleEvent {
+            height: mined_info.mined_height,
+            header_hash: mined_info.header_hash,
+        },
+        spent,
+        is_pruned: mined_info.output.is_pruned(),
+    })
+}
+
 fn fetch_block_by_utxo_commitment<T: BlockchainBackend>(
     db: &T,
     commitment: &Commitment,
@@ -2370,6 +2952,8 @@ fn prune_database_if_needed<T: BlockchainBackend>(
     db: &mut T,
     pruning_horizon: u64,
     pruning_interval: u64,
+    prune_txo_index: bool,
+    prune_block_hash_index: bool,
 ) -> Result<(), ChainStorageError> {
     let metadata = db.fetch_chain_metadata()?;
     if !metadata.is_pruned_node() {
@@ -2387,13 +2971,18 @@ fn prune_database_if_needed<T: BlockchainBackend>(
         pruning_interval,
     );
     if metadata.pruned_height() < abs_pruning_horizon.saturating_sub(pruning_interval) {
-        prune_to_height(db, abs_pruning_horizon)?;
+        prune_to_height(db, abs_pruning_horizon, prune_txo_index, prune_block_hash_index)?;
     }
 
     Ok(())
 }
 
-fn prune_to_height<T: BlockchainBackend>(db: &mut T, target_horizon_height: u64) -> Result<(), ChainStorageError> {
+fn prune_to_height<T: BlockchainBackend>(
+    db: &mut T,
+    target_horizon_height: u64,
+    prune_txo_index: bool,
+    prune_block_hash_index: bool,
+) -> Result<(), ChainStorageError> {
     let metadata = db.fetch_chain_metadata()?;
     let last_pruned = metadata.pruned_height();
     if target_horizon_height < last_pruned {
@@ -2436,6 +3025,14 @@ fn prune_to_height<T: BlockchainBackend>(db: &mut T, target_horizon_height: u64)
         "height",
         last_pruned.to_string(),
     )?;
+    // The hash of the block at the pruned height is kept, it is the boundary the rest of the chain builds on. So is the
+    // hash of the genesis block. Each pass only removes the hashes of the blocks between the previous and the new
+    // boundary.
+    let mut prev_boundary_hash = if prune_block_hash_index && last_pruned > 0 {
+        Some(*db.fetch_chain_header_by_height(last_pruned)?.hash())
+    } else {
+        None
+    };
     let mut txn = DbTransaction::new();
     for block_to_prune in (last_pruned + 1)..=target_horizon_height {
         let header = db.fetch_chain_header_by_height(block_to_prune)?;
@@ -2450,7 +3047,15 @@ fn prune_to_height<T: BlockchainBackend>(db: &mut T, target_horizon_height: u64)
         last_block = curr_block;
 
         txn.prune_outputs_at_positions(output_mmr_positions.to_vec());
+        if prune_txo_index {
+            txn.prune_txo_index_entries(output_mmr_positions.to_vec());
+        }
         txn.delete_all_inputs_in_block(*header.hash());
+        if prune_block_hash_index {
+            if let Some(hash) = prev_boundary_hash.replace(*header.hash()) {
+                txn.prune_block_hash_index_entry(hash);
+            }
+        }
         if txn.operations().len() >= 100 {
             txn.set_pruned_height(block_to_prune);
             db.write(mem::take(&mut txn))?;
@@ -2482,6 +3087,8 @@ impl<T> Clone for BlockchainDatabase<T> {
             consensus_manager: self.consensus_manager.clone(),
             difficulty_calculator: self.difficulty_calculator.clone(),
             disable_add_block_flag: self.disable_add_block_flag.clone(),
+            reorg_event_publisher: self.reorg_event_publisher.clone(),
+            utxo_set_hash_cache: self.utxo_set_hash_cache.clone(),
         }
     }
 }
@@ -2732,6 +3339,38 @@ mod test {
         }
     }
 
+    mod reorg_event_stream {
+        use super::*;
+
+        #[test]
+        fn it_publishes_an_event_when_the_chain_reorgs() {
+            let db = create_new_blockchain();
+            let mut reorg_events = db.reorg_event_stream();
+
+            let (_, main_chain) = create_main_chain(&db, block_specs!(["1a->GB"], ["2a->1a"], ["3a->2a"]));
+            let fork_root = main_chain.get("1a").unwrap().clone();
+            let (_, orphan_chain) = create_chained_blocks(block_specs!(["2b->GB"], ["3b->2b"], ["4b->3b"]), fork_root);
+
+            for name in ["2b", "3b"] {
+                let block = orphan_chain.get(name).unwrap().clone();
+                assert!(db.add_block(block.to_arc_block()).unwrap().is_orphaned());
+            }
+            assert!(reorg_events.try_recv().is_err());
+
+            let block = orphan_chain.get("4b").unwrap().clone();
+            assert!(db.add_block(block.to_arc_block()).unwrap().is_chain_reorg());
+
+            let event = reorg_events.try_recv().unwrap();
+            assert_eq!(event, ReorgEvent {
+                fork_height: 1,
+                old_tip: *main_chain.get("3a").unwrap().hash(),
+                new_tip: *orphan_chain.get("4b").unwrap().hash(),
+                depth: 2,
+            });
+            assert!(reorg_events.try_recv().is_err());
+        }
+    }
+
     mod handle_possible_reorg {
         use super::*;
 
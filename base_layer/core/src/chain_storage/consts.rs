@@ -26,3 +26,9 @@ pub const BLOCKCHAIN_DATABASE_ORPHAN_STORAGE_CAPACITY: usize = 720;
 pub const BLOCKCHAIN_DATABASE_PRUNING_HORIZON: u64 = 0;
 /// The chain height interval used to determine when a pruned node should perform pruning.
 pub const BLOCKCHAIN_DATABASE_PRUNED_MODE_PRUNING_INTERVAL: u64 = 50;
+/// The maximum number of headers fetched from the backend under a single read lock.
+pub const BLOCKCHAIN_DATABASE_MAX_HEADER_BATCH: usize = 1_000;
+/// The maximum number of headers that may be requested in a single range query.
+pub const BLOCKCHAIN_DATABASE_MAX_HEADER_RANGE: usize = 10_000;
+/// The maximum number of full blocks that may be requested in a single range query.
+pub const BLOCKCHAIN_DATABASE_MAX_BLOCK_RANGE: usize = 100;
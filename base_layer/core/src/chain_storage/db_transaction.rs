@@ -23,15 +23,28 @@
 use std::{
     fmt,
     fmt::{Display, Error, Formatter},
+    io,
+    io::Write,
     sync::Arc,
 };
 
+use borsh::{BorshDeserialize, BorshSerialize};
 use croaring::Bitmap;
+use serde::{de::DeserializeOwned, Serialize};
 use tari_common_types::types::{BlockHash, Commitment, HashOutput};
+use tari_mmr::pruned_hashset::PrunedHashSet;
 use tari_utilities::hex::Hex;
 
 use crate::{
-    blocks::{Block, BlockHeader, BlockHeaderAccumulatedData, ChainBlock, ChainHeader, UpdateBlockAccumulatedData},
+    blocks::{
+        Block,
+        BlockHeader,
+        BlockHeaderAccumulatedData,
+        ChainBlock,
+        ChainHeader,
+        DeletedBitmap,
+        UpdateBlockAccumulatedData,
+    },
     chain_storage::{error::ChainStorageError, HorizonData, Reorg},
     transactions::transaction_components::{TransactionKernel, TransactionOutput},
 };
@@ -51,6 +64,29 @@ impl Display for DbTransaction {
     }
 }
 
+/// A `DbTransaction` can be serialized so that a failing write can be captured and replayed against a throwaway copy
+/// of the database, see [crate::chain_storage::replay_db_transaction_in_lmdb_database].
+impl BorshSerialize for DbTransaction {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        BorshSerialize::serialize(&self.operations, writer)
+    }
+}
+
+impl BorshDeserialize for DbTransaction {
+    fn deserialize(buf: &mut &[u8]) -> io::Result<Self> {
+        let operations = BorshDeserialize::deserialize(buf)?;
+        Ok(Self { operations })
+    }
+}
+
+impl From<WriteOperation> for DbTransaction {
+    fn from(operation: WriteOperation) -> Self {
+        Self {
+            operations: vec![operation],
+        }
+    }
+}
+
 impl Default for DbTransaction {
     fn default() -> Self {
         DbTransaction {
@@ -152,6 +188,21 @@ impl DbTransaction {
         self
     }
 
+    /// Drops the output key from the hash index entries of the pruned outputs at the given MMR positions
+    pub fn prune_txo_index_entries(&mut self, output_mmr_positions: Vec<u32>) -> &mut Self {
+        self.operations.push(WriteOperation::PruneTxoIndexEntries {
+            output_positions: output_mmr_positions,
+        });
+        self
+    }
+
+    /// Removes the block hash to height mapping of the given block. The header can still be fetched by height.
+    pub fn prune_block_hash_index_entry(&mut self, block_hash: HashOutput) -> &mut Self {
+        self.operations
+            .push(WriteOperation::PruneBlockHashIndexEntry { block_hash });
+        self
+    }
+
     pub fn delete_all_inputs_in_block(&mut self, block_hash: BlockHash) -> &mut Self {
         self.operations
             .push(WriteOperation::DeleteAllInputsInBlock { block_hash });
@@ -267,6 +318,10 @@ impl DbTransaction {
         &self.operations
     }
 
+    pub(crate) fn into_operations(self) -> Vec<WriteOperation> {
+        self.operations
+    }
+
     /// This will store the seed key with the height. This is called when a block is accepted into the main chain.
     /// This will only update the hieght of the seed, if its lower then currently stored.
     pub fn insert_monero_seed_height(&mut self, monero_seed: Vec<u8>, height: u64) {
@@ -358,6 +413,12 @@ pub enum WriteOperation {
         reorg: Reorg,
     },
     ClearAllReorgs,
+    PruneTxoIndexEntries {
+        output_positions: Vec<u32>,
+    },
+    PruneBlockHashIndexEntry {
+        block_hash: HashOutput,
+    },
 }
 
 impl fmt::Display for WriteOperation {
@@ -454,7 +515,321 @@ impl fmt::Display for WriteOperation {
             SetHorizonData { .. } => write!(f, "Set horizon data"),
             InsertReorg { .. } => write!(f, "Insert reorg"),
             ClearAllReorgs => write!(f, "Clear all reorgs"),
+            PruneTxoIndexEntries { output_positions } => {
+                write!(f, "Prune {} txo index entries", output_positions.len())
+            },
+            PruneBlockHashIndexEntry { block_hash } => {
+                write!(f, "Prune block hash index entry {}", block_hash.to_hex())
+            },
+        }
+    }
+}
+
+// Values that do not implement borsh are encoded with bincode, the same encoding used to store them in LMDB
+fn serialize_bincode<T: Serialize, W: Write>(value: &T, writer: &mut W) -> io::Result<()> {
+    let bytes = bincode::serialize(value).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    BorshSerialize::serialize(&bytes, writer)
+}
+
+fn deserialize_bincode<T: DeserializeOwned>(buf: &mut &[u8]) -> io::Result<T> {
+    let bytes: Vec<u8> = BorshDeserialize::deserialize(buf)?;
+    bincode::deserialize(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+}
+
+fn serialize_chain_block<W: Write>(block: &ChainBlock, writer: &mut W) -> io::Result<()> {
+    BorshSerialize::serialize(block.block(), writer)?;
+    serialize_bincode(block.accumulated_data(), writer)
+}
+
+fn deserialize_chain_block(buf: &mut &[u8]) -> io::Result<Arc<ChainBlock>> {
+    let block: Block = BorshDeserialize::deserialize(buf)?;
+    let accumulated_data = deserialize_bincode(buf)?;
+    ChainBlock::try_construct(Arc::new(block), accumulated_data)
+        .map(Arc::new)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Block does not match its accumulated data"))
+}
+
+impl BorshSerialize for WriteOperation {
+    #[allow(clippy::too_many_lines)]
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        #[allow(clippy::enum_glob_use)]
+        use WriteOperation::*;
+        match self {
+            InsertOrphanBlock(block) => {
+                BorshSerialize::serialize(&0u8, writer)?;
+                BorshSerialize::serialize(&**block, writer)?;
+            },
+            InsertChainOrphanBlock(block) => {
+                BorshSerialize::serialize(&1u8, writer)?;
+                serialize_chain_block(block, writer)?;
+            },
+            InsertChainHeader { header } => {
+                BorshSerialize::serialize(&2u8, writer)?;
+                BorshSerialize::serialize(&**header, writer)?;
+            },
+            InsertBlockBody { block } => {
+                BorshSerialize::serialize(&3u8, writer)?;
+                serialize_chain_block(block, writer)?;
+            },
+            InsertKernel {
+                header_hash,
+                kernel,
+                mmr_position,
+            } => {
+                BorshSerialize::serialize(&4u8, writer)?;
+                BorshSerialize::serialize(header_hash, writer)?;
+                BorshSerialize::serialize(&**kernel, writer)?;
+                BorshSerialize::serialize(mmr_position, writer)?;
+            },
+            InsertOutput {
+                header_hash,
+                header_height,
+                output,
+                mmr_position,
+                timestamp,
+            } => {
+                BorshSerialize::serialize(&5u8, writer)?;
+                BorshSerialize::serialize(header_hash, writer)?;
+                BorshSerialize::serialize(header_height, writer)?;
+                BorshSerialize::serialize(&**output, writer)?;
+                BorshSerialize::serialize(mmr_position, writer)?;
+                BorshSerialize::serialize(timestamp, writer)?;
+            },
+            InsertPrunedOutput {
+                header_hash,
+                header_height,
+                output_hash,
+                witness_hash,
+                mmr_position,
+                timestamp,
+            } => {
+                BorshSerialize::serialize(&6u8, writer)?;
+                BorshSerialize::serialize(header_hash, writer)?;
+                BorshSerialize::serialize(header_height, writer)?;
+                BorshSerialize::serialize(output_hash, writer)?;
+                BorshSerialize::serialize(witness_hash, writer)?;
+                BorshSerialize::serialize(mmr_position, writer)?;
+                BorshSerialize::serialize(timestamp, writer)?;
+            },
+            InsertBadBlock { hash, height } => {
+                BorshSerialize::serialize(&7u8, writer)?;
+                BorshSerialize::serialize(hash, writer)?;
+                BorshSerialize::serialize(height, writer)?;
+            },
+            DeleteHeader(height) => {
+                BorshSerialize::serialize(&8u8, writer)?;
+                BorshSerialize::serialize(height, writer)?;
+            },
+            DeleteOrphan(hash) => {
+                BorshSerialize::serialize(&9u8, writer)?;
+                BorshSerialize::serialize(hash, writer)?;
+            },
+            DeleteBlock(hash) => {
+                BorshSerialize::serialize(&10u8, writer)?;
+                BorshSerialize::serialize(hash, writer)?;
+            },
+            DeleteOrphanChainTip(hash) => {
+                BorshSerialize::serialize(&11u8, writer)?;
+                BorshSerialize::serialize(hash, writer)?;
+            },
+            InsertOrphanChainTip(hash) => {
+                BorshSerialize::serialize(&12u8, writer)?;
+                BorshSerialize::serialize(hash, writer)?;
+            },
+            InsertMoneroSeedHeight(seed, height) => {
+                BorshSerialize::serialize(&13u8, writer)?;
+                BorshSerialize::serialize(seed, writer)?;
+                BorshSerialize::serialize(height, writer)?;
+            },
+            UpdateBlockAccumulatedData { header_hash, values } => {
+                BorshSerialize::serialize(&14u8, writer)?;
+                BorshSerialize::serialize(header_hash, writer)?;
+                serialize_bincode(
+                    &(
+                        &values.kernel_hash_set,
+                        &values.utxo_hash_set,
+                        &values.witness_hash_set,
+                        &values.deleted_diff,
+                        &values.kernel_sum,
+                    ),
+                    writer,
+                )?;
+            },
+            UpdateDeletedBitmap { deleted } => {
+                BorshSerialize::serialize(&15u8, writer)?;
+                BorshSerialize::serialize(&deleted.serialize(), writer)?;
+            },
+            PruneOutputsAtMmrPositions { output_positions } => {
+                BorshSerialize::serialize(&16u8, writer)?;
+                BorshSerialize::serialize(output_positions, writer)?;
+            },
+            DeleteAllInputsInBlock { block_hash } => {
+                BorshSerialize::serialize(&17u8, writer)?;
+                BorshSerialize::serialize(block_hash, writer)?;
+            },
+            SetAccumulatedDataForOrphan(accumulated_data) => {
+                BorshSerialize::serialize(&18u8, writer)?;
+                serialize_bincode(accumulated_data, writer)?;
+            },
+            SetBestBlock {
+                height,
+                hash,
+                accumulated_difficulty,
+                expected_prev_best_block,
+                timestamp,
+            } => {
+                BorshSerialize::serialize(&19u8, writer)?;
+                BorshSerialize::serialize(height, writer)?;
+                BorshSerialize::serialize(hash, writer)?;
+                BorshSerialize::serialize(accumulated_difficulty, writer)?;
+                BorshSerialize::serialize(expected_prev_best_block, writer)?;
+                BorshSerialize::serialize(timestamp, writer)?;
+            },
+            SetPruningHorizonConfig(pruning_horizon) => {
+                BorshSerialize::serialize(&20u8, writer)?;
+                BorshSerialize::serialize(pruning_horizon, writer)?;
+            },
+            SetPrunedHeight { height } => {
+                BorshSerialize::serialize(&21u8, writer)?;
+                BorshSerialize::serialize(height, writer)?;
+            },
+            SetHorizonData { horizon_data } => {
+                BorshSerialize::serialize(&22u8, writer)?;
+                serialize_bincode(horizon_data, writer)?;
+            },
+            InsertReorg { reorg } => {
+                BorshSerialize::serialize(&23u8, writer)?;
+                serialize_bincode(reorg, writer)?;
+            },
+            ClearAllReorgs => {
+                BorshSerialize::serialize(&24u8, writer)?;
+            },
+            PruneTxoIndexEntries { output_positions } => {
+                BorshSerialize::serialize(&25u8, writer)?;
+                BorshSerialize::serialize(output_positions, writer)?;
+            },
+            PruneBlockHashIndexEntry { block_hash } => {
+                BorshSerialize::serialize(&26u8, writer)?;
+                BorshSerialize::serialize(block_hash, writer)?;
+            },
         }
+        Ok(())
+    }
+}
+
+impl BorshDeserialize for WriteOperation {
+    #[allow(clippy::too_many_lines)]
+    fn deserialize(buf: &mut &[u8]) -> io::Result<Self> {
+        #[allow(clippy::enum_glob_use)]
+        use WriteOperation::*;
+        let tag: u8 = BorshDeserialize::deserialize(buf)?;
+        let op = match tag {
+            0 => {
+                let block: Block = BorshDeserialize::deserialize(buf)?;
+                InsertOrphanBlock(Arc::new(block))
+            },
+            1 => InsertChainOrphanBlock(deserialize_chain_block(buf)?),
+            2 => InsertChainHeader {
+                header: Box::new(BorshDeserialize::deserialize(buf)?),
+            },
+            3 => InsertBlockBody {
+                block: deserialize_chain_block(buf)?,
+            },
+            4 => InsertKernel {
+                header_hash: BorshDeserialize::deserialize(buf)?,
+                kernel: Box::new(BorshDeserialize::deserialize(buf)?),
+                mmr_position: BorshDeserialize::deserialize(buf)?,
+            },
+            5 => InsertOutput {
+                header_hash: BorshDeserialize::deserialize(buf)?,
+                header_height: BorshDeserialize::deserialize(buf)?,
+                output: Box::new(BorshDeserialize::deserialize(buf)?),
+                mmr_position: BorshDeserialize::deserialize(buf)?,
+                timestamp: BorshDeserialize::deserialize(buf)?,
+            },
+            6 => InsertPrunedOutput {
+                header_hash: BorshDeserialize::deserialize(buf)?,
+                header_height: BorshDeserialize::deserialize(buf)?,
+                output_hash: BorshDeserialize::deserialize(buf)?,
+                witness_hash: BorshDeserialize::deserialize(buf)?,
+                mmr_position: BorshDeserialize::deserialize(buf)?,
+                timestamp: BorshDeserialize::deserialize(buf)?,
+            },
+            7 => InsertBadBlock {
+                hash: BorshDeserialize::deserialize(buf)?,
+                height: BorshDeserialize::deserialize(buf)?,
+            },
+            8 => DeleteHeader(BorshDeserialize::deserialize(buf)?),
+            9 => DeleteOrphan(BorshDeserialize::deserialize(buf)?),
+            10 => DeleteBlock(BorshDeserialize::deserialize(buf)?),
+            11 => DeleteOrphanChainTip(BorshDeserialize::deserialize(buf)?),
+            12 => InsertOrphanChainTip(BorshDeserialize::deserialize(buf)?),
+            13 => InsertMoneroSeedHeight(BorshDeserialize::deserialize(buf)?, BorshDeserialize::deserialize(buf)?),
+            14 => {
+                let header_hash = BorshDeserialize::deserialize(buf)?;
+                let (kernel_hash_set, utxo_hash_set, witness_hash_set, deleted_diff, kernel_sum): (
+                    Option<PrunedHashSet>,
+                    Option<PrunedHashSet>,
+                    Option<PrunedHashSet>,
+                    Option<DeletedBitmap>,
+                    Option<Commitment>,
+                ) = deserialize_bincode(buf)?;
+                UpdateBlockAccumulatedData {
+                    header_hash,
+                    values: UpdateBlockAccumulatedData {
+                        kernel_hash_set,
+                        utxo_hash_set,
+                        witness_hash_set,
+                        deleted_diff,
+                        kernel_sum,
+                    },
+                }
+            },
+            15 => {
+                let bytes: Vec<u8> = BorshDeserialize::deserialize(buf)?;
+                let deleted = Bitmap::try_deserialize(&bytes)
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid deleted bitmap"))?;
+                UpdateDeletedBitmap { deleted }
+            },
+            16 => PruneOutputsAtMmrPositions {
+                output_positions: BorshDeserialize::deserialize(buf)?,
+            },
+            17 => DeleteAllInputsInBlock {
+                block_hash: BorshDeserialize::deserialize(buf)?,
+            },
+            18 => SetAccumulatedDataForOrphan(deserialize_bincode(buf)?),
+            19 => SetBestBlock {
+                height: BorshDeserialize::deserialize(buf)?,
+                hash: BorshDeserialize::deserialize(buf)?,
+                accumulated_difficulty: BorshDeserialize::deserialize(buf)?,
+                expected_prev_best_block: BorshDeserialize::deserialize(buf)?,
+                timestamp: BorshDeserialize::deserialize(buf)?,
+            },
+            20 => SetPruningHorizonConfig(BorshDeserialize::deserialize(buf)?),
+            21 => SetPrunedHeight {
+                height: BorshDeserialize::deserialize(buf)?,
+            },
+            22 => SetHorizonData {
+                horizon_data: deserialize_bincode(buf)?,
+            },
+            23 => InsertReorg {
+                reorg: deserialize_bincode(buf)?,
+            },
+            24 => ClearAllReorgs,
+            25 => PruneTxoIndexEntries {
+                output_positions: BorshDeserialize::deserialize(buf)?,
+            },
+            26 => PruneBlockHashIndexEntry {
+                block_hash: BorshDeserialize::deserialize(buf)?,
+            },
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown write operation tag {}", tag),
+                ))
+            },
+        };
+        Ok(op)
     }
 }
 
@@ -98,6 +98,8 @@ pub enum ChainStorageError {
     BlockingTaskSpawnError(String),
     #[error("A request was out of range")]
     OutOfRange,
+    #[error("The requested range of {requested} items exceeds the maximum of {max}")]
+    RangeTooLarge { requested: u64, max: u64 },
     #[error("LMDB error: {source}")]
     LmdbError {
         #[from]
@@ -20,6 +20,9 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::io::{Read, Write};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 use lmdb_zero::error;
 use log::*;
 use serde::{de::DeserializeOwned, Serialize};
@@ -28,6 +31,14 @@ use crate::chain_storage::ChainStorageError;
 
 pub const LOG_TARGET: &str = "c::cs::lmdb_db::lmdb";
 
+/// Marks a value that is prefixed with its format byte. Values without the marker are legacy uncompressed values. A
+/// legacy serialized block starts with its little-endian u16 header version, which never reaches `u16::MAX`.
+const FORMAT_MARKER: [u8; 2] = [0xff, 0xff];
+/// The format byte of a value that is serialized without compression
+const FORMAT_UNCOMPRESSED: u8 = 0;
+/// The format byte of a value that is serialized and gzip-compressed
+const FORMAT_GZIP: u8 = 1;
+
 pub fn serialize<T>(data: &T) -> Result<Vec<u8>, ChainStorageError>
 where T: Serialize {
     let size = bincode::serialized_size(&data).map_err(|e| ChainStorageError::AccessError(e.to_string()))?;
@@ -49,3 +60,87 @@ where T: DeserializeOwned {
         })
         .map_err(|e| error::Error::ValRejected(e.to_string()))
 }
+
+/// Serializes the data, gzip-compressing the result if `compress` is set, and prefixes it with the format marker and
+/// format byte
+pub fn serialize_maybe_compressed<T>(data: &T, compress: bool) -> Result<Vec<u8>, ChainStorageError>
+where T: Serialize {
+    let buf = serialize(data)?;
+    let mut prefixed = Vec::with_capacity(buf.len() + FORMAT_MARKER.len() + 1);
+    prefixed.extend_from_slice(&FORMAT_MARKER);
+    if !compress {
+        prefixed.push(FORMAT_UNCOMPRESSED);
+        prefixed.extend_from_slice(&buf);
+        return Ok(prefixed);
+    }
+    prefixed.push(FORMAT_GZIP);
+    let mut encoder = GzEncoder::new(prefixed, Compression::default());
+    encoder.write_all(&buf).and_then(|_| encoder.finish()).map_err(|e| {
+        error!(target: LOG_TARGET, "Could not compress lmdb value: {:?}", e);
+        ChainStorageError::AccessError(e.to_string())
+    })
+}
+
+/// Deserializes a value written by either `serialize` or `serialize_maybe_compressed`. Values without the format
+/// marker are legacy values written by `serialize`, so this must only be used for types whose bincode serialization
+/// can never start with the marker.
+pub fn deserialize_maybe_compressed<T>(buf_bytes: &[u8]) -> Result<T, error::Error>
+where T: DeserializeOwned {
+    let buf_bytes = match buf_bytes.strip_prefix(&FORMAT_MARKER) {
+        Some(buf_bytes) => buf_bytes,
+        None => return deserialize(buf_bytes),
+    };
+    match buf_bytes.split_first() {
+        Some((&FORMAT_UNCOMPRESSED, buf_bytes)) => deserialize(buf_bytes),
+        Some((&FORMAT_GZIP, buf_bytes)) => {
+            let mut buf = Vec::new();
+            GzDecoder::new(buf_bytes).read_to_end(&mut buf).map_err(|e| {
+                error!(target: LOG_TARGET, "Could not decompress lmdb value: {:?}", e);
+                error::Error::ValRejected(e.to_string())
+            })?;
+            deserialize(&buf)
+        },
+        Some((format, _)) => {
+            error!(target: LOG_TARGET, "Unknown lmdb value format {}", format);
+            Err(error::Error::ValRejected(format!("Unknown value format {}", format)))
+        },
+        None => Err(error::Error::ValRejected("Missing value format".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reads_legacy_values_that_look_like_gzip() {
+        // The bincode serialization of 0x8b1f starts with the gzip magic bytes
+        let value = (0x8b1f_u16, vec![1u8, 2, 3]);
+        let buf = serialize(&value).unwrap();
+        assert!(buf.starts_with(&[0x1f, 0x8b]));
+        let legacy: (u16, Vec<u8>) = deserialize_maybe_compressed(&buf).unwrap();
+        assert_eq!(legacy, value);
+    }
+
+    #[test]
+    fn it_reads_prefixed_values_with_and_without_compression() {
+        let value = (0x8b1f_u16, vec![7u8; 1024]);
+        let uncompressed = serialize_maybe_compressed(&value, false).unwrap();
+        assert_eq!(uncompressed[..3], [0xff, 0xff, FORMAT_UNCOMPRESSED]);
+        let compressed = serialize_maybe_compressed(&value, true).unwrap();
+        assert_eq!(compressed[..3], [0xff, 0xff, FORMAT_GZIP]);
+        assert!(compressed.len() < uncompressed.len());
+
+        let read: (u16, Vec<u8>) = deserialize_maybe_compressed(&uncompressed).unwrap();
+        assert_eq!(read, value);
+        let read: (u16, Vec<u8>) = deserialize_maybe_compressed(&compressed).unwrap();
+        assert_eq!(read, value);
+    }
+
+    #[test]
+    fn it_rejects_an_unknown_format() {
+        let mut buf = serialize_maybe_compressed(&1u64, false).unwrap();
+        buf[2] = 9;
+        assert!(deserialize_maybe_compressed::<u64>(&buf).is_err());
+    }
+}
@@ -43,7 +43,7 @@ use crate::chain_storage::{
     error::ChainStorageError,
     lmdb_db::{
         cursors::KeyPrefixCursor,
-        helpers::{deserialize, serialize},
+        helpers::{deserialize, deserialize_maybe_compressed, serialize, serialize_maybe_compressed},
     },
     OrNotFound,
 };
@@ -62,8 +62,41 @@ where
     V: Serialize + Debug,
 {
     let val_buf = serialize(val)?;
+    lmdb_insert_serialized(txn, db, key, val, &val_buf, table_name)
+}
+
+/// Inserts the serialization of the value prefixed with its format, gzip-compressed if `compress` is set. Values
+/// inserted this way must be read using `lmdb_get_maybe_compressed` or `lmdb_filter_map_maybe_compressed_values`.
+pub fn lmdb_insert_maybe_compressed<K, V>(
+    txn: &WriteTransaction<'_>,
+    db: &Database,
+    key: &K,
+    val: &V,
+    compress: bool,
+    table_name: &'static str,
+) -> Result<(), ChainStorageError>
+where
+    K: AsLmdbBytes + ?Sized + Debug,
+    V: Serialize + Debug,
+{
+    let val_buf = serialize_maybe_compressed(val, compress)?;
+    lmdb_insert_serialized(txn, db, key, val, &val_buf, table_name)
+}
+
+fn lmdb_insert_serialized<K, V>(
+    txn: &WriteTransaction<'_>,
+    db: &Database,
+    key: &K,
+    val: &V,
+    val_buf: &[u8],
+    table_name: &'static str,
+) -> Result<(), ChainStorageError>
+where
+    K: AsLmdbBytes + ?Sized + Debug,
+    V: Debug,
+{
     trace!(target: LOG_TARGET, "LMDB: {} bytes inserted", val_buf.len());
-    match txn.access().put(db, key, &val_buf, put::NOOVERWRITE) {
+    match txn.access().put(db, key, val_buf, put::NOOVERWRITE) {
         Ok(_) => Ok(()),
         Err(lmdb_zero::Error::Code(lmdb_zero::error::KEYEXIST)) => Err(ChainStorageError::KeyExists {
             table_name,
@@ -200,6 +233,31 @@ pub fn lmdb_get<K, V>(txn: &ConstTransaction<'_>, db: &Database, key: &K) -> Res
 where
     K: AsLmdbBytes + ?Sized,
     V: DeserializeOwned,
+{
+    lmdb_get_with(txn, db, key, deserialize)
+}
+
+/// Fetches a value that may have been inserted with either `lmdb_insert` or `lmdb_insert_maybe_compressed`
+pub fn lmdb_get_maybe_compressed<K, V>(
+    txn: &ConstTransaction<'_>,
+    db: &Database,
+    key: &K,
+) -> Result<Option<V>, ChainStorageError>
+where
+    K: AsLmdbBytes + ?Sized,
+    V: DeserializeOwned,
+{
+    lmdb_get_with(txn, db, key, deserialize_maybe_compressed)
+}
+
+fn lmdb_get_with<K, V>(
+    txn: &ConstTransaction<'_>,
+    db: &Database,
+    key: &K,
+    deserialize_fn: fn(&[u8]) -> Result<V, Error>,
+) -> Result<Option<V>, ChainStorageError>
+where
+    K: AsLmdbBytes + ?Sized,
 {
     let access = txn.access();
     match access.get(db, key).to_opt() {
@@ -208,7 +266,7 @@ where
             error!(target: LOG_TARGET, "Could not get value from lmdb: {:?}", e);
             Err(ChainStorageError::AccessError(e.to_string()))
         },
-        Ok(Some(v)) => match deserialize(v) {
+        Ok(Some(v)) => match deserialize_fn(v) {
             Ok(val) => Ok(Some(val)),
             Err(e) => {
                 error!(
@@ -326,6 +384,30 @@ where
     Ok(result)
 }
 
+/// Counts the entries whose key starts with the given prefix, without deserializing their values
+pub fn lmdb_count_matching_prefix(
+    txn: &ConstTransaction<'_>,
+    db: &Database,
+    key_prefix: &[u8],
+) -> Result<usize, ChainStorageError> {
+    let access = txn.access();
+    let mut cursor = txn.cursor(db).map_err(|e| {
+        error!(target: LOG_TARGET, "Could not get read cursor from lmdb: {:?}", e);
+        ChainStorageError::AccessError(e.to_string())
+    })?;
+
+    let mut row = cursor.seek_range_k::<[u8], [u8]>(&access, key_prefix).to_opt()?;
+    let mut count = 0;
+    while let Some((key, _)) = row {
+        if !key.starts_with(key_prefix) {
+            break;
+        }
+        count += 1;
+        row = cursor.next::<[u8], [u8]>(&access).to_opt()?;
+    }
+    Ok(count)
+}
+
 pub fn lmdb_first_after<K, V>(
     txn: &ConstTransaction<'_>,
     db: &Database,
@@ -358,6 +440,32 @@ pub fn lmdb_filter_map_values<F, V, R>(
 where
     F: Fn(V) -> Option<R>,
     V: DeserializeOwned,
+{
+    lmdb_filter_map_values_with(txn, db, f, deserialize)
+}
+
+/// Like `lmdb_filter_map_values`, for values that may have been inserted with either `lmdb_insert` or
+/// `lmdb_insert_maybe_compressed`
+pub fn lmdb_filter_map_maybe_compressed_values<F, V, R>(
+    txn: &ConstTransaction<'_>,
+    db: &Database,
+    f: F,
+) -> Result<Vec<R>, ChainStorageError>
+where
+    F: Fn(V) -> Option<R>,
+    V: DeserializeOwned,
+{
+    lmdb_filter_map_values_with(txn, db, f, deserialize_maybe_compressed)
+}
+
+fn lmdb_filter_map_values_with<F, V, R>(
+    txn: &ConstTransaction<'_>,
+    db: &Database,
+    f: F,
+    deserialize_fn: fn(&[u8]) -> Result<V, Error>,
+) -> Result<Vec<R>, ChainStorageError>
+where
+    F: Fn(V) -> Option<R>,
 {
     let access = txn.access();
     let mut cursor = txn.cursor(db).map_err(|e| {
@@ -374,7 +482,7 @@ where
     let mut result = vec![];
     for row in iter {
         // result.push(Vec::from(row?.0));
-        let val = deserialize::<V>(row?.1)?;
+        let val = deserialize_fn(row?.1)?;
         if let Some(r) = f(val) {
             result.push(r);
         }
@@ -382,6 +490,38 @@ where
     Ok(result)
 }
 
+/// Like `lmdb_filter_map_values`, but also passes the raw key of each entry to `f`
+pub fn lmdb_filter_map_entries<F, V, R>(
+    txn: &ConstTransaction<'_>,
+    db: &Database,
+    f: F,
+) -> Result<Vec<R>, ChainStorageError>
+where
+    F: Fn(&[u8], V) -> Option<R>,
+    V: DeserializeOwned,
+{
+    let access = txn.access();
+    let mut cursor = txn.cursor(db).map_err(|e| {
+        error!(target: LOG_TARGET, "Could not get read cursor from lmdb: {:?}", e);
+        ChainStorageError::AccessError(e.to_string())
+    })?;
+    let iter = CursorIter::new(
+        MaybeOwned::Borrowed(&mut cursor),
+        &access,
+        |c, a| c.first(a),
+        Cursor::next::<[u8], [u8]>,
+    )?;
+
+    let mut result = vec![];
+    for row in iter {
+        let (key, val) = row?;
+        if let Some(r) = f(key, deserialize(val)?) {
+            result.push(r);
+        }
+    }
+    Ok(result)
+}
+
 /// Fetches the size of all key/values in the given DB. Returns the number of entries, the total size of all the
 /// keys and values in bytes.
 pub fn fetch_db_entry_sizes(txn: &ConstTransaction<'_>, db: &Database) -> Result<(u64, u64, u64), ChainStorageError> {
@@ -20,8 +20,21 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{convert::TryFrom, fmt, fs, fs::File, ops::Deref, path::Path, sync::Arc, time::Instant};
+use std::{
+    collections::HashSet,
+    convert::TryFrom,
+    fmt,
+    fs,
+    fs::File,
+    io::Write,
+    ops::Deref,
+    path::Path,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
 
+use borsh::BorshSerialize;
 use croaring::Bitmap;
 use fs2::FileExt;
 use lmdb_zero::{open, ConstTransaction, Database, Environment, ReadTransaction, WriteTransaction};
@@ -30,8 +43,9 @@ use serde::{Deserialize, Serialize};
 use tari_common_types::{
     chain_metadata::ChainMetadata,
     epoch::VnEpoch,
-    types::{BlockHash, Commitment, HashOutput, PublicKey, Signature},
+    types::{BlindingFactor, BlockHash, Commitment, CommitmentFactory, HashOutput, PublicKey, Signature},
 };
+use tari_crypto::commitment::HomomorphicCommitmentFactory;
 use tari_storage::lmdb_store::{db, LMDBBuilder, LMDBConfig, LMDBStore};
 use tari_utilities::{
     hex::{to_hex, Hex},
@@ -58,23 +72,29 @@ use crate::{
             lmdb::{
                 fetch_db_entry_sizes,
                 lmdb_clear,
+                lmdb_count_matching_prefix,
                 lmdb_delete,
                 lmdb_delete_each_where,
                 lmdb_delete_key_value,
                 lmdb_delete_keys_starting_with,
                 lmdb_exists,
                 lmdb_fetch_matching_after,
+                lmdb_filter_map_entries,
+                lmdb_filter_map_maybe_compressed_values,
                 lmdb_filter_map_values,
                 lmdb_first_after,
                 lmdb_get,
+                lmdb_get_maybe_compressed,
                 lmdb_get_multiple,
                 lmdb_insert,
                 lmdb_insert_dup,
+                lmdb_insert_maybe_compressed,
                 lmdb_last,
                 lmdb_len,
                 lmdb_replace,
             },
             validator_node_store::ValidatorNodeStore,
+            DbTransactionTimings,
             TransactionInputRowData,
             TransactionInputRowDataRef,
             TransactionKernelRowData,
@@ -84,9 +104,12 @@ use crate::{
         utxo_mined_info::UtxoMinedInfo,
         BlockchainBackend,
         DbBasicStats,
+        DbHealthReport,
         DbSize,
         HorizonData,
+        IndexAuditReport,
         MmrTree,
+        OrphanTree,
         PrunedOutput,
         Reorg,
         TemplateRegistrationEntry,
@@ -95,7 +118,9 @@ use crate::{
     consensus::{ConsensusConstants, ConsensusManager},
     transactions::{
         aggregated_body::AggregateBody,
+        tari_amount::MicroTari,
         transaction_components::{
+            KernelFeatures,
             TransactionError,
             TransactionInput,
             TransactionKernel,
@@ -127,6 +152,7 @@ const LMDB_DB_KERNEL_MMR_SIZE_INDEX: &str = "kernel_mmr_size_index";
 const LMDB_DB_UTXO_MMR_SIZE_INDEX: &str = "utxo_mmr_size_index";
 const LMDB_DB_DELETED_TXO_MMR_POSITION_TO_HEIGHT_INDEX: &str = "deleted_txo_mmr_position_to_height_index";
 const LMDB_DB_UTXO_COMMITMENT_INDEX: &str = "utxo_commitment_index";
+const LMDB_DB_SPENT_COMMITMENT_INDEX: &str = "spent_commitment_index";
 const LMDB_DB_UNIQUE_ID_INDEX: &str = "unique_id_index";
 const LMDB_DB_CONTRACT_ID_INDEX: &str = "contract_index";
 const LMDB_DB_ORPHANS: &str = "orphans";
@@ -134,6 +160,7 @@ const LMDB_DB_MONERO_SEED_HEIGHT: &str = "monero_seed_height";
 const LMDB_DB_ORPHAN_HEADER_ACCUMULATED_DATA: &str = "orphan_accumulated_data";
 const LMDB_DB_ORPHAN_CHAIN_TIPS: &str = "orphan_chain_tips";
 const LMDB_DB_ORPHAN_PARENT_MAP_INDEX: &str = "orphan_parent_map_index";
+const LMDB_DB_ORPHAN_INSERTION_TIME_INDEX: &str = "orphan_insertion_time_index";
 const LMDB_DB_BAD_BLOCK_LIST: &str = "bad_blocks";
 const LMDB_DB_REORGS: &str = "reorgs";
 const LMDB_DB_VALIDATOR_NODES: &str = "validator_nodes";
@@ -180,6 +207,7 @@ pub fn create_lmdb_database<P: AsRef<Path>>(
         .add_database(LMDB_DB_KERNEL_MMR_SIZE_INDEX, flags)
         .add_database(LMDB_DB_UTXO_MMR_SIZE_INDEX, flags)
         .add_database(LMDB_DB_UTXO_COMMITMENT_INDEX, flags)
+        .add_database(LMDB_DB_SPENT_COMMITMENT_INDEX, flags)
         .add_database(LMDB_DB_UNIQUE_ID_INDEX, flags)
         .add_database(LMDB_DB_CONTRACT_ID_INDEX, flags)
         .add_database(LMDB_DB_DELETED_TXO_MMR_POSITION_TO_HEIGHT_INDEX, flags | db::INTEGERKEY)
@@ -188,6 +216,7 @@ pub fn create_lmdb_database<P: AsRef<Path>>(
         .add_database(LMDB_DB_MONERO_SEED_HEIGHT, flags)
         .add_database(LMDB_DB_ORPHAN_CHAIN_TIPS, flags)
         .add_database(LMDB_DB_ORPHAN_PARENT_MAP_INDEX, flags | db::DUPSORT)
+        .add_database(LMDB_DB_ORPHAN_INSERTION_TIME_INDEX, flags)
         .add_database(LMDB_DB_BAD_BLOCK_LIST, flags)
         .add_database(LMDB_DB_REORGS, flags | db::INTEGERKEY)
         .add_database(LMDB_DB_VALIDATOR_NODES, flags)
@@ -230,6 +259,8 @@ pub struct LMDBDatabase {
     output_mmr_size_index: DatabaseRef,
     /// Maps commitment -> output_hash
     utxo_commitment_index: DatabaseRef,
+    /// Maps commitment -> output_hash for spent outputs
+    spent_commitment_index: DatabaseRef,
     /// Maps unique_id -> output_hash
     unique_id_index: DatabaseRef,
     /// Maps <contract_id, output_type> -> (block_hash, output_hash)
@@ -237,7 +268,7 @@ pub struct LMDBDatabase {
     contract_index: DatabaseRef,
     /// Maps output_mmr_pos -> <block_hash, output_hash>
     deleted_txo_mmr_position_to_height_index: DatabaseRef,
-    /// Maps block_hash -> Block
+    /// Maps block_hash -> Block, gzip-compressed if `compress_orphans` is enabled
     orphans_db: DatabaseRef,
     /// Maps randomx_seed -> height
     monero_seed_height_db: DatabaseRef,
@@ -247,6 +278,8 @@ pub struct LMDBDatabase {
     orphan_chain_tips_db: DatabaseRef,
     /// Maps parent_block_hash -> block_hash
     orphan_parent_map_index: DatabaseRef,
+    /// Maps block_hash -> orphan insertion time in milliseconds since the Unix epoch
+    orphan_insertion_time_index: DatabaseRef,
     /// Stores bad blocks by block_hash and height
     bad_blocks: DatabaseRef,
     /// Stores reorgs by epochtime and Reorg
@@ -259,6 +292,14 @@ pub struct LMDBDatabase {
     template_registrations: DatabaseRef,
     _file_lock: Arc<File>,
     consensus_manager: ConsensusManager,
+    /// The operation timings of the last applied transaction, only collected if `log_db_txn_timing` is enabled
+    last_txn_timings: Option<DbTransactionTimings>,
+    /// The last loaded or saved deleted bitmap, only used if `cache_deleted_bitmap` is enabled
+    deleted_bitmap_cache: Mutex<Option<DeletedBitmap>>,
+    /// The last `(height, block reward, supply)` reached on the emission schedule, so emission queries resume from it
+    emission_cache: Mutex<Option<(u64, MicroTari, MicroTari)>>,
+    /// The secondary indexes that were above the index size warning threshold when last reported
+    oversized_indexes: Mutex<HashSet<&'static str>>,
 }
 
 impl LMDBDatabase {
@@ -284,6 +325,7 @@ impl LMDBDatabase {
             kernel_mmr_size_index: get_database(store, LMDB_DB_KERNEL_MMR_SIZE_INDEX)?,
             output_mmr_size_index: get_database(store, LMDB_DB_UTXO_MMR_SIZE_INDEX)?,
             utxo_commitment_index: get_database(store, LMDB_DB_UTXO_COMMITMENT_INDEX)?,
+            spent_commitment_index: get_database(store, LMDB_DB_SPENT_COMMITMENT_INDEX)?,
             unique_id_index: get_database(store, LMDB_DB_UNIQUE_ID_INDEX)?,
             contract_index: get_database(store, LMDB_DB_CONTRACT_ID_INDEX)?,
             deleted_txo_mmr_position_to_height_index: get_database(
@@ -295,6 +337,7 @@ impl LMDBDatabase {
             monero_seed_height_db: get_database(store, LMDB_DB_MONERO_SEED_HEIGHT)?,
             orphan_chain_tips_db: get_database(store, LMDB_DB_ORPHAN_CHAIN_TIPS)?,
             orphan_parent_map_index: get_database(store, LMDB_DB_ORPHAN_PARENT_MAP_INDEX)?,
+            orphan_insertion_time_index: get_database(store, LMDB_DB_ORPHAN_INSERTION_TIME_INDEX)?,
             bad_blocks: get_database(store, LMDB_DB_BAD_BLOCK_LIST)?,
             reorgs: get_database(store, LMDB_DB_REORGS)?,
             validator_nodes: get_database(store, LMDB_DB_VALIDATOR_NODES)?,
@@ -304,6 +347,10 @@ impl LMDBDatabase {
             env_config: store.env_config(),
             _file_lock: Arc::new(file_lock),
             consensus_manager,
+            last_txn_timings: None,
+            deleted_bitmap_cache: Mutex::new(None),
+            emission_cache: Mutex::new(None),
+            oversized_indexes: Mutex::new(HashSet::new()),
         };
 
         run_migrations(&db)?;
@@ -323,13 +370,487 @@ impl LMDBDatabase {
         WriteTransaction::new(&*self.env).map_err(Into::into)
     }
 
+    /// Returns the per-operation timings of the last successfully applied transaction. This is always `None` unless
+    /// `log_db_txn_timing` is enabled in the LMDB config.
+    pub fn last_txn_timings(&self) -> Option<&DbTransactionTimings> {
+        self.last_txn_timings.as_ref()
+    }
+
+    /// Returns the total amount emitted in block rewards up to and including the block at `height`, as given by the
+    /// consensus emission schedule. The schedule is resumed from the last height queried, so successive queries at a
+    /// growing height only step over the blocks in between.
+    pub fn total_emission_at_height(&self, height: u64) -> Result<MicroTari, ChainStorageError> {
+        let schedule = self.consensus_manager.emission_schedule();
+        let mut cache = self
+            .emission_cache
+            .lock()
+            .map_err(|e| ChainStorageError::AccessError(format!("Emission cache lock poisoned: {}", e)))?;
+        let mut rate = match *cache {
+            Some((cached_height, reward, supply)) if cached_height <= height => {
+                schedule.iter_from(cached_height, reward, supply)
+            },
+            _ => schedule.iter(),
+        };
+        while rate.block_height() < height {
+            if rate.next().is_none() {
+                // The maximum supply has been reached
+                break;
+            }
+        }
+        *cache = Some((rate.block_height(), rate.block_reward(), rate.supply()));
+        Ok(rate.supply())
+    }
+
+    /// Tallies the block rewards claimed by the coinbases of the blocks up to and including `height`, as a cross-check
+    /// of `total_emission_at_height` against the chain. The coinbase outputs of every block after genesis must commit
+    /// to exactly the scheduled block reward plus the fees of the block, the genesis emission is taken from the
+    /// schedule. Fails if the block outputs needed for the check have been pruned.
+    pub fn tally_emission_at_height(&self, height: u64) -> Result<MicroTari, ChainStorageError> {
+        let factory = CommitmentFactory::default();
+        let mut schedule = self.consensus_manager.emission_schedule().iter();
+        let mut total = schedule.supply();
+        for block_height in 1..=height {
+            let (_, reward, _) = schedule.next().ok_or_else(|| {
+                ChainStorageError::InvalidOperation(format!("Total emission overflows at height {}", block_height))
+            })?;
+            let hash = *self.fetch_chain_header_by_height(block_height)?.hash();
+
+            let kernels = self.fetch_kernels_in_block(&hash)?;
+            let fees = kernels.iter().fold(MicroTari::zero(), |total, k| total + k.fee);
+            let mut claimed = factory.commit_value(&BlindingFactor::default(), (reward + fees).as_u64());
+            for kernel in kernels
+                .iter()
+                .filter(|k| k.features.contains(KernelFeatures::COINBASE_KERNEL))
+            {
+                claimed = &claimed + &kernel.excess;
+            }
+
+            let mut coinbase = Commitment::default();
+            for output in self.fetch_outputs_in_block(&hash)? {
+                match output {
+                    PrunedOutput::NotPruned { output } if output.is_coinbase() => {
+                        coinbase = &coinbase + &output.commitment;
+                    },
+                    PrunedOutput::NotPruned { .. } => {},
+                    PrunedOutput::Pruned { .. } => {
+                        return Err(ChainStorageError::InvalidOperation(format!(
+                            "Cannot tally the emission of block {} because its outputs have been pruned",
+                            block_height
+                        )));
+                    },
+                }
+            }
+
+            if coinbase != claimed {
+                return Err(ChainStorageError::DataInconsistencyDetected {
+                    function: "tally_emission_at_height",
+                    details: format!(
+                        "The coinbase of block {} does not claim the scheduled reward of {} plus {} in fees",
+                        block_height, reward, fees
+                    ),
+                });
+            }
+            total = total.checked_add(reward).ok_or_else(|| {
+                ChainStorageError::InvalidOperation(format!("Total emission overflows at height {}", block_height))
+            })?;
+        }
+        Ok(total)
+    }
+
+    /// Returns the main chain header whose `prev_hash` is the given hash, i.e. the main chain child of that block.
+    /// `None` is returned if the block is the tip or is not on the main chain. Orphan children are never returned.
+    pub fn fetch_header_by_prev_hash(&self, prev_hash: &HashOutput) -> Result<Option<BlockHeader>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        let height = match self.fetch_height_from_hash(&txn, prev_hash)? {
+            Some(height) => height,
+            None => return Ok(None),
+        };
+        let header: Option<BlockHeader> = lmdb_get(&txn, &self.headers_db, &height.saturating_add(1))?;
+        Ok(header.filter(|header| header.prev_hash == *prev_hash))
+    }
+
+    /// Returns the height of the block in which the output with the given commitment was spent, or `None` if the
+    /// commitment is unspent or unknown. The spent output is looked up in the spent commitment index, which holds the
+    /// most recently spent output for each commitment, and its spend height is read from the deleted txo index.
+    pub fn fetch_spent_height(&self, commitment: &Commitment) -> Result<Option<u64>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        if lmdb_exists(&txn, &self.utxo_commitment_index, commitment.as_bytes())? {
+            return Ok(None);
+        }
+        let output_hash: HashOutput = match lmdb_get(&txn, &self.spent_commitment_index, commitment.as_bytes())? {
+            Some(hash) => hash,
+            None => return Ok(None),
+        };
+        let mmr_position =
+            match lmdb_get::<_, (u32, Vec<u8>)>(&txn, &self.txos_hash_to_index_db, output_hash.as_slice())? {
+                Some((mmr_position, _)) => mmr_position,
+                None => return Ok(None),
+            };
+        let spent: Option<(u64, HashOutput)> =
+            lmdb_get(&txn, &self.deleted_txo_mmr_position_to_height_index, &mmr_position)?;
+        Ok(spent.map(|(height, _)| height))
+    }
+
+    /// Returns the weight of the main chain block at `height`, calculated from the block's inputs, outputs and kernels
+    /// using the transaction weight rules of the consensus constants at that height. The weight cannot be calculated
+    /// once any of the block's outputs have been pruned.
+    pub fn fetch_block_weight(&self, height: u64) -> Result<u64, ChainStorageError> {
+        let header_hash = {
+            let txn = self.read_transaction()?;
+            let header: BlockHeader =
+                lmdb_get(&txn, &self.headers_db, &height)?.ok_or_else(|| ChainStorageError::ValueNotFound {
+                    entity: "BlockHeader",
+                    field: "height",
+                    value: height.to_string(),
+                })?;
+            header.hash()
+        };
+        let outputs = self
+            .fetch_outputs_in_block(&header_hash)?
+            .into_iter()
+            .map(|output| {
+                output.into_unpruned_output().ok_or_else(|| {
+                    ChainStorageError::InvalidOperation(format!(
+                        "The weight of block {} cannot be calculated because some of its outputs have been pruned",
+                        height
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let body = AggregateBody::new(
+            self.fetch_inputs_in_block(&header_hash)?,
+            outputs,
+            self.fetch_kernels_in_block(&header_hash)?,
+        );
+        Ok(body.calculate_weight(self.consensus_manager.consensus_constants(height).transaction_weight()))
+    }
+
+    /// Returns the coinbase outputs of the main chain block at `height`. Pruned outputs cannot be identified as
+    /// coinbases and are not included.
+    pub fn fetch_coinbase_outputs_at_height(&self, height: u64) -> Result<Vec<PrunedOutput>, ChainStorageError> {
+        let header_hash = {
+            let txn = self.read_transaction()?;
+            let header: BlockHeader =
+                lmdb_get(&txn, &self.headers_db, &height)?.ok_or_else(|| ChainStorageError::ValueNotFound {
+                    entity: "BlockHeader",
+                    field: "height",
+                    value: height.to_string(),
+                })?;
+            header.hash()
+        };
+        Ok(self
+            .fetch_outputs_in_block(&header_hash)?
+            .into_iter()
+            .filter(|output| match output {
+                PrunedOutput::NotPruned { output } => output.is_coinbase(),
+                PrunedOutput::Pruned { .. } => false,
+            })
+            .collect())
+    }
+
+    /// Checks the main chain against a trusted checkpoint, returning true if the hash of the header at `height` matches
+    /// `expected_hash`.
+    pub fn verify_checkpoint(&self, height: u64, expected_hash: HashOutput) -> Result<bool, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        let header: BlockHeader =
+            lmdb_get(&txn, &self.headers_db, &height)?.ok_or_else(|| ChainStorageError::ValueNotFound {
+                entity: "BlockHeader",
+                field: "height",
+                value: height.to_string(),
+            })?;
+        Ok(header.hash() == expected_hash)
+    }
+
+    /// Returns an aggregate health report of the database, combining the basic and size statistics, orphan, bad block
+    /// and reorg counts, and a check that the tip header matches the chain metadata. All values are read within a
+    /// single read transaction.
+    pub fn health_report(&self) -> Result<DbHealthReport, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        let stats = self.fetch_basic_stats_in_txn(&txn)?;
+        let total_size_stats = self.fetch_total_size_stats_in_txn(&txn)?;
+        let orphan_count = lmdb_len(&txn, &self.orphans_db)?;
+        let bad_block_count = lmdb_len(&txn, &self.bad_blocks)?;
+        let reorgs: Vec<Reorg> = lmdb_filter_map_values(&txn, &self.reorgs, Some)?;
+        let metadata = fetch_metadata(&txn, &self.metadata_db)?;
+        let tip_header: Option<BlockHeader> = lmdb_get(&txn, &self.headers_db, &metadata.height_of_longest_chain())?;
+
+        Ok(DbHealthReport {
+            stats,
+            total_size_stats,
+            orphan_count,
+            bad_block_count,
+            reorg_count: reorgs.len(),
+            max_reorg_depth: reorgs.iter().map(|r| r.num_blocks_removed).max().unwrap_or(0),
+            tip_matches_metadata: tip_header.map_or(false, |header| header.hash() == *metadata.best_block()),
+        })
+    }
+
+    /// Cross-checks the kernel, output and block hash indexes against the tables they point into, reporting every index
+    /// entry that has no backing primary row. All indexes are read within a single read transaction.
+    pub fn audit_secondary_indexes(&self) -> Result<IndexAuditReport, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        let mut report = IndexAuditReport::default();
+
+        for (name, index) in [
+            ("kernel_excess_index", &self.kernel_excess_index),
+            ("kernel_excess_sig_index", &self.kernel_excess_sig_index),
+        ] {
+            let entries: Vec<(Vec<u8>, (HashOutput, u32, HashOutput))> =
+                lmdb_filter_map_entries(&txn, index, |k, v| Some((k.to_vec(), v)))?;
+            for (key, (header_hash, mmr_position, hash)) in entries {
+                let kernel_key = KernelKey::try_from_parts(&[
+                    header_hash.as_slice(),
+                    mmr_position.to_be_bytes().as_slice(),
+                    hash.as_slice(),
+                ])?;
+                report.record(name, key, lmdb_exists(&txn, &self.kernels_db, &kernel_key)?);
+            }
+        }
+
+        let entries: Vec<(Vec<u8>, (u32, Vec<u8>))> =
+            lmdb_filter_map_entries(&txn, &self.txos_hash_to_index_db, |k, v| Some((k.to_vec(), v)))?;
+        for (key, (mmr_position, output_key)) in entries {
+            let has_primary_row = if output_key.is_empty() {
+                match self.output_key_at_position(&txn, mmr_position) {
+                    Ok(output_key) => lmdb_exists(&txn, &self.utxos_db, &output_key)?,
+                    Err(ChainStorageError::ValueNotFound { .. }) => false,
+                    Err(e) => return Err(e),
+                }
+            } else {
+                lmdb_exists(&txn, &self.utxos_db, output_key.as_slice())?
+            };
+            report.record("txos_hash_to_index_db", key, has_primary_row);
+        }
+
+        let entries: Vec<(Vec<u8>, HashOutput)> =
+            lmdb_filter_map_entries(&txn, &self.utxo_commitment_index, |k, v| Some((k.to_vec(), v)))?;
+        for (key, output_hash) in entries {
+            let has_primary_row = lmdb_exists(&txn, &self.txos_hash_to_index_db, output_hash.as_slice())?;
+            report.record("utxo_commitment_index", key, has_primary_row);
+        }
+
+        let entries: Vec<(Vec<u8>, u64)> =
+            lmdb_filter_map_entries(&txn, &self.block_hashes_db, |k, v| Some((k.to_vec(), v)))?;
+        for (key, height) in entries {
+            let has_primary_row = lmdb_exists(&txn, &self.headers_db, &height)?;
+            report.record("block_hashes_db", key, has_primary_row);
+        }
+
+        Ok(report)
+    }
+
+    /// Reconstructs the parent/child structure of the orphan pool from the orphan parent index. Each returned tree is
+    /// rooted at an orphan whose parent is not itself an orphan. All values are read within a single read transaction.
+    pub fn dump_orphan_forest(&self) -> Result<Vec<OrphanTree>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        let tips: HashSet<HashOutput> = lmdb_filter_map_values(&txn, &self.orphan_chain_tips_db, Some)?
+            .into_iter()
+            .collect();
+        let links: Vec<(Vec<u8>, HashOutput)> =
+            lmdb_filter_map_entries(&txn, &self.orphan_parent_map_index, |k, v| Some((k.to_vec(), v)))?;
+
+        let mut forest = Vec::new();
+        for (parent_hash, hash) in links {
+            if lmdb_exists(&txn, &self.block_hashes_db, hash.as_slice())? {
+                continue;
+            }
+            let parent_is_orphan = lmdb_exists(&txn, &self.orphans_db, parent_hash.as_slice())? &&
+                !lmdb_exists(&txn, &self.block_hashes_db, parent_hash.as_slice())?;
+            if !parent_is_orphan {
+                forest.push(self.build_orphan_tree(&txn, hash, &tips)?);
+            }
+        }
+        forest.sort_by_key(|tree| (tree.height, tree.hash));
+        Ok(forest)
+    }
+
+    /// Returns the orphan chain tip with the greatest total accumulated difficulty, together with that difficulty, if
+    /// it exceeds the accumulated difficulty of the current main chain tip.
+    pub fn find_heaviest_orphan_chain(&self) -> Result<Option<(ChainHeader, u128)>, ChainStorageError> {
+        let tip_difficulty = self.fetch_chain_metadata()?.accumulated_difficulty();
+        let heaviest = self
+            .fetch_all_orphan_chain_tips()?
+            .into_iter()
+            .map(|header| {
+                let total_work = header.accumulated_data().total_accumulated_difficulty;
+                (header, total_work)
+            })
+            .filter(|(_, total_work)| *total_work > tip_difficulty)
+            .max_by_key(|(_, total_work)| *total_work);
+        Ok(heaviest)
+    }
+
+    /// Drops the output key from the hash index entries of all pruned outputs mined below `below_height`, e.g. to
+    /// reclaim index space in a database that was pruned before `prune_txo_index` was enabled, and returns the number
+    /// of entries pruned. Unlike pruning, this scans every output in the database.
+    pub fn prune_txo_index_entries(&mut self, below_height: u64) -> Result<usize, ChainStorageError> {
+        let txn = self.write_transaction()?;
+        let positions = lmdb_filter_map_values(&txn, &self.utxos_db, |row: TransactionOutputRowData| {
+            if row.output.is_none() && row.mined_height < below_height {
+                Some(row.mmr_position)
+            } else {
+                None
+            }
+        })?;
+        self.prune_txo_index_entries_at_positions(&txn, &positions)?;
+        txn.commit()?;
+        Ok(positions.len())
+    }
+
+    /// Writes the borsh encoded list of the main chain `ChainHeader`s from height `from` to `to_inclusive` to `writer`,
+    /// e.g. to bootstrap a new node with a trusted set of headers, and returns the number of headers written. All
+    /// headers are read within a single read transaction and are streamed to `writer` one at a time, in the same
+    /// encoding as a borsh `Vec<ChainHeader>`.
+    pub fn export_headers<W: Write>(
+        &self,
+        from: u64,
+        to_inclusive: u64,
+        mut writer: W,
+    ) -> Result<usize, ChainStorageError> {
+        if from > to_inclusive {
+            return Err(ChainStorageError::InvalidArguments {
+                func: "export_headers",
+                arg: "to_inclusive",
+                message: format!("to_inclusive ({}) is less than from ({})", to_inclusive, from),
+            });
+        }
+        let count = u32::try_from(to_inclusive - from + 1).map_err(|_| ChainStorageError::InvalidArguments {
+            func: "export_headers",
+            arg: "to_inclusive",
+            message: format!("Cannot export more than {} headers", u32::MAX),
+        })?;
+        let txn = self.read_transaction()?;
+        // Headers are stored contiguously, so checking the last one up front means the count written is never wrong
+        if !lmdb_exists(&txn, &self.headers_db, &to_inclusive)? {
+            return Err(ChainStorageError::ValueNotFound {
+                entity: "BlockHeader",
+                field: "height",
+                value: to_inclusive.to_string(),
+            });
+        }
+        count.serialize(&mut writer)?;
+        for height in from..=to_inclusive {
+            let header: BlockHeader =
+                lmdb_get(&txn, &self.headers_db, &height)?.ok_or_else(|| ChainStorageError::ValueNotFound {
+                    entity: "BlockHeader",
+                    field: "height",
+                    value: height.to_string(),
+                })?;
+            let accum_data = self
+                .fetch_header_accumulated_data_by_height(&txn, height)?
+                .ok_or_else(|| ChainStorageError::ValueNotFound {
+                    entity: "BlockHeaderAccumulatedData",
+                    field: "height",
+                    value: height.to_string(),
+                })?;
+            let chain_header = ChainHeader::try_construct(header, accum_data).ok_or_else(|| {
+                ChainStorageError::DataInconsistencyDetected {
+                    function: "export_headers",
+                    details: format!("Mismatch in accumulated data at height #{}", height),
+                }
+            })?;
+            chain_header.serialize(&mut writer)?;
+        }
+        writer.flush()?;
+        Ok(count as usize)
+    }
+
+    /// Returns the number of inputs spent in the block with the given hash, without deserializing them
+    pub fn inputs_count_in_block(&self, header_hash: &HashOutput) -> Result<usize, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        lmdb_count_matching_prefix(&txn, &self.inputs_db, header_hash.as_slice())
+    }
+
+    /// Returns the number of outputs created in the block with the given hash, without deserializing them
+    pub fn outputs_count_in_block(&self, header_hash: &HashOutput) -> Result<usize, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        lmdb_count_matching_prefix(&txn, &self.utxos_db, header_hash.as_slice())
+    }
+
+    fn build_orphan_tree(
+        &self,
+        txn: &ConstTransaction<'_>,
+        hash: HashOutput,
+        tips: &HashSet<HashOutput>,
+    ) -> Result<OrphanTree, ChainStorageError> {
+        let orphan: Block = lmdb_get_maybe_compressed(txn, &self.orphans_db, hash.as_slice())?.ok_or_else(|| {
+            ChainStorageError::ValueNotFound {
+                entity: "Orphan",
+                field: "hash",
+                value: hash.to_hex(),
+            }
+        })?;
+        let mut children = Vec::new();
+        for child in lmdb_get_multiple::<_, HashOutput>(txn, &self.orphan_parent_map_index, hash.as_slice())? {
+            children.push(self.build_orphan_tree(txn, child, tips)?);
+        }
+        children.sort_by_key(|tree| tree.hash);
+        Ok(OrphanTree {
+            hash,
+            height: orphan.header.height,
+            is_chain_tip: tips.contains(&hash),
+            children,
+        })
+    }
+
+    /// Returns the number of entries in each secondary index of the database. If an index size warning threshold is
+    /// configured, a warning is logged when an index grows past it. The warning is only repeated once the index has
+    /// dropped back to the threshold and then exceeds it again.
+    pub fn index_size_report(&self) -> Result<Vec<(String, usize)>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        let sizes = self
+            .secondary_indexes()
+            .iter()
+            .map(|(name, db)| Ok((*name, lmdb_len(&txn, db)?)))
+            .collect::<Result<Vec<_>, ChainStorageError>>()?;
+        if let Some(threshold) = self.env_config.index_size_warning_threshold() {
+            let mut oversized_indexes = self
+                .oversized_indexes
+                .lock()
+                .map_err(|e| ChainStorageError::AccessError(format!("Oversized index lock poisoned: {}", e)))?;
+            for (name, size) in &sizes {
+                if *size <= threshold {
+                    oversized_indexes.remove(name);
+                } else if oversized_indexes.insert(name) {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Index {} has {} entries, which exceeds the expected maximum of {}", name, size, threshold
+                    );
+                }
+            }
+        }
+        Ok(sizes.into_iter().map(|(name, size)| (name.to_string(), size)).collect())
+    }
+
+    /// Applies every operation of the transaction in its own database transaction and returns the description and
+    /// result of each, so that the operation responsible for a failed write can be identified. Operations are applied
+    /// in order and a failure does not stop the remaining operations from being applied.
+    pub fn replay_db_transaction(&mut self, txn: DbTransaction) -> Vec<(String, Result<(), ChainStorageError>)> {
+        txn.into_operations()
+            .into_iter()
+            .map(|op| {
+                let description = op.to_string();
+                let result = self.write(DbTransaction::from(op));
+                if let Err(e) = &result {
+                    warn!(target: LOG_TARGET, "Replayed operation {} failed: {:?}", description, e);
+                }
+                (description, result)
+            })
+            .collect()
+    }
+
     #[allow(clippy::too_many_lines)]
     fn apply_db_transaction(&mut self, txn: &DbTransaction) -> Result<(), ChainStorageError> {
         #[allow(clippy::enum_glob_use)]
         use WriteOperation::*;
         let write_txn = self.write_transaction()?;
+        let mut timings = self
+            .env_config
+            .log_db_txn_timing()
+            .then(|| DbTransactionTimings::with_capacity(txn.operations().len()));
         for op in txn.operations() {
             trace!(target: LOG_TARGET, "[apply_db_transaction] WriteOperation: {}", op);
+            let timer = timings.as_ref().map(|_| Instant::now());
             match op {
                 InsertOrphanBlock(block) => self.insert_orphan_block(&write_txn, block)?,
                 InsertChainHeader { header } => {
@@ -504,14 +1025,56 @@ impl LMDBDatabase {
                 ClearAllReorgs => {
                     lmdb_clear(&write_txn, &self.reorgs)?;
                 },
+                PruneTxoIndexEntries { output_positions } => {
+                    self.prune_txo_index_entries_at_positions(&write_txn, output_positions)?;
+                },
+                PruneBlockHashIndexEntry { block_hash } => {
+                    lmdb_delete(
+                        &write_txn,
+                        &self.block_hashes_db,
+                        block_hash.as_slice(),
+                        "block_hashes_db",
+                    )?;
+                },
+            }
+            if let (Some(timings), Some(timer)) = (timings.as_mut(), timer) {
+                let elapsed = timer.elapsed();
+                debug!(target: LOG_TARGET, "[apply_db_transaction] {} took {:.2?}", op, elapsed);
+                timings.record(op, elapsed);
             }
         }
         write_txn.commit()?;
 
+        if let Some(timings) = timings {
+            debug!(target: LOG_TARGET, "[apply_db_transaction] Completed {}", timings);
+            self.last_txn_timings = Some(timings);
+        }
+
         Ok(())
     }
 
-    fn all_dbs(&self) -> [(&'static str, &DatabaseRef); 27] {
+    fn secondary_indexes(&self) -> [(&'static str, &DatabaseRef); 13] {
+        [
+            ("txos_hash_to_index_db", &self.txos_hash_to_index_db),
+            ("kernel_excess_index", &self.kernel_excess_index),
+            ("kernel_excess_sig_index", &self.kernel_excess_sig_index),
+            ("kernel_mmr_size_index", &self.kernel_mmr_size_index),
+            ("output_mmr_size_index", &self.output_mmr_size_index),
+            ("utxo_commitment_index", &self.utxo_commitment_index),
+            ("spent_commitment_index", &self.spent_commitment_index),
+            ("unique_id_index", &self.unique_id_index),
+            ("contract_index", &self.contract_index),
+            (
+                "deleted_txo_mmr_position_to_height_index",
+                &self.deleted_txo_mmr_position_to_height_index,
+            ),
+            ("orphan_parent_map_index", &self.orphan_parent_map_index),
+            ("orphan_insertion_time_index", &self.orphan_insertion_time_index),
+            ("validator_nodes_mapping", &self.validator_nodes_mapping),
+        ]
+    }
+
+    fn all_dbs(&self) -> [(&'static str, &DatabaseRef); 29] {
         [
             ("metadata_db", &self.metadata_db),
             ("headers_db", &self.headers_db),
@@ -527,6 +1090,7 @@ impl LMDBDatabase {
             ("kernel_mmr_size_index", &self.kernel_mmr_size_index),
             ("output_mmr_size_index", &self.output_mmr_size_index),
             ("utxo_commitment_index", &self.utxo_commitment_index),
+            ("spent_commitment_index", &self.spent_commitment_index),
             ("contract_index", &self.contract_index),
             ("unique_id_index", &self.unique_id_index),
             (
@@ -541,6 +1105,7 @@ impl LMDBDatabase {
             ("monero_seed_height_db", &self.monero_seed_height_db),
             ("orphan_chain_tips_db", &self.orphan_chain_tips_db),
             ("orphan_parent_map_index", &self.orphan_parent_map_index),
+            ("orphan_insertion_time_index", &self.orphan_insertion_time_index),
             ("bad_blocks", &self.bad_blocks),
             ("reorgs", &self.reorgs),
             ("validator_nodes", &self.validator_nodes),
@@ -724,6 +1289,12 @@ impl LMDBDatabase {
             ChainStorageError::ValueNotFound { .. } => Ok(()),
             _ => Err(err),
         })?;
+        lmdb_replace(
+            txn,
+            &self.spent_commitment_index,
+            input.commitment()?.as_bytes(),
+            &input.output_hash(),
+        )?;
         lmdb_insert(
             txn,
             &self.deleted_txo_mmr_position_to_height_index,
@@ -765,7 +1336,21 @@ impl LMDBDatabase {
     fn insert_orphan_block(&self, txn: &WriteTransaction<'_>, block: &Block) -> Result<(), ChainStorageError> {
         let k = block.hash();
         lmdb_insert_dup(txn, &self.orphan_parent_map_index, block.header.prev_hash.deref(), &k)?;
-        lmdb_insert(txn, &self.orphans_db, k.as_slice(), &block, "orphans_db")?;
+        // Orphans are always fetched with `lmdb_get_maybe_compressed`, which reads the format prefix and treats orphans
+        // without one as legacy uncompressed blocks
+        lmdb_insert_maybe_compressed(
+            txn,
+            &self.orphans_db,
+            k.as_slice(),
+            &block,
+            self.env_config.compress_orphans(),
+            "orphans_db",
+        )?;
+        let inserted_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        lmdb_replace(txn, &self.orphan_insertion_time_index, k.as_slice(), &inserted_at)?;
 
         Ok(())
     }
@@ -1026,7 +1611,10 @@ impl LMDBDatabase {
                 &row.mmr_position,
                 "deleted_txo_mmr_position_to_height_index",
             )?;
-            if output_rows.iter().any(|r| r.hash == output_hash) {
+            if let Some(spent_in_block) = output_rows.iter().find(|r| r.hash == output_hash) {
+                if let Some(ref output) = spent_in_block.output {
+                    self.delete_spent_commitment(txn, &output.commitment)?;
+                }
                 continue;
             }
 
@@ -1062,6 +1650,7 @@ impl LMDBDatabase {
             }
 
             trace!(target: LOG_TARGET, "Input moved to UTXO set: {}", input);
+            self.delete_spent_commitment(txn, input.commitment()?)?;
             lmdb_insert(
                 txn,
                 &self.utxo_commitment_index,
@@ -1073,6 +1662,24 @@ impl LMDBDatabase {
         Ok(())
     }
 
+    fn delete_spent_commitment(
+        &self,
+        txn: &WriteTransaction<'_>,
+        commitment: &Commitment,
+    ) -> Result<(), ChainStorageError> {
+        lmdb_delete(
+            txn,
+            &self.spent_commitment_index,
+            commitment.as_bytes(),
+            "spent_commitment_index",
+        )
+        .or_else(|err| match err {
+            // Inputs spending outputs that were pruned before the index existed have no entry
+            ChainStorageError::ValueNotFound { .. } => Ok(()),
+            _ => Err(err),
+        })
+    }
+
     fn delete_block_kernels(&self, txn: &WriteTransaction<'_>, block_hash: &[u8]) -> Result<(), ChainStorageError> {
         let kernels = lmdb_delete_keys_starting_with::<TransactionKernelRowData>(txn, &self.kernels_db, block_hash)?;
         debug!(target: LOG_TARGET, "Deleted {} kernels...", kernels.len());
@@ -1107,7 +1714,7 @@ impl LMDBDatabase {
     }
 
     fn delete_orphan(&self, txn: &WriteTransaction<'_>, hash: &HashOutput) -> Result<(), ChainStorageError> {
-        let orphan = match lmdb_get::<_, Block>(txn, &self.orphans_db, hash.as_slice())? {
+        let orphan = match lmdb_get_maybe_compressed::<_, Block>(txn, &self.orphans_db, hash.as_slice())? {
             Some(orphan) => orphan,
             None => {
                 // delete_orphan is idempotent
@@ -1156,6 +1763,15 @@ impl LMDBDatabase {
                 "orphan_header_accumulated_data_db",
             )?;
         }
+
+        if lmdb_exists(txn, &self.orphan_insertion_time_index, hash.as_slice())? {
+            lmdb_delete(
+                txn,
+                &self.orphan_insertion_time_index,
+                hash.as_slice(),
+                "orphan_insertion_time_index",
+            )?;
+        }
         lmdb_delete(txn, &self.orphans_db, hash.as_slice(), "orphans_db")?;
         Ok(())
     }
@@ -1236,16 +1852,18 @@ impl LMDBDatabase {
         let leaf_count = witness_mmr.get_leaf_count()?;
 
         // Output hashes added before inputs so that inputs can spend outputs in this transaction (0-conf and combined)
+        let output_hashes = hash_outputs(&outputs, self.env_config.body_hashing_parallelism())?;
         let mut burned_outputs = Vec::new();
         let outputs = outputs
             .into_iter()
+            .zip(output_hashes)
             .enumerate()
-            .map(|(i, output)| {
-                output_mmr.push(output.hash().to_vec())?;
-                witness_mmr.push(output.witness_hash().to_vec())?;
+            .map(|(i, (output, (output_hash, witness_hash)))| {
+                output_mmr.push(output_hash.to_vec())?;
+                witness_mmr.push(witness_hash.to_vec())?;
                 // lets check burn
                 if output.is_burned() {
-                    let index = match output_mmr.find_leaf_index(output.hash().as_slice())? {
+                    let index = match output_mmr.find_leaf_index(output_hash.as_slice())? {
                         Some(index) => {
                             debug!(target: LOG_TARGET, "Output {} burned in current block", output);
                             burned_outputs.push(output.commitment.clone());
@@ -1510,7 +2128,18 @@ impl LMDBDatabase {
     where
         T: Deref<Target = ConstTransaction<'b>>,
     {
-        DeletedBitmapModel::load(txn, &self.metadata_db)
+        let cache = self
+            .env_config
+            .cache_deleted_bitmap()
+            .then(|| &self.deleted_bitmap_cache);
+        DeletedBitmapModel::load(txn, &self.metadata_db, cache)
+    }
+
+    /// Clears the cached deleted bitmap (if any) so that the next load reads it from the database.
+    fn invalidate_deleted_bitmap_cache(&self) {
+        if let Ok(mut cache) = self.deleted_bitmap_cache.lock() {
+            *cache = None;
+        }
     }
 
     fn insert_monero_seed_height(
@@ -1542,13 +2171,7 @@ impl LMDBDatabase {
         output_positions: &[u32],
     ) -> Result<(), ChainStorageError> {
         for pos in output_positions {
-            let (_height, hash) = lmdb_first_after::<_, (u64, Vec<u8>)>(
-                write_txn,
-                &self.output_mmr_size_index,
-                &u64::from(pos + 1).to_be_bytes(),
-            )
-            .or_not_found("BlockHeader", "mmr_position", pos.to_string())?;
-            let key = OutputKey::try_from_parts(&[hash.as_slice(), pos.to_be_bytes().as_slice()])?;
+            let key = self.output_key_at_position(write_txn, *pos)?;
             debug!(target: LOG_TARGET, "Pruning output: {}", key);
             self.prune_output(write_txn, &key)?;
         }
@@ -1556,6 +2179,42 @@ impl LMDBDatabase {
         Ok(())
     }
 
+    /// Drops the output key from the hash index entries of the pruned outputs at the given positions. The output hash
+    /// and MMR position are kept, so that a pruned output is still rejected as a duplicate and its key can be derived
+    /// again from its position.
+    fn prune_txo_index_entries_at_positions(
+        &self,
+        write_txn: &WriteTransaction<'_>,
+        output_positions: &[u32],
+    ) -> Result<(), ChainStorageError> {
+        for pos in output_positions {
+            let key = self.output_key_at_position(write_txn, *pos)?;
+            let row: TransactionOutputRowData =
+                lmdb_get(write_txn, &self.utxos_db, &key).or_not_found("TransactionOutput", "key", key.to_string())?;
+            lmdb_replace(
+                write_txn,
+                &self.txos_hash_to_index_db,
+                row.hash.as_slice(),
+                &(*pos, Vec::<u8>::new()),
+            )?;
+        }
+        debug!(
+            target: LOG_TARGET,
+            "Pruned {} txo index entries",
+            output_positions.len()
+        );
+
+        Ok(())
+    }
+
+    /// The key of the output row at the given output MMR position
+    fn output_key_at_position(&self, txn: &ConstTransaction<'_>, pos: u32) -> Result<OutputKey, ChainStorageError> {
+        let (_height, hash) =
+            lmdb_first_after::<_, (u64, Vec<u8>)>(txn, &self.output_mmr_size_index, &u64::from(pos + 1).to_be_bytes())
+                .or_not_found("BlockHeader", "mmr_position", pos.to_string())?;
+        OutputKey::try_from_parts(&[hash.as_slice(), pos.to_be_bytes().as_slice()])
+    }
+
     #[allow(clippy::ptr_arg)]
     fn fetch_mmr_leaf_index(
         &self,
@@ -1573,7 +2232,7 @@ impl LMDBDatabase {
 
     #[allow(clippy::ptr_arg)]
     fn fetch_orphan(&self, txn: &ConstTransaction<'_>, hash: &HashOutput) -> Result<Option<Block>, ChainStorageError> {
-        let val: Option<Block> = lmdb_get(txn, &self.orphans_db, hash.deref())?;
+        let val: Option<Block> = lmdb_get_maybe_compressed(txn, &self.orphans_db, hash.deref())?;
         Ok(val)
     }
 
@@ -1586,6 +2245,31 @@ impl LMDBDatabase {
         lmdb_get(txn, &self.block_accumulated_data_db, &height).map_err(Into::into)
     }
 
+    fn fetch_basic_stats_in_txn(&self, txn: &ConstTransaction<'_>) -> Result<DbBasicStats, ChainStorageError> {
+        let global = self.env.stat()?;
+        let env_info = self.env.info()?;
+        let db_stats = self
+            .all_dbs()
+            .iter()
+            .map(|(name, db)| txn.db_stat(db).map(|s| (*name, s)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(DbBasicStats::new(global, env_info, db_stats))
+    }
+
+    fn fetch_total_size_stats_in_txn(&self, txn: &ConstTransaction<'_>) -> Result<DbTotalSizeStats, ChainStorageError> {
+        self.all_dbs()
+            .iter()
+            .map(|(name, db)| {
+                fetch_db_entry_sizes(txn, db).map(|(num_entries, total_key_size, total_value_size)| DbSize {
+                    name,
+                    num_entries,
+                    total_key_size,
+                    total_value_size,
+                })
+            })
+            .collect()
+    }
+
     #[allow(clippy::ptr_arg)]
     fn fetch_height_from_hash(
         &self,
@@ -1662,6 +2346,12 @@ impl LMDBDatabase {
         output_hash: &[u8],
     ) -> Result<Option<UtxoMinedInfo>, ChainStorageError> {
         if let Some((index, key)) = lmdb_get::<_, (u32, Vec<u8>)>(txn, &self.txos_hash_to_index_db, output_hash)? {
+            // The key is dropped from the entries of pruned outputs when `prune_txo_index` is enabled
+            let key = if key.is_empty() {
+                self.output_key_at_position(txn, index)?.to_vec()
+            } else {
+                key
+            };
             debug!(
                 target: LOG_TARGET,
                 "Fetch output: {} Found ({}, {})",
@@ -1720,6 +2410,19 @@ impl LMDBDatabase {
     }
 }
 
+/// Creates (or opens) the LMDB database at `path` and applies each operation of the transaction to it in isolation.
+/// This is intended for reproducing failed writes against a throwaway copy of a database, see
+/// [LMDBDatabase::replay_db_transaction].
+pub fn replay_db_transaction_in_lmdb_database<P: AsRef<Path>>(
+    path: P,
+    config: LMDBConfig,
+    consensus_manager: ConsensusManager,
+    txn: DbTransaction,
+) -> Result<Vec<(String, Result<(), ChainStorageError>)>, ChainStorageError> {
+    let mut db = create_lmdb_database(path, config, consensus_manager)?;
+    Ok(db.replay_db_transaction(txn))
+}
+
 pub fn create_recovery_lmdb_database<P: AsRef<Path>>(path: P) -> Result<(), ChainStorageError> {
     let new_path = path.as_ref().join("temp_recovery");
     let _result = fs::create_dir_all(&new_path);
@@ -1768,6 +2471,14 @@ impl BlockchainBackend for LMDBDatabase {
                         num_operations,
                         mark.elapsed()
                     );
+                    if self.env_config.index_size_warning_threshold().is_some() {
+                        if let Err(e) = self.index_size_report() {
+                            warn!(
+                                target: LOG_TARGET,
+                                "Could not check the size of the database indexes: {:?}", e
+                            );
+                        }
+                    }
 
                     return Ok(());
                 },
@@ -1777,6 +2488,8 @@ impl BlockchainBackend for LMDBDatabase {
                         "Database resize required (resized {} time(s) in this transaction)",
                         i + 1
                     );
+                    // The aborted transaction may have updated the cached deleted bitmap
+                    self.invalidate_deleted_bitmap_cache();
                     // SAFETY: This depends on the thread safety of the caller. Technically, `write` is unsafe too
                     // however we happen to know that `LmdbDatabase` is wrapped in an exclusive write lock in
                     // BlockchainDatabase, so we know there are no other threads taking out LMDB transactions when this
@@ -1787,6 +2500,7 @@ impl BlockchainBackend for LMDBDatabase {
                 },
                 Err(e) => {
                     error!(target: LOG_TARGET, "Failed to apply DB transaction: {:?}", e);
+                    self.invalidate_deleted_bitmap_cache();
                     return Err(e);
                 },
             }
@@ -1883,6 +2597,36 @@ impl BlockchainBackend for LMDBDatabase {
         }
     }
 
+    fn fetch_header_accumulated_data_range(
+        &self,
+        start: u64,
+        end_inclusive: u64,
+    ) -> Result<Vec<BlockHeaderAccumulatedData>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        let mut accumulated_data = Vec::new();
+        for height in start..=end_inclusive {
+            match self.fetch_header_accumulated_data_by_height(&txn, height)? {
+                Some(data) => accumulated_data.push(data),
+                None => break,
+            }
+        }
+        Ok(accumulated_data)
+    }
+
+    fn fetch_header_accumulated_data_by_hashes(
+        &self,
+        hashes: &[HashOutput],
+    ) -> Result<Vec<Option<BlockHeaderAccumulatedData>>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        hashes
+            .iter()
+            .map(|hash| match self.fetch_height_from_hash(&txn, hash)? {
+                Some(height) => self.fetch_header_accumulated_data_by_height(&txn, height),
+                None => Ok(None),
+            })
+            .collect()
+    }
+
     fn fetch_chain_header_in_all_chains(&self, hash: &HashOutput) -> Result<ChainHeader, ChainStorageError> {
         let txn = self.read_transaction()?;
 
@@ -2013,6 +2757,21 @@ impl BlockchainBackend for LMDBDatabase {
         }
     }
 
+    fn fetch_kernel_mmr_root_at_height(&self, height: u64) -> Result<HashOutput, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        let kernels = self
+            .fetch_block_accumulated_data(&txn, height)?
+            .map(|data| data.kernels)
+            .ok_or_else(|| ChainStorageError::ValueNotFound {
+                entity: "BlockAccumulatedData",
+                field: "height",
+                value: height.to_string(),
+            })?;
+        let kernel_mmr = PrunedKernelMmr::new(kernels);
+        let root = kernel_mmr.get_merkle_root()?;
+        Ok(HashOutput::try_from(root)?)
+    }
+
     fn fetch_block_accumulated_data_by_height(
         &self,
         height: u64,
@@ -2052,6 +2811,26 @@ impl BlockchainBackend for LMDBDatabase {
         }
     }
 
+    fn fetch_kernel_by_excess(
+        &self,
+        excess: &Commitment,
+    ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        if let Some((header_hash, mmr_position, hash)) =
+            lmdb_get::<_, (HashOutput, u32, HashOutput)>(&txn, &self.kernel_excess_index, excess.as_bytes())?
+        {
+            let key = KernelKey::try_from_parts(&[
+                header_hash.as_slice(),
+                mmr_position.to_be_bytes().as_slice(),
+                hash.as_slice(),
+            ])?;
+            Ok(lmdb_get(&txn, &self.kernels_db, &key)?
+                .map(|kernel: TransactionKernelRowData| (kernel.kernel, header_hash)))
+        } else {
+            Ok(None)
+        }
+    }
+
     fn fetch_utxos_in_block(
         &self,
         header_hash: &HashOutput,
@@ -2116,6 +2895,11 @@ impl BlockchainBackend for LMDBDatabase {
         lmdb_get::<_, HashOutput>(&txn, &self.utxo_commitment_index, commitment.as_bytes())
     }
 
+    fn is_commitment_unspent(&self, commitment: &Commitment) -> Result<bool, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        lmdb_exists(&txn, &self.utxo_commitment_index, commitment.as_bytes())
+    }
+
     fn fetch_outputs_in_block(&self, header_hash: &HashOutput) -> Result<Vec<PrunedOutput>, ChainStorageError> {
         let txn = self.read_transaction()?;
         Ok(lmdb_fetch_matching_after(&txn, &self.utxos_db, header_hash.as_slice())?
@@ -2160,6 +2944,11 @@ impl BlockchainBackend for LMDBDatabase {
         lmdb_len(&txn, &self.orphans_db)
     }
 
+    fn fetch_orphan_insertion_time(&self, hash: &HashOutput) -> Result<Option<u64>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        lmdb_get(&txn, &self.orphan_insertion_time_index, hash.as_slice())
+    }
+
     /// Finds and returns the last stored header.
     fn fetch_last_header(&self) -> Result<BlockHeader, ChainStorageError> {
         let txn = self.read_transaction()?;
@@ -2243,12 +3032,13 @@ impl BlockchainBackend for LMDBDatabase {
             return Ok(None);
         }
 
-        let orphan: Block =
-            lmdb_get(&txn, &self.orphans_db, hash.as_slice())?.ok_or_else(|| ChainStorageError::ValueNotFound {
+        let orphan: Block = lmdb_get_maybe_compressed(&txn, &self.orphans_db, hash.as_slice())?.ok_or_else(|| {
+            ChainStorageError::ValueNotFound {
                 entity: "Orphan",
                 field: "hash",
                 value: hash.to_hex(),
-            })?;
+            }
+        })?;
 
         let accumulated_data =
             lmdb_get(&txn, &self.orphan_header_accumulated_data_db, hash.as_slice())?.ok_or_else(|| {
@@ -2275,10 +3065,12 @@ impl BlockchainBackend for LMDBDatabase {
         let mut result = Vec::new();
         for hash in tips {
             let orphan: Block =
-                lmdb_get(&txn, &self.orphans_db, hash.as_slice())?.ok_or_else(|| ChainStorageError::ValueNotFound {
-                    entity: "Orphan",
-                    field: "hash",
-                    value: hash.to_hex(),
+                lmdb_get_maybe_compressed(&txn, &self.orphans_db, hash.as_slice())?.ok_or_else(|| {
+                    ChainStorageError::ValueNotFound {
+                        entity: "Orphan",
+                        field: "hash",
+                        value: hash.to_hex(),
+                    }
                 })?;
 
             let accumulated_data = lmdb_get(&txn, &self.orphan_header_accumulated_data_db, hash.as_slice())?
@@ -2310,20 +3102,22 @@ impl BlockchainBackend for LMDBDatabase {
             lmdb_get_multiple(&txn, &self.orphan_parent_map_index, parent_hash.as_slice())?;
         let mut res = Vec::with_capacity(orphan_hashes.len());
         for hash in orphan_hashes {
-            res.push(lmdb_get(&txn, &self.orphans_db, hash.as_slice())?.ok_or_else(|| {
-                ChainStorageError::ValueNotFound {
-                    entity: "Orphan",
-                    field: "hash",
-                    value: hash.to_hex(),
-                }
-            })?)
+            res.push(
+                lmdb_get_maybe_compressed(&txn, &self.orphans_db, hash.as_slice())?.ok_or_else(|| {
+                    ChainStorageError::ValueNotFound {
+                        entity: "Orphan",
+                        field: "hash",
+                        value: hash.to_hex(),
+                    }
+                })?,
+            )
         }
         Ok(res)
     }
 
     fn fetch_orphan_chain_block(&self, hash: HashOutput) -> Result<Option<ChainBlock>, ChainStorageError> {
         let txn = self.read_transaction()?;
-        match lmdb_get::<_, Block>(&txn, &self.orphans_db, hash.as_slice())? {
+        match lmdb_get_maybe_compressed::<_, Block>(&txn, &self.orphans_db, hash.as_slice())? {
             Some(block) => {
                 match lmdb_get::<_, BlockHeaderAccumulatedData>(
                     &txn,
@@ -2347,6 +3141,29 @@ impl BlockchainBackend for LMDBDatabase {
         }
     }
 
+    fn fetch_orphan_chain_depth(&self, tip_hash: &HashOutput) -> Result<u64, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        let mut depth = 0u64;
+        let mut hash = *tip_hash;
+        // An orphan chain ends where it links up with the main chain or where the parent has not been received (yet)
+        while !lmdb_exists(&txn, &self.block_hashes_db, hash.as_slice())? {
+            match lmdb_get_maybe_compressed::<_, Block>(&txn, &self.orphans_db, hash.as_slice())? {
+                Some(orphan) => {
+                    depth += 1;
+                    hash = orphan.header.prev_hash;
+                },
+                None => break,
+            }
+        }
+        trace!(
+            target: LOG_TARGET,
+            "Orphan chain depth for tip {} is {}",
+            tip_hash.to_hex(),
+            depth
+        );
+        Ok(depth)
+    }
+
     fn fetch_deleted_bitmap(&self) -> Result<DeletedBitmap, ChainStorageError> {
         let txn = self.read_transaction()?;
         let deleted_bitmap = self.load_deleted_bitmap_model(&txn)?;
@@ -2387,7 +3204,7 @@ impl BlockchainBackend for LMDBDatabase {
         {
             let read_txn = self.read_transaction()?;
 
-            orphans = lmdb_filter_map_values(&read_txn, &self.orphans_db, |block: Block| {
+            orphans = lmdb_filter_map_maybe_compressed_values(&read_txn, &self.orphans_db, |block: Block| {
                 Some((block.header.height, block.hash()))
             })?;
         }
@@ -2422,31 +3239,13 @@ impl BlockchainBackend for LMDBDatabase {
     }
 
     fn get_stats(&self) -> Result<DbBasicStats, ChainStorageError> {
-        let global = self.env.stat()?;
-        let env_info = self.env.info()?;
-
         let txn = self.read_transaction()?;
-        let db_stats = self
-            .all_dbs()
-            .iter()
-            .map(|(name, db)| txn.db_stat(db).map(|s| (*name, s)))
-            .collect::<Result<Vec<_>, _>>()?;
-        Ok(DbBasicStats::new(global, env_info, db_stats))
+        self.fetch_basic_stats_in_txn(&txn)
     }
 
     fn fetch_total_size_stats(&self) -> Result<DbTotalSizeStats, ChainStorageError> {
         let txn = self.read_transaction()?;
-        self.all_dbs()
-            .iter()
-            .map(|(name, db)| {
-                fetch_db_entry_sizes(&txn, db).map(|(num_entries, total_key_size, total_value_size)| DbSize {
-                    name,
-                    num_entries,
-                    total_key_size,
-                    total_value_size,
-                })
-            })
-            .collect()
+        self.fetch_total_size_stats_in_txn(&txn)
     }
 
     fn bad_block_exists(&self, block_hash: HashOutput) -> Result<bool, ChainStorageError> {
@@ -2454,6 +3253,16 @@ impl BlockchainBackend for LMDBDatabase {
         lmdb_exists(&txn, &self.bad_blocks, block_hash.deref())
     }
 
+    fn fetch_all_bad_blocks(&self) -> Result<Vec<(HashOutput, u64)>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        let mut bad_blocks = lmdb_filter_map_entries(&txn, &self.bad_blocks, |key, height: u64| {
+            HashOutput::try_from(key).ok().map(|hash| (hash, height))
+        })?;
+        bad_blocks
+            .sort_by(|(a_hash, a_height), (b_hash, b_height)| a_height.cmp(b_height).then_with(|| a_hash.cmp(b_hash)));
+        Ok(bad_blocks)
+    }
+
     fn clear_all_pending_headers(&self) -> Result<usize, ChainStorageError> {
         let txn = self.write_transaction()?;
         let last_header = match self.fetch_last_header_in_txn(&txn)? {
@@ -2492,11 +3301,27 @@ impl BlockchainBackend for LMDBDatabase {
 
         // Get the current epoch for the height
         let end_epoch = constants.block_height_to_epoch(height);
-        // Subtract the registration validaty period to get the start epoch
-        let start_epoch = end_epoch.saturating_sub(constants.validator_node_validity_period_epochs());
-        // Convert these back to height as validators regs are indexed by height
-        let start_height = start_epoch.as_u64() * constants.epoch_length();
-        let end_height = end_epoch.as_u64() * constants.epoch_length();
+        let (start_height, end_height) = active_validator_node_height_range(constants, end_epoch);
+        let nodes = vn_store.get_vn_set(start_height, end_height)?;
+        Ok(nodes)
+    }
+
+    fn fetch_active_validator_nodes_at_epoch(
+        &self,
+        epoch: VnEpoch,
+    ) -> Result<Vec<(PublicKey, [u8; 32])>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        let vn_store = self.validator_node_store(&txn);
+        // The epoch length is the same for all consensus constants, so the tip constants can be used to find the start
+        // height of the epoch, and from that the constants in effect for the epoch
+        let tip_height = fetch_metadata(&txn, &self.metadata_db)?.height_of_longest_chain();
+        let epoch_start_height = self
+            .consensus_manager
+            .consensus_constants(tip_height)
+            .epoch_to_block_height(epoch);
+        let constants = self.consensus_manager.consensus_constants(epoch_start_height);
+
+        let (start_height, end_height) = active_validator_node_height_range(constants, epoch);
         let nodes = vn_store.get_vn_set(start_height, end_height)?;
         Ok(nodes)
     }
@@ -2534,6 +3359,45 @@ impl BlockchainBackend for LMDBDatabase {
     }
 }
 
+/// Returns the registration height range of the validator nodes that are active in `epoch`
+fn active_validator_node_height_range(constants: &ConsensusConstants, epoch: VnEpoch) -> (u64, u64) {
+    // Subtract the registration validity period to get the start epoch
+    let start_epoch = epoch.saturating_sub(constants.validator_node_validity_period_epochs());
+    // Convert these back to height as validators regs are indexed by height
+    let start_height = start_epoch.as_u64() * constants.epoch_length();
+    let end_height = epoch.as_u64() * constants.epoch_length();
+    (start_height, end_height)
+}
+
+/// Calculates the output and witness hashes of `outputs`, split over `parallelism` threads. The hashes are returned in
+/// the order of `outputs` regardless of the parallelism.
+fn hash_outputs(
+    outputs: &[TransactionOutput],
+    parallelism: usize,
+) -> Result<Vec<(HashOutput, HashOutput)>, ChainStorageError> {
+    let hash = |output: &TransactionOutput| (output.hash(), output.witness_hash());
+    if parallelism <= 1 || outputs.len() <= 1 {
+        return Ok(outputs.iter().map(hash).collect());
+    }
+
+    let chunk_size = (outputs.len() + parallelism - 1) / parallelism;
+    thread::scope(|scope| {
+        let handles = outputs
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(hash).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+
+        let mut hashes = Vec::with_capacity(outputs.len());
+        for handle in handles {
+            let chunk_hashes = handle
+                .join()
+                .map_err(|_| ChainStorageError::CriticalError("Output hashing thread panicked".to_string()))?;
+            hashes.extend(chunk_hashes);
+        }
+        Ok(hashes)
+    })
+}
+
 // Fetch the chain metadata
 fn fetch_metadata(txn: &ConstTransaction<'_>, db: &Database) -> Result<ChainMetadata, ChainStorageError> {
     Ok(ChainMetadata::new(
@@ -2732,9 +3596,12 @@ impl fmt::Display for MetadataValue {
 /// A struct that wraps a LMDB transaction and provides an interface to valid operations that can be performed
 /// on the current deleted bitmap state of the blockchain.
 /// A deleted bitmap contains the MMR leaf indexes of spent TXOs.
+/// If a cache is given, the bitmap is loaded from and saved to the cache so that it is only deserialized once. This
+/// relies on all writes being made while holding the exclusive write lock in `BlockchainDatabase`.
 struct DeletedBitmapModel<'a, T> {
     txn: &'a T,
     db: &'a Database<'static>,
+    cache: Option<&'a Mutex<Option<DeletedBitmap>>>,
     bitmap: DeletedBitmap,
     is_dirty: bool,
 }
@@ -2742,11 +3609,31 @@ struct DeletedBitmapModel<'a, T> {
 impl<'a, 'b, T> DeletedBitmapModel<'a, T>
 where T: Deref<Target = ConstTransaction<'b>>
 {
-    pub fn load(txn: &'a T, db: &'a Database<'static>) -> Result<Self, ChainStorageError> {
-        let bitmap = fetch_deleted_bitmap(txn, db)?;
+    pub fn load(
+        txn: &'a T,
+        db: &'a Database<'static>,
+        cache: Option<&'a Mutex<Option<DeletedBitmap>>>,
+    ) -> Result<Self, ChainStorageError> {
+        let bitmap = match cache {
+            Some(cache) => {
+                let mut cache = cache
+                    .lock()
+                    .map_err(|err| ChainStorageError::AccessError(format!("Deleted bitmap cache poisoned: {}", err)))?;
+                match cache.as_ref() {
+                    Some(bitmap) => bitmap.clone(),
+                    None => {
+                        let bitmap = fetch_deleted_bitmap(txn, db)?;
+                        *cache = Some(bitmap.clone());
+                        bitmap
+                    },
+                }
+            },
+            None => fetch_deleted_bitmap(txn, db)?,
+        };
         Ok(Self {
             txn,
             db,
+            cache,
             bitmap,
             is_dirty: false,
         })
@@ -2782,18 +3669,20 @@ impl<'a, 'b> DeletedBitmapModel<'a, WriteTransaction<'b>> {
         }
 
         self.bitmap.bitmap_mut().run_optimize();
-        lmdb_replace(
-            self.txn,
-            self.db,
-            &MetadataKey::DeletedBitmap.as_u32(),
-            &MetadataValue::DeletedBitmap(self.bitmap),
-        )?;
+        let value = MetadataValue::DeletedBitmap(self.bitmap);
+        lmdb_replace(self.txn, self.db, &MetadataKey::DeletedBitmap.as_u32(), &value)?;
+        if let (Some(cache), MetadataValue::DeletedBitmap(bitmap)) = (self.cache, value) {
+            let mut cache = cache
+                .lock()
+                .map_err(|err| ChainStorageError::AccessError(format!("Deleted bitmap cache poisoned: {}", err)))?;
+            *cache = Some(bitmap);
+        }
         Ok(())
     }
 }
 
 fn run_migrations(db: &LMDBDatabase) -> Result<(), ChainStorageError> {
-    const MIGRATION_VERSION: u64 = 1;
+    const MIGRATION_VERSION: u64 = 2;
     let txn = db.read_transaction()?;
 
     let k = MetadataKey::MigrationVersion;
@@ -2810,6 +3699,31 @@ fn run_migrations(db: &LMDBDatabase) -> Result<(), ChainStorageError> {
 
     if n < MIGRATION_VERSION {
         // Add migrations here
+        if n < 2 {
+            info!(target: LOG_TARGET, "Indexing spent commitments");
+            let txn = db.write_transaction()?;
+            let output_hashes = lmdb_filter_map_values(&txn, &db.inputs_db, |row: TransactionInputRowData| {
+                Some(row.input.output_hash())
+            })?;
+            for output_hash in output_hashes {
+                let output_key =
+                    match lmdb_get::<_, (u32, Vec<u8>)>(&txn, &db.txos_hash_to_index_db, output_hash.as_slice())? {
+                        Some((_, output_key)) => output_key,
+                        None => continue,
+                    };
+                let row: Option<TransactionOutputRowData> = lmdb_get(&txn, &db.utxos_db, output_key.as_slice())?;
+                // Pruned outputs no longer have a commitment to index
+                if let Some(output) = row.and_then(|row| row.output) {
+                    lmdb_replace(
+                        &txn,
+                        &db.spent_commitment_index,
+                        output.commitment.as_bytes(),
+                        &output_hash,
+                    )?;
+                }
+            }
+            txn.commit()?;
+        }
         info!(target: LOG_TARGET, "Migrated database to version {}", MIGRATION_VERSION);
         let txn = db.write_transaction()?;
         lmdb_replace(
@@ -2823,3 +3737,155 @@ fn run_migrations(db: &LMDBDatabase) -> Result<(), ChainStorageError> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chain_storage::StaleIndexEntry, test_helpers::blockchain::create_new_blockchain};
+
+    mod audit_secondary_indexes {
+        use super::*;
+
+        #[test]
+        fn it_reports_index_entries_without_a_primary_row() {
+            let db = create_new_blockchain();
+            let access = db.db_read_access().unwrap();
+            let report = access.audit_secondary_indexes().unwrap();
+            assert!(report.is_clean());
+            assert!(report.entries_checked > 0);
+
+            let txn = access.write_transaction().unwrap();
+            lmdb_insert(
+                &txn,
+                &access.kernel_excess_index,
+                [1u8; 32].as_slice(),
+                &(HashOutput::zero(), 0u32, HashOutput::zero()),
+                "kernel_excess_index",
+            )
+            .unwrap();
+            txn.commit().unwrap();
+
+            let report = access.audit_secondary_indexes().unwrap();
+            assert!(!report.is_clean());
+            assert_eq!(report.stale_entries, vec![StaleIndexEntry {
+                index: "kernel_excess_index",
+                key: vec![1u8; 32],
+            }]);
+        }
+    }
+
+    mod index_size_report {
+        use super::*;
+
+        #[test]
+        fn it_counts_unique_id_and_contract_index_entries() {
+            let db = create_new_blockchain();
+            let access = db.db_read_access().unwrap();
+            let size_of = |name: &str| {
+                access
+                    .index_size_report()
+                    .unwrap()
+                    .into_iter()
+                    .find(|(n, _)| n == name)
+                    .unwrap()
+                    .1
+            };
+            assert_eq!(size_of("unique_id_index"), 0);
+            assert_eq!(size_of("contract_index"), 0);
+
+            let txn = access.write_transaction().unwrap();
+            for i in 0..3u8 {
+                lmdb_insert(
+                    &txn,
+                    &access.unique_id_index,
+                    [i; 32].as_slice(),
+                    &HashOutput::zero(),
+                    "unique_id_index",
+                )
+                .unwrap();
+            }
+            for i in 0..2u8 {
+                lmdb_insert(
+                    &txn,
+                    &access.contract_index,
+                    [i; 32].as_slice(),
+                    &(HashOutput::zero(), HashOutput::zero()),
+                    "contract_index",
+                )
+                .unwrap();
+            }
+            txn.commit().unwrap();
+
+            assert_eq!(size_of("unique_id_index"), 3);
+            assert_eq!(size_of("contract_index"), 2);
+        }
+    }
+
+    mod prune_txo_index_entries {
+        use tari_test_utils::paths::create_temporary_data_path;
+
+        use super::*;
+        use crate::{chain_storage::PrunedOutput, test_helpers::create_consensus_rules};
+
+        #[test]
+        fn it_drops_the_output_key_of_pruned_outputs() {
+            let path = create_temporary_data_path();
+            let mut db = create_lmdb_database(&path, LMDBConfig::default(), create_consensus_rules()).unwrap();
+            let header_hash = HashOutput::zero();
+            let output_hash = HashOutput::from([1u8; 32]);
+            let output_key =
+                OutputKey::try_from_parts(&[header_hash.as_slice(), 0u32.to_be_bytes().as_slice()]).unwrap();
+
+            let txn = db.write_transaction().unwrap();
+            lmdb_insert(
+                &txn,
+                &db.output_mmr_size_index,
+                &1u64.to_be_bytes(),
+                &(1u64, header_hash.to_vec()),
+                "output_mmr_size_index",
+            )
+            .unwrap();
+            lmdb_insert(
+                &txn,
+                &db.utxos_db,
+                &output_key,
+                &TransactionOutputRowData {
+                    output: None,
+                    header_hash,
+                    mmr_position: 0,
+                    hash: output_hash,
+                    witness_hash: HashOutput::zero(),
+                    mined_height: 1,
+                    mined_timestamp: 0,
+                },
+                "utxos_db",
+            )
+            .unwrap();
+            lmdb_insert(
+                &txn,
+                &db.txos_hash_to_index_db,
+                output_hash.as_slice(),
+                &(0u32, output_key.to_vec()),
+                "txos_hash_to_index_db",
+            )
+            .unwrap();
+            txn.commit().unwrap();
+
+            // Outputs mined at or above the height are left alone
+            assert_eq!(db.prune_txo_index_entries(1).unwrap(), 0);
+            assert_eq!(db.prune_txo_index_entries(2).unwrap(), 1);
+
+            let txn = db.read_transaction().unwrap();
+            let entry: Option<(u32, Vec<u8>)> =
+                lmdb_get(&txn, &db.txos_hash_to_index_db, output_hash.as_slice()).unwrap();
+            assert_eq!(entry, Some((0, Vec::new())));
+            // The output can still be found by its hash
+            let output = db.fetch_output_in_txn(&txn, output_hash.as_slice()).unwrap().unwrap();
+            assert!(matches!(output.output, PrunedOutput::Pruned { output_hash: hash, .. } if hash == output_hash));
+            drop(txn);
+
+            drop(db);
+            fs::remove_dir_all(&path).unwrap();
+        }
+    }
+}
@@ -20,10 +20,16 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-pub use lmdb_db::{create_lmdb_database, create_recovery_lmdb_database, LMDBDatabase};
+pub use lmdb_db::{
+    create_lmdb_database,
+    create_recovery_lmdb_database,
+    replay_db_transaction_in_lmdb_database,
+    LMDBDatabase,
+};
 use serde::{Deserialize, Serialize};
 use tari_common_types::types::HashOutput;
 use tari_crypto::hash_domain;
+pub use txn_timings::DbTransactionTimings;
 
 use crate::transactions::transaction_components::{TransactionInput, TransactionKernel, TransactionOutput};
 
@@ -34,6 +40,7 @@ pub(crate) mod helpers;
 mod lmdb;
 #[allow(clippy::module_inception)]
 mod lmdb_db;
+mod txn_timings;
 mod validator_node_store;
 
 #[derive(Serialize, Deserialize, Debug)]
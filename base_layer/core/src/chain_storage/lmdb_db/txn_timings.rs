@@ -0,0 +1,80 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{fmt, time::Duration};
+
+use crate::chain_storage::WriteOperation;
+
+/// The number of operations listed in the summary of a transaction's slowest operations
+const NUM_SLOWEST_OPERATIONS: usize = 5;
+
+/// The time taken by each `WriteOperation` of a `DbTransaction`. These are only collected when `log_db_txn_timing` is
+/// enabled in the LMDB config.
+#[derive(Debug, Clone, Default)]
+pub struct DbTransactionTimings {
+    operations: Vec<(String, Duration)>,
+}
+
+impl DbTransactionTimings {
+    pub(super) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            operations: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub(super) fn record(&mut self, operation: &WriteOperation, elapsed: Duration) {
+        self.operations.push((operation.to_string(), elapsed));
+    }
+
+    /// The description and duration of each operation, in the order that they were applied
+    pub fn operations(&self) -> &[(String, Duration)] {
+        &self.operations
+    }
+
+    /// The total time spent applying the operations
+    pub fn total(&self) -> Duration {
+        self.operations.iter().map(|(_, elapsed)| *elapsed).sum()
+    }
+
+    /// Returns up to `n` of the slowest operations, slowest first
+    pub fn slowest(&self, n: usize) -> Vec<&(String, Duration)> {
+        let mut operations = self.operations.iter().collect::<Vec<_>>();
+        operations.sort_by(|a, b| b.1.cmp(&a.1));
+        operations.truncate(n);
+        operations
+    }
+}
+
+impl fmt::Display for DbTransactionTimings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} operation(s) in {:.2?}, slowest:",
+            self.operations.len(),
+            self.total()
+        )?;
+        for (operation, elapsed) in self.slowest(NUM_SLOWEST_OPERATIONS) {
+            write!(f, " [{} {:.2?}]", operation, elapsed)?;
+        }
+        Ok(())
+    }
+}
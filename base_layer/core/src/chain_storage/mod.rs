@@ -41,6 +41,7 @@ pub use blockchain_database::{
     fetch_header,
     fetch_headers,
     fetch_target_difficulty_for_next_block,
+    BlockRoots,
     BlockchainDatabase,
     BlockchainDatabaseConfig,
     MmrRoots,
@@ -68,13 +69,19 @@ mod pruned_output;
 pub use pruned_output::PrunedOutput;
 
 mod reorg;
-pub use reorg::Reorg;
+pub use reorg::{Reorg, ReorgEvent};
 
 mod lmdb_db;
-pub use lmdb_db::{create_lmdb_database, create_recovery_lmdb_database, LMDBDatabase};
+pub use lmdb_db::{
+    create_lmdb_database,
+    create_recovery_lmdb_database,
+    replay_db_transaction_in_lmdb_database,
+    DbTransactionTimings,
+    LMDBDatabase,
+};
 
 mod stats;
-pub use stats::{DbBasicStats, DbSize, DbStat, DbTotalSizeStats};
+pub use stats::{DbBasicStats, DbHealthReport, DbSize, DbStat, DbTotalSizeStats, IndexAuditReport, StaleIndexEntry};
 
 mod target_difficulties;
 mod utxo_mined_info;
@@ -86,3 +93,12 @@ pub use active_validator_node::ValidatorNodeEntry;
 
 mod template_registation;
 pub use template_registation::TemplateRegistrationEntry;
+
+mod transaction_graph;
+pub use transaction_graph::TransactionGraph;
+
+mod orphan_forest;
+pub use orphan_forest::OrphanTree;
+
+mod output_lifecycle;
+pub use output_lifecycle::{OutputLifecycle, OutputLifecycleEvent};
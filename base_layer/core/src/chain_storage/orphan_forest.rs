@@ -0,0 +1,47 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use tari_common_types::types::HashOutput;
+
+/// An orphan block and the orphans that build on it. The roots of the forest returned by
+/// `LMDBDatabase::dump_orphan_forest` are orphans whose parents are not in the orphan pool: their parent is either on
+/// the main chain or has not been received.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrphanTree {
+    pub hash: HashOutput,
+    pub height: u64,
+    /// True if this orphan is registered as the tip of an orphan chain
+    pub is_chain_tip: bool,
+    pub children: Vec<OrphanTree>,
+}
+
+impl OrphanTree {
+    /// The number of orphans in this tree, including the root
+    pub fn size(&self) -> usize {
+        1 + self.children.iter().map(OrphanTree::size).sum::<usize>()
+    }
+
+    /// The number of blocks on the longest path from the root to a leaf, including the root
+    pub fn depth(&self) -> usize {
+        1 + self.children.iter().map(OrphanTree::depth).max().unwrap_or(0)
+    }
+}
@@ -0,0 +1,48 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use tari_common_types::types::{BlockHash, HashOutput};
+
+/// The point in the chain at which an output was created or spent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputLifecycleEvent {
+    pub height: u64,
+    pub header_hash: BlockHash,
+}
+
+/// The lifecycle of an output on the main chain. This is available for as long as the output is indexed, even once
+/// the output body itself has been pruned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputLifecycle {
+    pub output_hash: HashOutput,
+    pub mmr_position: u32,
+    pub created: OutputLifecycleEvent,
+    /// The block the output was spent in, or `None` if it is unspent
+    pub spent: Option<OutputLifecycleEvent>,
+    pub is_pruned: bool,
+}
+
+impl OutputLifecycle {
+    pub fn is_spent(&self) -> bool {
+        self.spent.is_some()
+    }
+}
@@ -53,3 +53,34 @@ impl Reorg {
         }
     }
 }
+
+/// Published by the blockchain database whenever adding blocks reorganises the main chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReorgEvent {
+    /// The height of the last block the old and the new chain have in common
+    pub fork_height: u64,
+    /// The hash of the tip of the chain before the reorg
+    pub old_tip: HashOutput,
+    /// The hash of the tip of the chain after the reorg
+    pub new_tip: HashOutput,
+    /// The number of blocks that were removed from the main chain
+    pub depth: u64,
+}
+
+impl ReorgEvent {
+    /// Expects the added blocks ordered from lowest to highest height and the removed blocks from highest to lowest,
+    /// as in `BlockAddResult::ChainReorg`. Returns `None` if no blocks were added.
+    pub fn from_reorged_blocks(added: &[Arc<ChainBlock>], removed: &[Arc<ChainBlock>]) -> Option<Self> {
+        let fork_block = added.first()?;
+        let new_tip = added.last()?;
+        Some(Self {
+            fork_height: fork_block.height().saturating_sub(1),
+            old_tip: removed
+                .first()
+                .map(|b| *b.hash())
+                .unwrap_or(fork_block.header().prev_hash),
+            new_tip: *new_tip.hash(),
+            depth: removed.len() as u64,
+        })
+    }
+}
@@ -26,6 +26,7 @@ use std::{
 };
 
 use lmdb_zero as lmdb;
+use tari_utilities::hex::to_hex;
 
 #[derive(Debug, Clone)]
 pub struct DbBasicStats {
@@ -173,6 +174,87 @@ impl FromIterator<DbSize> for DbTotalSizeStats {
     }
 }
 
+/// An aggregate view of the health of the blockchain database, intended for operator dashboards.
+#[derive(Debug, Clone)]
+pub struct DbHealthReport {
+    /// Basic LMDB statistics for the environment and each database
+    pub stats: DbBasicStats,
+    /// Entry and byte sizes for each database
+    pub total_size_stats: DbTotalSizeStats,
+    /// Number of blocks in the orphan pool
+    pub orphan_count: usize,
+    /// Number of blocks in the bad block list
+    pub bad_block_count: usize,
+    /// Number of reorgs recorded
+    pub reorg_count: usize,
+    /// The largest number of blocks removed by a single recorded reorg
+    pub max_reorg_depth: u64,
+    /// True if the header stored at the chain metadata height hashes to the chain metadata best block
+    pub tip_matches_metadata: bool,
+}
+
+impl Display for DbHealthReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "orphans: {}, bad blocks: {}, reorgs: {} (max depth {}), tip matches metadata: {}",
+            self.orphan_count, self.bad_block_count, self.reorg_count, self.max_reorg_depth, self.tip_matches_metadata
+        )?;
+        write!(f, "{}", self.stats)
+    }
+}
+
+/// The result of cross-checking the secondary indexes of the database against their primary tables.
+#[derive(Debug, Clone, Default)]
+pub struct IndexAuditReport {
+    /// Number of index entries that were checked
+    pub entries_checked: usize,
+    /// Index entries that have no backing row in their primary table
+    pub stale_entries: Vec<StaleIndexEntry>,
+}
+
+impl IndexAuditReport {
+    pub(super) fn record(&mut self, index: &'static str, key: Vec<u8>, has_primary_row: bool) {
+        self.entries_checked += 1;
+        if !has_primary_row {
+            self.stale_entries.push(StaleIndexEntry { index, key });
+        }
+    }
+
+    /// Returns true if no stale index entries were found
+    pub fn is_clean(&self) -> bool {
+        self.stale_entries.is_empty()
+    }
+}
+
+impl Display for IndexAuditReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "index entries checked: {}, stale: {}",
+            self.entries_checked,
+            self.stale_entries.len()
+        )?;
+        for entry in &self.stale_entries {
+            write!(f, "\n{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+/// A secondary index entry that has no backing row in its primary table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleIndexEntry {
+    pub index: &'static str,
+    pub key: Vec<u8>,
+}
+
+impl Display for StaleIndexEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.index, to_hex(&self.key))
+    }
+}
+
 /// Configuration information about an environment.
 #[derive(Debug, Clone, Copy)]
 pub struct EnvInfo {
@@ -22,27 +22,41 @@
 // DAMAGE.
 use std::sync::Arc;
 
+use tari_common::configuration::Network;
+
 use crate::{
     blocks::{Block, BlockHeader, BlockHeaderAccumulatedData, ChainHeader, NewBlockTemplate},
-    chain_storage::{BlockchainDatabase, ChainStorageError},
+    chain_storage::{BlockchainDatabase, BlockchainDatabaseConfig, ChainStorageError, Validators},
+    consensus::ConsensusManager,
     proof_of_work::{AchievedTargetDifficulty, Difficulty, PowAlgorithm},
     test_helpers::{
-        blockchain::{create_new_blockchain, TempDatabase},
+        blockchain::{create_new_blockchain, create_store_with_consensus_and_validators_and_config, TempDatabase},
         create_block,
         BlockSpec,
     },
     transactions::{
-        tari_amount::T,
+        tari_amount::{MicroTari, T},
         test_helpers::schema_to_transaction,
         transaction_components::{Transaction, UnblindedOutput},
     },
     txn_schema,
+    validation::mocks::MockValidator,
 };
 
 fn setup() -> BlockchainDatabase<TempDatabase> {
     create_new_blockchain()
 }
 
+fn setup_with_config(config: BlockchainDatabaseConfig) -> BlockchainDatabase<TempDatabase> {
+    let validators = Validators::new(
+        MockValidator::new(true),
+        MockValidator::new(true),
+        MockValidator::new(true),
+    );
+    let rules = ConsensusManager::builder(Network::LocalNet).build();
+    create_store_with_consensus_and_validators_and_config(rules, validators, config)
+}
+
 fn create_next_block(
     db: &BlockchainDatabase<TempDatabase>,
     prev_block: &Block,
@@ -259,6 +273,102 @@ mod fetch_headers {
     }
 }
 
+mod fetch_chain_headers {
+    use super::*;
+
+    #[test]
+    fn it_fetches_a_range_within_the_limit_in_batches() {
+        let db = setup_with_config(BlockchainDatabaseConfig {
+            max_header_batch: 2,
+            max_header_range: 6,
+            ..Default::default()
+        });
+        add_many_chained_blocks(6, &db);
+        let headers = db.fetch_chain_headers(1..=6).unwrap();
+        assert_eq!(headers.len(), 6);
+        for (i, header) in headers.iter().enumerate() {
+            assert_eq!(header.height(), i as u64 + 1);
+        }
+        let headers = db.fetch_chain_headers(4..).unwrap();
+        assert_eq!(headers.len(), 3);
+        assert_eq!(headers[2].height(), 6);
+    }
+
+    #[test]
+    fn it_rejects_an_oversized_range() {
+        let db = setup_with_config(BlockchainDatabaseConfig {
+            max_header_batch: 2,
+            max_header_range: 6,
+            ..Default::default()
+        });
+        add_many_chained_blocks(6, &db);
+        let err = db.fetch_chain_headers_within_limit(0..=6).unwrap_err();
+        assert!(matches!(err, ChainStorageError::RangeTooLarge { requested: 7, max: 6 }));
+        let err = db.fetch_chain_headers_within_limit(..).unwrap_err();
+        assert!(matches!(err, ChainStorageError::RangeTooLarge { requested: 7, max: 6 }));
+        let headers = db.fetch_chain_headers_within_limit(1..=6).unwrap();
+        assert_eq!(headers.len(), 6);
+    }
+
+    #[test]
+    fn it_fetches_a_range_larger_than_the_limit_for_local_callers() {
+        let db = setup_with_config(BlockchainDatabaseConfig {
+            max_header_batch: 2,
+            max_header_range: 6,
+            ..Default::default()
+        });
+        add_many_chained_blocks(9, &db);
+        let headers = db.fetch_chain_headers(0..=9).unwrap();
+        assert_eq!(headers.len(), 10);
+        for (i, header) in headers.iter().enumerate() {
+            assert_eq!(header.height(), i as u64);
+        }
+        for pair in headers.windows(2) {
+            assert_eq!(pair[1].header().prev_hash, *pair[0].hash());
+        }
+        let headers = db.fetch_chain_headers(..).unwrap();
+        assert_eq!(headers.len(), 10);
+    }
+}
+
+mod fetch_blocks_in_range {
+    use super::*;
+
+    #[test]
+    fn it_reconstructs_the_blocks_in_the_range() {
+        let db = setup();
+        let (blocks, _) = add_many_chained_blocks(4, &db);
+        let chain_blocks = db.fetch_blocks_in_range(1, 3).unwrap();
+        assert_eq!(chain_blocks.len(), 3);
+        for (chain_block, block) in chain_blocks.iter().zip(&blocks) {
+            let header = db.fetch_header(chain_block.height()).unwrap().unwrap();
+            assert_eq!(*chain_block.hash(), header.hash());
+            assert_eq!(chain_block.block().hash(), header.hash());
+            assert_eq!(chain_block.block().hash(), block.hash());
+        }
+    }
+
+    #[test]
+    fn it_rejects_an_oversized_range() {
+        let db = setup_with_config(BlockchainDatabaseConfig {
+            max_block_range: 2,
+            ..Default::default()
+        });
+        add_many_chained_blocks(4, &db);
+        assert_eq!(db.fetch_blocks_in_range(2, 3).unwrap().len(), 2);
+        let err = db.fetch_blocks_in_range(1, 3).unwrap_err();
+        assert!(matches!(err, ChainStorageError::RangeTooLarge { requested: 3, max: 2 }));
+    }
+
+    #[test]
+    fn it_errors_if_the_range_is_beyond_the_tip() {
+        let db = setup();
+        add_many_chained_blocks(2, &db);
+        let err = db.fetch_blocks_in_range(1, 3).unwrap_err();
+        assert!(matches!(err, ChainStorageError::ValueNotFound { .. }));
+    }
+}
+
 mod find_headers_after_hash {
     use tari_common_types::types::FixedHash;
 
@@ -312,6 +422,157 @@ mod find_headers_after_hash {
     }
 }
 
+mod fetch_accumulated_data_range {
+    use super::*;
+
+    #[test]
+    fn it_returns_contiguous_accumulated_data_matching_per_height_fetches() {
+        let db = setup();
+        add_many_chained_blocks(5, &db);
+        let accumulated_data = db.fetch_accumulated_data_range(1, 4).unwrap();
+        assert_eq!(accumulated_data.len(), 4);
+        for (i, data) in accumulated_data.iter().enumerate() {
+            let header = db.fetch_header(i as u64 + 1).unwrap().unwrap();
+            assert_eq!(data.hash, header.hash());
+            assert_eq!(*data, db.fetch_header_accumulated_data(header.hash()).unwrap().unwrap());
+            if i > 0 {
+                assert_eq!(header.prev_hash, accumulated_data[i - 1].hash);
+                assert!(data.total_accumulated_difficulty > accumulated_data[i - 1].total_accumulated_difficulty);
+            }
+        }
+    }
+
+    #[test]
+    fn it_stops_at_the_tip() {
+        let db = setup();
+        add_many_chained_blocks(2, &db);
+        let accumulated_data = db.fetch_accumulated_data_range(1, 10).unwrap();
+        assert_eq!(accumulated_data.len(), 2);
+        assert!(db.fetch_accumulated_data_range(3, 10).unwrap().is_empty());
+    }
+}
+
+mod fetch_header_accumulated_data_by_hashes {
+    use tari_common_types::types::HashOutput;
+
+    use super::*;
+
+    #[test]
+    fn it_returns_index_aligned_results() {
+        let db = setup();
+        let (blocks, _) = add_many_chained_blocks(3, &db);
+        let unknown = HashOutput::from([0xffu8; 32]);
+        let hashes = vec![blocks[2].hash(), unknown, blocks[0].hash(), blocks[1].hash()];
+
+        let accumulated_data = db.fetch_header_accumulated_data_by_hashes(hashes.clone()).unwrap();
+        assert_eq!(accumulated_data.len(), hashes.len());
+        assert!(accumulated_data[1].is_none());
+        for (hash, data) in hashes.iter().zip(&accumulated_data) {
+            if *hash == unknown {
+                continue;
+            }
+            let data = data.as_ref().unwrap();
+            assert_eq!(data.hash, *hash);
+            assert_eq!(*data, db.fetch_header_accumulated_data(*hash).unwrap().unwrap());
+        }
+        assert!(db.fetch_header_accumulated_data_by_hashes(vec![]).unwrap().is_empty());
+    }
+}
+
+mod fetch_kernel_excess_sum_in_block {
+    use tari_common_types::types::HashOutput;
+
+    use super::*;
+
+    #[test]
+    fn it_matches_the_accumulated_kernel_sum_delta() {
+        let db = setup();
+        let (blocks, outputs) = add_many_chained_blocks(1, &db);
+        let (txns, _) = schema_to_transaction(&[txn_schema!(from: vec![outputs[0].clone()], to: vec![50 * T])]);
+        let (block, _) = create_next_block(&db, &blocks[0], txns);
+        db.add_block(block.clone()).unwrap().assert_added();
+        assert_eq!(block.body.kernels().len(), 2);
+
+        let prev_kernel_sum = db
+            .fetch_block_accumulated_data(blocks[0].hash())
+            .unwrap()
+            .kernel_sum()
+            .clone();
+        let kernel_sum = db
+            .fetch_block_accumulated_data(block.hash())
+            .unwrap()
+            .kernel_sum()
+            .clone();
+        let excess_sum = db.fetch_kernel_excess_sum_in_block(&block.hash()).unwrap();
+        assert_eq!(&prev_kernel_sum + &excess_sum, kernel_sum);
+    }
+
+    #[test]
+    fn it_errors_for_an_unknown_block() {
+        let db = setup();
+        assert!(db
+            .fetch_kernel_excess_sum_in_block(&HashOutput::from([0xffu8; 32]))
+            .is_err());
+    }
+}
+
+mod fetch_headers_since_hash {
+    use tari_common_types::types::FixedHash;
+
+    use super::*;
+
+    #[test]
+    fn it_returns_contiguous_headers_after_the_hash() {
+        let db = setup();
+        add_many_chained_blocks(5, &db);
+        let known_hash = db.fetch_block(2, true).unwrap().block().hash();
+        let (headers, is_found) = db.fetch_headers_since_hash(known_hash, 2).unwrap();
+        assert!(is_found);
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers[0].prev_hash, known_hash);
+        assert_eq!(headers[0].height, 3);
+        assert_eq!(headers[1].prev_hash, headers[0].hash());
+        assert_eq!(headers[1].height, 4);
+
+        let (headers, _) = db.fetch_headers_since_hash(known_hash, 10).unwrap();
+        assert_eq!(headers.len(), 3);
+        assert_eq!(headers[2].height, 5);
+    }
+
+    #[test]
+    fn it_distinguishes_the_tip_from_an_unknown_hash() {
+        let db = setup();
+        add_many_chained_blocks(2, &db);
+        let tip_hash = db.fetch_last_header().unwrap().hash();
+        let (headers, is_found) = db.fetch_headers_since_hash(tip_hash, 10).unwrap();
+        assert!(headers.is_empty());
+        assert!(is_found);
+
+        let (headers, is_found) = db.fetch_headers_since_hash(FixedHash::zero(), 10).unwrap();
+        assert!(headers.is_empty());
+        assert!(!is_found);
+    }
+
+    #[test]
+    fn it_clamps_the_request_to_the_tip_and_the_max_header_range() {
+        let db = setup_with_config(BlockchainDatabaseConfig {
+            max_header_range: 3,
+            ..Default::default()
+        });
+        add_many_chained_blocks(5, &db);
+        let genesis_hash = db.fetch_block(0, true).unwrap().block().hash();
+        let (headers, is_found) = db.fetch_headers_since_hash(genesis_hash, usize::MAX).unwrap();
+        assert!(is_found);
+        assert_eq!(headers.len(), 3);
+        assert_eq!(headers[2].height, 3);
+
+        let known_hash = db.fetch_block(4, true).unwrap().block().hash();
+        let (headers, _) = db.fetch_headers_since_hash(known_hash, usize::MAX).unwrap();
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[0].height, 5);
+    }
+}
+
 mod fetch_block_hashes_from_header_tip {
     use super::*;
 
@@ -622,4 +883,1137 @@ mod validator_node_merkle_root {
         let tip = db.fetch_tip_header().unwrap();
         assert_eq!(tip.header().validator_node_mr, merkle_root);
     }
+
+    #[test]
+    fn it_fetches_the_same_active_set_by_epoch_and_height() {
+        let db = setup();
+        let (blocks, outputs) = add_many_chained_blocks(1, &db);
+
+        let (sk, public_key) = PublicKey::random_keypair(&mut OsRng);
+        let signature = ValidatorNodeSignature::sign(&sk, &[]);
+        let features =
+            OutputFeatures::for_validator_node_registration(public_key.clone(), signature.signature().clone());
+        let (tx, _outputs) = schema_to_transaction(&[txn_schema!(
+            from: vec![outputs[0].clone()],
+            to: vec![50 * T],
+            features: features
+        )]);
+        let (block, _) = create_next_block(&db, &blocks[0], tx);
+        db.add_block(block).unwrap().assert_added();
+
+        let consts = db.consensus_constants().unwrap();
+        add_many_chained_blocks(consts.epoch_length() as usize, &db);
+
+        let height = consts.epoch_length();
+        let by_height = db.fetch_active_validator_nodes(height).unwrap();
+        let by_epoch = db
+            .fetch_active_validator_nodes_at_epoch(consts.block_height_to_epoch(height))
+            .unwrap();
+        assert_eq!(by_epoch, by_height);
+        assert!(by_epoch.iter().any(|(pk, _)| *pk == public_key));
+    }
+}
+
+mod fetch_transaction_graph_by_kernel {
+    use super::*;
+
+    #[test]
+    fn it_returns_the_inputs_and_outputs_of_the_transaction() {
+        let db = setup();
+        let (blocks, outputs) = add_many_chained_blocks(1, &db);
+        let (txns, _) = schema_to_transaction(&[txn_schema!(from: vec![outputs[0].clone()], to: vec![50 * T])]);
+        let tx = txns[0].clone();
+        let (block, _) = create_next_block(&db, &blocks[0], txns);
+        db.add_block(block.clone()).unwrap().assert_added();
+
+        let kernel = tx.body.kernels()[0].clone();
+        let graph = db.fetch_transaction_graph_by_kernel(&kernel.excess_sig).unwrap();
+        assert_eq!(graph.kernel, kernel);
+        assert_eq!(graph.header_hash, block.hash());
+        assert_eq!(graph.height, 2);
+
+        let input_hashes = graph.input_hashes();
+        assert_eq!(input_hashes.len(), tx.body.inputs().len());
+        assert!(tx.body.inputs().iter().all(|i| input_hashes.contains(&i.output_hash())));
+        // The coinbase output of the block is not part of the transaction
+        let output_hashes = graph.output_hashes();
+        assert_eq!(output_hashes.len(), tx.body.outputs().len());
+        assert!(tx.body.outputs().iter().all(|o| output_hashes.contains(&o.hash())));
+    }
+
+    #[test]
+    fn it_errors_for_an_unknown_kernel() {
+        let db = setup();
+        let (_, outputs) = add_many_chained_blocks(1, &db);
+        let (txns, _) = schema_to_transaction(&[txn_schema!(from: vec![outputs[0].clone()], to: vec![50 * T])]);
+        let err = db
+            .fetch_transaction_graph_by_kernel(&txns[0].body.kernels()[0].excess_sig)
+            .unwrap_err();
+        assert!(matches!(err, ChainStorageError::ValueNotFound { .. }));
+    }
+}
+
+mod fetch_output_lifecycle {
+    use super::*;
+
+    #[test]
+    fn it_reports_the_creation_and_spend_of_an_output() {
+        let db = setup();
+        let (blocks, outputs) = add_many_chained_blocks(1, &db);
+        let coinbase_hash = blocks[0].body.outputs()[0].hash();
+        let lifecycle = db.fetch_output_lifecycle(&coinbase_hash).unwrap();
+        assert_eq!(lifecycle.output_hash, coinbase_hash);
+        assert_eq!(lifecycle.created.height, 1);
+        assert_eq!(lifecycle.created.header_hash, blocks[0].hash());
+        assert!(!lifecycle.is_spent());
+        assert!(!lifecycle.is_pruned);
+
+        let (txns, _) = schema_to_transaction(&[txn_schema!(from: vec![outputs[0].clone()], to: vec![50 * T])]);
+        let tx = txns[0].clone();
+        let (block, _) = create_next_block(&db, &blocks[0], txns);
+        db.add_block(block.clone()).unwrap().assert_added();
+
+        let lifecycle = db.fetch_output_lifecycle(&coinbase_hash).unwrap();
+        assert_eq!(lifecycle.created.height, 1);
+        assert_eq!(lifecycle.created.header_hash, blocks[0].hash());
+        let spent = lifecycle.spent.unwrap();
+        assert_eq!(spent.height, 2);
+        assert_eq!(spent.header_hash, block.hash());
+
+        let new_output_hash = tx.body.outputs()[0].hash();
+        let lifecycle = db.fetch_output_lifecycle(&new_output_hash).unwrap();
+        assert_eq!(lifecycle.created.height, 2);
+        assert_eq!(lifecycle.created.header_hash, block.hash());
+        assert!(lifecycle.spent.is_none());
+    }
+
+    #[test]
+    fn it_errors_for_an_unknown_output() {
+        let db = setup();
+        let (_, outputs) = add_many_chained_blocks(1, &db);
+        let (txns, _) = schema_to_transaction(&[txn_schema!(from: vec![outputs[0].clone()], to: vec![50 * T])]);
+        let err = db
+            .fetch_output_lifecycle(&txns[0].body.outputs()[0].hash())
+            .unwrap_err();
+        assert!(matches!(err, ChainStorageError::ValueNotFound { .. }));
+    }
+}
+
+mod fetch_orphan_chain_depth {
+    use super::*;
+
+    #[test]
+    fn it_counts_connected_orphans() {
+        let db = setup();
+        let other_db = setup();
+        let (blocks, _) = add_many_chained_blocks(6, &other_db);
+        // Skip the first block so that the remaining blocks cannot connect to the main chain
+        for block in blocks.iter().skip(1) {
+            db.add_block(block.clone()).unwrap().assert_orphaned();
+        }
+
+        assert_eq!(db.fetch_orphan_chain_depth(blocks[5].hash()).unwrap(), 5);
+        assert_eq!(db.fetch_orphan_chain_depth(blocks[3].hash()).unwrap(), 3);
+    }
+
+    #[test]
+    fn it_returns_zero_for_main_chain_blocks() {
+        let db = setup();
+        let (blocks, _) = add_many_chained_blocks(2, &db);
+        assert_eq!(db.fetch_orphan_chain_depth(blocks[1].hash()).unwrap(), 0);
+    }
+}
+
+mod dump_orphan_forest {
+    use super::*;
+
+    #[test]
+    fn it_reconstructs_the_orphan_parent_child_structure() {
+        let db = setup();
+        let other_db = setup();
+        let (blocks, _) = add_many_chained_blocks(5, &other_db);
+        // A fork off of the third block
+        let fork_db = setup();
+        for block in blocks.iter().take(3) {
+            fork_db.add_block(block.clone()).unwrap().assert_added();
+        }
+        let (fork_blocks, _) = add_many_chained_blocks(2, &fork_db);
+        // An unrelated chain
+        let (unrelated_blocks, _) = add_many_chained_blocks(3, &setup());
+
+        // Skip the first block so that none of the blocks can connect to the main chain
+        for block in blocks
+            .iter()
+            .skip(1)
+            .chain(fork_blocks.iter())
+            .chain(unrelated_blocks.iter().skip(2))
+        {
+            db.add_block(block.clone()).unwrap().assert_orphaned();
+        }
+
+        let forest = db.db_read_access().unwrap().dump_orphan_forest().unwrap();
+        assert_eq!(forest.len(), 2);
+
+        let tree = &forest[0];
+        assert_eq!(tree.hash, blocks[1].hash());
+        assert_eq!(tree.height, 2);
+        assert_eq!(tree.size(), 6);
+        assert_eq!(tree.depth(), 4);
+        assert_eq!(tree.children.len(), 1);
+        let fork_point = &tree.children[0];
+        assert_eq!(fork_point.hash, blocks[2].hash());
+        let mut child_hashes = fork_point.children.iter().map(|c| c.hash).collect::<Vec<_>>();
+        child_hashes.sort();
+        let mut expected = vec![blocks[3].hash(), fork_blocks[0].hash()];
+        expected.sort();
+        assert_eq!(child_hashes, expected);
+        for child in &fork_point.children {
+            assert_eq!(child.height, 4);
+            assert_eq!(child.size(), 2);
+        }
+
+        let tree = &forest[1];
+        assert_eq!(tree.hash, unrelated_blocks[2].hash());
+        assert_eq!(tree.size(), 1);
+    }
+
+    #[test]
+    fn it_is_empty_without_orphans() {
+        let db = setup();
+        add_many_chained_blocks(2, &db);
+        assert!(db.db_read_access().unwrap().dump_orphan_forest().unwrap().is_empty());
+    }
+}
+
+mod find_heaviest_orphan_chain {
+    use super::*;
+    use crate::chain_storage::DbTransaction;
+
+    fn insert_chain_as_orphans(db: &BlockchainDatabase<TempDatabase>, source: &BlockchainDatabase<TempDatabase>) {
+        let tip_height = source.get_height().unwrap();
+        let mut txn = DbTransaction::new();
+        for height in 1..=tip_height {
+            let block = source
+                .fetch_block(height, true)
+                .unwrap()
+                .try_into_chain_block()
+                .unwrap();
+            txn.insert_chained_orphan(Arc::new(block));
+        }
+        txn.insert_orphan_chain_tip(*source.fetch_tip_header().unwrap().hash());
+        db.write(txn).unwrap();
+    }
+
+    #[test]
+    fn it_finds_an_orphan_chain_heavier_than_the_tip() {
+        let db = setup();
+        add_many_chained_blocks(2, &db);
+        let heavier_db = setup();
+        add_many_chained_blocks(5, &heavier_db);
+        let lighter_db = setup();
+        add_many_chained_blocks(1, &lighter_db);
+        insert_chain_as_orphans(&db, &heavier_db);
+        insert_chain_as_orphans(&db, &lighter_db);
+
+        let (header, total_work) = db
+            .db_read_access()
+            .unwrap()
+            .find_heaviest_orphan_chain()
+            .unwrap()
+            .unwrap();
+        let expected_tip = heavier_db.fetch_tip_header().unwrap();
+        assert_eq!(header.hash(), expected_tip.hash());
+        assert_eq!(header.height(), 5);
+        assert_eq!(
+            total_work,
+            heavier_db.get_chain_metadata().unwrap().accumulated_difficulty()
+        );
+        assert!(total_work > db.get_chain_metadata().unwrap().accumulated_difficulty());
+    }
+
+    #[test]
+    fn it_returns_none_if_no_orphan_chain_is_heavier() {
+        let db = setup();
+        add_many_chained_blocks(5, &db);
+        let lighter_db = setup();
+        add_many_chained_blocks(2, &lighter_db);
+        insert_chain_as_orphans(&db, &lighter_db);
+        assert!(db
+            .db_read_access()
+            .unwrap()
+            .find_heaviest_orphan_chain()
+            .unwrap()
+            .is_none());
+    }
+}
+
+mod export_headers {
+    use borsh::BorshDeserialize;
+
+    use super::*;
+
+    #[test]
+    fn it_exports_the_headers_in_the_range() {
+        let db = setup();
+        add_many_chained_blocks(5, &db);
+        let mut buf = Vec::new();
+        let count = db.db_read_access().unwrap().export_headers(1, 4, &mut buf).unwrap();
+        assert_eq!(count, 4);
+
+        let headers = Vec::<ChainHeader>::try_from_slice(&buf).unwrap();
+        assert_eq!(headers.len(), 4);
+        for (header, height) in headers.iter().zip(1..=4) {
+            assert_eq!(*header, db.fetch_chain_header(height).unwrap());
+        }
+    }
+
+    #[test]
+    fn it_errors_for_an_invalid_range() {
+        let db = setup();
+        add_many_chained_blocks(2, &db);
+        let db = db.db_read_access().unwrap();
+        assert!(db.export_headers(2, 1, Vec::new()).is_err());
+        assert!(matches!(
+            db.export_headers(1, 3, Vec::new()),
+            Err(ChainStorageError::ValueNotFound { .. })
+        ));
+    }
+}
+
+mod block_inputs_and_outputs_count {
+    use tari_common_types::types::FixedHash;
+
+    use super::*;
+    use crate::chain_storage::BlockchainBackend;
+
+    #[test]
+    fn it_matches_the_number_of_fetched_inputs_and_outputs() {
+        let db = setup();
+        let (blocks, outputs) = add_many_chained_blocks(2, &db);
+        let (txns, _) = schema_to_transaction(&[
+            txn_schema!(from: vec![outputs[0].clone()], to: vec![10 * T, 10 * T, 10 * T]),
+            txn_schema!(from: vec![outputs[1].clone()], to: vec![20 * T]),
+        ]);
+        let (block, _) = create_next_block(&db, &blocks[1], txns);
+        db.add_block(block.clone()).unwrap().assert_added();
+
+        let hashes = [blocks[0].hash(), blocks[1].hash(), block.hash()];
+        let backend = db.db_read_access().unwrap();
+        for hash in &hashes {
+            let inputs = backend.fetch_inputs_in_block(hash).unwrap();
+            let outputs = backend.fetch_outputs_in_block(hash).unwrap();
+            assert_eq!(backend.inputs_count_in_block(hash).unwrap(), inputs.len());
+            assert_eq!(backend.outputs_count_in_block(hash).unwrap(), outputs.len());
+        }
+        assert_eq!(backend.inputs_count_in_block(&block.hash()).unwrap(), 2);
+        assert_eq!(backend.inputs_count_in_block(&blocks[0].hash()).unwrap(), 0);
+        assert_eq!(backend.outputs_count_in_block(&FixedHash::zero()).unwrap(), 0);
+    }
+}
+
+mod is_commitment_unspent {
+    use super::*;
+
+    #[test]
+    fn it_returns_false_once_the_output_is_spent() {
+        let db = setup();
+        let (blocks, outputs) = add_many_chained_blocks(1, &db);
+        let commitment = blocks[0].body.outputs()[0].commitment.clone();
+        assert!(db.is_commitment_unspent(&commitment).unwrap());
+
+        let (txns, _) = schema_to_transaction(&[txn_schema!(from: vec![outputs[0].clone()], to: vec![50 * T])]);
+        let (block, _) = create_next_block(&db, &blocks[0], txns);
+        db.add_block(block).unwrap().assert_added();
+        assert!(!db.is_commitment_unspent(&commitment).unwrap());
+    }
+}
+
+mod fetch_kernel_by_excess {
+    use tari_common_types::types::Commitment;
+
+    use super::*;
+
+    #[test]
+    fn it_matches_the_excess_sig_lookup() {
+        let db = setup();
+        let (blocks, _) = add_many_chained_blocks(2, &db);
+        let kernel = blocks[1].body.kernels()[0].clone();
+
+        let by_excess = db.fetch_kernel_by_excess(kernel.excess.clone()).unwrap().unwrap();
+        let by_excess_sig = db
+            .fetch_kernel_by_excess_sig(kernel.excess_sig.clone())
+            .unwrap()
+            .unwrap();
+        assert_eq!(by_excess, by_excess_sig);
+        assert_eq!(by_excess.0, kernel);
+        assert_eq!(by_excess.1, blocks[1].hash());
+    }
+
+    #[test]
+    fn it_returns_none_for_an_unknown_excess() {
+        let db = setup();
+        assert!(db.fetch_kernel_by_excess(Commitment::default()).unwrap().is_none());
+    }
+}
+
+mod fetch_kernel_mmr_root_at_height {
+    use super::*;
+
+    #[test]
+    fn it_matches_the_kernel_mr_of_each_header() {
+        let db = setup();
+        let (blocks, outputs) = add_many_chained_blocks(3, &db);
+        let (txns, _) = schema_to_transaction(&[txn_schema!(from: vec![outputs[0].clone()], to: vec![50 * T])]);
+        let (block, _) = create_next_block(&db, &blocks[2], txns);
+        db.add_block(block).unwrap().assert_added();
+
+        for height in 1..=4 {
+            let header = db.fetch_header(height).unwrap().unwrap();
+            assert_eq!(db.fetch_kernel_mmr_root_at_height(height).unwrap(), header.kernel_mr);
+        }
+    }
+
+    #[test]
+    fn it_errors_for_an_unknown_height() {
+        let db = setup();
+        let err = db.fetch_kernel_mmr_root_at_height(10).unwrap_err();
+        assert!(matches!(err, ChainStorageError::ValueNotFound { .. }));
+    }
+}
+
+mod fetch_utxo_set_root_at_height {
+    use super::*;
+
+    #[test]
+    fn it_matches_the_output_mr_of_each_header() {
+        let db = setup();
+        let (blocks, outputs) = add_many_chained_blocks(3, &db);
+        let (txns, _) = schema_to_transaction(&[txn_schema!(from: vec![outputs[0].clone()], to: vec![50 * T])]);
+        let (block, _) = create_next_block(&db, &blocks[2], txns);
+        db.add_block(block.clone()).unwrap().assert_added();
+        let (txns, _) = schema_to_transaction(&[txn_schema!(from: vec![outputs[1].clone()], to: vec![20 * T])]);
+        let (block, _) = create_next_block(&db, &block, txns);
+        db.add_block(block).unwrap().assert_added();
+
+        for height in 1..=5 {
+            let header = db.fetch_header(height).unwrap().unwrap();
+            assert_eq!(db.fetch_utxo_set_root_at_height(height).unwrap(), header.output_mr);
+        }
+    }
+
+    #[test]
+    fn it_errors_for_an_unknown_height() {
+        let db = setup();
+        let err = db.fetch_utxo_set_root_at_height(10).unwrap_err();
+        assert!(matches!(err, ChainStorageError::ValueNotFound { .. }));
+    }
+}
+
+mod fetch_mmr_size_at_height {
+    use super::*;
+    use crate::chain_storage::MmrTree;
+
+    #[test]
+    fn it_matches_the_mmr_sizes_of_each_header() {
+        let db = setup();
+        let (blocks, outputs) = add_many_chained_blocks(3, &db);
+        let (txns, _) = schema_to_transaction(&[txn_schema!(from: vec![outputs[0].clone()], to: vec![20 * T, 20 * T])]);
+        let (block, _) = create_next_block(&db, &blocks[2], txns);
+        db.add_block(block).unwrap().assert_added();
+
+        for height in 0..=4 {
+            let header = db.fetch_header(height).unwrap().unwrap();
+            assert_eq!(
+                db.fetch_mmr_size_at_height(MmrTree::Kernel, height).unwrap(),
+                header.kernel_mmr_size
+            );
+            assert_eq!(
+                db.fetch_mmr_size_at_height(MmrTree::Utxo, height).unwrap(),
+                header.output_mmr_size
+            );
+        }
+        let tip_height = db.get_height().unwrap();
+        assert_eq!(
+            db.fetch_mmr_size_at_height(MmrTree::Kernel, tip_height).unwrap(),
+            db.fetch_mmr_size(MmrTree::Kernel).unwrap()
+        );
+        assert!(
+            db.fetch_mmr_size_at_height(MmrTree::Utxo, tip_height).unwrap() >
+                db.fetch_mmr_size_at_height(MmrTree::Utxo, tip_height - 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_errors_for_an_unknown_height() {
+        let db = setup();
+        let err = db.fetch_mmr_size_at_height(MmrTree::Kernel, 10).unwrap_err();
+        assert!(matches!(err, ChainStorageError::ValueNotFound { .. }));
+    }
+}
+
+mod fetch_block_weight {
+    use super::*;
+
+    #[test]
+    fn it_matches_the_weight_of_the_block_contents() {
+        let db = setup();
+        let (blocks, outputs) = add_many_chained_blocks(3, &db);
+        let (txns, _) = schema_to_transaction(&[txn_schema!(from: vec![outputs[0].clone()], to: vec![20 * T, 20 * T])]);
+        let (block, _) = create_next_block(&db, &blocks[2], txns);
+        db.add_block(block.clone()).unwrap().assert_added();
+
+        let weight = *db.consensus_constants().unwrap().transaction_weight();
+        let features_and_scripts_size = block
+            .body
+            .outputs()
+            .iter()
+            .map(|o| weight.round_up_features_and_scripts_size(o.get_features_and_scripts_size()))
+            .sum();
+        let expected = weight.calculate(
+            block.body.kernels().len(),
+            block.body.inputs().len(),
+            block.body.outputs().len(),
+            features_and_scripts_size,
+        );
+        assert_eq!(block.body.inputs().len(), 1);
+        assert_eq!(
+            db.db_read_access()
+                .unwrap()
+                .fetch_block_weight(block.header.height)
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn it_errors_for_an_unknown_height() {
+        let db = setup();
+        let err = db.db_read_access().unwrap().fetch_block_weight(10).unwrap_err();
+        assert!(matches!(err, ChainStorageError::ValueNotFound { .. }));
+    }
+}
+
+mod fetch_coinbase_outputs_at_height {
+    use super::*;
+    use crate::{chain_storage::PrunedOutput, transactions::transaction_components::OutputType};
+
+    #[test]
+    fn it_returns_only_the_coinbase_outputs() {
+        let db = setup();
+        let (blocks, outputs) = add_many_chained_blocks(3, &db);
+        let (txns, _) = schema_to_transaction(&[txn_schema!(from: vec![outputs[0].clone()], to: vec![20 * T, 20 * T])]);
+        let (block, _) = create_next_block(&db, &blocks[2], txns);
+        db.add_block(block.clone()).unwrap().assert_added();
+        assert!(block.body.outputs().len() > 1);
+
+        let coinbases = db
+            .db_read_access()
+            .unwrap()
+            .fetch_coinbase_outputs_at_height(block.header.height)
+            .unwrap();
+        assert_eq!(coinbases.len(), 1);
+        let expected = block
+            .body
+            .outputs()
+            .iter()
+            .find(|o| o.features.output_type == OutputType::Coinbase)
+            .unwrap();
+        match &coinbases[0] {
+            PrunedOutput::NotPruned { output } => assert_eq!(output, expected),
+            PrunedOutput::Pruned { .. } => panic!("Coinbase output should not be pruned"),
+        }
+    }
+
+    #[test]
+    fn it_errors_for_an_unknown_height() {
+        let db = setup();
+        let err = db
+            .db_read_access()
+            .unwrap()
+            .fetch_coinbase_outputs_at_height(10)
+            .unwrap_err();
+        assert!(matches!(err, ChainStorageError::ValueNotFound { .. }));
+    }
+}
+
+mod verify_checkpoint {
+    use tari_common_types::types::FixedHash;
+
+    use super::*;
+
+    #[test]
+    fn it_matches_the_hash_of_the_header_at_the_height() {
+        let db = setup();
+        let (blocks, _) = add_many_chained_blocks(3, &db);
+        let expected_hash = blocks[2].hash();
+        let height = blocks[2].header.height;
+        let access = db.db_read_access().unwrap();
+
+        assert!(access.verify_checkpoint(height, expected_hash).unwrap());
+        assert!(!access.verify_checkpoint(height, FixedHash::zero()).unwrap());
+        assert!(!access.verify_checkpoint(height - 1, expected_hash).unwrap());
+    }
+
+    #[test]
+    fn it_errors_for_an_unknown_height() {
+        let db = setup();
+        let err = db
+            .db_read_access()
+            .unwrap()
+            .verify_checkpoint(10, FixedHash::zero())
+            .unwrap_err();
+        assert!(matches!(err, ChainStorageError::ValueNotFound { .. }));
+    }
+}
+
+mod fetch_header_by_prev_hash {
+    use tari_common_types::types::FixedHash;
+
+    use super::*;
+
+    #[test]
+    fn it_walks_the_main_chain_from_genesis_to_tip() {
+        let db = setup();
+        add_many_chained_blocks(5, &db);
+        let tip = db.fetch_tip_header().unwrap();
+
+        let mut header = db.fetch_header(0).unwrap().unwrap();
+        let mut walked = vec![header.clone()];
+        while let Some(child) = db
+            .db_read_access()
+            .unwrap()
+            .fetch_header_by_prev_hash(&header.hash())
+            .unwrap()
+        {
+            walked.push(child.clone());
+            header = child;
+        }
+
+        assert_eq!(walked.len() as u64, tip.height() + 1);
+        assert_eq!(header.hash(), *tip.hash());
+        for (height, header) in walked.iter().enumerate() {
+            assert_eq!(*header, db.fetch_header(height as u64).unwrap().unwrap());
+        }
+    }
+
+    #[test]
+    fn it_returns_none_for_the_tip_and_unknown_hashes() {
+        let db = setup();
+        let (blocks, _) = add_many_chained_blocks(2, &db);
+        let db_read = db.db_read_access().unwrap();
+        assert!(db_read.fetch_header_by_prev_hash(&blocks[1].hash()).unwrap().is_none());
+        assert!(db_read.fetch_header_by_prev_hash(&FixedHash::zero()).unwrap().is_none());
+    }
+}
+
+mod fetch_spent_height {
+    use super::*;
+
+    #[test]
+    fn it_returns_the_height_the_commitment_was_spent_at() {
+        let db = setup();
+        let (blocks, outputs) = add_many_chained_blocks(2, &db);
+        let commitment = blocks[0].body.outputs()[0].commitment.clone();
+        let db_read = db.db_read_access().unwrap();
+        assert!(db_read.fetch_spent_height(&commitment).unwrap().is_none());
+        drop(db_read);
+
+        let (txns, _) = schema_to_transaction(&[txn_schema!(from: vec![outputs[0].clone()], to: vec![50 * T])]);
+        let (block, _) = create_next_block(&db, &blocks[1], txns);
+        db.add_block(block.clone()).unwrap().assert_added();
+        add_many_chained_blocks(2, &db);
+
+        let db_read = db.db_read_access().unwrap();
+        assert_eq!(
+            db_read.fetch_spent_height(&commitment).unwrap(),
+            Some(block.header.height)
+        );
+        let unspent_commitment = blocks[1].body.outputs()[0].commitment.clone();
+        assert!(db_read.fetch_spent_height(&unspent_commitment).unwrap().is_none());
+        drop(db_read);
+
+        db.rewind_to_height(blocks[1].header.height).unwrap();
+        let db_read = db.db_read_access().unwrap();
+        assert!(db_read.fetch_spent_height(&commitment).unwrap().is_none());
+    }
+}
+
+mod fetch_orphan_insertion_time {
+    use std::{thread, time::Duration};
+
+    use super::*;
+    use crate::chain_storage::DbTransaction;
+
+    #[test]
+    fn it_records_the_insertion_order() {
+        let db = setup();
+        let other_db = setup();
+        let (blocks, _) = add_many_chained_blocks(4, &other_db);
+        // Skip the first block so that the remaining blocks cannot connect to the main chain
+        for block in blocks.iter().skip(1) {
+            db.add_block(block.clone()).unwrap().assert_orphaned();
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let times = blocks
+            .iter()
+            .skip(1)
+            .map(|block| db.fetch_orphan_insertion_time(block.hash()).unwrap().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(times.len(), 3);
+        assert!(times.windows(2).all(|w| w[0] < w[1]), "{:?}", times);
+        assert!(db.fetch_orphan_insertion_time(blocks[0].hash()).unwrap().is_none());
+    }
+
+    #[test]
+    fn it_removes_the_insertion_time_with_the_orphan() {
+        let db = setup();
+        let other_db = setup();
+        let (blocks, _) = add_many_chained_blocks(2, &other_db);
+        db.add_block(blocks[1].clone()).unwrap().assert_orphaned();
+        assert!(db.fetch_orphan_insertion_time(blocks[1].hash()).unwrap().is_some());
+
+        let mut txn = DbTransaction::new();
+        txn.delete_orphan(blocks[1].hash());
+        db.write(txn).unwrap();
+        assert!(db.fetch_orphan_insertion_time(blocks[1].hash()).unwrap().is_none());
+    }
+}
+
+mod fetch_block_roots {
+    use super::*;
+
+    #[test]
+    fn it_matches_the_header_roots() {
+        let db = setup();
+        let (blocks, outputs) = add_many_chained_blocks(2, &db);
+        let (txns, _) = schema_to_transaction(&[txn_schema!(from: vec![outputs[0].clone()], to: vec![50 * T])]);
+        let (block, _) = create_next_block(&db, &blocks[1], txns);
+        db.add_block(block).unwrap().assert_added();
+        add_many_chained_blocks(2, &db);
+
+        for height in 1..=5 {
+            let roots = db.fetch_block_roots(height).unwrap();
+            let header = db.fetch_header(height).unwrap().unwrap();
+            assert_eq!(roots.height, height);
+            assert_eq!(roots.header_output_mr, header.output_mr);
+            assert_eq!(roots.header_kernel_mr, header.kernel_mr);
+            assert_eq!(roots.header_witness_mr, header.witness_mr);
+            assert_eq!(roots.header_input_mr, header.input_mr);
+            assert!(
+                roots.is_consistent(),
+                "Roots at height {} have drifted: {:?}",
+                height,
+                roots
+            );
+        }
+    }
+
+    #[test]
+    fn it_errors_for_an_unknown_height() {
+        let db = setup();
+        add_many_chained_blocks(1, &db);
+        assert!(db.fetch_block_roots(2).is_err());
+    }
+}
+
+mod fetch_all_bad_blocks {
+    use tari_common_types::types::HashOutput;
+
+    use super::*;
+    use crate::chain_storage::DbTransaction;
+
+    #[test]
+    fn it_returns_an_empty_list_by_default() {
+        let db = setup();
+        assert!(db.fetch_all_bad_blocks().unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_returns_all_bad_blocks_ordered_by_height() {
+        let db = setup();
+        let hashes = (1u8..=4).map(|i| HashOutput::from([i; 32])).collect::<Vec<_>>();
+        let mut txn = DbTransaction::new();
+        txn.insert_bad_block(hashes[0], 30)
+            .insert_bad_block(hashes[1], 10)
+            .insert_bad_block(hashes[2], 40)
+            .insert_bad_block(hashes[3], 20);
+        db.write(txn).unwrap();
+
+        let bad_blocks = db.fetch_all_bad_blocks().unwrap();
+        assert_eq!(bad_blocks, vec![
+            (hashes[1], 10),
+            (hashes[3], 20),
+            (hashes[0], 30),
+            (hashes[2], 40)
+        ]);
+        assert!(bad_blocks.iter().all(|(hash, _)| db.bad_block_exists(*hash).unwrap()));
+    }
+}
+
+mod current_utxo_set_hash {
+    use super::*;
+
+    #[test]
+    fn it_matches_the_output_mr_of_the_tip() {
+        let db = setup();
+        let genesis_hash = db.current_utxo_set_hash().unwrap();
+        assert_eq!(genesis_hash, db.fetch_tip_header().unwrap().header().output_mr);
+        // The cached value is returned while the tip is unchanged
+        assert_eq!(db.current_utxo_set_hash().unwrap(), genesis_hash);
+
+        let (blocks, _) = add_many_chained_blocks(1, &db);
+        let utxo_set_hash = db.current_utxo_set_hash().unwrap();
+        assert_ne!(utxo_set_hash, genesis_hash);
+        assert_eq!(utxo_set_hash, blocks[0].header.output_mr);
+        assert_eq!(utxo_set_hash, db.fetch_tip_header().unwrap().header().output_mr);
+    }
+}
+
+mod health_report {
+    use super::*;
+
+    #[test]
+    fn it_is_consistent_with_the_individual_queries() {
+        let db = setup();
+        add_many_chained_blocks(3, &db);
+        let other_db = setup();
+        let (blocks, _) = add_many_chained_blocks(6, &other_db);
+        for block in blocks.iter().skip(4) {
+            db.add_block(block.clone()).unwrap().assert_orphaned();
+        }
+
+        let report = db.db_read_access().unwrap().health_report().unwrap();
+        assert_eq!(report.orphan_count, 2);
+        assert_eq!(report.orphan_count, db.orphan_count().unwrap());
+        assert_eq!(report.bad_block_count, 0);
+        assert_eq!(report.reorg_count, db.fetch_all_reorgs().unwrap().len());
+        assert!(report.tip_matches_metadata);
+
+        let stats = db.get_stats().unwrap();
+        assert_eq!(report.stats.db_stats().len(), stats.db_stats().len());
+        for (reported, expected) in report.stats.db_stats().iter().zip(stats.db_stats()) {
+            assert_eq!(reported.name, expected.name);
+            assert_eq!(reported.entries, expected.entries);
+        }
+        let sizes = db.fetch_total_size_stats().unwrap();
+        for (reported, expected) in report.total_size_stats.sizes().iter().zip(sizes.sizes()) {
+            assert_eq!(reported.name, expected.name);
+            assert_eq!(reported.num_entries, expected.num_entries);
+            assert_eq!(reported.total(), expected.total());
+        }
+    }
+}
+
+mod prune_txo_index_entries {
+    use super::*;
+    use crate::{
+        chain_storage::PrunedOutput,
+        transactions::transaction_components::TransactionOutput,
+        validation::{helpers::check_not_duplicate_txo, ValidationError},
+    };
+
+    fn prune_spent_output(db: &BlockchainDatabase<TempDatabase>) -> TransactionOutput {
+        let (blocks, outputs) = add_many_chained_blocks(1, db);
+        let spent = blocks[0].body.outputs()[0].clone();
+        let (txns, _) = schema_to_transaction(&[txn_schema!(from: vec![outputs[0].clone()], to: vec![50 * T])]);
+        let (block, _) = create_next_block(db, &blocks[0], txns);
+        db.add_block(block).unwrap();
+        add_many_chained_blocks(2, db);
+        db.prune_to_height(2).unwrap();
+        spent
+    }
+
+    fn assert_pruned_output_is_indexed(db: &BlockchainDatabase<TempDatabase>, spent: &TransactionOutput) {
+        let output = db.fetch_utxo(spent.hash()).unwrap().unwrap();
+        assert!(matches!(output, PrunedOutput::Pruned { .. }));
+        // Re-submitting the pruned output must still be rejected
+        assert!(matches!(
+            check_not_duplicate_txo(&*db.db_read_access().unwrap(), spent),
+            Err(ValidationError::ContainsTxO)
+        ));
+    }
+
+    #[test]
+    fn it_keeps_the_index_entries_by_default() {
+        let db = setup_with_config(BlockchainDatabaseConfig::default());
+        let spent = prune_spent_output(&db);
+        assert_pruned_output_is_indexed(&db, &spent);
+    }
+
+    #[test]
+    fn it_keeps_pruned_outputs_indexed_when_pruning_the_index() {
+        let db = setup_with_config(BlockchainDatabaseConfig {
+            prune_txo_index: true,
+            ..Default::default()
+        });
+        let spent = prune_spent_output(&db);
+        assert_pruned_output_is_indexed(&db, &spent);
+        let tip = db.fetch_block(4, true).unwrap();
+        let unspent_hash = tip.block().body.outputs()[0].hash();
+        assert!(matches!(
+            db.fetch_utxo(unspent_hash).unwrap(),
+            Some(PrunedOutput::NotPruned { .. })
+        ));
+        assert!(db
+            .db_read_access()
+            .unwrap()
+            .audit_secondary_indexes()
+            .unwrap()
+            .is_clean());
+    }
+}
+
+mod fetch_outputs_created_between {
+    use super::*;
+
+    #[test]
+    fn it_returns_the_outputs_of_every_block_in_the_range() {
+        let db = setup();
+        let (blocks, outputs) = add_many_chained_blocks(2, &db);
+        let (txns, _) = schema_to_transaction(&[txn_schema!(from: vec![outputs[0].clone()], to: vec![20 * T, 20 * T])]);
+        let (block, _) = create_next_block(&db, &blocks[1], txns);
+        db.add_block(block.clone()).unwrap();
+        add_many_chained_blocks(1, &db);
+
+        let mut expected = [&blocks[1], &block]
+            .into_iter()
+            .zip(2..=3)
+            .flat_map(|(block, height)| block.body.outputs().iter().map(move |o| (height, o.hash())))
+            .collect::<Vec<_>>();
+        expected.sort();
+        let mut created = db.fetch_outputs_created_between(2, 3).unwrap();
+        created.sort();
+        assert_eq!(created, expected);
+        // The coinbase of each block, plus the two outputs and change of the spending transaction
+        assert!(created.len() > 2);
+    }
+
+    #[test]
+    fn it_returns_an_empty_list_for_an_empty_range() {
+        let db = setup();
+        add_many_chained_blocks(2, &db);
+        assert!(db.fetch_outputs_created_between(2, 1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_rejects_an_oversized_range() {
+        let db = setup_with_config(BlockchainDatabaseConfig {
+            max_block_range: 2,
+            ..Default::default()
+        });
+        add_many_chained_blocks(4, &db);
+        assert!(db.fetch_outputs_created_between(2, 3).is_ok());
+        let err = db.fetch_outputs_created_between(1, 3).unwrap_err();
+        assert!(matches!(err, ChainStorageError::RangeTooLarge { requested: 3, max: 2 }));
+    }
+
+    #[test]
+    fn it_errors_if_the_range_is_beyond_the_tip() {
+        let db = setup();
+        add_many_chained_blocks(2, &db);
+        let err = db.fetch_outputs_created_between(1, 3).unwrap_err();
+        assert!(matches!(err, ChainStorageError::ValueNotFound { .. }));
+    }
+}
+
+mod prune_block_hash_index {
+    use super::*;
+
+    #[test]
+    fn it_removes_the_hashes_below_the_pruned_height() {
+        let db = setup_with_config(BlockchainDatabaseConfig {
+            prune_block_hash_index: true,
+            ..Default::default()
+        });
+        let genesis_hash = db.fetch_block(0, true).unwrap().block().hash();
+        let (blocks, _) = add_many_chained_blocks(5, &db);
+        db.prune_to_height(3).unwrap();
+
+        for block in &blocks[..2] {
+            assert!(db.fetch_header_by_block_hash(block.hash()).unwrap().is_none());
+        }
+        // The pruned boundary and everything above it can still be resolved
+        for block in &blocks[2..] {
+            let header = db.fetch_header_by_block_hash(block.hash()).unwrap().unwrap();
+            assert_eq!(header.hash(), block.hash());
+        }
+        assert!(db.fetch_header_by_block_hash(genesis_hash).unwrap().is_some());
+        // Headers are still available by height
+        assert_eq!(db.fetch_header(1).unwrap().unwrap().hash(), blocks[0].hash());
+        assert_eq!(db.fetch_block_hash_index_pruned_height().unwrap(), 3);
+    }
+
+    #[test]
+    fn it_only_removes_the_newly_pruned_range() {
+        let db = setup_with_config(BlockchainDatabaseConfig {
+            prune_block_hash_index: true,
+            ..Default::default()
+        });
+        let (blocks, _) = add_many_chained_blocks(5, &db);
+        db.prune_to_height(2).unwrap();
+        assert!(db.fetch_header_by_block_hash(blocks[0].hash()).unwrap().is_none());
+        assert!(db.fetch_header_by_block_hash(blocks[1].hash()).unwrap().is_some());
+
+        // The previous boundary is removed along with the blocks pruned in this pass
+        db.prune_to_height(4).unwrap();
+        for block in &blocks[..3] {
+            assert!(db.fetch_header_by_block_hash(block.hash()).unwrap().is_none());
+        }
+        for block in &blocks[3..] {
+            assert!(db.fetch_header_by_block_hash(block.hash()).unwrap().is_some());
+        }
+        assert_eq!(db.fetch_block_hash_index_pruned_height().unwrap(), 4);
+    }
+
+    #[test]
+    fn it_keeps_the_hashes_by_default() {
+        let db = setup();
+        let (blocks, _) = add_many_chained_blocks(5, &db);
+        db.prune_to_height(3).unwrap();
+        for block in &blocks {
+            assert!(db.fetch_header_by_block_hash(block.hash()).unwrap().is_some());
+        }
+        assert_eq!(db.fetch_block_hash_index_pruned_height().unwrap(), 0);
+    }
+}
+
+mod would_reorg {
+    use super::*;
+
+    fn with_accumulated_difficulty(header: ChainHeader, total_accumulated_difficulty: u128) -> ChainHeader {
+        let (header, mut accumulated_data) = header.into_parts();
+        accumulated_data.total_accumulated_difficulty = total_accumulated_difficulty;
+        ChainHeader::try_construct(header, accumulated_data).unwrap()
+    }
+
+    #[test]
+    fn it_returns_the_fork_height_of_a_heavier_candidate() {
+        let db = setup();
+        let (blocks, _) = add_many_chained_blocks(5, &db);
+        // A fork off of the second block
+        let fork_db = setup();
+        for block in blocks.iter().take(2) {
+            fork_db.add_block(block.clone()).unwrap().assert_added();
+        }
+        let (fork_blocks, _) = add_many_chained_blocks(2, &fork_db);
+        db.add_block(fork_blocks[0].clone()).unwrap().assert_orphaned();
+
+        let tip_difficulty = db
+            .fetch_tip_header()
+            .unwrap()
+            .accumulated_data()
+            .total_accumulated_difficulty;
+        let heavier = |hash| {
+            with_accumulated_difficulty(
+                fork_db.fetch_chain_header_by_block_hash(hash).unwrap().unwrap(),
+                tip_difficulty + 1,
+            )
+        };
+
+        // Connects directly to the main chain
+        assert_eq!(db.would_reorg(&heavier(fork_blocks[0].hash())).unwrap(), Some(2));
+        // Connects through the orphan
+        assert_eq!(db.would_reorg(&heavier(fork_blocks[1].hash())).unwrap(), Some(2));
+    }
+
+    #[test]
+    fn it_returns_none_for_a_weaker_candidate() {
+        let db = setup();
+        let (blocks, _) = add_many_chained_blocks(5, &db);
+        let fork_db = setup();
+        for block in blocks.iter().take(2) {
+            fork_db.add_block(block.clone()).unwrap().assert_added();
+        }
+        let (fork_blocks, _) = add_many_chained_blocks(1, &fork_db);
+
+        let candidate = fork_db
+            .fetch_chain_header_by_block_hash(fork_blocks[0].hash())
+            .unwrap()
+            .unwrap();
+        assert_eq!(db.would_reorg(&candidate).unwrap(), None);
+        assert_eq!(db.would_reorg(&db.fetch_tip_header().unwrap()).unwrap(), None);
+    }
+
+    #[test]
+    fn it_returns_none_for_a_candidate_that_extends_the_tip() {
+        let db = setup();
+        let (blocks, _) = add_many_chained_blocks(3, &db);
+        let next_db = setup();
+        for block in &blocks {
+            next_db.add_block(block.clone()).unwrap().assert_added();
+        }
+        let (next_blocks, _) = add_many_chained_blocks(1, &next_db);
+
+        let tip_difficulty = db
+            .fetch_tip_header()
+            .unwrap()
+            .accumulated_data()
+            .total_accumulated_difficulty;
+        let candidate = with_accumulated_difficulty(
+            next_db
+                .fetch_chain_header_by_block_hash(next_blocks[0].hash())
+                .unwrap()
+                .unwrap(),
+            tip_difficulty + 1,
+        );
+        assert_eq!(candidate.header().prev_hash, *db.fetch_tip_header().unwrap().hash());
+        assert_eq!(db.would_reorg(&candidate).unwrap(), None);
+    }
+
+    #[test]
+    fn it_returns_none_for_a_candidate_that_does_not_connect() {
+        let db = setup();
+        add_many_chained_blocks(2, &db);
+        let other_db = setup();
+        let (blocks, _) = add_many_chained_blocks(3, &other_db);
+
+        let candidate = with_accumulated_difficulty(
+            other_db
+                .fetch_chain_header_by_block_hash(blocks[2].hash())
+                .unwrap()
+                .unwrap(),
+            u128::MAX,
+        );
+        assert_eq!(db.would_reorg(&candidate).unwrap(), None);
+    }
+}
+
+mod tally_emission_at_height {
+    use super::*;
+
+    #[test]
+    fn it_matches_the_emission_schedule() {
+        let db = setup();
+        add_many_chained_blocks(5, &db);
+        let backend = db.db_read_access().unwrap();
+        for height in 0..=5 {
+            assert_eq!(
+                backend.tally_emission_at_height(height).unwrap(),
+                backend.total_emission_at_height(height).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn it_detects_a_coinbase_claiming_the_wrong_reward() {
+        let db = setup();
+        let (blocks, _) = add_many_chained_blocks(2, &db);
+        let reward = db.rules().get_block_reward_at(3);
+        let (block, _) = create_block(
+            db.rules(),
+            &blocks[1],
+            BlockSpec::new().with_reward(reward + MicroTari::from(1)).finish(),
+        );
+        db.add_block(Arc::new(apply_mmr_to_block(&db, block)))
+            .unwrap()
+            .assert_added();
+
+        let backend = db.db_read_access().unwrap();
+        assert!(backend.tally_emission_at_height(2).is_ok());
+        assert!(matches!(
+            backend.tally_emission_at_height(3),
+            Err(ChainStorageError::DataInconsistencyDetected { .. })
+        ));
+    }
 }
@@ -0,0 +1,53 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use tari_common_types::types::{BlockHash, HashOutput};
+
+use crate::{
+    chain_storage::PrunedOutput,
+    transactions::transaction_components::{TransactionInput, TransactionKernel},
+};
+
+/// The inputs spent and outputs created by the on-chain transaction that a kernel belongs to.
+///
+/// Once mined, transaction bodies are aggregated into the block body and sorted, so the link between a kernel and
+/// its inputs and outputs is lost. The graph is therefore a block-level approximation: it contains all of the inputs
+/// and outputs of the block that the kernel was mined in, excluding coinbase outputs when the kernel is not itself a
+/// coinbase kernel. It is only exact when the block contains a single non-coinbase transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionGraph {
+    pub kernel: TransactionKernel,
+    pub header_hash: BlockHash,
+    pub height: u64,
+    pub inputs: Vec<TransactionInput>,
+    pub outputs: Vec<PrunedOutput>,
+}
+
+impl TransactionGraph {
+    pub fn input_hashes(&self) -> Vec<HashOutput> {
+        self.inputs.iter().map(|input| input.output_hash()).collect()
+    }
+
+    pub fn output_hashes(&self) -> Vec<HashOutput> {
+        self.outputs.iter().map(|output| output.hash()).collect()
+    }
+}
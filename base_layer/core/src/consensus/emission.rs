@@ -185,6 +185,17 @@ impl EmissionSchedule {
     pub fn iter(&self) -> EmissionRate {
         EmissionRate::new(self)
     }
+
+    /// Resumes iterating over the emission schedule from a state previously reached by an [EmissionRate] of this
+    /// schedule, i.e. the block height, block reward and supply it reported.
+    pub fn iter_from(&self, block_num: u64, reward: MicroTari, supply: MicroTari) -> EmissionRate {
+        EmissionRate {
+            block_num,
+            supply,
+            reward,
+            schedule: self,
+        }
+    }
 }
 
 pub struct EmissionRate<'a> {
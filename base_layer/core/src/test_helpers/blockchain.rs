@@ -32,6 +32,7 @@ use croaring::Bitmap;
 use tari_common::configuration::Network;
 use tari_common_types::{
     chain_metadata::ChainMetadata,
+    epoch::VnEpoch,
     types::{Commitment, HashOutput, PublicKey, Signature},
 };
 use tari_storage::lmdb_store::LMDBConfig;
@@ -236,6 +237,27 @@ impl BlockchainBackend for TempDatabase {
         self.db.as_ref().unwrap().fetch_header_accumulated_data(hash)
     }
 
+    fn fetch_header_accumulated_data_range(
+        &self,
+        start: u64,
+        end_inclusive: u64,
+    ) -> Result<Vec<BlockHeaderAccumulatedData>, ChainStorageError> {
+        self.db
+            .as_ref()
+            .unwrap()
+            .fetch_header_accumulated_data_range(start, end_inclusive)
+    }
+
+    fn fetch_header_accumulated_data_by_hashes(
+        &self,
+        hashes: &[HashOutput],
+    ) -> Result<Vec<Option<BlockHeaderAccumulatedData>>, ChainStorageError> {
+        self.db
+            .as_ref()
+            .unwrap()
+            .fetch_header_accumulated_data_by_hashes(hashes)
+    }
+
     fn fetch_chain_header_in_all_chains(&self, hash: &HashOutput) -> Result<ChainHeader, ChainStorageError> {
         self.db.as_ref().unwrap().fetch_chain_header_in_all_chains(hash)
     }
@@ -280,6 +302,13 @@ impl BlockchainBackend for TempDatabase {
         self.db.as_ref().unwrap().fetch_kernel_by_excess_sig(excess_sig)
     }
 
+    fn fetch_kernel_by_excess(
+        &self,
+        excess: &Commitment,
+    ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError> {
+        self.db.as_ref().unwrap().fetch_kernel_by_excess(excess)
+    }
+
     fn fetch_utxos_in_block(
         &self,
         header_hash: &HashOutput,
@@ -302,6 +331,10 @@ impl BlockchainBackend for TempDatabase {
             .fetch_unspent_output_hash_by_commitment(commitment)
     }
 
+    fn is_commitment_unspent(&self, commitment: &Commitment) -> Result<bool, ChainStorageError> {
+        self.db.as_ref().unwrap().is_commitment_unspent(commitment)
+    }
+
     fn fetch_outputs_in_block(&self, header_hash: &HashOutput) -> Result<Vec<PrunedOutput>, ChainStorageError> {
         self.db.as_ref().unwrap().fetch_outputs_in_block(header_hash)
     }
@@ -314,6 +347,10 @@ impl BlockchainBackend for TempDatabase {
         self.db.as_ref().unwrap().fetch_mmr_size(tree)
     }
 
+    fn fetch_kernel_mmr_root_at_height(&self, height: u64) -> Result<HashOutput, ChainStorageError> {
+        self.db.as_ref().unwrap().fetch_kernel_mmr_root_at_height(height)
+    }
+
     fn fetch_mmr_leaf_index(&self, tree: MmrTree, hash: &HashOutput) -> Result<Option<u32>, ChainStorageError> {
         self.db.as_ref().unwrap().fetch_mmr_leaf_index(tree, hash)
     }
@@ -358,6 +395,10 @@ impl BlockchainBackend for TempDatabase {
         self.db.as_ref().unwrap().fetch_all_orphan_chain_tips()
     }
 
+    fn fetch_orphan_insertion_time(&self, hash: &HashOutput) -> Result<Option<u64>, ChainStorageError> {
+        self.db.as_ref().unwrap().fetch_orphan_insertion_time(hash)
+    }
+
     fn fetch_orphan_children_of(&self, hash: HashOutput) -> Result<Vec<Block>, ChainStorageError> {
         self.db.as_ref().unwrap().fetch_orphan_children_of(hash)
     }
@@ -366,6 +407,10 @@ impl BlockchainBackend for TempDatabase {
         self.db.as_ref().unwrap().fetch_orphan_chain_block(hash)
     }
 
+    fn fetch_orphan_chain_depth(&self, tip_hash: &HashOutput) -> Result<u64, ChainStorageError> {
+        self.db.as_ref().unwrap().fetch_orphan_chain_depth(tip_hash)
+    }
+
     fn fetch_deleted_bitmap(&self) -> Result<DeletedBitmap, ChainStorageError> {
         self.db.as_ref().unwrap().fetch_deleted_bitmap()
     }
@@ -411,6 +456,10 @@ impl BlockchainBackend for TempDatabase {
         self.db.as_ref().unwrap().bad_block_exists(block_hash)
     }
 
+    fn fetch_all_bad_blocks(&self) -> Result<Vec<(HashOutput, u64)>, ChainStorageError> {
+        self.db.as_ref().unwrap().fetch_all_bad_blocks()
+    }
+
     fn fetch_all_reorgs(&self) -> Result<Vec<Reorg>, ChainStorageError> {
         self.db.as_ref().unwrap().fetch_all_reorgs()
     }
@@ -419,6 +468,13 @@ impl BlockchainBackend for TempDatabase {
         self.db.as_ref().unwrap().fetch_active_validator_nodes(height)
     }
 
+    fn fetch_active_validator_nodes_at_epoch(
+        &self,
+        epoch: VnEpoch,
+    ) -> Result<Vec<(PublicKey, [u8; 32])>, ChainStorageError> {
+        self.db.as_ref().unwrap().fetch_active_validator_nodes_at_epoch(epoch)
+    }
+
     fn get_shard_key(&self, height: u64, public_key: PublicKey) -> Result<Option<[u8; 32]>, ChainStorageError> {
         self.db.as_ref().unwrap().get_shard_key(height, public_key)
     }
@@ -65,6 +65,40 @@ impl Fee {
         max(Self::MINIMUM_TRANSACTION_FEE, fee)
     }
 
+    /// Rounds the given fee up to the nearest multiple of `multiple`. A multiple of zero leaves the fee unchanged.
+    pub fn round_up(fee: MicroTari, multiple: MicroTari) -> MicroTari {
+        if multiple == MicroTari::zero() {
+            return fee;
+        }
+        match fee.as_u64() % multiple.as_u64() {
+            0 => fee,
+            remainder => fee + MicroTari::from(multiple.as_u64() - remainder),
+        }
+    }
+
+    /// Splits the `surplus` left over after paying the amounts and `fee_without_change` between the fee and a change
+    /// output. The fee of a transaction with a change output is rounded up to `fee_rounding`. If the change can't pay
+    /// for its own output or would be below `min_change_value`, the whole surplus is added to the fee instead. Returns
+    /// the fee and the change value, which is `None` if no change output should be created.
+    pub fn split_change(
+        surplus: MicroTari,
+        fee_without_change: MicroTari,
+        fee_with_change: MicroTari,
+        fee_rounding: Option<MicroTari>,
+        min_change_value: Option<MicroTari>,
+    ) -> (MicroTari, Option<MicroTari>) {
+        let fee_with_change = match fee_rounding {
+            Some(multiple) => Self::round_up(fee_with_change, multiple),
+            None => fee_with_change,
+        };
+        match (surplus + fee_without_change).checked_sub(fee_with_change) {
+            Some(change) if change > MicroTari::zero() && min_change_value.map_or(true, |min| change >= min) => {
+                (fee_with_change, Some(change))
+            },
+            _ => (fee_without_change + surplus, None),
+        }
+    }
+
     pub fn weighting(&self) -> &TransactionWeight {
         &self.0
     }
@@ -108,6 +142,44 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_round_up() {
+        assert_eq!(Fee::round_up(MicroTari(1234), MicroTari(100)), MicroTari(1300));
+        assert_eq!(Fee::round_up(MicroTari(1300), MicroTari(100)), MicroTari(1300));
+        assert_eq!(Fee::round_up(MicroTari(1), MicroTari(1000)), MicroTari(1000));
+        assert_eq!(Fee::round_up(MicroTari(1234), MicroTari(0)), MicroTari(1234));
+    }
+
+    #[test]
+    fn test_split_change() {
+        let split = |fee_rounding, min_change_value| {
+            Fee::split_change(
+                MicroTari(1000),
+                MicroTari(100),
+                MicroTari(150),
+                fee_rounding,
+                min_change_value,
+            )
+        };
+        assert_eq!(split(None, None), (MicroTari(150), Some(MicroTari(950))));
+        assert_eq!(
+            split(Some(MicroTari(200)), None),
+            (MicroTari(200), Some(MicroTari(900)))
+        );
+        assert_eq!(
+            split(None, Some(MicroTari(950))),
+            (MicroTari(150), Some(MicroTari(950)))
+        );
+        assert_eq!(split(None, Some(MicroTari(951))), (MicroTari(1100), None));
+        // Rounding pushes the change below the minimum
+        assert_eq!(
+            split(Some(MicroTari(200)), Some(MicroTari(950))),
+            (MicroTari(1100), None)
+        );
+        // The change can't pay for its own output
+        assert_eq!(split(Some(MicroTari(2000)), None), (MicroTari(1100), None));
+    }
+
     #[test]
     fn test_calculate_body() {
         let hash = vec![0u8; 32].try_into().unwrap();
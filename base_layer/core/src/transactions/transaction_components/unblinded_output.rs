@@ -29,6 +29,7 @@ use std::{
     ops::Shl,
 };
 
+use borsh::{BorshDeserialize, BorshSerialize};
 use rand::rngs::OsRng;
 use serde::{Deserialize, Serialize};
 use tari_common_types::types::{
@@ -78,7 +79,7 @@ use crate::{
 /// build both inputs and outputs (every input comes from an output)
 // TODO: Try to get rid of 'Serialize' and 'Deserialize' traits here; see related comment at 'struct RawTransactionInfo'
 // #LOGGED
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct UnblindedOutput {
     pub version: TransactionOutputVersion,
     pub value: MicroTari,
@@ -107,6 +107,8 @@ pub struct SenderTransactionInitializer {
     tx_id: Option<TxId>,
     kernel_features: KernelFeatures,
     burn_commitment: Option<Commitment>,
+    fee_rounding: Option<MicroTari>,
+    min_change_value: Option<MicroTari>,
     fee: Fee,
 }
 
@@ -153,6 +155,8 @@ impl SenderTransactionInitializer {
             private_commitment_nonces: FixedSet::new(num_recipients),
             kernel_features: KernelFeatures::empty(),
             burn_commitment: None,
+            fee_rounding: None,
+            min_change_value: None,
             tx_id: None,
         }
     }
@@ -285,6 +289,19 @@ impl SenderTransactionInitializer {
         self
     }
 
+    /// Round the fee of a transaction with a change output up to the nearest multiple of the given amount. The extra
+    /// fee is deducted from the change.
+    pub fn with_fee_rounding(&mut self, fee_rounding: Option<MicroTari>) -> &mut Self {
+        self.fee_rounding = fee_rounding;
+        self
+    }
+
+    /// Don't create a change output worth less than the given amount, the change is added to the fee instead.
+    pub fn with_min_change_value(&mut self, min_change_value: Option<MicroTari>) -> &mut Self {
+        self.min_change_value = min_change_value;
+        self
+    }
+
     fn get_total_features_and_scripts_size_for_outputs(&self) -> usize {
         let mut size = 0;
         size += self
@@ -374,14 +391,19 @@ impl SenderTransactionInitializer {
             )),
             Some(MicroTari(0)) => Ok((fee_without_change, MicroTari(0), None)),
             Some(v) => {
-                let change_amount = v.checked_sub(change_fee);
+                let (fee, change_amount) = Fee::split_change(
+                    v,
+                    fee_without_change,
+                    fee_without_change + change_fee,
+                    self.fee_rounding,
+                    self.min_change_value,
+                );
                 let change_sender_offset_private_key = PrivateKey::random(&mut OsRng);
                 self.change_sender_offset_private_key = Some(change_sender_offset_private_key.clone());
                 match change_amount {
-                    // You can't win. Just add the change to the fee (which is less than the cost of adding another
-                    // output and go without a change output
-                    None => Ok((fee_without_change + v, MicroTari(0), None)),
-                    Some(MicroTari(0)) => Ok((fee_without_change + v, MicroTari(0), None)),
+                    // You can't win. The change is added to the fee (which is less than the cost of adding another
+                    // output, or the change would be dust) and we go without a change output
+                    None => Ok((fee, MicroTari(0), None)),
                     Some(v) => {
                         let change_script = self
                             .change_script
@@ -439,7 +461,7 @@ impl SenderTransactionInitializer {
                             encrypted_data,
                             minimum_value_promise,
                         );
-                        Ok((fee_without_change + change_fee, v, Some(change_unblinded_output)))
+                        Ok((fee, v, Some(change_unblinded_output)))
                     },
                 }
             },
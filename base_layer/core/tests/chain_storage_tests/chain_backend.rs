@@ -20,6 +20,10 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::sync::Arc;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use croaring::Bitmap;
 use rand::rngs::OsRng;
 use tari_common::configuration::Network;
 use tari_common_types::types::{
@@ -32,21 +36,43 @@ use tari_common_types::types::{
     Signature,
 };
 use tari_core::{
-    blocks::{BlockAccumulatedData, BlockHeader, BlockHeaderAccumulatedData, ChainHeader, UpdateBlockAccumulatedData},
-    chain_storage::{create_lmdb_database, BlockchainBackend, ChainStorageError, DbKey, DbTransaction, DbValue},
-    consensus::{ConsensusManager, ConsensusManagerBuilder},
+    blocks::{
+        Block,
+        BlockAccumulatedData,
+        BlockHeader,
+        BlockHeaderAccumulatedData,
+        ChainBlock,
+        ChainHeader,
+        UpdateBlockAccumulatedData,
+    },
+    chain_storage::{
+        create_lmdb_database,
+        replay_db_transaction_in_lmdb_database,
+        BlockchainBackend,
+        ChainStorageError,
+        DbKey,
+        DbTransaction,
+        DbValue,
+        LMDBDatabase,
+    },
+    consensus::{emission::Emission, ConsensusManager, ConsensusManagerBuilder},
     covenants::Covenant,
     test_helpers::blockchain::create_test_db,
-    transactions::transaction_components::{
-        EncryptedOpenings,
-        KernelFeatures,
-        OutputFeatures,
-        TransactionKernel,
-        TransactionKernelVersion,
-        TransactionOutput,
-        TransactionOutputVersion,
+    transactions::{
+        aggregated_body::AggregateBody,
+        transaction_components::{
+            EncryptedOpenings,
+            KernelFeatures,
+            OutputFeatures,
+            TransactionKernel,
+            TransactionKernelVersion,
+            TransactionOutput,
+            TransactionOutputVersion,
+        },
     },
     tx,
+    PrunedOutputMmr,
+    PrunedWitnessMmr,
 };
 use tari_crypto::keys::{PublicKey as PKtrait, SecretKey as SKtrait};
 use tari_script::TariScript;
@@ -55,6 +81,40 @@ use tari_test_utils::paths::create_temporary_data_path;
 
 use crate::helpers::database::create_orphan_block;
 
+fn create_random_kernel() -> TransactionKernel {
+    let pvt_key = PrivateKey::random(&mut OsRng);
+    let pub_key = PublicKey::from_secret_key(&pvt_key);
+    let commitment = Commitment::from_public_key(&pub_key);
+    let sig = Signature::new(pub_key, pvt_key);
+    TransactionKernel::new(
+        TransactionKernelVersion::V0,
+        KernelFeatures::default(),
+        0.into(),
+        0,
+        commitment,
+        sig,
+        None,
+    )
+}
+
+fn create_random_utxo() -> TransactionOutput {
+    let pvt_key = PrivateKey::random(&mut OsRng);
+    let pub_key = PublicKey::from_secret_key(&pvt_key);
+    let commitment = Commitment::from_public_key(&pub_key);
+    TransactionOutput::new(
+        TransactionOutputVersion::V0,
+        OutputFeatures::default(),
+        commitment,
+        Some(RangeProof::default()),
+        TariScript::default(),
+        pub_key,
+        ComAndPubSignature::default(),
+        Covenant::default(),
+        EncryptedOpenings::default(),
+        0.into(),
+    )
+}
+
 #[test]
 fn test_lmdb_insert_contains_delete_and_fetch_orphan() {
     let network = Network::LocalNet;
@@ -184,6 +244,280 @@ fn test_utxo_order() {
     }
 }
 
+#[test]
+fn test_lmdb_db_txn_timing() {
+    let temp_path = create_temporary_data_path();
+    {
+        let consensus_manager = ConsensusManager::builder(Network::LocalNet).build();
+        let config = LMDBConfig::default().with_log_db_txn_timing(true);
+        let mut db = create_lmdb_database(&temp_path, config, consensus_manager.clone()).unwrap();
+        assert!(db.last_txn_timings().is_none());
+
+        let mut txn = DbTransaction::new();
+        for height in 10..13 {
+            let txs = vec![(tx!(1000.into(), fee: 4.into(), inputs: 2, outputs: 1)).0];
+            txn.insert_orphan(create_orphan_block(height, txs, &consensus_manager).into());
+        }
+        db.write(txn).unwrap();
+
+        let timings = db.last_txn_timings().unwrap();
+        assert_eq!(timings.operations().len(), 3);
+        assert!(timings
+            .operations()
+            .iter()
+            .all(|(operation, _)| operation.starts_with("InsertOrphanBlock")));
+        assert_eq!(timings.slowest(2).len(), 2);
+        assert!(timings.slowest(1)[0].1 <= timings.total());
+    }
+
+    if std::path::Path::new(&temp_path).exists() {
+        std::fs::remove_dir_all(&temp_path).expect("Could not clear temp storage for db");
+    }
+}
+
+#[test]
+fn test_lmdb_compressed_orphans() {
+    let compressed_path = create_temporary_data_path();
+    let uncompressed_path = create_temporary_data_path();
+    {
+        let consensus_manager = ConsensusManager::builder(Network::LocalNet).build();
+        let config = LMDBConfig::default().with_compress_orphans(true);
+        let mut compressed_db = create_lmdb_database(&compressed_path, config, consensus_manager.clone()).unwrap();
+        let mut uncompressed_db =
+            create_lmdb_database(&uncompressed_path, LMDBConfig::default(), consensus_manager.clone()).unwrap();
+
+        let txs = vec![
+            (tx!(1000.into(), fee: 4.into(), inputs: 2, outputs: 2)).0,
+            (tx!(2000.into(), fee: 6.into(), inputs: 1, outputs: 3)).0,
+        ];
+        let orphan = create_orphan_block(10, txs, &consensus_manager);
+        let hash = orphan.hash();
+        for db in [&mut compressed_db, &mut uncompressed_db] {
+            let mut txn = DbTransaction::new();
+            txn.insert_orphan(orphan.clone().into());
+            db.write(txn).unwrap();
+        }
+
+        assert!(compressed_db.contains(&DbKey::OrphanBlock(hash)).unwrap());
+        if let Some(DbValue::OrphanBlock(retrieved_orphan)) = compressed_db.fetch(&DbKey::OrphanBlock(hash)).unwrap() {
+            assert_eq!(*retrieved_orphan, orphan);
+        } else {
+            panic!("Compressed orphan should be fetched");
+        }
+        assert_eq!(
+            compressed_db.fetch_orphan_children_of(orphan.header.prev_hash).unwrap(),
+            vec![orphan]
+        );
+
+        let orphans_size = |db: &LMDBDatabase| {
+            db.fetch_total_size_stats()
+                .unwrap()
+                .sizes()
+                .iter()
+                .find(|size| size.name == "orphans_db")
+                .unwrap()
+                .total_value_size
+        };
+        assert!(orphans_size(&compressed_db) < orphans_size(&uncompressed_db));
+    }
+
+    for path in [compressed_path, uncompressed_path] {
+        if std::path::Path::new(&path).exists() {
+            std::fs::remove_dir_all(&path).expect("Could not clear temp storage for db");
+        }
+    }
+}
+
+#[test]
+fn test_lmdb_cached_deleted_bitmap() {
+    let temp_path = create_temporary_data_path();
+    {
+        let consensus_manager = ConsensusManager::builder(Network::LocalNet).build();
+        let config = LMDBConfig::default().with_cache_deleted_bitmap(true);
+        let mut db = create_lmdb_database(&temp_path, config, consensus_manager.clone()).unwrap();
+        assert!(db.fetch_deleted_bitmap().unwrap().bitmap().is_empty());
+
+        for positions in [vec![1u32, 2, 3], vec![5], vec![2, 8]] {
+            let mut txn = DbTransaction::new();
+            txn.update_deleted_bitmap(Bitmap::of(&positions));
+            db.write(txn).unwrap();
+        }
+        let cached = db.fetch_deleted_bitmap().unwrap();
+        assert_eq!(cached.bitmap().to_vec(), vec![1, 2, 3, 5, 8]);
+        drop(db);
+
+        let db = create_lmdb_database(&temp_path, LMDBConfig::default(), consensus_manager).unwrap();
+        assert_eq!(db.fetch_deleted_bitmap().unwrap().bitmap(), cached.bitmap());
+    }
+
+    if std::path::Path::new(&temp_path).exists() {
+        std::fs::remove_dir_all(&temp_path).expect("Could not clear temp storage for db");
+    }
+}
+
+#[test]
+fn test_lmdb_parallel_body_hashing_matches_serial() {
+    let outputs = (0..500).map(|_| create_random_utxo()).collect::<Vec<_>>();
+    let block = Arc::new(Block::new(
+        BlockHeader::new(0),
+        AggregateBody::new(vec![], outputs, vec![]),
+    ));
+    let accumulated_data = BlockHeaderAccumulatedData {
+        hash: block.hash(),
+        ..Default::default()
+    };
+
+    let mut roots = Vec::new();
+    for parallelism in [0, 4] {
+        let temp_path = create_temporary_data_path();
+        {
+            let consensus_manager = ConsensusManager::builder(Network::LocalNet).build();
+            let config = LMDBConfig::default().with_body_hashing_parallelism(parallelism);
+            let mut db = create_lmdb_database(&temp_path, config, consensus_manager).unwrap();
+
+            let mut txn = DbTransaction::new();
+            txn.insert_chain_header(
+                ChainHeader::try_construct(block.header.clone(), accumulated_data.clone()).unwrap(),
+            );
+            txn.insert_block_body(Arc::new(
+                ChainBlock::try_construct(block.clone(), accumulated_data.clone()).unwrap(),
+            ));
+            db.write(txn).unwrap();
+
+            let (_, output_set, witness_set, _) = db
+                .fetch_block_accumulated_data_by_height(0)
+                .unwrap()
+                .unwrap()
+                .dissolve();
+            roots.push((
+                PrunedOutputMmr::new(output_set).get_merkle_root().unwrap(),
+                PrunedWitnessMmr::new(witness_set).get_merkle_root().unwrap(),
+            ));
+        }
+
+        if std::path::Path::new(&temp_path).exists() {
+            std::fs::remove_dir_all(&temp_path).expect("Could not clear temp storage for db");
+        }
+    }
+    assert_eq!(roots[0], roots[1]);
+}
+
+#[test]
+fn test_lmdb_index_size_report() {
+    let temp_path = create_temporary_data_path();
+    {
+        let consensus_manager = ConsensusManager::builder(Network::LocalNet).build();
+        let config = LMDBConfig::default().with_index_size_warning_threshold(Some(5));
+        let mut db = create_lmdb_database(&temp_path, config, consensus_manager).unwrap();
+
+        let block_hash = FixedHash::zero();
+        for i in 0..10u32 {
+            let mut tx = DbTransaction::new();
+            tx.insert_kernel(create_random_kernel(), block_hash, i);
+            db.write(tx).unwrap();
+        }
+        for i in 0..7u32 {
+            let mut tx = DbTransaction::new();
+            tx.insert_utxo(create_random_utxo(), block_hash, 0, i, 0);
+            db.write(tx).unwrap();
+        }
+
+        let report = db.index_size_report().unwrap();
+        let size_of = |name: &str| report.iter().find(|(n, _)| n == name).unwrap().1;
+        assert_eq!(size_of("kernel_excess_index"), 10);
+        assert_eq!(size_of("kernel_excess_sig_index"), 10);
+        assert_eq!(size_of("txos_hash_to_index_db"), 7);
+        assert_eq!(size_of("utxo_commitment_index"), 7);
+    }
+
+    if std::path::Path::new(&temp_path).exists() {
+        std::fs::remove_dir_all(&temp_path).expect("Could not clear temp storage for db");
+    }
+}
+
+#[test]
+fn test_replay_serialized_db_transaction() {
+    let original_path = create_temporary_data_path();
+    let replay_path = create_temporary_data_path();
+    {
+        let consensus_manager = ConsensusManager::builder(Network::LocalNet).build();
+        let orphan = create_orphan_block(
+            10,
+            vec![(tx!(1000.into(), fee: 4.into(), inputs: 2, outputs: 1)).0],
+            &consensus_manager,
+        );
+        let orphan_hash = orphan.hash();
+        let block_hash = FixedHash::zero();
+
+        let kernel = create_random_kernel();
+        let utxo = create_random_utxo();
+
+        let mut txn = DbTransaction::new();
+        txn.insert_orphan(orphan.into())
+            .insert_kernel(kernel.clone(), block_hash, 0)
+            .insert_utxo(utxo.clone(), block_hash, 0, 0, 0)
+            .update_deleted_bitmap(Bitmap::of(&[1, 4]))
+            .insert_bad_block(orphan_hash, 10);
+        let serialized = txn.try_to_vec().unwrap();
+
+        let mut original_db =
+            create_lmdb_database(&original_path, LMDBConfig::default(), consensus_manager.clone()).unwrap();
+        original_db.write(txn).unwrap();
+
+        let replayed_txn = DbTransaction::try_from_slice(&serialized).unwrap();
+        let results = replay_db_transaction_in_lmdb_database(
+            &replay_path,
+            LMDBConfig::default(),
+            consensus_manager.clone(),
+            replayed_txn,
+        )
+        .unwrap();
+        assert_eq!(results.len(), 5);
+        assert!(results.iter().all(|(_, result)| result.is_ok()));
+
+        let replayed_db = create_lmdb_database(&replay_path, LMDBConfig::default(), consensus_manager).unwrap();
+        for db in [&original_db, &replayed_db] {
+            assert!(db.contains(&DbKey::OrphanBlock(orphan_hash)).unwrap());
+            assert!(db.bad_block_exists(orphan_hash).unwrap());
+            let (fetched_kernel, _) = db.fetch_kernel_by_excess_sig(&kernel.excess_sig).unwrap().unwrap();
+            assert_eq!(fetched_kernel, kernel);
+            let output = db.fetch_output(&utxo.hash()).unwrap().unwrap();
+            assert_eq!(output.output.as_transaction_output(), Some(&utxo));
+        }
+        assert_eq!(
+            replayed_db.fetch_deleted_bitmap().unwrap().bitmap(),
+            original_db.fetch_deleted_bitmap().unwrap().bitmap()
+        );
+        assert_eq!(
+            replayed_db.index_size_report().unwrap(),
+            original_db.index_size_report().unwrap()
+        );
+    }
+
+    for path in [original_path, replay_path] {
+        if std::path::Path::new(&path).exists() {
+            std::fs::remove_dir_all(&path).expect("Could not clear temp storage for db");
+        }
+    }
+}
+
+#[test]
+fn test_total_emission_at_height() {
+    let db = create_test_db();
+    let consensus_manager = ConsensusManager::builder(Network::LocalNet).build();
+    // Heights below the last queried height are not resumed from the cached schedule state
+    for height in [0, 1, 2, 10, 100, 1000, 500, 3, 1001] {
+        let total = db.total_emission_at_height(height).unwrap();
+        assert_eq!(total, consensus_manager.emission_schedule().supply_at_block(height));
+    }
+    // Only the genesis block exists to tally
+    assert_eq!(
+        db.tally_emission_at_height(0).unwrap(),
+        consensus_manager.emission_schedule().supply_at_block(0)
+    );
+    assert!(db.tally_emission_at_height(1).is_err());
+}
+
 #[test]
 fn test_lmdb_file_lock() {
     // Create temporary test folder
@@ -121,6 +121,25 @@ where
     pub fn update_key_index(&mut self, new_index: u64) {
         self.primary_key_index = new_index;
     }
+
+    /// Probe the key chain from index 0 onwards and return the last index whose key satisfies `is_used` before
+    /// `gap_limit` consecutive unused indices are encountered, or `None` if no used index is found in that range.
+    pub fn find_highest_used_index<F>(&self, gap_limit: u64, mut is_used: F) -> Result<Option<u64>, ByteArrayError>
+    where F: FnMut(&K) -> bool {
+        let mut highest_used = None;
+        let mut gap = 0u64;
+        let mut index = 0u64;
+        while gap < gap_limit {
+            if is_used(&self.derive_key(index)?.k) {
+                highest_used = Some(index);
+                gap = 0;
+            } else {
+                gap += 1;
+            }
+            index += 1;
+        }
+        Ok(highest_used)
+    }
 }
 
 impl<K, D> Default for KeyManager<K, D>
@@ -195,4 +214,18 @@ mod test {
         let next_key2 = km2.next_key().unwrap();
         assert_ne!(next_key1.k, next_key2.k);
     }
+
+    #[test]
+    fn test_find_highest_used_index() {
+        let km = KeyManager::<RistrettoSecretKey, Blake256>::new();
+        let used = [1u64, 2, 5, 12]
+            .iter()
+            .map(|i| km.derive_key(*i).unwrap().k)
+            .collect::<Vec<_>>();
+        let is_used = |k: &RistrettoSecretKey| used.contains(k);
+        assert_eq!(km.find_highest_used_index(2, is_used).unwrap(), Some(2));
+        assert_eq!(km.find_highest_used_index(3, is_used).unwrap(), Some(5));
+        assert_eq!(km.find_highest_used_index(7, is_used).unwrap(), Some(12));
+        assert_eq!(km.find_highest_used_index(10, |_| false).unwrap(), None);
+    }
 }
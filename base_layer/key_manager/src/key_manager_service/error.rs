@@ -36,6 +36,14 @@ pub enum KeyManagerServiceError {
     MasterSeedMismatch,
     #[error("Could not find key in key manager")]
     KeyNotFoundInKeyChain,
+    #[error("Key manager branch `{branch}` is tagged with network `{found}` instead of `{expected}`")]
+    NetworkMismatch {
+        branch: String,
+        found: String,
+        expected: String,
+    },
+    #[error("Key manager branch `{0}` is not tagged with a network")]
+    UntaggedBranch(String),
     #[error("Storage error: `{0}`")]
     KeyManagerStorageError(#[from] KeyManagerStorageError),
     #[error("Byte array error: `{0}`")]
@@ -74,6 +82,8 @@ pub enum KeyManagerStorageError {
     ByteArrayError(#[from] ByteArrayError),
     #[error("Aead error: `{0}`")]
     AeadError(String),
+    #[error("The key manager database is locked")]
+    Locked,
     #[error("Binary not stored as valid hex:{0}")]
     HexError(#[from] HexError),
     #[error("Tari Key Manager error: `{0}`")]
@@ -57,6 +57,22 @@ where TBackend: KeyManagerBackend + 'static
             key_manager_inner: Arc::new(RwLock::new(KeyManagerInner::new(master_seed, db))),
         }
     }
+
+    /// Creates a new key manager that tags every branch with `network_tag`, see
+    /// [KeyManagerInner::new_with_network_tag].
+    pub fn new_with_network_tag(
+        master_seed: CipherSeed,
+        db: KeyManagerDatabase<TBackend>,
+        network_tag: String,
+    ) -> Result<Self, KeyManagerServiceError> {
+        Ok(KeyManagerHandle {
+            key_manager_inner: Arc::new(RwLock::new(KeyManagerInner::new_with_network_tag(
+                master_seed,
+                db,
+                network_tag,
+            )?)),
+        })
+    }
 }
 
 #[async_trait::async_trait]
@@ -98,6 +114,36 @@ where TBackend: KeyManagerBackend + 'static
             .await
     }
 
+    async fn find_key_index_with_gap_limit<T: Into<String> + Send>(
+        &self,
+        branch: T,
+        key: &PrivateKey,
+        gap_limit: u64,
+    ) -> Result<u64, KeyManagerServiceError> {
+        (*self.key_manager_inner)
+            .read()
+            .await
+            .find_key_index_with_gap_limit(branch.into(), key, gap_limit)
+            .await
+    }
+
+    async fn find_highest_used_index<T, F>(
+        &self,
+        branch: T,
+        gap_limit: u64,
+        is_used: F,
+    ) -> Result<u64, KeyManagerServiceError>
+    where
+        T: Into<String> + Send,
+        F: FnMut(&PrivateKey) -> bool + Send,
+    {
+        (*self.key_manager_inner)
+            .read()
+            .await
+            .find_highest_used_index(branch.into(), gap_limit, is_used)
+            .await
+    }
+
     async fn update_current_key_index_if_higher<T: Into<String> + Send>(
         &self,
         branch: T,
@@ -44,6 +44,7 @@ where T: KeyManagerBackend
 {
     backend: Option<T>,
     master_seed: CipherSeed,
+    network_tag: Option<String>,
 }
 
 impl<T> KeyManagerInitializer<T>
@@ -54,8 +55,16 @@ where T: KeyManagerBackend + 'static
         Self {
             backend: Some(backend),
             master_seed,
+            network_tag: None,
         }
     }
+
+    /// Tags every key manager branch with the given network so the same seed derives distinct keys per network.
+    /// Initialization fails if the backend holds branches tagged with a different network.
+    pub fn with_network_tag(mut self, network_tag: String) -> Self {
+        self.network_tag = Some(network_tag);
+        self
+    }
 }
 
 #[async_trait]
@@ -68,7 +77,11 @@ where T: KeyManagerBackend + 'static
             .take()
             .expect("Cannot start Key Manager Service without setting a storage backend");
 
-        let key_manager = KeyManagerHandle::new(self.master_seed.clone(), KeyManagerDatabase::new(backend));
+        let db = KeyManagerDatabase::new(backend);
+        let key_manager = match self.network_tag.clone() {
+            Some(network_tag) => KeyManagerHandle::new_with_network_tag(self.master_seed.clone(), db, network_tag)?,
+            None => KeyManagerHandle::new(self.master_seed.clone(), db),
+        };
         context.register_handle(key_manager);
 
         Ok(())
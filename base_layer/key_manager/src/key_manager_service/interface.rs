@@ -71,6 +71,27 @@ pub trait KeyManagerInterface: Clone + Send + Sync + 'static {
         key: &PrivateKey,
     ) -> Result<u64, KeyManagerServiceError>;
 
+    /// Searches the branch to find the index used to generated the key, looking at most `gap_limit` keys past the
+    /// current index of the branch.
+    async fn find_key_index_with_gap_limit<T: Into<String> + Send>(
+        &self,
+        branch: T,
+        key: &PrivateKey,
+        gap_limit: u64,
+    ) -> Result<u64, KeyManagerServiceError>;
+
+    /// Probes the branch by deriving keys from index 0 and returns the last index whose key satisfies `is_used` before
+    /// an unbroken gap of `gap_limit` unused indices.
+    async fn find_highest_used_index<T, F>(
+        &self,
+        branch: T,
+        gap_limit: u64,
+        is_used: F,
+    ) -> Result<u64, KeyManagerServiceError>
+    where
+        T: Into<String> + Send,
+        F: FnMut(&PrivateKey) -> bool + Send;
+
     /// Will update the index of the branch if the index given is higher than the current saved index
     async fn update_current_key_index_if_higher<T: Into<String> + Send>(
         &self,
@@ -109,12 +109,24 @@ impl KeyManagerMock {
 
     /// Search the specified branch key manager key chain to find the index of the specified key.
     pub async fn find_key_index_mock(&self, branch: String, key: &PrivateKey) -> Result<u64, KeyManagerServiceError> {
+        self.find_key_index_with_gap_limit_mock(branch, key, KEY_MANAGER_MAX_SEARCH_DEPTH)
+            .await
+    }
+
+    /// Search the specified branch key manager key chain to find the index of the specified key, searching at most
+    /// `gap_limit` keys past the current index of the branch.
+    pub async fn find_key_index_with_gap_limit_mock(
+        &self,
+        branch: String,
+        key: &PrivateKey,
+        gap_limit: u64,
+    ) -> Result<u64, KeyManagerServiceError> {
         let lock = self.key_managers.read().await;
         let km = lock.get(&branch).ok_or(KeyManagerServiceError::UnknownKeyBranch)?;
 
         let current_index = km.key_index();
 
-        for i in 0u64..current_index + KEY_MANAGER_MAX_SEARCH_DEPTH {
+        for i in 0u64..current_index.saturating_add(gap_limit) {
             if km.derive_key(i)?.k == *key {
                 trace!(target: LOG_TARGET, "Key found in {} Key Chain at index {}", branch, i);
                 return Ok(i);
@@ -124,6 +136,23 @@ impl KeyManagerMock {
         Err(KeyManagerServiceError::KeyNotFoundInKeyChain)
     }
 
+    /// Return the last index whose key satisfies `is_used` before `gap_limit` consecutive unused indices are
+    /// encountered.
+    pub async fn find_highest_used_index_mock<F>(
+        &self,
+        branch: String,
+        gap_limit: u64,
+        is_used: F,
+    ) -> Result<u64, KeyManagerServiceError>
+    where
+        F: FnMut(&PrivateKey) -> bool,
+    {
+        let lock = self.key_managers.read().await;
+        let km = lock.get(&branch).ok_or(KeyManagerServiceError::UnknownKeyBranch)?;
+        km.find_highest_used_index(gap_limit, is_used)?
+            .ok_or(KeyManagerServiceError::KeyNotFoundInKeyChain)
+    }
+
     /// If the supplied index is higher than the current UTXO key chain indices then they will be updated.
     pub async fn update_current_key_index_if_higher_mock(
         &self,
@@ -167,6 +196,30 @@ impl KeyManagerInterface for KeyManagerMock {
         self.find_key_index_mock(branch.into(), key).await
     }
 
+    async fn find_key_index_with_gap_limit<T: Into<String> + Send>(
+        &self,
+        branch: T,
+        key: &PrivateKey,
+        gap_limit: u64,
+    ) -> Result<u64, KeyManagerServiceError> {
+        self.find_key_index_with_gap_limit_mock(branch.into(), key, gap_limit)
+            .await
+    }
+
+    async fn find_highest_used_index<T, F>(
+        &self,
+        branch: T,
+        gap_limit: u64,
+        is_used: F,
+    ) -> Result<u64, KeyManagerServiceError>
+    where
+        T: Into<String> + Send,
+        F: FnMut(&PrivateKey) -> bool + Send,
+    {
+        self.find_highest_used_index_mock(branch.into(), gap_limit, is_used)
+            .await
+    }
+
     async fn update_current_key_index_if_higher<T: Into<String> + Send>(
         &self,
         branch: T,
@@ -39,11 +39,15 @@ use crate::{
 
 const LOG_TARGET: &str = "key_manager::key_manager_service";
 const KEY_MANAGER_MAX_SEARCH_DEPTH: u64 = 1_000_000;
+/// Separates the network tag from the branch name in the stored branch of a network-tagged key manager
+const NETWORK_TAG_SEPARATOR: char = '/';
 
 pub struct KeyManagerInner<TBackend> {
     key_managers: HashMap<String, Mutex<KeyManager<PrivateKey, KeyDigest>>>,
     db: KeyManagerDatabase<TBackend>,
     master_seed: CipherSeed,
+    /// If set, every branch is stored and derived as `<network_tag>/<branch>`
+    network_tag: Option<String>,
 }
 
 impl<TBackend> KeyManagerInner<TBackend>
@@ -54,6 +58,41 @@ where TBackend: KeyManagerBackend + 'static
             key_managers: HashMap::new(),
             db,
             master_seed,
+            network_tag: None,
+        }
+    }
+
+    /// Creates a key manager whose branches are tagged with the given network, so the same seed derives distinct keys
+    /// on every network. Fails with `NetworkMismatch` if the database holds branches tagged with another network, and
+    /// with `UntaggedBranch` if it holds branches stored without a tag, whose keys would no longer be found.
+    pub fn new_with_network_tag(
+        master_seed: CipherSeed,
+        db: KeyManagerDatabase<TBackend>,
+        network_tag: String,
+    ) -> Result<Self, KeyManagerServiceError> {
+        for branch in db.get_all_branches()? {
+            match branch.split_once(NETWORK_TAG_SEPARATOR) {
+                Some((tag, name)) if tag != network_tag => {
+                    return Err(KeyManagerServiceError::NetworkMismatch {
+                        branch: name.to_string(),
+                        found: tag.to_string(),
+                        expected: network_tag,
+                    });
+                },
+                Some(_) => {},
+                None => return Err(KeyManagerServiceError::UntaggedBranch(branch)),
+            }
+        }
+        let mut inner = Self::new(master_seed, db);
+        inner.network_tag = Some(network_tag);
+        Ok(inner)
+    }
+
+    /// The branch as it is stored and used for key derivation
+    fn stored_branch(&self, branch: &str) -> String {
+        match &self.network_tag {
+            Some(tag) => format!("{}{}{}", tag, NETWORK_TAG_SEPARATOR, branch),
+            None => branch.to_string(),
         }
     }
 
@@ -63,10 +102,11 @@ where TBackend: KeyManagerBackend + 'static
         } else {
             AddResult::NewEntry
         };
-        let state = match self.db.get_key_manager_state(branch.clone())? {
+        let stored_branch = self.stored_branch(&branch);
+        let state = match self.db.get_key_manager_state(stored_branch.clone())? {
             None => {
                 let starting_state = KeyManagerState {
-                    branch_seed: branch.to_string(),
+                    branch_seed: stored_branch,
                     primary_key_index: 0,
                 };
                 self.db.set_key_manager_state(starting_state.clone())?;
@@ -93,7 +133,7 @@ where TBackend: KeyManagerBackend + 'static
             .lock()
             .await;
         let key = km.next_key()?;
-        self.db.increment_key_index(branch)?;
+        self.db.increment_key_index(self.stored_branch(&branch))?;
         Ok(NextKeyResult {
             key: key.k,
             index: km.key_index(),
@@ -113,6 +153,18 @@ where TBackend: KeyManagerBackend + 'static
 
     /// Search the specified branch key manager key chain to find the index of the specified key.
     pub async fn find_key_index(&self, branch: String, key: &PrivateKey) -> Result<u64, KeyManagerServiceError> {
+        self.find_key_index_with_gap_limit(branch, key, KEY_MANAGER_MAX_SEARCH_DEPTH)
+            .await
+    }
+
+    /// Search the specified branch key manager key chain to find the index of the specified key, searching at most
+    /// `gap_limit` keys past the current index of the branch.
+    pub async fn find_key_index_with_gap_limit(
+        &self,
+        branch: String,
+        key: &PrivateKey,
+        gap_limit: u64,
+    ) -> Result<u64, KeyManagerServiceError> {
         let km = self
             .key_managers
             .get(&branch)
@@ -122,7 +174,7 @@ where TBackend: KeyManagerBackend + 'static
 
         let current_index = km.key_index();
 
-        for i in 0u64..current_index + KEY_MANAGER_MAX_SEARCH_DEPTH {
+        for i in 0u64..current_index.saturating_add(gap_limit) {
             if km.derive_key(i)?.k == *key {
                 trace!(target: LOG_TARGET, "Key found in {} Key Chain at index {}", branch, i);
                 return Ok(i);
@@ -132,6 +184,27 @@ where TBackend: KeyManagerBackend + 'static
         Err(KeyManagerServiceError::KeyNotFoundInKeyChain)
     }
 
+    /// Probe the branch from index 0 onwards and return the last index whose key satisfies `is_used`, e.g. because
+    /// the wallet holds an output with that key, before `gap_limit` consecutive unused indices are encountered.
+    pub async fn find_highest_used_index<F>(
+        &self,
+        branch: String,
+        gap_limit: u64,
+        is_used: F,
+    ) -> Result<u64, KeyManagerServiceError>
+    where
+        F: FnMut(&PrivateKey) -> bool,
+    {
+        let km = self
+            .key_managers
+            .get(&branch)
+            .ok_or(KeyManagerServiceError::UnknownKeyBranch)?
+            .lock()
+            .await;
+        km.find_highest_used_index(gap_limit, is_used)?
+            .ok_or(KeyManagerServiceError::KeyNotFoundInKeyChain)
+    }
+
     /// If the supplied index is higher than the current UTXO key chain indices then they will be updated.
     pub async fn update_current_key_index_if_higher(
         &self,
@@ -147,7 +220,7 @@ where TBackend: KeyManagerBackend + 'static
         let current_index = km.key_index();
         if index > current_index {
             km.update_key_index(index);
-            self.db.set_key_index(branch, index)?;
+            self.db.set_key_index(self.stored_branch(&branch), index)?;
             trace!(target: LOG_TARGET, "Updated UTXO Key Index to {}", index);
         }
         Ok(())
@@ -20,6 +20,8 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use chacha20poly1305::XChaCha20Poly1305;
+
 use crate::key_manager_service::{error::KeyManagerStorageError, storage::database::KeyManagerState};
 
 /// This trait defines the required behaviour that a storage backend must provide for the Key Manager service.
@@ -33,4 +35,10 @@ pub trait KeyManagerBackend: Send + Sync + Clone {
     fn increment_key_index(&self, branch: String) -> Result<(), KeyManagerStorageError>;
     /// This method will set the currently stored key index for the key manager.
     fn set_key_index(&self, branch: String, index: u64) -> Result<(), KeyManagerStorageError>;
+    /// This will retrieve the branch strings of all the key managers that are stored.
+    fn get_all_branches(&self) -> Result<Vec<String>, KeyManagerStorageError>;
+    /// Discard the in-memory database cipher. Encrypted values cannot be read or written until the database is unlocked
+    fn lock(&self);
+    /// Restore the database cipher after the database was locked
+    fn unlock(&self, cipher: XChaCha20Poly1305);
 }
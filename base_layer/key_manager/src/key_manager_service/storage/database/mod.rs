@@ -71,4 +71,9 @@ where T: KeyManagerBackend + 'static
     pub fn set_key_index(&self, branch: String, index: u64) -> Result<(), KeyManagerStorageError> {
         self.db.set_key_index(branch, index)
     }
+
+    /// Retrieves the branches of all the stored key manager states.
+    pub fn get_all_branches(&self) -> Result<Vec<String>, KeyManagerStorageError> {
+        self.db.get_all_branches()
+    }
 }
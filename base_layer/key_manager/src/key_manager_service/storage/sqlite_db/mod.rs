@@ -22,16 +22,21 @@
 
 use std::{
     convert::TryFrom,
-    sync::{Arc, RwLock},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+        RwLock,
+        RwLockReadGuard,
+    },
 };
 
-use chacha20poly1305::XChaCha20Poly1305;
+use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 pub use key_manager_state::{KeyManagerStateSql, NewKeyManagerStateSql};
 use log::*;
 use tari_common_sqlite::{error::SqliteStorageError, sqlite_connection_pool::PooledDbConnection};
 use tari_common_types::encryption::Encryptable;
-use tari_utilities::acquire_read_lock;
+use tari_utilities::{acquire_read_lock, acquire_write_lock};
 use tokio::time::Instant;
 
 use crate::key_manager_service::{
@@ -48,6 +53,7 @@ const LOG_TARGET: &str = "wallet::key_manager_service::database::wallet";
 pub struct KeyManagerSqliteDatabase<TKeyManagerDbConnection> {
     database_connection: Arc<TKeyManagerDbConnection>,
     cipher: Arc<RwLock<XChaCha20Poly1305>>,
+    is_locked: Arc<AtomicBool>,
 }
 
 impl<TKeyManagerDbConnection: PooledDbConnection<Error = SqliteStorageError> + Clone>
@@ -60,9 +66,19 @@ impl<TKeyManagerDbConnection: PooledDbConnection<Error = SqliteStorageError> + C
         Self {
             database_connection: Arc::new(database_connection),
             cipher: Arc::new(RwLock::new(cipher)),
+            is_locked: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// Acquires the database cipher, failing if the database has been locked
+    fn read_cipher(&self) -> Result<RwLockReadGuard<'_, XChaCha20Poly1305>, KeyManagerStorageError> {
+        let cipher = acquire_read_lock!(self.cipher);
+        if self.is_locked.load(Ordering::SeqCst) {
+            return Err(KeyManagerStorageError::Locked);
+        }
+        Ok(cipher)
+    }
+
     pub fn init(database_connection: TKeyManagerDbConnection, cipher: XChaCha20Poly1305) -> Self {
         let db = Self::new(database_connection, cipher);
         db.run_migrations().expect("Migrations to run");
@@ -90,6 +106,19 @@ impl<TKeyManagerDbConnection: PooledDbConnection<Error = SqliteStorageError> + C
 impl<TKeyManagerDbConnection: PooledDbConnection<Error = SqliteStorageError> + Send + Sync + Clone> KeyManagerBackend
     for KeyManagerSqliteDatabase<TKeyManagerDbConnection>
 {
+    fn lock(&self) {
+        let mut cipher = acquire_write_lock!(self.cipher);
+        // Replacing the cipher drops the one holding the main key
+        *cipher = XChaCha20Poly1305::new(&Key::default());
+        self.is_locked.store(true, Ordering::SeqCst);
+    }
+
+    fn unlock(&self, cipher: XChaCha20Poly1305) {
+        let mut current = acquire_write_lock!(self.cipher);
+        *current = cipher;
+        self.is_locked.store(false, Ordering::SeqCst);
+    }
+
     fn get_key_manager(&self, branch: String) -> Result<Option<KeyManagerState>, KeyManagerStorageError> {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
@@ -98,7 +127,7 @@ impl<TKeyManagerDbConnection: PooledDbConnection<Error = SqliteStorageError> + S
         let result = match KeyManagerStateSql::get_state(&branch, &mut conn).ok() {
             None => None,
             Some(km) => {
-                let cipher = acquire_read_lock!(self.cipher);
+                let cipher = self.read_cipher()?;
                 let km = km
                     .decrypt(&cipher)
                     .map_err(|e| KeyManagerStorageError::AeadError(format!("Decryption Error: {}", e)))?;
@@ -122,7 +151,7 @@ impl<TKeyManagerDbConnection: PooledDbConnection<Error = SqliteStorageError> + S
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         let km_sql = NewKeyManagerStateSql::from(key_manager);
         let km_sql = km_sql
@@ -146,7 +175,7 @@ impl<TKeyManagerDbConnection: PooledDbConnection<Error = SqliteStorageError> + S
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
         let km = KeyManagerStateSql::get_state(&branch, &mut conn)?;
         let mut km = km
             .decrypt(&cipher)
@@ -176,7 +205,7 @@ impl<TKeyManagerDbConnection: PooledDbConnection<Error = SqliteStorageError> + S
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
         let km = KeyManagerStateSql::get_state(&branch, &mut conn)?;
         let mut km = km
             .decrypt(&cipher)
@@ -198,6 +227,14 @@ impl<TKeyManagerDbConnection: PooledDbConnection<Error = SqliteStorageError> + S
 
         Ok(())
     }
+
+    fn get_all_branches(&self) -> Result<Vec<String>, KeyManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        Ok(KeyManagerStateSql::index(&mut conn)?
+            .into_iter()
+            .map(|km| km.branch_seed)
+            .collect())
+    }
 }
 
 #[cfg(test)]
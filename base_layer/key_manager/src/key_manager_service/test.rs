@@ -20,7 +20,28 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::{cipher_seed::CipherSeed, key_manager_service::KeyManagerMock};
+use std::path::Path;
+
+use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
+use tari_common_sqlite::connection::{DbConnection, DbConnectionUrl};
+use tempfile::tempdir;
+
+use crate::{
+    cipher_seed::CipherSeed,
+    key_manager_service::{
+        storage::{database::KeyManagerDatabase, sqlite_db::KeyManagerSqliteDatabase},
+        KeyManagerHandle,
+        KeyManagerInterface,
+        KeyManagerMock,
+        KeyManagerServiceError,
+    },
+};
+
+fn create_sqlite_db(path: &Path) -> KeyManagerDatabase<KeyManagerSqliteDatabase<DbConnection>> {
+    let connection = DbConnection::connect_url(&DbConnectionUrl::File(path.join("key_manager.sqlite3"))).unwrap();
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(&[7u8; 32]));
+    KeyManagerDatabase::new(KeyManagerSqliteDatabase::init(connection, cipher))
+}
 
 #[tokio::test]
 async fn get_next_key_test_mock() {
@@ -69,3 +90,70 @@ async fn get_key_at_test_mock() {
     assert_ne!(key_2.key, key_1.key);
     assert_eq!(key_1.key, key_1_2);
 }
+
+#[tokio::test]
+async fn network_tagged_branches_derive_distinct_keys() {
+    let seed = CipherSeed::new();
+    let branch = "test_branch".to_string();
+    let mut keys = Vec::new();
+    for network_tag in [None, Some("esmeralda"), Some("mainnet")] {
+        let temp_dir = tempdir().unwrap();
+        let db = create_sqlite_db(temp_dir.path());
+        let key_manager = match network_tag {
+            Some(tag) => KeyManagerHandle::new_with_network_tag(seed.clone(), db, tag.to_string()).unwrap(),
+            None => KeyManagerHandle::new(seed.clone(), db),
+        };
+        key_manager.add_new_branch(branch.clone()).await.unwrap();
+        keys.push(key_manager.get_key_at_index(branch.clone(), 0).await.unwrap());
+    }
+
+    assert_ne!(keys[0], keys[1]);
+    assert_ne!(keys[0], keys[2]);
+    assert_ne!(keys[1], keys[2]);
+}
+
+#[tokio::test]
+async fn network_tagged_key_manager_refuses_mismatched_network() {
+    let seed = CipherSeed::new();
+    let branch = "test_branch".to_string();
+    let temp_dir = tempdir().unwrap();
+
+    let key_manager =
+        KeyManagerHandle::new_with_network_tag(seed.clone(), create_sqlite_db(temp_dir.path()), "esmeralda".into())
+            .unwrap();
+    key_manager.add_new_branch(branch.clone()).await.unwrap();
+    let key = key_manager.get_next_key(branch.clone()).await.unwrap();
+
+    let result =
+        KeyManagerHandle::new_with_network_tag(seed.clone(), create_sqlite_db(temp_dir.path()), "mainnet".into());
+    assert!(matches!(
+        result,
+        Err(KeyManagerServiceError::NetworkMismatch { branch: b, found, expected })
+            if b == branch && found == "esmeralda" && expected == "mainnet"
+    ));
+
+    // Reopening on the same network picks up the stored branch state
+    let key_manager =
+        KeyManagerHandle::new_with_network_tag(seed, create_sqlite_db(temp_dir.path()), "esmeralda".into()).unwrap();
+    key_manager.add_new_branch(branch.clone()).await.unwrap();
+    assert_eq!(
+        key_manager.get_key_at_index(branch.clone(), key.index).await.unwrap(),
+        key.key
+    );
+    assert_eq!(key_manager.get_next_key(branch).await.unwrap().index, key.index + 1);
+}
+
+#[tokio::test]
+async fn network_tagged_key_manager_refuses_untagged_branches() {
+    let seed = CipherSeed::new();
+    let temp_dir = tempdir().unwrap();
+
+    let key_manager = KeyManagerHandle::new(seed.clone(), create_sqlite_db(temp_dir.path()));
+    key_manager.add_new_branch("test_branch").await.unwrap();
+
+    let result = KeyManagerHandle::new_with_network_tag(seed, create_sqlite_db(temp_dir.path()), "esmeralda".into());
+    assert!(matches!(
+        result,
+        Err(KeyManagerServiceError::UntaggedBranch(branch)) if branch == "test_branch"
+    ));
+}
@@ -0,0 +1 @@
+ALTER TABLE outbound_transactions DROP COLUMN priority;
@@ -0,0 +1 @@
+ALTER TABLE outbound_transactions ADD priority INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1 @@
+ALTER TABLE completed_transactions DROP COLUMN body_pruned;
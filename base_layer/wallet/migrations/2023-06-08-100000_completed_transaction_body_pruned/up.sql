@@ -0,0 +1 @@
+ALTER TABLE completed_transactions ADD body_pruned INTEGER NOT NULL DEFAULT 0;
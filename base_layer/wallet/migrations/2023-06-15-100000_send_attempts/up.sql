@@ -0,0 +1,10 @@
+CREATE TABLE send_attempts
+(
+    id        INTEGER PRIMARY KEY NOT NULL,
+    tx_id     BIGINT              NOT NULL,
+    timestamp DATETIME            NOT NULL,
+    mechanism INTEGER             NOT NULL,
+    succeeded INTEGER             NOT NULL
+);
+
+CREATE INDEX idx_send_attempts_tx_id ON send_attempts (tx_id);
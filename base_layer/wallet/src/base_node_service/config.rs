@@ -35,6 +35,10 @@ pub struct BaseNodeServiceConfig {
     pub base_node_rpc_pool_size: usize,
     /// This is the size of the event channel used to communicate base node events to the wallet
     pub event_channel_size: usize,
+    /// How long the current base node may be unreachable before failing over to the next base node in the failover
+    /// list. This is also the interval at which a higher priority base node is checked for recovery.
+    #[serde(with = "serializers::seconds")]
+    pub base_node_failover_grace_period: Duration,
 }
 
 impl Default for BaseNodeServiceConfig {
@@ -43,6 +47,7 @@ impl Default for BaseNodeServiceConfig {
             base_node_monitor_max_refresh_interval: Duration::from_secs(90),
             base_node_rpc_pool_size: 10,
             event_channel_size: 250,
+            base_node_failover_grace_period: Duration::from_secs(60),
         }
     }
 }
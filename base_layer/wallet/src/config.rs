@@ -108,6 +108,9 @@ pub struct WalletConfig {
     pub base_node_service_peers: StringList,
     /// The amount of times wallet recovery will be retried before being abandoned
     pub recovery_retry_limit: usize,
+    /// The expected time in milliseconds it takes to scan a single block during wallet recovery, used to estimate how
+    /// long a recovery will take
+    pub recovery_scan_ms_per_block: u64,
     /// The default uT fee per gram to use for transaction fees
     pub fee_per_gram: u64,
     /// Number of required transaction confirmations used for UI purposes
@@ -117,6 +120,11 @@ pub struct WalletConfig {
     pub use_libtor: bool,
     /// A path to the file that stores the base node identity and secret key
     pub identity_file: Option<PathBuf>,
+    /// If set to `true`, every key manager branch is tagged with the network so the same seed derives distinct keys
+    /// on each network. The wallet refuses to start if its database holds branches tagged with another network, or
+    /// branches without a tag, whose keys would not be found with it enabled. This can therefore only be enabled for a
+    /// new wallet.
+    pub network_tagged_key_branches: bool,
 }
 
 impl Default for WalletConfig {
@@ -150,10 +158,12 @@ impl Default for WalletConfig {
             custom_base_node: None,
             base_node_service_peers: StringList::default(),
             recovery_retry_limit: 3,
+            recovery_scan_ms_per_block: 20,
             fee_per_gram: 5,
             num_required_confirmations: 3,
             use_libtor: false,
             identity_file: None,
+            network_tagged_key_branches: false,
         }
     }
 }
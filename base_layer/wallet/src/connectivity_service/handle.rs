@@ -40,6 +40,7 @@ pub enum WalletConnectivityRequest {
 pub struct WalletConnectivityHandle {
     sender: mpsc::Sender<WalletConnectivityRequest>,
     base_node_watch: Watch<Option<Peer>>,
+    base_node_peers_watch: Watch<Vec<Peer>>,
     online_status_rx: watch::Receiver<OnlineStatus>,
 }
 
@@ -47,11 +48,13 @@ impl WalletConnectivityHandle {
     pub(super) fn new(
         sender: mpsc::Sender<WalletConnectivityRequest>,
         base_node_watch: Watch<Option<Peer>>,
+        base_node_peers_watch: Watch<Vec<Peer>>,
         online_status_rx: watch::Receiver<OnlineStatus>,
     ) -> Self {
         Self {
             sender,
             base_node_watch,
+            base_node_peers_watch,
             online_status_rx,
         }
     }
@@ -60,6 +63,8 @@ impl WalletConnectivityHandle {
 #[async_trait::async_trait]
 impl WalletConnectivityInterface for WalletConnectivityHandle {
     fn set_base_node(&mut self, base_node_peer: Peer) {
+        // Selecting a single base node replaces any failover list
+        self.base_node_peers_watch.send(vec![base_node_peer.clone()]);
         if let Some(peer) = self.base_node_watch.borrow().as_ref() {
             if peer.public_key == base_node_peer.public_key {
                 return;
@@ -68,6 +73,22 @@ impl WalletConnectivityInterface for WalletConnectivityHandle {
         self.base_node_watch.send(Some(base_node_peer));
     }
 
+    fn set_base_node_peers(&self, peers: Vec<Peer>) {
+        let primary = peers.first().cloned();
+        self.base_node_peers_watch.send(peers);
+        if let Some(primary) = primary {
+            let is_current = self
+                .base_node_watch
+                .borrow()
+                .as_ref()
+                .map(|peer| peer.public_key == primary.public_key)
+                .unwrap_or(false);
+            if !is_current {
+                self.base_node_watch.send(Some(primary));
+            }
+        }
+    }
+
     fn get_current_base_node_watcher(&self) -> watch::Receiver<Option<Peer>> {
         self.base_node_watch.get_receiver()
     }
@@ -53,10 +53,12 @@ impl ServiceInitializer for WalletConnectivityInitializer {
     async fn initialize(&mut self, context: ServiceInitializerContext) -> Result<(), ServiceInitializationError> {
         let (sender, receiver) = mpsc::channel(5);
         let base_node_watch = Watch::new(None);
+        let base_node_peers_watch = Watch::new(Vec::new());
         let online_status_watch = Watch::new(OnlineStatus::Offline);
         context.register_handle(WalletConnectivityHandle::new(
             sender,
             base_node_watch.clone(),
+            base_node_peers_watch.clone(),
             online_status_watch.get_receiver(),
         ));
 
@@ -67,7 +69,8 @@ impl ServiceInitializer for WalletConnectivityInitializer {
             let service = WalletConnectivityService::new(
                 config,
                 receiver,
-                base_node_watch.get_receiver(),
+                base_node_watch,
+                base_node_peers_watch.get_receiver(),
                 online_status_watch,
                 connectivity,
             );
@@ -36,6 +36,11 @@ use crate::connectivity_service::OnlineStatus;
 pub trait WalletConnectivityInterface: Clone + Send + Sync + 'static {
     fn set_base_node(&mut self, base_node_peer: Peer);
 
+    /// Set a prioritized list of base nodes. The wallet connects to the first base node in the list and fails over to
+    /// the next one if the current base node is unreachable for longer than the failover grace period. The wallet
+    /// switches back to a higher priority base node once it is reachable again.
+    fn set_base_node_peers(&self, peers: Vec<Peer>);
+
     fn get_current_base_node_watcher(&self) -> watch::Receiver<Option<Peer>>;
 
     /// Obtain a BaseNodeWalletRpcClient.
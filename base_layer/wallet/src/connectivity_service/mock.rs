@@ -69,6 +69,10 @@ impl WalletConnectivityMock {
         self.base_node_watch.send(Some(base_node_peer));
     }
 
+    pub fn set_connectivity_status(&self, status: OnlineStatus) {
+        self.online_status_watch.send(status);
+    }
+
     pub async fn base_node_changed(&mut self) -> Option<Peer> {
         self.base_node_watch.changed().await;
         self.base_node_watch.borrow().as_ref().cloned()
@@ -86,6 +90,12 @@ impl WalletConnectivityInterface for WalletConnectivityMock {
         self.notify_base_node_set(base_node_peer);
     }
 
+    fn set_base_node_peers(&self, peers: Vec<Peer>) {
+        if let Some(primary) = peers.into_iter().next() {
+            self.notify_base_node_set(primary);
+        }
+    }
+
     fn get_current_base_node_watcher(&self) -> Receiver<Option<Peer>> {
         self.base_node_watch.get_receiver()
     }
@@ -20,7 +20,10 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{mem, time::Duration};
+use std::{
+    mem,
+    time::{Duration, Instant},
+};
 
 use log::*;
 use tari_comms::{
@@ -32,6 +35,7 @@ use tari_comms::{
 use tari_core::base_node::{rpc::BaseNodeWalletRpcClient, sync::rpc::BaseNodeSyncRpcClient};
 use tokio::{
     sync::{mpsc, oneshot, watch},
+    task,
     time,
     time::MissedTickBehavior,
 };
@@ -43,6 +47,8 @@ use crate::{
 };
 
 const LOG_TARGET: &str = "wallet::connectivity";
+/// The maximum time to wait when checking if a higher priority base node has recovered
+const FAILBACK_DIAL_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// Connection status of the Base Node
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -57,9 +63,24 @@ pub struct WalletConnectivityService {
     request_receiver: mpsc::Receiver<WalletConnectivityRequest>,
     connectivity: ConnectivityRequester,
     base_node_watch: watch::Receiver<Option<Peer>>,
+    /// Used to change the current base node when failing over
+    base_node_selector: Watch<Option<Peer>>,
+    /// The base node failover list, in order of priority
+    base_node_peers: watch::Receiver<Vec<Peer>>,
     pools: Option<ClientPoolContainer>,
     online_status_watch: Watch<OnlineStatus>,
     pending_requests: Vec<ReplyOneshot>,
+    failing_since: Option<Instant>,
+    last_failback_attempt: Option<Instant>,
+    failback_probe_running: bool,
+    failback_sender: mpsc::Sender<FailbackProbeResult>,
+    failback_receiver: mpsc::Receiver<FailbackProbeResult>,
+}
+
+/// The outcome of checking whether a higher priority base node is reachable while connected to `current`
+struct FailbackProbeResult {
+    current: NodeId,
+    reachable: Option<Peer>,
 }
 
 struct ClientPoolContainer {
@@ -71,18 +92,27 @@ impl WalletConnectivityService {
     pub(super) fn new(
         config: BaseNodeServiceConfig,
         request_receiver: mpsc::Receiver<WalletConnectivityRequest>,
-        base_node_watch: watch::Receiver<Option<Peer>>,
+        base_node_watch: Watch<Option<Peer>>,
+        base_node_peers: watch::Receiver<Vec<Peer>>,
         online_status_watch: Watch<OnlineStatus>,
         connectivity: ConnectivityRequester,
     ) -> Self {
+        let (failback_sender, failback_receiver) = mpsc::channel(1);
         Self {
             config,
             request_receiver,
             connectivity,
-            base_node_watch,
+            base_node_watch: base_node_watch.get_receiver(),
+            base_node_selector: base_node_watch,
+            base_node_peers,
             pools: None,
             pending_requests: Vec::new(),
             online_status_watch,
+            failing_since: None,
+            last_failback_attempt: None,
+            failback_probe_running: false,
+            failback_sender,
+            failback_receiver,
         }
     }
 
@@ -108,6 +138,10 @@ impl WalletConnectivityService {
                     self.handle_request(req).await;
                 },
 
+                Some(result) = self.failback_receiver.recv() => {
+                    self.handle_failback_result(result);
+                },
+
                 _ = check_connection.tick() => {
                     self.check_connection().await;
                 }
@@ -118,7 +152,9 @@ impl WalletConnectivityService {
     async fn check_connection(&mut self) {
         match self.pools.as_ref() {
             Some(pool) => {
-                if !pool.base_node_wallet_rpc_client.is_connected().await {
+                if pool.base_node_wallet_rpc_client.is_connected().await {
+                    self.try_failback();
+                } else {
                     debug!(target: LOG_TARGET, "Peer connection lost. Attempting to reconnect...");
                     self.set_online_status(OnlineStatus::Offline);
                     self.setup_base_node_connection().await;
@@ -249,6 +285,7 @@ impl WalletConnectivityService {
             self.set_online_status(OnlineStatus::Connecting);
             match self.try_setup_rpc_pool(node_id.clone()).await {
                 Ok(true) => {
+                    self.failing_since = None;
                     self.set_online_status(OnlineStatus::Online);
                     debug!(
                         target: LOG_TARGET,
@@ -270,15 +307,19 @@ impl WalletConnectivityService {
                         self.config.base_node_monitor_max_refresh_interval.as_secs()
                     );
                     self.set_online_status(OnlineStatus::Offline);
-                    time::sleep(self.config.base_node_monitor_max_refresh_interval).await;
+                    if !self.try_failover(&node_id) {
+                        time::sleep(self.config.base_node_monitor_max_refresh_interval).await;
+                    }
                     continue;
                 },
                 Err(e) => {
                     warn!(target: LOG_TARGET, "{}", e);
                     if self.current_base_node().as_ref() == Some(&node_id) {
-                        self.disconnect_base_node(node_id).await;
+                        self.disconnect_base_node(node_id.clone()).await;
                         self.set_online_status(OnlineStatus::Offline);
-                        time::sleep(self.config.base_node_monitor_max_refresh_interval).await;
+                        if !self.try_failover(&node_id) {
+                            time::sleep(self.config.base_node_monitor_max_refresh_interval).await;
+                        }
                     }
                     continue;
                 },
@@ -286,6 +327,92 @@ impl WalletConnectivityService {
         }
     }
 
+    /// Switches to the next base node in the failover list if the current base node has been unreachable for longer
+    /// than the grace period. Returns true if the base node was changed.
+    fn try_failover(&mut self, node_id: &NodeId) -> bool {
+        let failing_since = *self.failing_since.get_or_insert_with(Instant::now);
+        if failing_since.elapsed() < self.config.base_node_failover_grace_period {
+            return false;
+        }
+        let next_peer = match next_failover_peer(&self.base_node_peers.borrow(), node_id) {
+            Some(peer) => peer,
+            None => return false,
+        };
+        warn!(
+            target: LOG_TARGET,
+            "Base node {} has been unreachable for {:.0?}. Failing over to base node {}",
+            node_id,
+            failing_since.elapsed(),
+            next_peer.node_id
+        );
+        self.failing_since = None;
+        self.base_node_selector.send(Some(next_peer));
+        true
+    }
+
+    /// If the current base node is not the preferred base node, check (at most once per grace period) whether a higher
+    /// priority base node is reachable again. The higher priority base nodes are dialed in a separate task, which
+    /// reports back through the failback channel so that pending requests are not held up by unreachable peers.
+    fn try_failback(&mut self) {
+        if self.failback_probe_running {
+            return;
+        }
+        let current = match self.current_base_node() {
+            Some(n) => n,
+            None => return,
+        };
+        let preferred = {
+            let peers = self.base_node_peers.borrow();
+            let current_index = match peers.iter().position(|p| p.node_id == current) {
+                Some(i) => i,
+                // The current base node was set outside of the failover list
+                None => return,
+            };
+            peers.iter().take(current_index).cloned().collect::<Vec<_>>()
+        };
+        if preferred.is_empty() {
+            self.last_failback_attempt = None;
+            return;
+        }
+        if self
+            .last_failback_attempt
+            .map(|t| t.elapsed() < self.config.base_node_failover_grace_period)
+            .unwrap_or(false)
+        {
+            return;
+        }
+        self.last_failback_attempt = Some(Instant::now());
+        self.failback_probe_running = true;
+
+        let connectivity = self.connectivity.clone();
+        let failback_sender = self.failback_sender.clone();
+        task::spawn(async move {
+            let reachable = find_reachable_peer(connectivity, preferred).await;
+            let _result = failback_sender.send(FailbackProbeResult { current, reachable }).await;
+        });
+    }
+
+    fn handle_failback_result(&mut self, result: FailbackProbeResult) {
+        self.failback_probe_running = false;
+        let peer = match result.reachable {
+            Some(peer) => peer,
+            None => return,
+        };
+        if self.current_base_node().as_ref() != Some(&result.current) {
+            debug!(
+                target: LOG_TARGET,
+                "The base node changed while checking if base node {} is reachable. Not switching back.", peer.node_id
+            );
+            return;
+        }
+        info!(
+            target: LOG_TARGET,
+            "Higher priority base node {} is reachable again. Switching back from {}", peer.node_id, result.current
+        );
+        self.last_failback_attempt = None;
+        self.base_node_selector.send(Some(peer));
+    }
+
     fn set_online_status(&self, status: OnlineStatus) {
         self.online_status_watch.send(status);
     }
@@ -339,6 +466,40 @@ impl WalletConnectivityService {
     }
 }
 
+/// Returns the base node to fail over to from `current`, which is the next base node in the failover list or, if
+/// `current` is not in the list, the first base node that isn't `current`
+pub(super) fn next_failover_peer(peers: &[Peer], current: &NodeId) -> Option<Peer> {
+    match peers.iter().position(|p| p.node_id == *current) {
+        Some(i) => peers
+            .iter()
+            .cycle()
+            .skip(i + 1)
+            .take(peers.len())
+            .find(|p| p.node_id != *current),
+        None => peers.iter().find(|p| p.node_id != *current),
+    }
+    .cloned()
+}
+
+/// Dials each of the peers in order and returns the first one that is reachable
+async fn find_reachable_peer(connectivity: ConnectivityRequester, peers: Vec<Peer>) -> Option<Peer> {
+    for peer in peers {
+        match time::timeout(FAILBACK_DIAL_TIMEOUT, connectivity.dial_peer(peer.node_id.clone())).await {
+            Ok(Ok(_)) => return Some(peer),
+            Ok(Err(e)) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Base node {} is still unreachable: {}", peer.node_id, e
+                );
+            },
+            Err(_) => {
+                debug!(target: LOG_TARGET, "Timed out dialing base node {}", peer.node_id);
+            },
+        }
+    }
+    None
+}
+
 enum ReplyOneshot {
     WalletRpc(oneshot::Sender<RpcClientLease<BaseNodeWalletRpcClient>>),
     SyncRpc(oneshot::Sender<RpcClientLease<BaseNodeSyncRpcClient>>),
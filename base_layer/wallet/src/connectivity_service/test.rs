@@ -21,7 +21,7 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use core::convert;
-use std::{iter, sync::Arc};
+use std::{iter, sync::Arc, time::Duration};
 
 use futures::future;
 use tari_comms::{
@@ -40,10 +40,12 @@ use tari_test_utils::runtime::spawn_until_shutdown;
 use tokio::{
     sync::{mpsc, Barrier},
     task,
+    time,
 };
 
-use super::service::WalletConnectivityService;
+use super::service::{next_failover_peer, WalletConnectivityService};
 use crate::{
+    base_node_service::config::BaseNodeServiceConfig,
     connectivity_service::{OnlineStatus, WalletConnectivityHandle, WalletConnectivityInterface},
     util::watch::Watch,
 };
@@ -53,18 +55,36 @@ async fn setup() -> (
     MockRpcServer<MockRpcImpl>,
     ConnectivityManagerMockState,
     Shutdown,
+) {
+    setup_with_config(Default::default()).await
+}
+
+async fn setup_with_config(
+    config: BaseNodeServiceConfig,
+) -> (
+    WalletConnectivityHandle,
+    MockRpcServer<MockRpcImpl>,
+    ConnectivityManagerMockState,
+    Shutdown,
 ) {
     let (tx, rx) = mpsc::channel(1);
     let base_node_watch = Watch::new(None);
+    let base_node_peers_watch = Watch::new(Vec::new());
     let online_status_watch = Watch::new(OnlineStatus::Offline);
-    let handle = WalletConnectivityHandle::new(tx, base_node_watch.clone(), online_status_watch.get_receiver());
+    let handle = WalletConnectivityHandle::new(
+        tx,
+        base_node_watch.clone(),
+        base_node_peers_watch.clone(),
+        online_status_watch.get_receiver(),
+    );
     let (connectivity, mock) = create_connectivity_mock();
     let mock_state = mock.spawn();
     // let peer_manager = create_peer_manager(tempdir().unwrap());
     let service = WalletConnectivityService::new(
-        Default::default(),
+        config,
         rx,
-        base_node_watch.get_receiver(),
+        base_node_watch,
+        base_node_peers_watch.get_receiver(),
         online_status_watch,
         connectivity,
     );
@@ -239,3 +259,58 @@ async fn it_gracefully_handles_multiple_connection_failures() {
     // Still able to get a base node rpc client
     pending_request.await.unwrap();
 }
+
+#[tokio::test]
+async fn it_fails_over_to_the_next_base_node() {
+    let config = BaseNodeServiceConfig {
+        base_node_monitor_max_refresh_interval: Duration::from_millis(10),
+        base_node_failover_grace_period: Duration::from_millis(50),
+        ..Default::default()
+    };
+    let (mut handle, mock_server, mock_state, _shutdown) = setup_with_config(config).await;
+    let primary = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let secondary = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    // Only the secondary base node is reachable
+    let conn = mock_server.create_mockimpl_connection(secondary.to_peer()).await;
+    mock_state.add_active_connection(conn).await;
+
+    let mut base_node_watcher = handle.get_current_base_node_watcher();
+    handle.set_base_node_peers(vec![primary.to_peer(), secondary.to_peer()]);
+    assert_eq!(handle.get_current_base_node_id().unwrap(), *primary.node_id());
+
+    time::timeout(Duration::from_secs(10), async {
+        loop {
+            base_node_watcher.changed().await.unwrap();
+            let current = base_node_watcher.borrow().as_ref().map(|p| p.node_id.clone());
+            if current.as_ref() == Some(secondary.node_id()) {
+                break;
+            }
+        }
+    })
+    .await
+    .expect("Expected the base node to change to the secondary");
+
+    mock_state.expect_dial_peer(primary.node_id()).await;
+    let rpc_client = handle.obtain_base_node_wallet_rpc_client().await.unwrap();
+    assert!(rpc_client.is_connected());
+    assert_eq!(handle.get_current_base_node_id().unwrap(), *secondary.node_id());
+}
+
+#[test]
+fn it_fails_over_from_a_base_node_outside_the_failover_list() {
+    let primary = build_node_identity(PeerFeatures::COMMUNICATION_NODE).to_peer();
+    let secondary = build_node_identity(PeerFeatures::COMMUNICATION_NODE).to_peer();
+    let unlisted = build_node_identity(PeerFeatures::COMMUNICATION_NODE).to_peer();
+    let peers = vec![primary.clone(), secondary.clone()];
+
+    let next = next_failover_peer(&peers, &primary.node_id).unwrap();
+    assert_eq!(next.node_id, secondary.node_id);
+    let next = next_failover_peer(&peers, &secondary.node_id).unwrap();
+    assert_eq!(next.node_id, primary.node_id);
+    let next = next_failover_peer(&peers, &unlisted.node_id).unwrap();
+    assert_eq!(next.node_id, primary.node_id);
+
+    // There is nothing to fail over to if the failing base node is the only one in the list
+    assert!(next_failover_peer(&[primary.clone()], &primary.node_id).is_none());
+    assert!(next_failover_peer(&[], &unlisted.node_id).is_none());
+}
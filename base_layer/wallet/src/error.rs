@@ -103,6 +103,12 @@ pub enum WalletError {
     UnexpectedApiResponse { method: String, api: String },
     #[error("Public address not set for this wallet")]
     PublicAddressNotSet,
+    #[error("The base node has not reported a chain tip yet")]
+    ChainTipUnknown,
+    #[error("The wallet is locked")]
+    Locked,
+    #[error("IO error: `{0}`")]
+    IoError(#[from] std::io::Error),
 }
 
 pub const LOG_TARGET: &str = "tari::application";
@@ -178,6 +184,8 @@ pub enum WalletStorageError {
     RecoverySeedError(String),
     #[error("Bad encryption version: `{0}`")]
     BadEncryptionVersion(String),
+    #[error("The wallet database is locked")]
+    Locked,
 }
 
 impl From<WalletStorageError> for ExitError {
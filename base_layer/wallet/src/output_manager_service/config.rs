@@ -20,10 +20,21 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
 use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use tari_common::configuration::serializers;
+use tari_core::transactions::{tari_amount::MicroTari, transaction_components::OutputType};
+
+use crate::output_manager_service::{error::OutputManagerError, resources::OutputManagerKeyManagerBranch};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct OutputManagerServiceConfig {
     /// If a large amount of tiny valued uT UTXOs are used as inputs to a transaction, the fee may be larger than the
     /// transaction amount. Set this value to `false` to allow spending of "dust" UTXOs for small valued transactions.
@@ -43,6 +54,85 @@ pub struct OutputManagerServiceConfig {
     pub autoignore_onesided_utxos: bool,
     /// The number of seconds that have to pass for the wallet to run revalidation of invalid UTXOs on startup.
     pub num_of_seconds_to_revalidate_invalid_utxos: u64,
+    /// If set to `true`, the wallet will periodically join small unspent outputs into a single output. Only mature,
+    /// unencumbered outputs are ever considered for consolidation.
+    pub auto_consolidate: bool,
+    /// The period between automatic consolidation attempts
+    #[serde(with = "serializers::seconds")]
+    pub auto_consolidate_interval: Duration,
+    /// Only unspent outputs with a value below this threshold are considered for automatic consolidation
+    pub auto_consolidate_value_threshold: MicroTari,
+    /// Automatic consolidation only takes place when the number of eligible outputs exceeds this limit
+    pub auto_consolidate_max_outputs: usize,
+    /// The fee per gram used for automatic consolidation transactions
+    pub auto_consolidate_fee_per_gram: MicroTari,
+    /// If set, matured coinbase outputs with a value above this threshold are automatically split into
+    /// `auto_split_coinbase_count` equal outputs when a new block is detected. Disabled by default.
+    pub auto_split_coinbase_threshold: Option<MicroTari>,
+    /// The number of outputs a large coinbase output is split into
+    pub auto_split_coinbase_count: usize,
+    /// The fee per gram used for automatic coinbase split transactions
+    pub auto_split_coinbase_fee_per_gram: MicroTari,
+    /// If set to `true`, outputs are not validated automatically when a new block is detected or when unvalidated
+    /// outputs are added. Validation then only takes place when it is explicitly requested.
+    pub manual_validation_only: bool,
+    /// The key manager branch used to derive the script keys of the wallet's spendable outputs. Outputs created under
+    /// a custom branch can only be recovered by a wallet configured with the same branch.
+    pub script_key_branch: String,
+    /// The key manager branch used to derive sender offset keys
+    pub sender_offset_key_branch: String,
+    /// If set, transactions whose fee exceeds this value are rejected unless the caller explicitly allows it. This
+    /// protects against accidentally paying an exorbitant fee, e.g. due to a mistyped fee per gram.
+    pub max_fee_per_transaction: Option<MicroTari>,
+    /// If set, the fee of a transaction with a change output is rounded up to the nearest multiple of this value. The
+    /// extra is added to the fee and deducted from the change.
+    pub fee_rounding: Option<MicroTari>,
+    /// If set, a transaction whose change would be worth less than this value gets no change output. The change is
+    /// added to the fee instead of creating a dust output.
+    pub min_change_value: Option<MicroTari>,
+    /// The maximum number of inputs a transaction may spend. Selecting inputs fails with `TooManyInputsRequired` if
+    /// more are needed to cover the amount, the wallet's outputs have to be consolidated first. A value of 0 disables
+    /// the limit.
+    pub max_inputs_per_transaction: usize,
+    /// If set to `true`, received one-sided payments that are dust, i.e. whose value does not exceed the fee of
+    /// spending them as an input at `dust_fee_per_gram`, are logged and not recorded since they can never be spent
+    /// economically
+    pub ignore_received_dust: bool,
+    /// The fee per gram used to decide whether a received output is dust
+    pub dust_fee_per_gram: MicroTari,
+    /// The number of threads used to trial-decrypt outputs when scanning for recoverable outputs. A value of 0 or 1
+    /// scans sequentially.
+    pub scan_parallelism: usize,
+    /// The maximum number of trial-decryption results kept in memory, so outputs that are scanned more than once (e.g.
+    /// overlapping ranges after a reorg) are not decrypted again. A value of 0 disables the cache.
+    pub trial_decryption_cache_size: usize,
+    /// If set, only outputs of these types are trial-decrypted when scanning for recoverable outputs and one-sided
+    /// payments, all other outputs are skipped. By default outputs of every type are scanned.
+    #[serde(default, with = "output_type_set")]
+    pub scan_output_types: Option<HashSet<OutputType>>,
+    /// The minimum number of confirmations (difference between tip height and mined height) an unspent output of the
+    /// given type needs before it is counted towards the available balance, until then its value is reported as
+    /// pending incoming. Output types that are not listed have no additional requirement.
+    #[serde(with = "output_type_map")]
+    pub min_confirmations_by_output_type: HashMap<OutputType, u64>,
+    /// If set to `true`, a txo validation that fails because the base node could not be reached or returned
+    /// inconsistent data is retried automatically, up to `txo_validation_max_retries` times
+    pub auto_retry_failed_validation: bool,
+    /// The delay before the first automatic validation retry, doubled for every subsequent retry
+    #[serde(with = "serializers::seconds")]
+    pub txo_validation_retry_delay: Duration,
+    /// The maximum number of automatic retries of a failed txo validation
+    pub txo_validation_max_retries: u32,
+    /// If set to `true`, outputs that can't be loaded from the database (e.g. due to corrupted encrypted data) are
+    /// logged and skipped when fetching the unspent outputs, selecting outputs to spend and validating outputs,
+    /// instead of failing the whole request
+    pub skip_corrupt_outputs: bool,
+    /// If set to `true`, a transaction that creates a change output also spends the smallest unspent output below
+    /// `auto_consolidate_value_threshold` that wasn't otherwise selected, so the change absorbs it
+    pub auto_absorb_small_change: bool,
+    /// The number of keys past the current index of a key manager branch that are searched for the key of a
+    /// recovered output before giving up on it
+    pub recovery_key_gap_limit: u64,
 }
 
 impl Default for OutputManagerServiceConfig {
@@ -54,6 +144,143 @@ impl Default for OutputManagerServiceConfig {
             tx_validator_batch_size: 100,
             autoignore_onesided_utxos: false,
             num_of_seconds_to_revalidate_invalid_utxos: 60 * 60 * 24 * 3,
+            auto_consolidate: false,
+            auto_consolidate_interval: Duration::from_secs(60 * 60),
+            auto_consolidate_value_threshold: MicroTari::from(100_000),
+            auto_consolidate_max_outputs: 50,
+            auto_consolidate_fee_per_gram: MicroTari::from(5),
+            auto_split_coinbase_threshold: None,
+            auto_split_coinbase_count: 10,
+            auto_split_coinbase_fee_per_gram: MicroTari::from(5),
+            manual_validation_only: false,
+            script_key_branch: OutputManagerKeyManagerBranch::SpendScript.get_branch_key(),
+            sender_offset_key_branch: OutputManagerKeyManagerBranch::SenderOffset.get_branch_key(),
+            max_fee_per_transaction: None,
+            fee_rounding: None,
+            min_change_value: None,
+            max_inputs_per_transaction: 0,
+            ignore_received_dust: false,
+            dust_fee_per_gram: MicroTari::from(5),
+            scan_parallelism: 1,
+            trial_decryption_cache_size: 10_000,
+            scan_output_types: None,
+            min_confirmations_by_output_type: [(OutputType::Coinbase, 6)].into_iter().collect(),
+            auto_retry_failed_validation: false,
+            txo_validation_retry_delay: Duration::from_secs(10),
+            txo_validation_max_retries: 5,
+            skip_corrupt_outputs: false,
+            auto_absorb_small_change: false,
+            recovery_key_gap_limit: 1_000_000,
+        }
+    }
+}
+
+impl OutputManagerServiceConfig {
+    /// Checks that the configured key manager branches are non-empty, distinct from one another and do not clash with
+    /// any of the other built-in branches.
+    pub fn validate_key_branches(&self) -> Result<(), OutputManagerError> {
+        let branches = [
+            (
+                "script_key_branch",
+                &self.script_key_branch,
+                OutputManagerKeyManagerBranch::SpendScript,
+            ),
+            (
+                "sender_offset_key_branch",
+                &self.sender_offset_key_branch,
+                OutputManagerKeyManagerBranch::SenderOffset,
+            ),
+        ];
+        for (name, branch, default_branch) in branches {
+            if branch.is_empty() {
+                return Err(OutputManagerError::InvalidKeyManagerBranch(format!(
+                    "`{}` must not be empty",
+                    name
+                )));
+            }
+            if *branch != default_branch.get_branch_key() &&
+                OutputManagerKeyManagerBranch::iter().any(|b| b.get_branch_key() == *branch)
+            {
+                return Err(OutputManagerError::InvalidKeyManagerBranch(format!(
+                    "`{}` clashes with the built-in `{}` branch",
+                    name, branch
+                )));
+            }
         }
+        if self.script_key_branch == self.sender_offset_key_branch {
+            return Err(OutputManagerError::InvalidKeyManagerBranch(
+                "`script_key_branch` and `sender_offset_key_branch` must be distinct".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns true if outputs of the given type are considered when scanning
+    pub fn scans_output_type(&self, output_type: OutputType) -> bool {
+        self.scan_output_types
+            .as_ref()
+            .map_or(true, |types| types.contains(&output_type))
+    }
+}
+
+/// (De)serializes a map keyed by `OutputType` using the output type names (e.g. `Coinbase`) as keys
+mod output_type_map {
+    use std::collections::HashMap;
+
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+    use tari_core::transactions::transaction_components::OutputType;
+
+    pub fn serialize<S>(map: &HashMap<OutputType, u64>, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        map.iter()
+            .map(|(output_type, value)| (output_type.to_string(), *value))
+            .collect::<HashMap<_, _>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashMap<OutputType, u64>, D::Error>
+    where D: Deserializer<'de> {
+        HashMap::<String, u64>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(name, value)| {
+                OutputType::all()
+                    .iter()
+                    .find(|output_type| output_type.to_string() == name)
+                    .map(|output_type| (*output_type, value))
+                    .ok_or_else(|| D::Error::custom(format!("Unknown output type `{}`", name)))
+            })
+            .collect()
+    }
+}
+
+mod output_type_set {
+    use std::collections::HashSet;
+
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+    use tari_core::transactions::transaction_components::OutputType;
+
+    pub fn serialize<S>(set: &Option<HashSet<OutputType>>, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        set.as_ref()
+            .map(|set| set.iter().map(ToString::to_string).collect::<Vec<_>>())
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<HashSet<OutputType>>, D::Error>
+    where D: Deserializer<'de> {
+        Option::<Vec<String>>::deserialize(deserializer)?
+            .map(|names| {
+                names
+                    .into_iter()
+                    .map(|name| {
+                        OutputType::all()
+                            .iter()
+                            .find(|output_type| output_type.to_string() == name)
+                            .copied()
+                            .ok_or_else(|| D::Error::custom(format!("Unknown output type `{}`", name)))
+                    })
+                    .collect()
+            })
+            .transpose()
     }
 }
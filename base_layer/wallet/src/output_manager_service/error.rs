@@ -26,6 +26,7 @@ use tari_common_sqlite::error::SqliteStorageError;
 use tari_comms::{connectivity::ConnectivityError, peer_manager::node_id::NodeIdError, protocol::rpc::RpcError};
 use tari_comms_dht::outbound::DhtOutboundError;
 use tari_core::transactions::{
+    tari_amount::MicroTari,
     transaction_components::{EncryptedDataError, TransactionError},
     transaction_protocol::TransactionProtocolError,
     CoinbaseBuildError,
@@ -43,7 +44,7 @@ use thiserror::Error;
 use crate::{
     base_node_service::error::BaseNodeServiceError,
     error::WalletStorageError,
-    output_manager_service::UtxoSelectionCriteria,
+    output_manager_service::{storage::OutputStatus, UtxoSelectionCriteria},
 };
 
 #[derive(Debug, Error)]
@@ -86,6 +87,15 @@ pub enum OutputManagerError {
     UnexpectedApiResponse,
     #[error("Invalid config provided to Output Manager")]
     InvalidConfig,
+    #[error("Invalid key manager branch configuration: {0}")]
+    InvalidKeyManagerBranch(String),
+    #[error("The transaction fee of {fee} exceeds the configured maximum fee of {max}")]
+    FeeExceedsMaximum { fee: MicroTari, max: MicroTari },
+    #[error(
+        "Covering the amount requires more than the configured maximum of {max} inputs per transaction, consolidate \
+         the wallet's outputs first"
+    )]
+    TooManyInputsRequired { max: usize },
     #[error("The response received from another service is an incorrect variant: `{0}`")]
     InvalidResponseError(String),
     #[error("No Base Node public key has been provided for this service to use for contacting a base node")]
@@ -141,6 +151,12 @@ pub enum OutputManagerError {
     InvalidArgument(String),
     #[error("Validation in progress")]
     ValidationInProgress,
+    #[error("Output cannot be spent while its status is {0}")]
+    OutputNotSpendable(OutputStatus),
+    #[error("Output matures at height {maturity}, but the chain tip is at height {tip_height}")]
+    OutputNotMatured { maturity: u64, tip_height: u64 },
+    #[error("Output script is locked until height {script_lock_height}, but the chain tip is at height {tip_height}")]
+    OutputScriptLocked { script_lock_height: u64, tip_height: u64 },
     #[error("Invalid data: `{0}`")]
     RangeProofError(#[from] RangeProofError),
 }
@@ -155,6 +171,10 @@ pub enum OutputManagerStorageError {
     DuplicateTransaction,
     #[error("Value not found")]
     ValueNotFound,
+    #[error("The database is busy with another connection's transaction")]
+    DatabaseBusy,
+    #[error("The output manager database is locked")]
+    Locked,
     #[error("Unexpected result: `{0}`")]
     UnexpectedResult(String),
     #[error("If an pending transaction does not exist to be confirmed")]
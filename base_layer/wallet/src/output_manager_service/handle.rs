@@ -22,6 +22,7 @@
 
 use std::{fmt, fmt::Formatter, sync::Arc};
 
+use chacha20poly1305::XChaCha20Poly1305;
 use tari_common_types::{
     transaction::TxId,
     types::{Commitment, HashOutput, PrivateKey, PublicKey},
@@ -53,7 +54,7 @@ use crate::output_manager_service::{
     service::{Balance, OutputStatusesByTxId},
     storage::{
         database::OutputBackendQuery,
-        models::{DbUnblindedOutput, KnownOneSidedPaymentScript, SpendingPriority},
+        models::{CorruptOutput, DbUnblindedOutput, KnownOneSidedPaymentScript, SpendingPriority},
     },
     UtxoSelectionCriteria,
 };
@@ -62,6 +63,9 @@ use crate::output_manager_service::{
 #[allow(clippy::large_enum_variant)]
 pub enum OutputManagerRequest {
     GetBalance,
+    HasSpendableFunds,
+    GetMinUnspentConfirmations,
+    FindHighestUsedSpendKeyIndex(u64),
     AddOutput((Box<UnblindedOutput>, Option<SpendingPriority>)),
     AddOutputWithTxId((TxId, Box<UnblindedOutput>, Option<SpendingPriority>)),
     AddUnvalidatedOutput((TxId, Box<UnblindedOutput>, Option<SpendingPriority>)),
@@ -86,6 +90,7 @@ pub enum OutputManagerRequest {
         script: TariScript,
         covenant: Covenant,
         minimum_value_promise: MicroTari,
+        allow_fee_above_maximum: bool,
     },
     CreatePayToSelfTransaction {
         tx_id: TxId,
@@ -103,18 +108,37 @@ pub enum OutputManagerRequest {
     CancelTransaction(TxId),
     GetSpentOutputs,
     GetUnspentOutputs,
+    GetUnspentOutputsSkippingCorrupt,
+    GetLargestUnspentOutputs(usize),
+    GetValueHistogram(Vec<MicroTari>),
+    MarkOutputSpentExternal {
+        commitment: Commitment,
+        spent_in_tx: Option<TxId>,
+    },
+    GetReceivedOutputs(bool),
+    VerifyTransactionOutputs {
+        tx_id: TxId,
+        commitments: Vec<Commitment>,
+    },
     GetOutputsBy(OutputBackendQuery),
     GetInvalidOutputs,
     ValidateUtxos,
     RevalidateTxos,
     CreateCoinSplit((Vec<Commitment>, MicroTari, usize, MicroTari)),
     CreateCoinSplitEven((Vec<Commitment>, usize, MicroTari)),
+    SplitSpecificOutput {
+        commitment: Commitment,
+        split_count: usize,
+        fee_per_gram: MicroTari,
+    },
     PreviewCoinJoin((Vec<Commitment>, MicroTari)),
     PreviewCoinSplitEven((Vec<Commitment>, usize, MicroTari)),
     CreateCoinJoin {
         commitments: Vec<Commitment>,
         fee_per_gram: MicroTari,
     },
+    RunAutoConsolidation,
+    RunCoinbaseAutoSplit,
     FeeEstimate {
         amount: MicroTari,
         selection_criteria: UtxoSelectionCriteria,
@@ -122,9 +146,12 @@ pub enum OutputManagerRequest {
         num_kernels: usize,
         num_outputs: usize,
     },
-
+    EstimateSweep(MicroTari),
     ScanForRecoverableOutputs(Vec<TransactionOutput>),
     ScanOutputs(Vec<TransactionOutput>),
+    GetTrialDecryptionCacheStats,
+    VacuumDatabase,
+    RotateEncryption(Box<(XChaCha20Poly1305, XChaCha20Poly1305)>),
     AddKnownOneSidedPaymentScript(KnownOneSidedPaymentScript),
     CreateOutputWithFeatures {
         value: MicroTari,
@@ -136,6 +163,7 @@ pub enum OutputManagerRequest {
     CreateClaimShaAtomicSwapTransaction(HashOutput, PublicKey, MicroTari),
     CreateHtlcRefundTransaction(HashOutput, MicroTari),
     GetOutputStatusesByTxId(TxId),
+    GetEncumberedOutputsByTxId(TxId),
     GetNextSpendAndScriptKeys,
     GetRecoveryData,
 }
@@ -146,6 +174,9 @@ impl fmt::Display for OutputManagerRequest {
         use OutputManagerRequest::*;
         match self {
             GetBalance => write!(f, "GetBalance"),
+            HasSpendableFunds => write!(f, "HasSpendableFunds"),
+            GetMinUnspentConfirmations => write!(f, "GetMinUnspentConfirmations"),
+            FindHighestUsedSpendKeyIndex(gap_limit) => write!(f, "FindHighestUsedSpendKeyIndex ({})", gap_limit),
             AddOutput((v, _)) => write!(f, "AddOutput ({})", v.value),
             AddOutputWithTxId((t, v, _)) => write!(f, "AddOutputWithTxId ({}: {})", t, v.value),
             AddUnvalidatedOutput((t, v, _)) => {
@@ -167,6 +198,14 @@ impl fmt::Display for OutputManagerRequest {
             CancelTransaction(v) => write!(f, "CancelTransaction ({})", v),
             GetSpentOutputs => write!(f, "GetSpentOutputs"),
             GetUnspentOutputs => write!(f, "GetUnspentOutputs"),
+            GetUnspentOutputsSkippingCorrupt => write!(f, "GetUnspentOutputsSkippingCorrupt"),
+            GetLargestUnspentOutputs(n) => write!(f, "GetLargestUnspentOutputs ({})", n),
+            GetValueHistogram(boundaries) => write!(f, "GetValueHistogram ({} buckets)", boundaries.len()),
+            MarkOutputSpentExternal { commitment, .. } => {
+                write!(f, "MarkOutputSpentExternal ({})", commitment.to_hex())
+            },
+            GetReceivedOutputs(exclude_change) => write!(f, "GetReceivedOutputs (exclude_change: {})", exclude_change),
+            VerifyTransactionOutputs { tx_id, .. } => write!(f, "VerifyTransactionOutputs ({})", tx_id),
             GetOutputsBy(q) => write!(f, "GetOutputs({:#?})", q),
             GetInvalidOutputs => write!(f, "GetInvalidOutputs"),
             ValidateUtxos => write!(f, "ValidateUtxos"),
@@ -183,6 +222,17 @@ impl fmt::Display for OutputManagerRequest {
             ),
             CreateCoinSplit(v) => write!(f, "CreateCoinSplit ({:?})", v.0),
             CreateCoinSplitEven(v) => write!(f, "CreateCoinSplitEven ({:?})", v.0),
+            SplitSpecificOutput {
+                commitment,
+                split_count,
+                fee_per_gram,
+            } => write!(
+                f,
+                "SplitSpecificOutput(commitment={}, split_count={}, fee_per_gram={})",
+                commitment.to_hex(),
+                split_count,
+                fee_per_gram
+            ),
             CreateCoinJoin {
                 commitments,
                 fee_per_gram,
@@ -191,6 +241,8 @@ impl fmt::Display for OutputManagerRequest {
                 "CreateCoinJoin: commitments={:#?}, fee_per_gram={}",
                 commitments, fee_per_gram,
             ),
+            RunAutoConsolidation => write!(f, "RunAutoConsolidation"),
+            RunCoinbaseAutoSplit => write!(f, "RunCoinbaseAutoSplit"),
             GetCoinbaseTransaction { .. } => write!(f, "GetCoinbaseTransaction"),
             FeeEstimate {
                 amount,
@@ -203,8 +255,12 @@ impl fmt::Display for OutputManagerRequest {
                 "FeeEstimate(amount: {}, fee_per_gram: {}, num_kernels: {}, num_outputs: {}, selection_criteria: {:?})",
                 amount, fee_per_gram, num_kernels, num_outputs, selection_criteria
             ),
+            EstimateSweep(fee_per_gram) => write!(f, "EstimateSweep(fee_per_gram: {})", fee_per_gram),
             ScanForRecoverableOutputs(_) => write!(f, "ScanForRecoverableOutputs"),
             ScanOutputs(_) => write!(f, "ScanOutputs"),
+            GetTrialDecryptionCacheStats => write!(f, "GetTrialDecryptionCacheStats"),
+            VacuumDatabase => write!(f, "VacuumDatabase"),
+            RotateEncryption(_) => write!(f, "RotateEncryption"),
             AddKnownOneSidedPaymentScript(_) => write!(f, "AddKnownOneSidedPaymentScript"),
             CreateOutputWithFeatures { value, features } => {
                 write!(f, "CreateOutputWithFeatures({}, {})", value, features,)
@@ -227,6 +283,7 @@ impl fmt::Display for OutputManagerRequest {
             ),
 
             GetOutputStatusesByTxId(t) => write!(f, "GetOutputStatusesByTxId: {}", t),
+            GetEncumberedOutputsByTxId(t) => write!(f, "GetEncumberedOutputsByTxId: {}", t),
             GetNextSpendAndScriptKeys => write!(f, "GetNextSpendAndScriptKeys"),
             GetRecoveryData => write!(f, "GetRecoveryData"),
         }
@@ -237,6 +294,9 @@ impl fmt::Display for OutputManagerRequest {
 #[derive(Debug, Clone)]
 pub enum OutputManagerResponse {
     Balance(Balance),
+    SpendableFunds(bool),
+    MinUnspentConfirmations(Option<u64>),
+    HighestUsedSpendKeyIndex(u64),
     OutputAdded,
     ConvertedToTransactionOutput(Box<TransactionOutput>),
     OutputMetadataSignatureUpdated,
@@ -249,6 +309,11 @@ pub enum OutputManagerResponse {
     TransactionCancelled,
     SpentOutputs(Vec<UnblindedOutput>),
     UnspentOutputs(Vec<DbUnblindedOutput>),
+    UnspentOutputsSkippingCorrupt((Vec<DbUnblindedOutput>, Vec<CorruptOutput>)),
+    ValueHistogram(Vec<(MicroTari, usize)>),
+    OutputMarkedSpentExternal,
+    ReceivedOutputs(Vec<DbUnblindedOutput>),
+    TransactionOutputsVerified(bool),
     Outputs(Vec<UnblindedOutput>),
     InvalidOutputs(Vec<UnblindedOutput>),
     BaseNodePublicKeySet,
@@ -257,8 +322,12 @@ pub enum OutputManagerResponse {
     PublicRewindKeys(Box<PublicRewindKeys>),
     RecoveryByte(u8),
     FeeEstimate(MicroTari),
+    SweepEstimate((MicroTari, usize)),
     RewoundOutputs(Vec<RecoveredOutput>),
     ScanOutputs(Vec<RecoveredOutput>),
+    TrialDecryptionCacheStats(TrialDecryptionCacheStats),
+    DatabaseVacuumed(u64),
+    EncryptionRotated(usize),
     AddKnownOneSidedPaymentScript,
     CreateOutputWithFeatures {
         output: Box<UnblindedOutputBuilder>,
@@ -271,12 +340,15 @@ pub enum OutputManagerResponse {
     CoinbaseAbandonedSet,
     ClaimHtlcTransaction((TxId, MicroTari, MicroTari, Transaction)),
     OutputStatusesByTxId(OutputStatusesByTxId),
+    EncumberedOutputs(Vec<DbUnblindedOutput>),
     CoinPreview((Vec<MicroTari>, MicroTari)),
     NextSpendAndScriptKeys {
         spend_key: PrivateKey,
         script_key: PrivateKey,
     },
     RecoveryData(RecoveryData),
+    AutoConsolidation(Option<(TxId, Transaction, MicroTari)>),
+    CoinbaseAutoSplit(Vec<(TxId, Transaction, MicroTari)>),
 }
 
 pub type OutputManagerEventSender = broadcast::Sender<Arc<OutputManagerEvent>>;
@@ -289,6 +361,24 @@ pub enum OutputManagerEvent {
     TxoValidationInternalFailure(u64),
     TxoValidationCommunicationFailure(u64),
     TxoValidationAlreadyBusy(u64),
+    /// A failed validation is automatically being retried, `attempt` counts the retries so far
+    TxoValidationRetrying {
+        id: u64,
+        attempt: u32,
+    },
+    /// An automatic consolidation transaction was built and its inputs encumbered; it still needs to be submitted
+    AutoConsolidationTransactionCreated {
+        tx_id: TxId,
+        transaction: Box<Transaction>,
+        amount: MicroTari,
+    },
+    /// A large matured coinbase output was automatically split and its input encumbered; the transaction still needs
+    /// to be submitted
+    CoinbaseSplitTransactionCreated {
+        tx_id: TxId,
+        transaction: Box<Transaction>,
+        amount: MicroTari,
+    },
 }
 
 impl fmt::Display for OutputManagerEvent {
@@ -306,6 +396,15 @@ impl fmt::Display for OutputManagerEvent {
             OutputManagerEvent::TxoValidationAlreadyBusy(tx) => {
                 write!(f, "Txo is already running, stopping {}", tx)
             },
+            OutputManagerEvent::TxoValidationRetrying { id, attempt } => {
+                write!(f, "TxoValidationRetrying for {} (attempt {})", id, attempt)
+            },
+            OutputManagerEvent::AutoConsolidationTransactionCreated { tx_id, amount, .. } => {
+                write!(f, "AutoConsolidationTransactionCreated for {} ({})", tx_id, amount)
+            },
+            OutputManagerEvent::CoinbaseSplitTransactionCreated { tx_id, amount, .. } => {
+                write!(f, "CoinbaseSplitTransactionCreated for {} ({})", tx_id, amount)
+            },
         }
     }
 }
@@ -321,6 +420,17 @@ pub struct RecoveredOutput {
     pub output: UnblindedOutput,
 }
 
+/// Statistics of the cache of trial-decryption results used when scanning for recoverable outputs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrialDecryptionCacheStats {
+    /// The number of results currently held in the cache
+    pub size: usize,
+    /// The number of scanned outputs whose result was found in the cache
+    pub hits: u64,
+    /// The number of scanned outputs that had to be trial-decrypted
+    pub misses: u64,
+}
+
 #[derive(Clone)]
 pub struct OutputManagerHandle {
     handle: SenderService<OutputManagerRequest, Result<OutputManagerResponse, OutputManagerError>>,
@@ -436,6 +546,40 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Returns true if at least one output can be spent right now, without computing the full balance
+    pub async fn has_spendable_funds(&mut self) -> Result<bool, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::HasSpendableFunds).await?? {
+            OutputManagerResponse::SpendableFunds(has_funds) => Ok(has_funds),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the number of confirmations of the least confirmed unspent output that has been mined, or `None` if no
+    /// unspent output has been mined or the chain tip is not known yet
+    pub async fn min_unspent_confirmations(&mut self) -> Result<Option<u64>, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetMinUnspentConfirmations)
+            .await??
+        {
+            OutputManagerResponse::MinUnspentConfirmations(confirmations) => Ok(confirmations),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the highest spend key index used by an output held by the wallet, probing the derived spend keys until
+    /// `gap_limit` consecutive keys are not used by any output
+    pub async fn find_highest_used_spend_key_index(&mut self, gap_limit: u64) -> Result<u64, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::FindHighestUsedSpendKeyIndex(gap_limit))
+            .await??
+        {
+            OutputManagerResponse::HighestUsedSpendKeyIndex(index) => Ok(index),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn revalidate_all_outputs(&mut self) -> Result<u64, OutputManagerError> {
         match self.handle.call(OutputManagerRequest::RevalidateTxos).await?? {
             OutputManagerResponse::TxoValidationStarted(request_key) => Ok(request_key),
@@ -493,6 +637,67 @@ impl OutputManagerHandle {
         script: TariScript,
         covenant: Covenant,
         minimum_value_promise: MicroTari,
+    ) -> Result<SenderTransactionProtocol, OutputManagerError> {
+        self.request_transaction_to_send(
+            tx_id,
+            amount,
+            utxo_selection,
+            output_features,
+            fee_per_gram,
+            tx_meta,
+            message,
+            script,
+            covenant,
+            minimum_value_promise,
+            false,
+        )
+        .await
+    }
+
+    /// As `prepare_transaction_to_send`, but the transaction is not rejected if its fee exceeds the configured
+    /// `max_fee_per_transaction`.
+    pub async fn prepare_transaction_to_send_with_fee_override(
+        &mut self,
+        tx_id: TxId,
+        amount: MicroTari,
+        utxo_selection: UtxoSelectionCriteria,
+        output_features: OutputFeatures,
+        fee_per_gram: MicroTari,
+        tx_meta: TransactionMetadata,
+        message: String,
+        script: TariScript,
+        covenant: Covenant,
+        minimum_value_promise: MicroTari,
+    ) -> Result<SenderTransactionProtocol, OutputManagerError> {
+        self.request_transaction_to_send(
+            tx_id,
+            amount,
+            utxo_selection,
+            output_features,
+            fee_per_gram,
+            tx_meta,
+            message,
+            script,
+            covenant,
+            minimum_value_promise,
+            true,
+        )
+        .await
+    }
+
+    async fn request_transaction_to_send(
+        &mut self,
+        tx_id: TxId,
+        amount: MicroTari,
+        utxo_selection: UtxoSelectionCriteria,
+        output_features: OutputFeatures,
+        fee_per_gram: MicroTari,
+        tx_meta: TransactionMetadata,
+        message: String,
+        script: TariScript,
+        covenant: Covenant,
+        minimum_value_promise: MicroTari,
+        allow_fee_above_maximum: bool,
     ) -> Result<SenderTransactionProtocol, OutputManagerError> {
         match self
             .handle
@@ -507,6 +712,7 @@ impl OutputManagerHandle {
                 script,
                 covenant,
                 minimum_value_promise,
+                allow_fee_above_maximum,
             })
             .await??
         {
@@ -541,6 +747,19 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Estimate a transaction that sweeps all spendable outputs into a single recipient output at the specified fee
+    /// per gram. Returns the amount that can be sent (the spendable balance minus the fee) and the number of inputs.
+    pub async fn estimate_sweep(&mut self, fee_per_gram: MicroTari) -> Result<(MicroTari, usize), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::EstimateSweep(fee_per_gram))
+            .await??
+        {
+            OutputManagerResponse::SweepEstimate(estimate) => Ok(estimate),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn confirm_pending_transaction(&mut self, tx_id: TxId) -> Result<(), OutputManagerError> {
         match self
             .handle
@@ -578,6 +797,109 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Returns the unspent outputs that could be loaded, together with the database ids and, where they can be decoded,
+    /// the commitments of the ones that could not (e.g. due to corrupted encrypted data) so they can be investigated
+    pub async fn get_unspent_outputs_skipping_corrupt(
+        &mut self,
+    ) -> Result<(Vec<DbUnblindedOutput>, Vec<CorruptOutput>), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetUnspentOutputsSkippingCorrupt)
+            .await??
+        {
+            OutputManagerResponse::UnspentOutputsSkippingCorrupt(result) => Ok(result),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the `n` unspent outputs with the highest value, sorted from highest value to lowest. Encumbered outputs
+    /// are excluded.
+    pub async fn get_largest_unspent_outputs(
+        &mut self,
+        n: usize,
+    ) -> Result<Vec<DbUnblindedOutput>, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetLargestUnspentOutputs(n))
+            .await??
+        {
+            OutputManagerResponse::UnspentOutputs(s) => Ok(s),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the number of unspent outputs in each value bucket. Every boundary starts a bucket that runs up to, but
+    /// excludes, the next boundary, and the last bucket has no upper limit. The boundaries are sorted and deduplicated,
+    /// and outputs worth less than the lowest boundary are not counted.
+    pub async fn get_value_histogram(
+        &mut self,
+        bucket_boundaries: Vec<MicroTari>,
+    ) -> Result<Vec<(MicroTari, usize)>, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetValueHistogram(bucket_boundaries))
+            .await??
+        {
+            OutputManagerResponse::ValueHistogram(histogram) => Ok(histogram),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Marks an unspent output as spent by a transaction that was not built by this wallet, e.g. one spent from
+    /// another wallet holding the same keys. The output leaves the available balance straight away and the next TXO
+    /// validation either confirms the spend or returns the output to the unspent pool.
+    pub async fn mark_output_spent_external(
+        &mut self,
+        commitment: Commitment,
+        spent_in_tx: Option<TxId>,
+    ) -> Result<(), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::MarkOutputSpentExternal {
+                commitment,
+                spent_in_tx,
+            })
+            .await??
+        {
+            OutputManagerResponse::OutputMarkedSpentExternal => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the unspent and incoming outputs of the wallet. If `exclude_change` is set, change outputs created by
+    /// the wallet's own transactions are left out.
+    pub async fn get_received_outputs(
+        &mut self,
+        exclude_change: bool,
+    ) -> Result<Vec<DbUnblindedOutput>, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetReceivedOutputs(exclude_change))
+            .await??
+        {
+            OutputManagerResponse::ReceivedOutputs(s) => Ok(s),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Re-derives the keys and commitments of the outputs the wallet received in the given transaction from the
+    /// wallet's keys and returns true if the keys match the stored ones and every commitment is contained in
+    /// `commitments`. Returns false if the wallet received no outputs in the transaction.
+    pub async fn verify_transaction_outputs(
+        &mut self,
+        tx_id: TxId,
+        commitments: Vec<Commitment>,
+    ) -> Result<bool, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::VerifyTransactionOutputs { tx_id, commitments })
+            .await??
+        {
+            OutputManagerResponse::TransactionOutputsVerified(verified) => Ok(verified),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     // ToDo: This API method call could probably be removed by expanding test utils if only needed for testing
     pub async fn get_invalid_outputs(&mut self) -> Result<Vec<UnblindedOutput>, OutputManagerError> {
         match self.handle.call(OutputManagerRequest::GetInvalidOutputs).await?? {
@@ -672,6 +994,30 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Create a transaction that splits exactly the output with the given commitment into `split_count` outputs of
+    /// (roughly) equal value. Returns an error if the output does not exist, is not unspent, or has not reached its
+    /// maturity or script lock height.
+    /// Returns (tx_id, tx, utxos_total_value).
+    pub async fn split_specific_output(
+        &mut self,
+        commitment: Commitment,
+        split_count: usize,
+        fee_per_gram: MicroTari,
+    ) -> Result<(TxId, Transaction, MicroTari), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::SplitSpecificOutput {
+                commitment,
+                split_count,
+                fee_per_gram,
+            })
+            .await??
+        {
+            OutputManagerResponse::Transaction(ct) => Ok(ct),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn create_coin_join(
         &mut self,
         commitments: Vec<Commitment>,
@@ -690,6 +1036,28 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Run a single automatic consolidation cycle, regardless of whether `auto_consolidate` is enabled.
+    /// Returns (tx_id, tx, utxos_total_value) if a consolidation transaction was created, which the caller is then
+    /// responsible for submitting.
+    pub async fn run_auto_consolidation(
+        &mut self,
+    ) -> Result<Option<(TxId, Transaction, MicroTari)>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::RunAutoConsolidation).await?? {
+            OutputManagerResponse::AutoConsolidation(result) => Ok(result),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Split all matured coinbase outputs above `auto_split_coinbase_threshold`, returning (tx_id, tx, input_value)
+    /// for every split transaction created. The caller is responsible for submitting them. Nothing is split if the
+    /// threshold is not configured.
+    pub async fn run_coinbase_auto_split(&mut self) -> Result<Vec<(TxId, Transaction, MicroTari)>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::RunCoinbaseAutoSplit).await?? {
+            OutputManagerResponse::CoinbaseAutoSplit(result) => Ok(result),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn create_htlc_refund_transaction(
         &mut self,
         output: HashOutput,
@@ -739,6 +1107,48 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Returns the statistics of the trial-decryption cache used by `scan_for_recoverable_outputs`
+    pub async fn get_trial_decryption_cache_stats(&mut self) -> Result<TrialDecryptionCacheStats, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetTrialDecryptionCacheStats)
+            .await??
+        {
+            OutputManagerResponse::TrialDecryptionCacheStats(stats) => Ok(stats),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Rebuild the output manager database to reclaim the space left behind by deleted records. Returns the number of
+    /// bytes reclaimed on disk, or a `DatabaseBusy` storage error if another wallet service kept a transaction open on
+    /// the shared database file, in which case the vacuum can be retried later.
+    pub async fn vacuum_database(&mut self) -> Result<u64, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::VacuumDatabase).await?? {
+            OutputManagerResponse::DatabaseVacuumed(reclaimed) => Ok(reclaimed),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Re-encrypt all of the stored output data, which is currently encrypted with `old_cipher`, using `new_cipher`,
+    /// e.g. after the wallet passphrase was changed. This happens in a single database transaction, so nothing is
+    /// changed if any output fails to re-encrypt. Returns the number of outputs re-encrypted.
+    pub async fn rotate_encryption(
+        &mut self,
+        old_cipher: XChaCha20Poly1305,
+        new_cipher: XChaCha20Poly1305,
+    ) -> Result<usize, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::RotateEncryption(Box::new((
+                old_cipher, new_cipher,
+            ))))
+            .await??
+        {
+            OutputManagerResponse::EncryptionRotated(num_outputs) => Ok(num_outputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn scan_outputs_for_one_sided_payments(
         &mut self,
         outputs: Vec<TransactionOutput>,
@@ -845,6 +1255,21 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Returns the outputs that are encumbered to be spent or received by the given transaction
+    pub async fn get_encumbered_outputs_by_tx_id(
+        &mut self,
+        tx_id: TxId,
+    ) -> Result<Vec<DbUnblindedOutput>, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetEncumberedOutputsByTxId(tx_id))
+            .await??
+        {
+            OutputManagerResponse::EncumberedOutputs(outputs) => Ok(outputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn get_next_spend_and_script_keys(&mut self) -> Result<(PrivateKey, PrivateKey), OutputManagerError> {
         match self
             .handle
@@ -41,6 +41,13 @@ pub struct UtxoSelectionCriteria {
     pub ordering: UtxoSelectionOrdering,
     pub excluding: Vec<Commitment>,
     pub excluding_onesided: bool,
+    /// Allow coinbase outputs that have not reached their maturity yet to be selected in safe mode. A transaction
+    /// spending such an output will be rejected by base nodes until the output matures, so this should only be used
+    /// when the transaction will not be broadcast before then.
+    pub allow_immature_coinbase: bool,
+    /// Prefer outputs that have reached the required number of confirmations over recently mined ones, e.g. fresh
+    /// change, which could still be invalidated by a reorg. The requested ordering is kept within each group.
+    pub prefer_confirmed: bool,
 }
 
 impl UtxoSelectionCriteria {
@@ -23,3 +23,6 @@
 mod standard_outputs_recoverer;
 
 pub(crate) use standard_outputs_recoverer::StandardUtxoRecoverer;
+
+mod trial_decryption_cache;
+pub(crate) use trial_decryption_cache::TrialDecryptionCache;
@@ -20,7 +20,12 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::time::Instant;
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    thread,
+    time::Instant,
+};
 
 use log::*;
 use rand::rngs::OsRng;
@@ -30,7 +35,7 @@ use tari_common_types::{
 };
 use tari_core::transactions::{
     tari_amount::MicroTari,
-    transaction_components::{EncryptedData, TransactionOutput, UnblindedOutput},
+    transaction_components::{EncryptedData, OutputType, TransactionOutput, UnblindedOutput},
     transaction_protocol::RecoveryData,
     CryptoFactories,
 };
@@ -44,6 +49,7 @@ use tari_script::{inputs, script, Opcode};
 use crate::output_manager_service::{
     error::{OutputManagerError, OutputManagerStorageError},
     handle::RecoveredOutput,
+    recovery::TrialDecryptionCache,
     resources::OutputManagerKeyManagerBranch,
     storage::{
         database::{OutputManagerBackend, OutputManagerDatabase},
@@ -54,11 +60,20 @@ use crate::output_manager_service::{
 
 const LOG_TARGET: &str = "wallet::output_manager_service::recovery";
 
+/// The result of trial-decrypting an output: the recovered blinding factor and value if the output belongs to this
+/// wallet, otherwise `None`
+type TrialDecryptionResult = Option<(BlindingFactor, MicroTari)>;
+
 pub(crate) struct StandardUtxoRecoverer<TBackend: OutputManagerBackend + 'static, TKeyManagerInterface> {
     master_key_manager: TKeyManagerInterface,
     recovery_data: RecoveryData,
     factories: CryptoFactories,
     db: OutputManagerDatabase<TBackend>,
+    script_key_branch: String,
+    scan_parallelism: usize,
+    scan_output_types: Option<HashSet<OutputType>>,
+    trial_decryption_cache: Arc<Mutex<TrialDecryptionCache>>,
+    key_gap_limit: u64,
 }
 
 impl<TBackend, TKeyManagerInterface> StandardUtxoRecoverer<TBackend, TKeyManagerInterface>
@@ -71,17 +86,32 @@ where
         recovery_data: RecoveryData,
         factories: CryptoFactories,
         db: OutputManagerDatabase<TBackend>,
+        script_key_branch: String,
+        scan_parallelism: usize,
+        scan_output_types: Option<HashSet<OutputType>>,
+        trial_decryption_cache: Arc<Mutex<TrialDecryptionCache>>,
+        key_gap_limit: u64,
     ) -> Self {
         Self {
             master_key_manager,
             recovery_data,
             factories,
             db,
+            script_key_branch,
+            scan_parallelism,
+            scan_output_types,
+            trial_decryption_cache,
+            key_gap_limit,
         }
     }
 
     /// Attempt to rewind all of the given transaction outputs into unblinded outputs. If they can be rewound then add
-    /// them to the database and increment the key manager index
+    /// them to the database and increment the key manager index.
+    ///
+    /// The trial decryption of the outputs is split across `scan_parallelism` threads. The key manager and database
+    /// are only accessed once all threads have finished, in the original output order, so the result does not depend
+    /// on the parallelism setting. Trial-decryption results are cached by output hash, so outputs that have been
+    /// scanned before are not decrypted again. Outputs whose type is not in `scan_output_types` are skipped.
     pub async fn scan_and_recover_outputs(
         &mut self,
         outputs: Vec<TransactionOutput>,
@@ -91,14 +121,26 @@ where
 
         let known_scripts = self.db.get_all_known_one_sided_payment_scripts()?;
 
-        let mut rewound_outputs: Vec<UnblindedOutput> = Vec::new();
-        for output in outputs {
-            let known_script_index = known_scripts.iter().position(|s| s.script == output.script);
-            if output.script != script!(Nop) && known_script_index.is_none() {
-                continue;
-            }
+        let candidates = outputs
+            .into_iter()
+            .filter_map(|output| {
+                if let Some(types) = &self.scan_output_types {
+                    if !types.contains(&output.features.output_type) {
+                        return None;
+                    }
+                }
+                let known_script_index = known_scripts.iter().position(|s| s.script == output.script);
+                if output.script != script!(Nop) && known_script_index.is_none() {
+                    return None;
+                }
+                Some((output, known_script_index))
+            })
+            .collect::<Vec<_>>();
+        let recovery_results = self.attempt_outputs_recovery(&candidates)?;
 
-            let (blinding_factor, committed_value) = match self.attempt_output_recovery(&output)? {
+        let mut rewound_outputs: Vec<UnblindedOutput> = Vec::new();
+        for ((output, known_script_index), recovered) in candidates.into_iter().zip(recovery_results) {
+            let (blinding_factor, committed_value) = match recovered {
                 Some(recovered) => recovered,
                 None => continue,
             };
@@ -186,24 +228,92 @@ where
         Ok(rewound_outputs_with_tx_id)
     }
 
-    fn attempt_output_recovery(
+    /// Attempts to recover every candidate output, returning the results in the same order as the candidates. Outputs
+    /// the cache knows not to belong to this wallet are skipped. Only ownership is cached, so owned outputs are
+    /// trial-decrypted again to recover their blinding factor and value.
+    fn attempt_outputs_recovery(
         &self,
-        output: &TransactionOutput,
-    ) -> Result<Option<(BlindingFactor, MicroTari)>, OutputManagerError> {
-        let (committed_value, blinding_factor) = match EncryptedData::decrypt_data(
-            &self.recovery_data.encryption_key,
-            &output.commitment,
-            &output.encrypted_data,
-        ) {
-            Ok(value) => value,
-            Err(_) => return Ok(None),
-        };
+        candidates: &[(TransactionOutput, Option<usize>)],
+    ) -> Result<Vec<TrialDecryptionResult>, OutputManagerError> {
+        let mut cache = self
+            .trial_decryption_cache
+            .lock()
+            .map_err(|_| OutputManagerError::ServiceError("Trial decryption cache lock poisoned".to_string()))?;
+        if !cache.is_enabled() {
+            drop(cache);
+            let outputs = candidates.iter().map(|(output, _)| output).collect::<Vec<_>>();
+            return self.trial_decrypt_outputs(&outputs);
+        }
+
+        let hashes = candidates.iter().map(|(output, _)| output.hash()).collect::<Vec<_>>();
+        let cached = hashes.iter().map(|hash| cache.get(hash)).collect::<Vec<_>>();
+        drop(cache);
+
+        let to_decrypt = candidates
+            .iter()
+            .zip(&cached)
+            .filter(|(_, is_owned)| **is_owned != Some(false))
+            .map(|((output, _), _)| output)
+            .collect::<Vec<_>>();
+        let mut decrypted = self.trial_decrypt_outputs(&to_decrypt)?.into_iter();
 
-        if !output.verify_mask(&self.factories.range_proof, &blinding_factor, committed_value.into())? {
-            return Ok(None);
+        let mut cache = self
+            .trial_decryption_cache
+            .lock()
+            .map_err(|_| OutputManagerError::ServiceError("Trial decryption cache lock poisoned".to_string()))?;
+        let mut results = Vec::with_capacity(candidates.len());
+        for (hash, is_owned) in hashes.into_iter().zip(cached) {
+            if is_owned == Some(false) {
+                results.push(None);
+                continue;
+            }
+            let recovered = decrypted.next().flatten();
+            if is_owned.is_none() {
+                cache.insert(hash, recovered.is_some());
+            }
+            results.push(recovered);
         }
 
-        Ok(Some((blinding_factor, committed_value)))
+        Ok(results)
+    }
+
+    /// Trial-decrypts every output, returning the results in the same order as the outputs
+    fn trial_decrypt_outputs(
+        &self,
+        outputs: &[&TransactionOutput],
+    ) -> Result<Vec<TrialDecryptionResult>, OutputManagerError> {
+        let recovery_data = &self.recovery_data;
+        let factories = &self.factories;
+        if self.scan_parallelism <= 1 || outputs.len() <= 1 {
+            return outputs
+                .iter()
+                .map(|output| attempt_output_recovery(recovery_data, factories, output))
+                .collect();
+        }
+
+        let chunk_size = (outputs.len() + self.scan_parallelism - 1) / self.scan_parallelism;
+        thread::scope(|scope| {
+            let handles = outputs
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|output| attempt_output_recovery(recovery_data, factories, output))
+                            .collect::<Result<Vec<_>, _>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            let mut results = Vec::with_capacity(outputs.len());
+            for handle in handles {
+                let chunk_results = handle.join().map_err(|_| {
+                    OutputManagerError::ServiceError("Recoverable output scanning thread panicked".to_string())
+                })??;
+                results.extend(chunk_results);
+            }
+            Ok(results)
+        })
     }
 
     /// Find the key manager index that corresponds to the spending key in the rewound output, if found then modify
@@ -216,9 +326,10 @@ where
         let script_key = if output.features.is_coinbase() {
             let found_index = self
                 .master_key_manager
-                .find_key_index(
+                .find_key_index_with_gap_limit(
                     OutputManagerKeyManagerBranch::Coinbase.get_branch_key(),
                     &output.spending_key,
+                    self.key_gap_limit,
                 )
                 .await?;
 
@@ -231,9 +342,10 @@ where
         } else {
             let found_index = self
                 .master_key_manager
-                .find_key_index(
+                .find_key_index_with_gap_limit(
                     OutputManagerKeyManagerBranch::Spend.get_branch_key(),
                     &output.spending_key,
+                    self.key_gap_limit,
                 )
                 .await?;
 
@@ -241,14 +353,11 @@ where
                 .update_current_key_index_if_higher(OutputManagerKeyManagerBranch::Spend.get_branch_key(), found_index)
                 .await?;
             self.master_key_manager
-                .update_current_key_index_if_higher(
-                    OutputManagerKeyManagerBranch::SpendScript.get_branch_key(),
-                    found_index,
-                )
+                .update_current_key_index_if_higher(self.script_key_branch.clone(), found_index)
                 .await?;
 
             self.master_key_manager
-                .get_key_at_index(OutputManagerKeyManagerBranch::SpendScript.get_branch_key(), found_index)
+                .get_key_at_index(self.script_key_branch.clone(), found_index)
                 .await?
         };
 
@@ -257,3 +366,24 @@ where
         Ok(())
     }
 }
+
+fn attempt_output_recovery(
+    recovery_data: &RecoveryData,
+    factories: &CryptoFactories,
+    output: &TransactionOutput,
+) -> Result<TrialDecryptionResult, OutputManagerError> {
+    let (committed_value, blinding_factor) = match EncryptedData::decrypt_data(
+        &recovery_data.encryption_key,
+        &output.commitment,
+        &output.encrypted_data,
+    ) {
+        Ok(value) => value,
+        Err(_) => return Ok(None),
+    };
+
+    if !output.verify_mask(&factories.range_proof, &blinding_factor, committed_value.into())? {
+        return Ok(None);
+    }
+
+    Ok(Some((blinding_factor, committed_value)))
+}
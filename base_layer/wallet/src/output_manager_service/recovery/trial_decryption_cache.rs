@@ -0,0 +1,113 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::collections::{BTreeMap, HashMap};
+
+use tari_common_types::types::HashOutput;
+
+use crate::output_manager_service::handle::TrialDecryptionCacheStats;
+
+/// A bounded least-recently-used cache of trial-decryption outcomes keyed by output hash. This allows outputs that are
+/// scanned more than once (e.g. overlapping ranges after a reorg) and do not belong to this wallet to skip the
+/// expensive trial decryption. Only whether the output belongs to this wallet is cached, never the recovered secrets.
+pub(crate) struct TrialDecryptionCache {
+    capacity: usize,
+    /// Maps the output hash to whether the output belongs to this wallet and the last access tick
+    entries: HashMap<HashOutput, (bool, u64)>,
+    /// Maps the last access tick to the output hash, the first entry is the least recently used
+    access_order: BTreeMap<u64, HashOutput>,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl TrialDecryptionCache {
+    /// Create a new cache that holds at most `capacity` results. A capacity of 0 disables the cache.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            access_order: BTreeMap::new(),
+            tick: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Returns whether the output with the given hash belongs to this wallet, if known, and marks it as the most
+    /// recently used entry
+    pub fn get(&mut self, hash: &HashOutput) -> Option<bool> {
+        let tick = self.next_tick();
+        match self.entries.get_mut(hash) {
+            Some((is_owned, last_access)) => {
+                self.access_order.remove(last_access);
+                self.access_order.insert(tick, *hash);
+                *last_access = tick;
+                self.hits += 1;
+                Some(*is_owned)
+            },
+            None => {
+                self.misses += 1;
+                None
+            },
+        }
+    }
+
+    /// Record whether the output with the given hash belongs to this wallet, evicting the least recently used entry if
+    /// the cache is full
+    pub fn insert(&mut self, hash: HashOutput, is_owned: bool) {
+        if !self.is_enabled() {
+            return;
+        }
+        let tick = self.next_tick();
+        if let Some((_, last_access)) = self.entries.insert(hash, (is_owned, tick)) {
+            self.access_order.remove(&last_access);
+        }
+        self.access_order.insert(tick, hash);
+        while self.entries.len() > self.capacity {
+            let oldest = match self.access_order.keys().next() {
+                Some(tick) => *tick,
+                None => break,
+            };
+            if let Some(hash) = self.access_order.remove(&oldest) {
+                self.entries.remove(&hash);
+            }
+        }
+    }
+
+    pub fn stats(&self) -> TrialDecryptionCacheStats {
+        TrialDecryptionCacheStats {
+            size: self.entries.len(),
+            hits: self.hits,
+            misses: self.misses,
+        }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
@@ -20,6 +20,8 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::sync::{Arc, Mutex};
+
 use strum::EnumIter;
 use tari_core::{
     consensus::ConsensusConstants,
@@ -30,6 +32,7 @@ use tari_shutdown::ShutdownSignal;
 use crate::output_manager_service::{
     config::OutputManagerServiceConfig,
     handle::OutputManagerEventSender,
+    recovery::TrialDecryptionCache,
     storage::database::OutputManagerDatabase,
 };
 
@@ -45,6 +48,7 @@ pub(crate) struct OutputManagerResources<TBackend, TWalletConnectivity, TKeyMana
     pub connectivity: TWalletConnectivity,
     pub shutdown_signal: ShutdownSignal,
     pub recovery_data: RecoveryData,
+    pub trial_decryption_cache: Arc<Mutex<TrialDecryptionCache>>,
 }
 
 #[derive(Clone, Copy, EnumIter)]
@@ -55,6 +59,7 @@ pub enum OutputManagerKeyManagerBranch {
     CoinbaseScript,
     ContractIssuer,
     OpeningsEncryption,
+    SenderOffset,
 }
 
 impl OutputManagerKeyManagerBranch {
@@ -68,6 +73,7 @@ impl OutputManagerKeyManagerBranch {
             OutputManagerKeyManagerBranch::CoinbaseScript => "coinbase_script".to_string(),
             OutputManagerKeyManagerBranch::ContractIssuer => "contract_issuer".to_string(),
             OutputManagerKeyManagerBranch::OpeningsEncryption => "openings_encryption".to_string(),
+            OutputManagerKeyManagerBranch::SenderOffset => "sender_offset".to_string(),
         }
     }
 }
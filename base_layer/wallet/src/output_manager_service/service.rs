@@ -20,10 +20,10 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{convert::TryInto, fmt, sync::Arc};
+use std::{cmp::Reverse, collections::HashSet, convert::TryInto, fmt, sync::Arc, time::Duration};
 
 use diesel::result::{DatabaseErrorKind, Error as DieselError};
-use futures::{pin_mut, StreamExt};
+use futures::{future, pin_mut, StreamExt};
 use itertools::Itertools;
 use log::*;
 use rand::{rngs::OsRng, RngCore};
@@ -65,12 +65,12 @@ use tari_crypto::{
     errors::RangeProofError,
     keys::{PublicKey as PublicKeyTrait, SecretKey},
 };
-use tari_key_manager::key_manager_service::KeyManagerInterface;
+use tari_key_manager::key_manager_service::{KeyManagerInterface, KeyManagerServiceError};
 use tari_script::{inputs, script, Opcode, TariScript};
 use tari_service_framework::reply_channel;
 use tari_shutdown::ShutdownSignal;
 use tari_utilities::{hex::Hex, ByteArray};
-use tokio::sync::Mutex;
+use tokio::{sync::Mutex, time, time::MissedTickBehavior};
 
 use crate::{
     base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle},
@@ -86,7 +86,7 @@ use crate::{
             RecoveredOutput,
         },
         input_selection::UtxoSelectionCriteria,
-        recovery::StandardUtxoRecoverer,
+        recovery::{StandardUtxoRecoverer, TrialDecryptionCache},
         resources::{OutputManagerKeyManagerBranch, OutputManagerResources},
         storage::{
             database::{OutputBackendQuery, OutputManagerBackend, OutputManagerDatabase},
@@ -142,12 +142,17 @@ where
         node_identity: Arc<NodeIdentity>,
         key_manager: TKeyManagerInterface,
     ) -> Result<Self, OutputManagerError> {
-        Self::initialise_key_manager(&key_manager).await?;
+        config.validate_key_branches()?;
+        db.set_skip_corrupt_outputs(config.skip_corrupt_outputs);
+        Self::initialise_key_manager(&key_manager, &config).await?;
         let encryption_key = key_manager
             .get_key_at_index(OutputManagerKeyManagerBranch::OpeningsEncryption.get_branch_key(), 0)
             .await?;
         let recovery_data = RecoveryData { encryption_key };
 
+        let trial_decryption_cache = Arc::new(std::sync::Mutex::new(TrialDecryptionCache::new(
+            config.trial_decryption_cache_size,
+        )));
         let resources = OutputManagerResources {
             config,
             db,
@@ -158,6 +163,7 @@ where
             consensus_constants,
             shutdown_signal,
             recovery_data,
+            trial_decryption_cache,
         };
 
         Ok(Self {
@@ -170,10 +176,18 @@ where
         })
     }
 
-    async fn initialise_key_manager(key_manager: &TKeyManagerInterface) -> Result<(), OutputManagerError> {
+    async fn initialise_key_manager(
+        key_manager: &TKeyManagerInterface,
+        config: &OutputManagerServiceConfig,
+    ) -> Result<(), OutputManagerError> {
         for branch in OutputManagerKeyManagerBranch::iter() {
             key_manager.add_new_branch(branch.get_branch_key()).await?;
         }
+        // Custom branches are added alongside the built-in ones; adding an existing branch has no effect
+        key_manager.add_new_branch(config.script_key_branch.clone()).await?;
+        key_manager
+            .add_new_branch(config.sender_offset_key_branch.clone())
+            .await?;
         Ok(())
     }
 
@@ -189,12 +203,23 @@ where
 
         let mut base_node_service_event_stream = self.base_node_service.get_event_stream();
 
+        let auto_consolidate_interval = self
+            .resources
+            .config
+            .auto_consolidate_interval
+            .max(Duration::from_secs(1));
+        let mut auto_consolidate_tick = time::interval_at(
+            time::Instant::now() + auto_consolidate_interval,
+            auto_consolidate_interval,
+        );
+        auto_consolidate_tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
         debug!(target: LOG_TARGET, "Output Manager Service started");
         loop {
             tokio::select! {
                 event = base_node_service_event_stream.recv() => {
                     match event {
-                        Ok(msg) => self.handle_base_node_service_event(msg),
+                        Ok(msg) => self.handle_base_node_service_event(msg).await,
                         Err(e) => debug!(target: LOG_TARGET, "Lagging read on base node event broadcast channel: {}", e),
                     }
                 },
@@ -210,6 +235,9 @@ where
                         e
                     });
                 },
+                _ = auto_consolidate_tick.tick(), if self.resources.config.auto_consolidate => {
+                    self.handle_auto_consolidate_tick().await;
+                },
                 _ = shutdown.wait() => {
                     info!(target: LOG_TARGET, "Output manager service shutting down because it received the shutdown signal");
                     break;
@@ -248,6 +276,36 @@ where
                 self.get_balance(current_tip_for_time_lock_calculation)
                     .map(OutputManagerResponse::Balance)
             },
+            OutputManagerRequest::HasSpendableFunds => {
+                let tip = match self.base_node_service.get_chain_metadata().await {
+                    Ok(metadata) => metadata.map(|m| m.height_of_longest_chain()),
+                    Err(_) => None,
+                };
+                self.resources
+                    .db
+                    .has_spendable_outputs(tip)
+                    .map(OutputManagerResponse::SpendableFunds)
+                    .map_err(OutputManagerError::from)
+            },
+            OutputManagerRequest::GetMinUnspentConfirmations => {
+                let tip = match self.base_node_service.get_chain_metadata().await {
+                    Ok(metadata) => metadata.map(|m| m.height_of_longest_chain()),
+                    Err(_) => None,
+                };
+                let confirmations = match tip {
+                    Some(tip) => self
+                        .resources
+                        .db
+                        .fetch_max_unspent_mined_height()?
+                        .map(|height| tip.saturating_sub(height)),
+                    None => None,
+                };
+                Ok(OutputManagerResponse::MinUnspentConfirmations(confirmations))
+            },
+            OutputManagerRequest::FindHighestUsedSpendKeyIndex(gap_limit) => self
+                .find_highest_used_spend_key_index(gap_limit)
+                .await
+                .map(OutputManagerResponse::HighestUsedSpendKeyIndex),
             OutputManagerRequest::GetRecipientTransaction(tsm) => self
                 .get_recipient_transaction(tsm)
                 .await
@@ -273,6 +331,7 @@ where
                 script,
                 covenant,
                 minimum_value_promise,
+                allow_fee_above_maximum,
             } => self
                 .prepare_transaction_to_send(
                     tx_id,
@@ -285,6 +344,7 @@ where
                     script,
                     covenant,
                     minimum_value_promise,
+                    allow_fee_above_maximum,
                 )
                 .await
                 .map(OutputManagerResponse::TransactionToSend),
@@ -316,6 +376,10 @@ where
                 .fee_estimate(amount, selection_criteria, fee_per_gram, num_kernels, num_outputs)
                 .await
                 .map(OutputManagerResponse::FeeEstimate),
+            OutputManagerRequest::EstimateSweep(fee_per_gram) => self
+                .estimate_sweep(fee_per_gram)
+                .await
+                .map(OutputManagerResponse::SweepEstimate),
             OutputManagerRequest::ConfirmPendingTransaction(tx_id) => self
                 .confirm_encumberance(tx_id)
                 .map(|_| OutputManagerResponse::PendingTransactionConfirmed),
@@ -330,6 +394,35 @@ where
                 let outputs = self.fetch_unspent_outputs()?;
                 Ok(OutputManagerResponse::UnspentOutputs(outputs))
             },
+            OutputManagerRequest::GetUnspentOutputsSkippingCorrupt => {
+                Ok(OutputManagerResponse::UnspentOutputsSkippingCorrupt(
+                    self.resources.db.fetch_unspent_outputs_skipping_corrupt()?,
+                ))
+            },
+            OutputManagerRequest::GetLargestUnspentOutputs(n) => {
+                let outputs = self.fetch_largest_unspent_outputs(n)?;
+                Ok(OutputManagerResponse::UnspentOutputs(outputs))
+            },
+            OutputManagerRequest::GetValueHistogram(bucket_boundaries) => self
+                .get_value_histogram(bucket_boundaries)
+                .map(OutputManagerResponse::ValueHistogram),
+            OutputManagerRequest::MarkOutputSpentExternal {
+                commitment,
+                spent_in_tx,
+            } => {
+                self.resources
+                    .db
+                    .mark_output_as_spent_externally(commitment, spent_in_tx)?;
+                Ok(OutputManagerResponse::OutputMarkedSpentExternal)
+            },
+            OutputManagerRequest::GetReceivedOutputs(exclude_change) => {
+                let outputs = self.fetch_received_outputs(exclude_change)?;
+                Ok(OutputManagerResponse::ReceivedOutputs(outputs))
+            },
+            OutputManagerRequest::VerifyTransactionOutputs { tx_id, commitments } => self
+                .verify_transaction_outputs(tx_id, &commitments)
+                .await
+                .map(OutputManagerResponse::TransactionOutputsVerified),
             OutputManagerRequest::GetOutputsBy(q) => {
                 let outputs = self.fetch_outputs_by(q)?.into_iter().map(|v| v.into()).collect();
                 Ok(OutputManagerResponse::Outputs(outputs))
@@ -383,6 +476,14 @@ where
                         .map(OutputManagerResponse::Transaction)
                 }
             },
+            OutputManagerRequest::SplitSpecificOutput {
+                commitment,
+                split_count,
+                fee_per_gram,
+            } => self
+                .split_specific_output(commitment, split_count, fee_per_gram)
+                .await
+                .map(OutputManagerResponse::Transaction),
             OutputManagerRequest::CreateCoinJoin {
                 commitments,
                 fee_per_gram,
@@ -390,12 +491,25 @@ where
                 .create_coin_join(commitments, fee_per_gram)
                 .await
                 .map(OutputManagerResponse::Transaction),
+            OutputManagerRequest::RunAutoConsolidation => self
+                .run_auto_consolidation()
+                .await
+                .map(OutputManagerResponse::AutoConsolidation),
+            OutputManagerRequest::RunCoinbaseAutoSplit => self
+                .run_coinbase_auto_split()
+                .await
+                .map(OutputManagerResponse::CoinbaseAutoSplit),
 
             OutputManagerRequest::ScanForRecoverableOutputs(outputs) => StandardUtxoRecoverer::new(
                 self.resources.master_key_manager.clone(),
                 self.resources.recovery_data.clone(),
                 self.resources.factories.clone(),
                 self.resources.db.clone(),
+                self.resources.config.script_key_branch.clone(),
+                self.resources.config.scan_parallelism,
+                self.resources.config.scan_output_types.clone(),
+                self.resources.trial_decryption_cache.clone(),
+                self.resources.config.recovery_key_gap_limit,
             )
             .scan_and_recover_outputs(outputs)
             .await
@@ -403,6 +517,21 @@ where
             OutputManagerRequest::ScanOutputs(outputs) => self
                 .scan_outputs_for_one_sided_payments(outputs)
                 .map(OutputManagerResponse::ScanOutputs),
+            OutputManagerRequest::GetTrialDecryptionCacheStats => self
+                .resources
+                .trial_decryption_cache
+                .lock()
+                .map(|cache| OutputManagerResponse::TrialDecryptionCacheStats(cache.stats()))
+                .map_err(|_| OutputManagerError::ServiceError("Trial decryption cache lock poisoned".to_string())),
+            OutputManagerRequest::VacuumDatabase => {
+                let reclaimed = self.resources.db.vacuum()?;
+                Ok(OutputManagerResponse::DatabaseVacuumed(reclaimed))
+            },
+            OutputManagerRequest::RotateEncryption(ciphers) => {
+                let (old_cipher, new_cipher) = *ciphers;
+                let num_outputs = self.resources.db.rotate_encryption(&old_cipher, new_cipher)?;
+                Ok(OutputManagerResponse::EncryptionRotated(num_outputs))
+            },
             OutputManagerRequest::AddKnownOneSidedPaymentScript(known_script) => self
                 .add_known_script(known_script)
                 .map(|_| OutputManagerResponse::AddKnownOneSidedPaymentScript),
@@ -443,6 +572,9 @@ where
                 let output_statuses_by_tx_id = self.get_output_status_by_tx_id(tx_id)?;
                 Ok(OutputManagerResponse::OutputStatusesByTxId(output_statuses_by_tx_id))
             },
+            OutputManagerRequest::GetEncumberedOutputsByTxId(tx_id) => Ok(OutputManagerResponse::EncumberedOutputs(
+                self.get_encumbered_outputs_by_tx_id(tx_id)?,
+            )),
             OutputManagerRequest::GetNextSpendAndScriptKeys => {
                 let (spend_key, script_key) = self.get_spend_and_script_keys().await?;
                 Ok(OutputManagerResponse::NextSpendAndScriptKeys { spend_key, script_key })
@@ -475,6 +607,135 @@ where
         })
     }
 
+    fn get_encumbered_outputs_by_tx_id(&self, tx_id: TxId) -> Result<Vec<DbUnblindedOutput>, OutputManagerError> {
+        let outputs = self.resources.db.fetch_outputs_by_tx_id(tx_id)?;
+        Ok(outputs
+            .into_iter()
+            .filter(|output| {
+                matches!(
+                    output.status,
+                    OutputStatus::EncumberedToBeReceived |
+                        OutputStatus::EncumberedToBeSpent |
+                        OutputStatus::ShortTermEncumberedToBeReceived |
+                        OutputStatus::ShortTermEncumberedToBeSpent
+                )
+            })
+            .collect())
+    }
+
+    /// Re-derives the keys of every output received in the given transaction from the wallet's key manager, checks
+    /// them against the stored keys and checks that each re-derived commitment is contained in `commitments`. Returns
+    /// false if the wallet did not receive any outputs in the transaction. One-sided outputs are not derived from the
+    /// wallet's key chain, so their commitments are recomputed from the stored spending key instead.
+    async fn verify_transaction_outputs(
+        &self,
+        tx_id: TxId,
+        commitments: &[Commitment],
+    ) -> Result<bool, OutputManagerError> {
+        let outputs = self
+            .resources
+            .db
+            .fetch_outputs_by_tx_id(tx_id)?
+            .into_iter()
+            .filter(|output| output.received_in_tx_id == Some(tx_id))
+            .collect::<Vec<_>>();
+        if outputs.is_empty() {
+            debug!(
+                target: LOG_TARGET,
+                "Transaction {} has no outputs received by this wallet to verify", tx_id
+            );
+            return Ok(false);
+        }
+        for output in &outputs {
+            let spending_key = match output.source {
+                OutputSource::OneSided | OutputSource::StealthOneSided => output.unblinded_output.spending_key.clone(),
+                _ => match self.derive_output_keys(output).await? {
+                    Some((spending_key, script_private_key))
+                        if spending_key == output.unblinded_output.spending_key &&
+                            script_private_key == output.unblinded_output.script_private_key =>
+                    {
+                        spending_key
+                    },
+                    _ => {
+                        debug!(
+                            target: LOG_TARGET,
+                            "Stored keys of output {} in transaction {} do not match the keys derived for its key \
+                             index",
+                            output.commitment.to_hex(),
+                            tx_id
+                        );
+                        return Ok(false);
+                    },
+                },
+            };
+            let commitment = self
+                .resources
+                .factories
+                .commitment
+                .commit_value(&spending_key, output.unblinded_output.value.as_u64());
+            if !commitments.contains(&commitment) {
+                debug!(
+                    target: LOG_TARGET,
+                    "Re-derived commitment {} of transaction {} is not in the transaction body",
+                    commitment.to_hex(),
+                    tx_id
+                );
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Derives the spending and script keys at the key index the output was created with. Coinbase keys are derived
+    /// at the height of the block the coinbase was built for, any other output's keys at the index of its spending key
+    /// on the spend branch. Returns None if the output's key index cannot be found among the keys the wallet issued.
+    async fn derive_output_keys(
+        &self,
+        output: &DbUnblindedOutput,
+    ) -> Result<Option<(PrivateKey, PrivateKey)>, OutputManagerError> {
+        let (spend_branch, script_branch, index) = if output.unblinded_output.features.is_coinbase() {
+            let index = match output
+                .unblinded_output
+                .features
+                .maturity
+                .checked_sub(self.resources.consensus_constants.coinbase_lock_height())
+            {
+                Some(index) => index,
+                None => return Ok(None),
+            };
+            (
+                OutputManagerKeyManagerBranch::Coinbase.get_branch_key(),
+                OutputManagerKeyManagerBranch::CoinbaseScript.get_branch_key(),
+                index,
+            )
+        } else {
+            let branch = OutputManagerKeyManagerBranch::Spend.get_branch_key();
+            // A gap of one includes the branch's current index, so only keys the wallet has issued are searched
+            let index = match self
+                .resources
+                .master_key_manager
+                .find_key_index_with_gap_limit(branch.clone(), &output.unblinded_output.spending_key, 1)
+                .await
+            {
+                Ok(index) => index,
+                Err(KeyManagerServiceError::KeyNotFoundInKeyChain) => return Ok(None),
+                Err(e) => return Err(e.into()),
+            };
+            (branch, self.resources.config.script_key_branch.clone(), index)
+        };
+        let spending_key = self
+            .resources
+            .master_key_manager
+            .get_key_at_index(spend_branch, index)
+            .await?;
+        let script_private_key = self
+            .resources
+            .master_key_manager
+            .get_key_at_index(script_branch, index)
+            .await?;
+        Ok(Some((spending_key, script_private_key)))
+    }
+
     async fn claim_sha_atomic_swap_with_hash(
         &mut self,
         output_hash: HashOutput,
@@ -492,7 +753,7 @@ where
             .map(OutputManagerResponse::ClaimHtlcTransaction)
     }
 
-    fn handle_base_node_service_event(&mut self, event: Arc<BaseNodeEvent>) {
+    async fn handle_base_node_service_event(&mut self, event: Arc<BaseNodeEvent>) {
         match (*event).clone() {
             BaseNodeEvent::BaseNodeStateChanged(_state) => {
                 trace!(
@@ -502,6 +763,17 @@ where
             },
             BaseNodeEvent::NewBlockDetected(_hash, height) => {
                 self.last_seen_tip_height = Some(height);
+                if self.resources.config.auto_split_coinbase_threshold.is_some() {
+                    self.handle_coinbase_auto_split().await;
+                }
+                if self.resources.config.manual_validation_only {
+                    trace!(
+                        target: LOG_TARGET,
+                        "New block detected at height {}, skipping automatic txo validation",
+                        height
+                    );
+                    return;
+                }
                 let _id = self.validate_outputs().map_err(|e| {
                     warn!(target: LOG_TARGET, "Error validating  txos: {:?}", e);
                     e
@@ -510,6 +782,168 @@ where
         }
     }
 
+    async fn handle_auto_consolidate_tick(&mut self) {
+        match self.run_auto_consolidation().await {
+            Ok(Some((tx_id, transaction, amount))) => {
+                let event = OutputManagerEvent::AutoConsolidationTransactionCreated {
+                    tx_id,
+                    transaction: Box::new(transaction),
+                    amount,
+                };
+                if let Err(e) = self.resources.event_publisher.send(Arc::new(event)) {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Could not publish auto consolidation transaction (tx_id={}): {:?}", tx_id, e
+                    );
+                    // Nobody is listening to submit the transaction, so release its inputs again
+                    if let Err(e) = self.cancel_transaction(tx_id) {
+                        error!(
+                            target: LOG_TARGET,
+                            "Could not cancel auto consolidation transaction (tx_id={}): {:?}", tx_id, e
+                        );
+                    }
+                }
+            },
+            Ok(None) => trace!(target: LOG_TARGET, "No outputs need to be consolidated"),
+            Err(e) => warn!(target: LOG_TARGET, "Automatic consolidation failed: {:?}", e),
+        }
+    }
+
+    async fn handle_coinbase_auto_split(&mut self) {
+        let splits = match self.run_coinbase_auto_split().await {
+            Ok(splits) => splits,
+            Err(e) => {
+                warn!(target: LOG_TARGET, "Automatic coinbase split failed: {:?}", e);
+                return;
+            },
+        };
+        for (tx_id, transaction, amount) in splits {
+            let event = OutputManagerEvent::CoinbaseSplitTransactionCreated {
+                tx_id,
+                transaction: Box::new(transaction),
+                amount,
+            };
+            if let Err(e) = self.resources.event_publisher.send(Arc::new(event)) {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not publish coinbase split transaction (tx_id={}): {:?}", tx_id, e
+                );
+                // Nobody is listening to submit the transaction, so release its input again
+                if let Err(e) = self.cancel_transaction(tx_id) {
+                    error!(
+                        target: LOG_TARGET,
+                        "Could not cancel coinbase split transaction (tx_id={}): {:?}", tx_id, e
+                    );
+                }
+            }
+        }
+    }
+
+    /// Splits every matured, unencumbered coinbase output with a value above the configured threshold into
+    /// `auto_split_coinbase_count` equal outputs. Each coinbase output is split in its own transaction. A coinbase that
+    /// cannot be split is logged and skipped, so the splits that succeeded are still returned.
+    pub async fn run_coinbase_auto_split(&mut self) -> Result<Vec<(TxId, Transaction, MicroTari)>, OutputManagerError> {
+        let threshold = match self.resources.config.auto_split_coinbase_threshold {
+            Some(threshold) => threshold,
+            None => return Ok(Vec::new()),
+        };
+        let tip_height = match self.base_node_service.get_chain_metadata().await? {
+            Some(metadata) => metadata.height_of_longest_chain(),
+            None => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Skipping coinbase auto split, the chain tip is not known yet"
+                );
+                return Ok(Vec::new());
+            },
+        };
+
+        let coinbases = self
+            .resources
+            .db
+            .fetch_all_unspent_outputs()?
+            .into_iter()
+            .filter(|o| {
+                let features = &o.unblinded_output.features;
+                features.is_coinbase() && features.maturity <= tip_height && o.unblinded_output.value > threshold
+            })
+            .collect::<Vec<_>>();
+
+        let number_of_splits = self.resources.config.auto_split_coinbase_count;
+        let fee_per_gram = self.resources.config.auto_split_coinbase_fee_per_gram;
+        let mut splits = Vec::with_capacity(coinbases.len());
+        for output in coinbases {
+            debug!(
+                target: LOG_TARGET,
+                "Auto splitting coinbase output {} ({}) into {} outputs",
+                output.commitment.to_hex(),
+                output.unblinded_output.value,
+                number_of_splits
+            );
+            let commitment = output.commitment.clone();
+            match self
+                .create_coin_split_even(vec![output], number_of_splits, fee_per_gram)
+                .await
+            {
+                Ok(split) => splits.push(split),
+                Err(e) => warn!(
+                    target: LOG_TARGET,
+                    "Could not auto split coinbase output {}: {:?}",
+                    commitment.to_hex(),
+                    e
+                ),
+            }
+        }
+        Ok(splits)
+    }
+
+    /// Joins all mature unspent outputs with a value below the configured threshold into a single output, provided
+    /// there are more of them than the configured limit. Encumbered outputs are never selected.
+    pub async fn run_auto_consolidation(
+        &mut self,
+    ) -> Result<Option<(TxId, Transaction, MicroTari)>, OutputManagerError> {
+        let tip_height = match self.base_node_service.get_chain_metadata().await? {
+            Some(metadata) => metadata.height_of_longest_chain(),
+            None => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Skipping auto consolidation, the chain tip is not known yet"
+                );
+                return Ok(None);
+            },
+        };
+
+        // NOTE: the default (safe) selection mode filters out outputs that have not reached their maturity or script
+        // lock height yet
+        let selection_criteria = UtxoSelectionCriteria {
+            excluding_onesided: self.resources.config.autoignore_onesided_utxos,
+            ..UtxoSelectionCriteria::smallest_first()
+        };
+        let value_threshold = self.resources.config.auto_consolidate_value_threshold;
+        let commitments = self
+            .resources
+            .db
+            .fetch_unspent_outputs_for_spending(&selection_criteria, MicroTari::zero(), Some(tip_height))?
+            .into_iter()
+            .filter(|o| o.unblinded_output.value < value_threshold)
+            .map(|o| o.commitment)
+            .collect::<Vec<_>>();
+
+        if commitments.len() <= self.resources.config.auto_consolidate_max_outputs {
+            return Ok(None);
+        }
+
+        debug!(
+            target: LOG_TARGET,
+            "Auto consolidating {} outputs below {}",
+            commitments.len(),
+            value_threshold
+        );
+        let fee_per_gram = self.resources.config.auto_consolidate_fee_per_gram;
+        self.create_coin_join(commitments, fee_per_gram).await.map(Some)
+    }
+
+    #[allow(clippy::too_many_lines)]
     fn validate_outputs(&mut self) -> Result<u64, OutputManagerError> {
         let current_base_node = self
             .resources
@@ -517,13 +951,16 @@ where
             .get_current_base_node_id()
             .ok_or(OutputManagerError::NoBaseNodeKeysProvided)?;
         let id = OsRng.next_u64();
-        let txo_validation = TxoValidationTask::new(
-            id,
-            self.resources.db.clone(),
-            self.resources.connectivity.clone(),
-            self.resources.event_publisher.clone(),
-            self.resources.config.clone(),
-        );
+        let db = self.resources.db.clone();
+        let connectivity = self.resources.connectivity.clone();
+        let config = self.resources.config.clone();
+        let new_txo_validation = move |event_publisher| {
+            TxoValidationTask::new(id, db.clone(), connectivity.clone(), event_publisher, config.clone())
+        };
+        let txo_validation = new_txo_validation(self.resources.event_publisher.clone());
+        let auto_retry = self.resources.config.auto_retry_failed_validation;
+        let max_retries = self.resources.config.txo_validation_max_retries;
+        let retry_delay = self.resources.config.txo_validation_retry_delay;
 
         let mut shutdown = self.resources.shutdown_signal.clone();
         let mut base_node_watch = self.resources.connectivity.get_current_base_node_watcher();
@@ -532,7 +969,7 @@ where
         tokio::spawn(async move {
             // Note: We do not want the validation task to be queued
             let mut _lock = match validation_in_progress.try_lock() {
-                Ok(val) => val,
+                Ok(val) => Some(val),
                 _ => {
                     if let Err(e) = event_publisher.send(Arc::new(OutputManagerEvent::TxoValidationAlreadyBusy(id))) {
                         debug!(
@@ -550,6 +987,7 @@ where
 
             let exec_fut = txo_validation.execute();
             tokio::pin!(exec_fut);
+            let mut attempt = 0;
             loop {
                 tokio::select! {
                     result = &mut exec_fut => {
@@ -566,6 +1004,78 @@ where
                                     target: LOG_TARGET,
                                     "Error completing UTXO Validation Protocol (Id: {}): {}", id, error
                                 );
+                                let is_retryable = matches!(
+                                    error,
+                                    OutputManagerError::InconsistentBaseNodeDataError(_) |
+                                        OutputManagerError::RpcError(_)
+                                );
+                                if auto_retry && is_retryable && attempt < max_retries {
+                                    attempt += 1;
+                                    // Back off exponentially between attempts
+                                    let delay = retry_delay.saturating_mul(1 << (attempt - 1).min(16));
+                                    info!(
+                                        target: LOG_TARGET,
+                                        "Retrying UTXO Validation Protocol (Id: {}) in {:.2?} (attempt {} of {})",
+                                        id,
+                                        delay,
+                                        attempt,
+                                        max_retries
+                                    );
+                                    if let Err(e) = event_publisher
+                                        .send(Arc::new(OutputManagerEvent::TxoValidationRetrying { id, attempt }))
+                                    {
+                                        debug!(
+                                            target: LOG_TARGET,
+                                            "Error sending event because there are no subscribers: {:?}", e
+                                        );
+                                    }
+                                    // Other validations may run while this one is backing off
+                                    _lock = None;
+                                    let base_node_changed = async {
+                                        loop {
+                                            if base_node_watch.changed().await.is_err() {
+                                                future::pending::<()>().await;
+                                            }
+                                            if matches!(
+                                                base_node_watch.borrow().as_ref(),
+                                                Some(peer) if peer.node_id != current_base_node
+                                            ) {
+                                                break;
+                                            }
+                                        }
+                                    };
+                                    tokio::select! {
+                                        _ = time::sleep(delay) => {},
+                                        _ = shutdown.wait() => {
+                                            debug!(target: LOG_TARGET, "TXO Validation Protocol (Id: {}) retry \
+                                                cancelled because the system is shutting down", id);
+                                            return;
+                                        },
+                                        _ = base_node_changed => {
+                                            debug!(
+                                                target: LOG_TARGET,
+                                                "TXO Validation Protocol (Id: {}) retry cancelled because base node \
+                                                 changed",
+                                                id
+                                            );
+                                            return;
+                                        },
+                                    }
+                                    _lock = match validation_in_progress.try_lock() {
+                                        Ok(val) => Some(val),
+                                        _ => {
+                                            debug!(
+                                                target: LOG_TARGET,
+                                                "TXO Validation Protocol (Id: {}) retry skipped because another \
+                                                 protocol is busy",
+                                                id
+                                            );
+                                            return;
+                                        },
+                                    };
+                                    exec_fut.set(new_txo_validation(event_publisher.clone()).execute());
+                                    continue;
+                                }
                                 let event_payload = match error {
                                     OutputManagerError::InconsistentBaseNodeDataError(_) |
                                     OutputManagerError::BaseNodeChanged |
@@ -669,7 +1179,9 @@ where
         self.resources.db.add_unvalidated_output(tx_id, output)?;
 
         // Because we added new outputs, let try to trigger a validation for them
-        self.validate_outputs()?;
+        if !self.resources.config.manual_validation_only {
+            self.validate_outputs()?;
+        }
         Ok(())
     }
 
@@ -688,14 +1200,20 @@ where
         let script_key = self
             .resources
             .master_key_manager
-            .get_key_at_index(
-                OutputManagerKeyManagerBranch::SpendScript.get_branch_key(),
-                result.index,
-            )
+            .get_key_at_index(self.resources.config.script_key_branch.clone(), result.index)
             .await?;
         Ok((result.key, script_key))
     }
 
+    async fn get_next_sender_offset_key(&self) -> Result<PrivateKey, OutputManagerError> {
+        let result = self
+            .resources
+            .master_key_manager
+            .get_next_key(self.resources.config.sender_offset_key_branch.clone())
+            .await?;
+        Ok(result.key)
+    }
+
     async fn create_output_with_features(
         &mut self,
         value: MicroTari,
@@ -714,11 +1232,45 @@ where
     }
 
     fn get_balance(&self, current_tip_for_time_lock_calculation: Option<u64>) -> Result<Balance, OutputManagerError> {
-        let balance = self.resources.db.get_balance(current_tip_for_time_lock_calculation)?;
+        let mut balance = self.resources.db.get_balance(current_tip_for_time_lock_calculation)?;
+        if let Some(tip) = current_tip_for_time_lock_calculation {
+            for (output_type, min_confirmations) in &self.resources.config.min_confirmations_by_output_type {
+                let unconfirmed = self.resources.db.fetch_insufficiently_confirmed_unspent_value(
+                    *output_type,
+                    *min_confirmations,
+                    tip,
+                )?;
+                balance.available_balance = balance.available_balance.saturating_sub(unconfirmed);
+                balance.pending_incoming_balance += unconfirmed;
+            }
+        }
         trace!(target: LOG_TARGET, "Balance: {:?}", balance);
         Ok(balance)
     }
 
+    async fn find_highest_used_spend_key_index(&self, gap_limit: u64) -> Result<u64, OutputManagerError> {
+        // Every output the wallet has ever stored counts, spent or not, since its key has been handed out
+        let used_keys = self
+            .resources
+            .db
+            .fetch_outputs_by(OutputBackendQuery {
+                status: vec![],
+                ..Default::default()
+            })?
+            .into_iter()
+            .map(|output| output.unblinded_output.spending_key.to_vec())
+            .collect::<HashSet<_>>();
+        Ok(self
+            .resources
+            .master_key_manager
+            .find_highest_used_index(
+                OutputManagerKeyManagerBranch::Spend.get_branch_key(),
+                gap_limit,
+                |key| used_keys.contains(key.as_bytes()),
+            )
+            .await?)
+    }
+
     /// Request a receiver transaction be generated from the supplied Sender Message
     async fn get_recipient_transaction(
         &mut self,
@@ -734,6 +1286,35 @@ where
             return Err(OutputManagerError::InvalidScriptHash);
         }
 
+        // A repeated delivery of the same sender message (e.g. a duplicate SAF delivery) must not create another
+        // output, so the reply is rebuilt from the output that was stored when the message was first received
+        if let Some(existing) = self
+            .resources
+            .db
+            .fetch_outputs_by_tx_id(single_round_sender_data.tx_id)?
+            .into_iter()
+            .find(|o| o.received_in_tx_id == Some(single_round_sender_data.tx_id))
+        {
+            if existing.unblinded_output.value != single_round_sender_data.amount {
+                return Err(OutputManagerError::InvalidArgument(format!(
+                    "An output of a different value has already been received for TxId {}",
+                    single_round_sender_data.tx_id
+                )));
+            }
+            debug!(
+                target: LOG_TARGET,
+                "Sender message for TxId {} has already been received, replying with the existing output",
+                single_round_sender_data.tx_id
+            );
+            return Ok(ReceiverTransactionProtocol::new_with_recoverable_output(
+                sender_message.clone(),
+                PrivateKey::random(&mut OsRng),
+                existing.unblinded_output.spending_key,
+                &self.resources.factories,
+                &existing.unblinded_output.encrypted_data,
+            ));
+        }
+
         let (spending_key, script_private_key) = self.get_spend_and_script_keys().await?;
 
         let commitment = self
@@ -870,7 +1451,8 @@ where
     }
 
     /// Prepare a Sender Transaction Protocol for the amount and fee_per_gram specified. If required a change output
-    /// will be produced.
+    /// will be produced. The transaction is rejected if its fee exceeds the configured `max_fee_per_transaction`,
+    /// unless `allow_fee_above_maximum` is set.
     #[allow(clippy::too_many_lines)]
     pub async fn prepare_transaction_to_send(
         &mut self,
@@ -884,6 +1466,7 @@ where
         recipient_script: TariScript,
         recipient_covenant: Covenant,
         recipient_minimum_value_promise: MicroTari,
+        allow_fee_above_maximum: bool,
     ) -> Result<SenderTransactionProtocol, OutputManagerError> {
         debug!(
             target: LOG_TARGET,
@@ -918,6 +1501,8 @@ where
         let mut builder = SenderTransactionProtocol::builder(1, self.resources.consensus_constants.clone());
         builder
             .with_fee_per_gram(fee_per_gram)
+            .with_fee_rounding(self.resources.config.fee_rounding)
+            .with_min_change_value(self.resources.config.min_change_value)
             .with_offset(offset.clone())
             .with_private_nonce(nonce.clone())
             .with_amount(0, amount)
@@ -970,6 +1555,14 @@ where
             )
             .map_err(|e| OutputManagerError::BuildError(e.message))?;
 
+        // Check the fee of the built transaction, which includes any fee rounding and dust change added to the fee
+        if let Some(max) = self.resources.config.max_fee_per_transaction {
+            let fee = stp.get_fee_amount()?;
+            if fee > max && !allow_fee_above_maximum {
+                return Err(OutputManagerError::FeeExceedsMaximum { fee, max });
+            }
+        }
+
         // If a change output was created add it to the pending_outputs list.
         let mut change_output = Vec::<DbUnblindedOutput>::new();
         if input_selection.requires_change_output() {
@@ -982,7 +1575,7 @@ where
                 unblinded_output,
                 &self.resources.factories,
                 None,
-                OutputSource::default(),
+                OutputSource::Change,
                 Some(tx_id),
                 None,
             )?);
@@ -1102,6 +1695,8 @@ where
         builder
             .with_lock_height(0)
             .with_fee_per_gram(fee_per_gram)
+            .with_fee_rounding(self.resources.config.fee_rounding)
+            .with_min_change_value(self.resources.config.min_change_value)
             .with_offset(offset.clone())
             .with_private_nonce(nonce.clone())
             .with_prevent_fee_gt_amount(false)
@@ -1128,7 +1723,7 @@ where
 
         let mut db_outputs = vec![];
         for mut unblinded_output in outputs {
-            let sender_offset_private_key = PrivateKey::random(&mut OsRng);
+            let sender_offset_private_key = self.get_next_sender_offset_key().await?;
             unblinded_output.sign_as_sender_and_receiver(&sender_offset_private_key)?;
 
             let ub = unblinded_output.try_build()?;
@@ -1201,13 +1796,15 @@ where
 
         let offset = PrivateKey::random(&mut OsRng);
         let nonce = PrivateKey::random(&mut OsRng);
-        let sender_offset_private_key = PrivateKey::random(&mut OsRng);
+        let sender_offset_private_key = self.get_next_sender_offset_key().await?;
 
         // Create builder with no recipients (other than ourselves)
         let mut builder = SenderTransactionProtocol::builder(0, self.resources.consensus_constants.clone());
         builder
             .with_lock_height(lock_height.unwrap_or(0))
             .with_fee_per_gram(fee_per_gram)
+            .with_fee_rounding(self.resources.config.fee_rounding)
+            .with_min_change_value(self.resources.config.min_change_value)
             .with_offset(offset.clone())
             .with_private_nonce(nonce.clone())
             .with_recoverable_outputs(self.resources.recovery_data.clone())
@@ -1352,6 +1949,46 @@ where
         Ok(())
     }
 
+    /// Estimate a transaction that spends every spendable output into a single recipient output without change.
+    /// Outputs that have not reached their maturity or script lock height are not included.
+    async fn estimate_sweep(&mut self, fee_per_gram: MicroTari) -> Result<(MicroTari, usize), OutputManagerError> {
+        let chain_metadata = self.base_node_service.get_chain_metadata().await?;
+        let tip_height = chain_metadata.as_ref().map(|m| m.height_of_longest_chain());
+
+        let selection_criteria = UtxoSelectionCriteria {
+            excluding_onesided: self.resources.config.autoignore_onesided_utxos,
+            ..Default::default()
+        };
+        let utxos =
+            self.resources
+                .db
+                .fetch_unspent_outputs_for_spending(&selection_criteria, MicroTari::zero(), tip_height)?;
+        if utxos.is_empty() {
+            return Err(OutputManagerError::NotEnoughFunds);
+        }
+
+        let total_value = utxos.iter().map(|o| o.unblinded_output.value).sum::<MicroTari>();
+        let fee = Fee::normalize(self.get_fee_calc().calculate(
+            fee_per_gram,
+            1,
+            utxos.len(),
+            1,
+            self.default_features_and_scripts_size(),
+        ));
+        if total_value <= fee {
+            return Err(OutputManagerError::NotEnoughFunds);
+        }
+
+        debug!(
+            target: LOG_TARGET,
+            "Sweep estimate: {} inputs, total value {}, fee {}",
+            utxos.len(),
+            total_value,
+            fee
+        );
+        Ok((total_value - fee, utxos.len()))
+    }
+
     /// Select which unspent transaction outputs to use to send a transaction of the specified amount. Use the specified
     /// selection strategy to choose the outputs. It also determines if a change output is required.
     #[allow(clippy::too_many_lines)]
@@ -1385,16 +2022,33 @@ where
             selection_criteria.excluding_onesided = self.resources.config.autoignore_onesided_utxos;
         }
 
+        if selection_criteria.allow_immature_coinbase {
+            warn!(
+                target: LOG_TARGET,
+                "Immature coinbase outputs may be selected for this transaction. It will be rejected by base nodes \
+                 until every selected coinbase output has matured."
+            );
+        }
+
         debug!(
             target: LOG_TARGET,
             "select_utxos selection criteria: {}", selection_criteria
         );
         let tip_height = chain_metadata.as_ref().map(|m| m.height_of_longest_chain());
-        let uo = self
+        let mut uo = self
             .resources
             .db
             .fetch_unspent_outputs_for_spending(&selection_criteria, amount, tip_height)?;
 
+        if let (true, Some(tip)) = (selection_criteria.prefer_confirmed, tip_height) {
+            let required = self.resources.config.num_confirmations_required;
+            // The sort is stable, so the spending priority and the requested ordering are otherwise preserved
+            uo.sort_by_key(|o| {
+                let deeply_confirmed = o.mined_height.map_or(false, |h| tip.saturating_sub(h) >= required);
+                (Reverse(u32::from(o.spending_priority.clone())), !deeply_confirmed)
+            });
+        }
+
         // For non-standard queries, we want to ensure that the intended UTXOs are selected
         if !selection_criteria.filter.is_standard() && uo.is_empty() {
             return Err(OutputManagerError::NoUtxosSelected {
@@ -1412,11 +2066,22 @@ where
 
         trace!(target: LOG_TARGET, "We found {} UTXOs to select from", uo.len());
 
+        let max_inputs = self.resources.config.max_inputs_per_transaction;
         let mut requires_change_output = false;
         let mut utxos_total_value = MicroTari::from(0);
         let mut fee_without_change = MicroTari::from(0);
         let mut fee_with_change = MicroTari::from(0);
-        for o in uo {
+        let mut uo = uo.into_iter();
+        while let Some(o) = uo.next() {
+            if max_inputs > 0 && utxos.len() == max_inputs {
+                // Only report the limit if the remaining outputs could have covered the amount
+                let remaining_value =
+                    o.unblinded_output.value + uo.by_ref().map(|o| o.unblinded_output.value).sum::<MicroTari>();
+                if utxos_total_value + remaining_value > amount + fee_with_change {
+                    return Err(OutputManagerError::TooManyInputsRequired { max: max_inputs });
+                }
+                break;
+            }
             utxos_total_value += o.unblinded_output.value;
 
             trace!(target: LOG_TARGET, "-- utxos_total_value = {:?}", utxos_total_value);
@@ -1447,6 +2112,62 @@ where
             }
         }
 
+        // Spend a small output that wasn't selected as well, so that the change absorbs it
+        if requires_change_output &&
+            self.resources.config.auto_absorb_small_change &&
+            selection_criteria.filter.is_standard() &&
+            (max_inputs == 0 || utxos.len() < max_inputs)
+        {
+            let threshold = self.resources.config.auto_consolidate_value_threshold;
+            if let Some(small) = uo
+                .filter(|o| o.unblinded_output.value < threshold)
+                .min_by_key(|o| o.unblinded_output.value)
+            {
+                let fee_with_absorbed = fee_calc.calculate(
+                    fee_per_gram,
+                    1,
+                    utxos.len() + 1,
+                    num_outputs + 1,
+                    total_output_features_and_scripts_byte_size + default_features_and_scripts_size,
+                );
+                // Only worth it if the output is worth more than the fee of spending it
+                if small.unblinded_output.value > fee_with_absorbed - fee_with_change {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Absorbing small output {} into the change",
+                        small.commitment.to_hex()
+                    );
+                    utxos_total_value += small.unblinded_output.value;
+                    utxos.push(small);
+                    fee_without_change = fee_calc.calculate(
+                        fee_per_gram,
+                        1,
+                        utxos.len(),
+                        num_outputs,
+                        total_output_features_and_scripts_byte_size,
+                    );
+                    fee_with_change = fee_with_absorbed;
+                }
+            }
+        }
+
+        // Round the fee and fold dust change into it the same way the transaction builder will
+        if requires_change_output {
+            let (fee, change) = Fee::split_change(
+                utxos_total_value - amount - fee_without_change,
+                fee_without_change,
+                fee_with_change,
+                self.resources.config.fee_rounding,
+                self.resources.config.min_change_value,
+            );
+            if change.is_some() {
+                fee_with_change = fee;
+            } else {
+                requires_change_output = false;
+                fee_without_change = fee;
+            }
+        }
+
         let perfect_utxo_selection = utxos_total_value == amount + fee_without_change;
         let enough_spendable = utxos_total_value > amount + fee_with_change;
 
@@ -1478,10 +2199,49 @@ where
         Ok(self.resources.db.fetch_all_unspent_outputs()?)
     }
 
+    pub fn fetch_largest_unspent_outputs(&self, n: usize) -> Result<Vec<DbUnblindedOutput>, OutputManagerError> {
+        Ok(self.resources.db.fetch_largest_unspent_outputs(n)?)
+    }
+
+    fn get_value_histogram(
+        &self,
+        mut bucket_boundaries: Vec<MicroTari>,
+    ) -> Result<Vec<(MicroTari, usize)>, OutputManagerError> {
+        bucket_boundaries.sort();
+        bucket_boundaries.dedup();
+        let mut histogram: Vec<(MicroTari, usize)> = bucket_boundaries.into_iter().map(|b| (b, 0)).collect();
+        for output in self.resources.db.fetch_all_unspent_outputs()? {
+            let value = output.unblinded_output.value;
+            // The number of boundaries at or below the value, so the bucket is the one started by the last of them
+            let bucket = histogram.partition_point(|(boundary, _)| *boundary <= value);
+            if bucket > 0 {
+                histogram[bucket - 1].1 += 1;
+            }
+        }
+        Ok(histogram)
+    }
+
     pub fn fetch_outputs_by(&self, q: OutputBackendQuery) -> Result<Vec<DbUnblindedOutput>, OutputManagerError> {
         Ok(self.resources.db.fetch_outputs_by(q)?)
     }
 
+    /// Fetch the unspent and incoming outputs of the wallet, optionally leaving out change outputs
+    pub fn fetch_received_outputs(&self, exclude_change: bool) -> Result<Vec<DbUnblindedOutput>, OutputManagerError> {
+        let outputs = self.fetch_outputs_by(OutputBackendQuery {
+            status: vec![
+                OutputStatus::Unspent,
+                OutputStatus::UnspentMinedUnconfirmed,
+                OutputStatus::EncumberedToBeReceived,
+                OutputStatus::ShortTermEncumberedToBeReceived,
+            ],
+            ..Default::default()
+        })?;
+        Ok(outputs
+            .into_iter()
+            .filter(|output| !(exclude_change && output.is_change()))
+            .collect())
+    }
+
     pub fn fetch_invalid_outputs(&self) -> Result<Vec<DbUnblindedOutput>, OutputManagerError> {
         Ok(self.resources.db.get_invalid_outputs()?)
     }
@@ -1605,6 +2365,47 @@ where
         }
     }
 
+    async fn split_specific_output(
+        &mut self,
+        commitment: Commitment,
+        number_of_splits: usize,
+        fee_per_gram: MicroTari,
+    ) -> Result<(TxId, Transaction, MicroTari), OutputManagerError> {
+        let output = self.resources.db.fetch_by_commitment(commitment)?;
+        match output.status {
+            OutputStatus::Unspent => {},
+            OutputStatus::Spent | OutputStatus::SpentMinedUnconfirmed => {
+                return Err(OutputManagerStorageError::OutputAlreadySpent.into());
+            },
+            OutputStatus::EncumberedToBeSpent | OutputStatus::ShortTermEncumberedToBeSpent => {
+                return Err(OutputManagerStorageError::OutputAlreadyEncumbered.into());
+            },
+            status => return Err(OutputManagerError::OutputNotSpendable(status)),
+        }
+
+        // Without a known chain tip only outputs without any lock can be spent
+        let tip_height = self
+            .base_node_service
+            .get_chain_metadata()
+            .await?
+            .map(|metadata| metadata.height_of_longest_chain())
+            .unwrap_or(0);
+        let maturity = output.unblinded_output.features.maturity;
+        if maturity > tip_height {
+            return Err(OutputManagerError::OutputNotMatured { maturity, tip_height });
+        }
+        let script_lock_height = output.unblinded_output.script_lock_height;
+        if script_lock_height > tip_height {
+            return Err(OutputManagerError::OutputScriptLocked {
+                script_lock_height,
+                tip_height,
+            });
+        }
+
+        self.create_coin_split_even(vec![output], number_of_splits, fee_per_gram)
+            .await
+    }
+
     async fn create_coin_split_auto(
         &mut self,
         amount_per_split: Option<MicroTari>,
@@ -1664,6 +2465,9 @@ where
 
         let aftertax_amount = accumulated_amount.saturating_sub(fee);
         let amount_per_split = MicroTari(aftertax_amount.as_u64() / number_of_splits as u64);
+        if amount_per_split == MicroTari::zero() {
+            return Err(OutputManagerError::NotEnoughFunds);
+        }
         let unspent_remainder = MicroTari(aftertax_amount.as_u64() % amount_per_split.as_u64());
 
         // preliminary balance check
@@ -1677,6 +2481,7 @@ where
         tx_builder
             .with_lock_height(0)
             .with_fee_per_gram(fee_per_gram)
+            .with_fee_rounding(self.resources.config.fee_rounding)
             .with_offset(PrivateKey::random(&mut OsRng))
             .with_private_nonce(PrivateKey::random(&mut OsRng))
             .with_kernel_features(KernelFeatures::empty())
@@ -1715,7 +2520,7 @@ where
             let output_features = OutputFeatures::default();
 
             // generating sender's keypair
-            let sender_offset_private_key = PrivateKey::random(&mut OsRng);
+            let sender_offset_private_key = self.get_next_sender_offset_key().await?;
             let sender_offset_public_key = PublicKey::from_secret_key(&sender_offset_private_key);
             let commitment = self
                 .resources
@@ -1898,6 +2703,7 @@ where
         tx_builder
             .with_lock_height(0)
             .with_fee_per_gram(fee_per_gram)
+            .with_fee_rounding(self.resources.config.fee_rounding)
             .with_offset(PrivateKey::random(&mut OsRng))
             .with_private_nonce(PrivateKey::random(&mut OsRng))
             .with_recoverable_outputs(self.resources.recovery_data.clone())
@@ -1932,7 +2738,7 @@ where
             let output_features = OutputFeatures::default();
 
             // generating sender's keypair
-            let sender_offset_private_key = PrivateKey::random(&mut OsRng);
+            let sender_offset_private_key = self.get_next_sender_offset_key().await?;
             let sender_offset_public_key = PublicKey::from_secret_key(&sender_offset_private_key);
             let commitment = self
                 .resources
@@ -2109,6 +2915,7 @@ where
         tx_builder
             .with_lock_height(0)
             .with_fee_per_gram(fee_per_gram)
+            .with_fee_rounding(self.resources.config.fee_rounding)
             .with_offset(PrivateKey::random(&mut OsRng))
             .with_private_nonce(PrivateKey::random(&mut OsRng))
             .with_recoverable_outputs(self.resources.recovery_data.clone())
@@ -2139,7 +2946,7 @@ where
         let output_features = OutputFeatures::default();
 
         // generating sender's keypair
-        let sender_offset_private_key = PrivateKey::random(&mut OsRng);
+        let sender_offset_private_key = self.get_next_sender_offset_key().await?;
         let sender_offset_public_key = PublicKey::from_secret_key(&sender_offset_private_key);
         let commitment = self
             .resources
@@ -2298,6 +3105,7 @@ where
                 builder
                     .with_lock_height(0)
                     .with_fee_per_gram(fee_per_gram)
+                    .with_fee_rounding(self.resources.config.fee_rounding)
                     .with_offset(offset.clone())
                     .with_private_nonce(nonce.clone())
                     .with_message(message)
@@ -2385,6 +3193,7 @@ where
         builder
             .with_lock_height(0)
             .with_fee_per_gram(fee_per_gram)
+            .with_fee_rounding(self.resources.config.fee_rounding)
             .with_offset(offset.clone())
             .with_private_nonce(nonce.clone())
             .with_message(message)
@@ -2479,6 +3288,9 @@ where
         let mut scanned_outputs = vec![];
 
         for output in outputs {
+            if !self.resources.config.scans_output_type(output.features.output_type) {
+                continue;
+            }
             match output.script.as_slice() {
                 // ----------------------------------------------------------------------------
                 // simple one-sided address
@@ -2553,6 +3365,15 @@ where
                     &blinding_factor,
                     committed_value.into(),
                 )? {
+                    if self.resources.config.ignore_received_dust && self.is_dust(committed_value) {
+                        info!(
+                            target: LOG_TARGET,
+                            "Ignoring one-sided payment output {} with dust value {}",
+                            output.commitment.to_hex(),
+                            committed_value,
+                        );
+                        continue;
+                    }
                     let rewound_output = UnblindedOutput::new(
                         output.version,
                         committed_value,
@@ -2613,6 +3434,13 @@ where
         Ok(rewound_outputs)
     }
 
+    /// An output is dust if spending it as an input costs at least as much as its value
+    fn is_dust(&self, value: MicroTari) -> bool {
+        value <=
+            self.get_fee_calc()
+                .calculate(self.resources.config.dust_fee_per_gram, 0, 1, 0, 0)
+    }
+
     fn get_fee_calc(&self) -> Fee {
         Fee::new(*self.resources.consensus_constants.transaction_weight())
     }
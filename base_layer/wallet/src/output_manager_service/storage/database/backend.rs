@@ -1,6 +1,7 @@
 // Copyright 2022 The Tari Project
 // SPDX-License-Identifier: BSD-3-Clause
 
+use chacha20poly1305::XChaCha20Poly1305;
 use tari_common_types::{
     transaction::TxId,
     types::{Commitment, FixedHash},
@@ -13,7 +14,7 @@ use crate::output_manager_service::{
     service::Balance,
     storage::{
         database::{DbKey, DbValue, OutputBackendQuery, WriteOperation},
-        models::DbUnblindedOutput,
+        models::{CorruptOutput, DbUnblindedOutput},
     },
 };
 
@@ -28,6 +29,8 @@ pub trait OutputManagerBackend: Send + Sync + Clone {
     fn fetch_with_features(&self, features: OutputType) -> Result<Vec<DbUnblindedOutput>, OutputManagerStorageError>;
     /// Retrieve unspent outputs.
     fn fetch_sorted_unspent_outputs(&self) -> Result<Vec<DbUnblindedOutput>, OutputManagerStorageError>;
+    /// Retrieve the `n` unspent outputs with the highest value, ordered from highest to lowest value
+    fn fetch_largest_unspent_outputs(&self, n: usize) -> Result<Vec<DbUnblindedOutput>, OutputManagerStorageError>;
     /// Retrieve outputs that have been mined but not spent yet (have not been deleted)
     fn fetch_mined_unspent_outputs(&self) -> Result<Vec<DbUnblindedOutput>, OutputManagerStorageError>;
     /// Retrieve outputs that are invalid
@@ -84,17 +87,59 @@ pub trait OutputManagerBackend: Send + Sync + Clone {
     fn update_output_metadata_signature(&self, output: &TransactionOutput) -> Result<(), OutputManagerStorageError>;
     /// If an invalid output is found to be valid this function will turn it back into an unspent output
     fn revalidate_unspent_output(&self, spending_key: &Commitment) -> Result<(), OutputManagerStorageError>;
+    /// Mark an unspent output as spent by a transaction that this wallet did not build. The output is set to
+    /// `SpentMinedUnconfirmed` without a deleted height so that the next TXO validation either confirms the spend or
+    /// turns it back into an unspent output.
+    fn mark_output_as_spent_externally(
+        &self,
+        commitment: &Commitment,
+        spent_in_tx_id: Option<TxId>,
+    ) -> Result<(), OutputManagerStorageError>;
 
     /// Get the output that was most recently mined, ordered descending by mined height
     fn get_last_mined_output(&self) -> Result<Option<DbUnblindedOutput>, OutputManagerStorageError>;
     /// Get the output that was most recently spent, ordered descending by mined height
     fn get_last_spent_output(&self) -> Result<Option<DbUnblindedOutput>, OutputManagerStorageError>;
+    /// Rebuild the database to reclaim unused space, returning the number of bytes reclaimed
+    fn vacuum(&self) -> Result<u64, OutputManagerStorageError>;
+    /// Discard the in-memory database cipher. Encrypted values cannot be read or written until the database is unlocked
+    fn lock(&self);
+    /// Restore the database cipher after the database was locked
+    fn unlock(&self, cipher: XChaCha20Poly1305);
+    /// Re-encrypt all stored encrypted data that was encrypted with `old_cipher` using `new_cipher` and use
+    /// `new_cipher` from then on. This is atomic, no data is changed if any of it fails to re-encrypt. Returns the
+    /// number of outputs re-encrypted.
+    fn rotate_encryption(
+        &self,
+        old_cipher: &XChaCha20Poly1305,
+        new_cipher: XChaCha20Poly1305,
+    ) -> Result<usize, OutputManagerStorageError>;
     /// Set if a coinbase output is abandoned or not
     fn set_coinbase_abandoned(&self, tx_id: TxId, abandoned: bool) -> Result<(), OutputManagerStorageError>;
     /// Reinstate a cancelled inbound output
     fn reinstate_cancelled_inbound_output(&self, tx_id: TxId) -> Result<(), OutputManagerStorageError>;
     /// Return the available, time locked, pending incoming and pending outgoing balance
     fn get_balance(&self, tip: Option<u64>) -> Result<Balance, OutputManagerStorageError>;
+    /// Return the total value of unspent outputs of the given type that have been mined but have fewer than
+    /// `min_confirmations` confirmations at `tip`
+    fn fetch_insufficiently_confirmed_unspent_value(
+        &self,
+        output_type: OutputType,
+        min_confirmations: u64,
+        tip: u64,
+    ) -> Result<MicroTari, OutputManagerStorageError>;
+    /// Returns true if at least one unspent output can be spent at `tip`
+    fn has_spendable_outputs(&self, tip: Option<u64>) -> Result<bool, OutputManagerStorageError>;
+    /// Returns the highest mined height of all unspent outputs, or `None` if none of them have been mined
+    fn fetch_max_unspent_mined_height(&self) -> Result<Option<u64>, OutputManagerStorageError>;
+    /// Fetch all unspent outputs, skipping the ones that can't be loaded. Returns the outputs that were loaded and the
+    /// skipped rows.
+    fn fetch_unspent_outputs_skipping_corrupt(
+        &self,
+    ) -> Result<(Vec<DbUnblindedOutput>, Vec<CorruptOutput>), OutputManagerStorageError>;
+    /// If set, outputs that can't be loaded are logged and skipped when loading the unspent, spendable and to be
+    /// validated outputs, instead of failing the whole request
+    fn set_skip_corrupt_outputs(&self, skip: bool);
     /// Import unvalidated output
     fn add_unvalidated_output(&self, output: DbUnblindedOutput, tx_id: TxId) -> Result<(), OutputManagerStorageError>;
     fn fetch_unspent_outputs_for_spending(
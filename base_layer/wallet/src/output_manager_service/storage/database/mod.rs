@@ -27,6 +27,7 @@ use std::{
 };
 
 pub use backend::OutputManagerBackend;
+use chacha20poly1305::XChaCha20Poly1305;
 use log::*;
 use tari_common_types::{
     transaction::TxId,
@@ -43,7 +44,7 @@ use crate::output_manager_service::{
     input_selection::UtxoSelectionCriteria,
     service::Balance,
     storage::{
-        models::{DbUnblindedOutput, KnownOneSidedPaymentScript},
+        models::{CorruptOutput, DbUnblindedOutput, KnownOneSidedPaymentScript},
         OutputStatus,
     },
 };
@@ -188,6 +189,34 @@ where T: OutputManagerBackend + 'static
         self.db.get_balance(current_tip_for_time_lock_calculation)
     }
 
+    pub fn fetch_insufficiently_confirmed_unspent_value(
+        &self,
+        output_type: OutputType,
+        min_confirmations: u64,
+        tip: u64,
+    ) -> Result<MicroTari, OutputManagerStorageError> {
+        self.db
+            .fetch_insufficiently_confirmed_unspent_value(output_type, min_confirmations, tip)
+    }
+
+    pub fn has_spendable_outputs(&self, tip: Option<u64>) -> Result<bool, OutputManagerStorageError> {
+        self.db.has_spendable_outputs(tip)
+    }
+
+    pub fn fetch_max_unspent_mined_height(&self) -> Result<Option<u64>, OutputManagerStorageError> {
+        self.db.fetch_max_unspent_mined_height()
+    }
+
+    pub fn fetch_unspent_outputs_skipping_corrupt(
+        &self,
+    ) -> Result<(Vec<DbUnblindedOutput>, Vec<CorruptOutput>), OutputManagerStorageError> {
+        self.db.fetch_unspent_outputs_skipping_corrupt()
+    }
+
+    pub fn set_skip_corrupt_outputs(&self, skip: bool) {
+        self.db.set_skip_corrupt_outputs(skip)
+    }
+
     /// This method is called when a transaction is built to be sent. It will encumber unspent outputs against a pending
     /// transaction in the short term.
     pub fn encumber_outputs(
@@ -280,6 +309,10 @@ where T: OutputManagerBackend + 'static
         Ok(utxos)
     }
 
+    pub fn fetch_largest_unspent_outputs(&self, n: usize) -> Result<Vec<DbUnblindedOutput>, OutputManagerStorageError> {
+        self.db.fetch_largest_unspent_outputs(n)
+    }
+
     pub fn fetch_mined_unspent_outputs(&self) -> Result<Vec<DbUnblindedOutput>, OutputManagerStorageError> {
         let utxos = self.db.fetch_mined_unspent_outputs()?;
         Ok(utxos)
@@ -324,6 +357,14 @@ where T: OutputManagerBackend + 'static
         self.db.revalidate_unspent_output(&commitment)
     }
 
+    pub fn mark_output_as_spent_externally(
+        &self,
+        commitment: Commitment,
+        spent_in_tx_id: Option<TxId>,
+    ) -> Result<(), OutputManagerStorageError> {
+        self.db.mark_output_as_spent_externally(&commitment, spent_in_tx_id)
+    }
+
     pub fn reinstate_cancelled_inbound_output(&self, tx_id: TxId) -> Result<(), OutputManagerStorageError> {
         self.db.reinstate_cancelled_inbound_output(tx_id)
     }
@@ -366,6 +407,18 @@ where T: OutputManagerBackend + 'static
         self.db.get_last_spent_output()
     }
 
+    pub fn vacuum(&self) -> Result<u64, OutputManagerStorageError> {
+        self.db.vacuum()
+    }
+
+    pub fn rotate_encryption(
+        &self,
+        old_cipher: &XChaCha20Poly1305,
+        new_cipher: XChaCha20Poly1305,
+    ) -> Result<usize, OutputManagerStorageError> {
+        self.db.rotate_encryption(old_cipher, new_cipher)
+    }
+
     pub fn add_known_script(&self, known_script: KnownOneSidedPaymentScript) -> Result<(), OutputManagerStorageError> {
         self.db
             .write(WriteOperation::Insert(DbKeyValuePair::KnownOneSidedPaymentScripts(
@@ -80,6 +80,11 @@ impl DbUnblindedOutput {
             spent_in_tx_id,
         })
     }
+
+    /// Returns true if this output is change created by the wallet when sending a transaction
+    pub fn is_change(&self) -> bool {
+        self.source == OutputSource::Change
+    }
 }
 
 impl From<DbUnblindedOutput> for UnblindedOutput {
@@ -150,3 +155,12 @@ impl PartialEq for KnownOneSidedPaymentScript {
         self.script_hash == other.script_hash
     }
 }
+
+/// An output row that could not be loaded from the database, e.g. due to corrupted encrypted data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorruptOutput {
+    /// The database id of the row
+    pub id: i32,
+    /// The commitment of the output, if it could be decoded
+    pub commitment: Option<Commitment>,
+}
@@ -43,6 +43,7 @@ pub enum OutputSource {
     StealthOneSided,
     Refund,
     AtomicSwap,
+    Change,
 }
 
 impl TryFrom<i32> for OutputSource {
@@ -58,9 +59,10 @@ impl TryFrom<i32> for OutputSource {
             5 => OutputSource::StealthOneSided,
             6 => OutputSource::Refund,
             7 => OutputSource::AtomicSwap,
+            8 => OutputSource::Change,
             _ => {
                 return Err(OutputManagerStorageError::ConversionError {
-                    reason: "Was expecting value between 0 and 7 for OutputSource".to_string(),
+                    reason: "Was expecting value between 0 and 8 for OutputSource".to_string(),
                 })
             },
         })
@@ -22,13 +22,20 @@
 
 use std::{
     convert::TryFrom,
-    sync::{Arc, RwLock},
+    fs,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+        RwLock,
+        RwLockReadGuard,
+    },
 };
 
-use chacha20poly1305::XChaCha20Poly1305;
+use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
 use chrono::{NaiveDateTime, Utc};
 use derivative::Derivative;
 use diesel::{
+    connection::SimpleConnection,
     prelude::*,
     r2d2::{ConnectionManager, PooledConnection},
     result::Error as DieselError,
@@ -43,12 +50,15 @@ use tari_common_types::{
     transaction::TxId,
     types::{Commitment, FixedHash, PrivateKey},
 };
-use tari_core::transactions::transaction_components::{OutputType, TransactionOutput};
+use tari_core::transactions::{
+    tari_amount::MicroTari,
+    transaction_components::{OutputType, TransactionOutput},
+};
 use tari_crypto::tari_utilities::{hex::Hex, ByteArray};
 use tari_script::{ExecutionStack, TariScript};
 use tari_utilities::Hidden;
 use tokio::time::Instant;
-use zeroize::Zeroize;
+use zeroize::{Zeroize, Zeroizing};
 
 use crate::{
     output_manager_service::{
@@ -56,7 +66,7 @@ use crate::{
         service::Balance,
         storage::{
             database::{DbKey, DbKeyValuePair, DbValue, OutputBackendQuery, OutputManagerBackend, WriteOperation},
-            models::{DbUnblindedOutput, KnownOneSidedPaymentScript},
+            models::{CorruptOutput, DbUnblindedOutput, KnownOneSidedPaymentScript},
             OutputStatus,
         },
         UtxoSelectionCriteria,
@@ -73,6 +83,8 @@ const LOG_TARGET: &str = "wallet::output_manager_service::database::wallet";
 pub struct OutputManagerSqliteDatabase {
     database_connection: WalletDbConnection,
     cipher: Arc<RwLock<XChaCha20Poly1305>>,
+    is_locked: Arc<AtomicBool>,
+    skip_corrupt_outputs: Arc<AtomicBool>,
 }
 
 impl OutputManagerSqliteDatabase {
@@ -80,7 +92,34 @@ impl OutputManagerSqliteDatabase {
         Self {
             database_connection,
             cipher: Arc::new(RwLock::new(cipher)),
+            is_locked: Arc::new(AtomicBool::new(false)),
+            skip_corrupt_outputs: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Acquires the database cipher, failing if the database has been locked
+    fn read_cipher(&self) -> Result<RwLockReadGuard<'_, XChaCha20Poly1305>, OutputManagerStorageError> {
+        let cipher = acquire_read_lock!(self.cipher);
+        if self.is_locked.load(Ordering::SeqCst) {
+            return Err(OutputManagerStorageError::Locked);
+        }
+        Ok(cipher)
+    }
+
+    /// Decrypts the output rows. If `skip_corrupt_outputs` is set the rows that can't be loaded are logged and skipped,
+    /// otherwise the first one fails the whole load.
+    fn load_outputs(
+        &self,
+        outputs: Vec<OutputSql>,
+        cipher: &XChaCha20Poly1305,
+    ) -> Result<Vec<DbUnblindedOutput>, OutputManagerStorageError> {
+        if self.skip_corrupt_outputs.load(Ordering::SeqCst) {
+            return Ok(load_outputs_skipping_corrupt(outputs, cipher).0);
         }
+        outputs
+            .into_iter()
+            .map(|o| o.to_db_unblinded_output(cipher))
+            .collect::<Result<Vec<_>, _>>()
     }
 
     fn insert(
@@ -88,7 +127,7 @@ impl OutputManagerSqliteDatabase {
         key_value_pair: DbKeyValuePair,
         conn: &mut SqliteConnection,
     ) -> Result<(), OutputManagerStorageError> {
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         match key_value_pair {
             DbKeyValuePair::UnspentOutput(c, o) => {
@@ -131,6 +170,36 @@ impl OutputManagerSqliteDatabase {
     }
 }
 
+/// Decrypts the output rows, returning the outputs that could be loaded and the rows that could not
+fn load_outputs_skipping_corrupt(
+    outputs: Vec<OutputSql>,
+    cipher: &XChaCha20Poly1305,
+) -> (Vec<DbUnblindedOutput>, Vec<CorruptOutput>) {
+    let mut loaded = Vec::with_capacity(outputs.len());
+    let mut corrupt = Vec::new();
+    for output in outputs {
+        let id = output.id;
+        let commitment = output.commitment.as_ref().and_then(|c| Commitment::from_vec(c).ok());
+        match output.to_db_unblinded_output(cipher) {
+            Ok(output) => loaded.push(output),
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Skipping corrupt output {} with commitment {}: {}",
+                    id,
+                    commitment
+                        .as_ref()
+                        .map(|c| c.to_hex())
+                        .unwrap_or_else(|| "<undecodable>".to_string()),
+                    e
+                );
+                corrupt.push(CorruptOutput { id, commitment });
+            },
+        }
+    }
+    (loaded, corrupt)
+}
+
 impl OutputManagerBackend for OutputManagerSqliteDatabase {
     #[allow(clippy::cognitive_complexity)]
     #[allow(clippy::too_many_lines)]
@@ -138,7 +207,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         let result = match key {
             DbKey::SpentOutput(k) => match OutputSql::find_status(&k.to_vec(), OutputStatus::Spent, &mut conn) {
@@ -175,7 +244,10 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
             },
             DbKey::AnyOutputByCommitment(commitment) => {
                 match OutputSql::find_by_commitment(&commitment.to_vec(), &mut conn) {
-                    Ok(o) => Some(DbValue::AnyOutput(Box::new(o.to_db_unblinded_output(&cipher)?))),
+                    Ok(o) => self
+                        .load_outputs(vec![o], &cipher)?
+                        .pop()
+                        .map(|o| DbValue::AnyOutput(Box::new(o))),
                     Err(e) => {
                         match e {
                             OutputManagerStorageError::DieselError(DieselError::NotFound) => (),
@@ -201,12 +273,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                     &mut conn,
                 )?;
 
-                Some(DbValue::UnspentOutputs(
-                    outputs
-                        .iter()
-                        .map(|o| o.clone().to_db_unblinded_output(&cipher))
-                        .collect::<Result<Vec<_>, _>>()?,
-                ))
+                Some(DbValue::UnspentOutputs(self.load_outputs(outputs, &cipher)?))
             },
             DbKey::SpentOutputs => {
                 let outputs = OutputSql::index_status(vec![OutputStatus::Spent], &mut conn)?;
@@ -269,7 +336,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
     ) -> Result<Vec<DbUnblindedOutput>, OutputManagerStorageError> {
         let mut conn = self.database_connection.get_pooled_connection()?;
         let outputs = OutputSql::index_by_output_type(output_type, &mut conn)?;
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         outputs
             .iter()
@@ -280,7 +347,18 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
     fn fetch_sorted_unspent_outputs(&self) -> Result<Vec<DbUnblindedOutput>, OutputManagerStorageError> {
         let mut conn = self.database_connection.get_pooled_connection()?;
         let outputs = OutputSql::index_unspent(&mut conn)?;
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
+
+        outputs
+            .into_iter()
+            .map(|o| o.to_db_unblinded_output(&cipher))
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    fn fetch_largest_unspent_outputs(&self, n: usize) -> Result<Vec<DbUnblindedOutput>, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let outputs = OutputSql::index_largest_unspent(n, &mut conn)?;
+        let cipher = self.read_cipher()?;
 
         outputs
             .into_iter()
@@ -293,7 +371,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
         let outputs = OutputSql::index_marked_deleted_in_block_is_null(&mut conn)?;
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         if start.elapsed().as_millis() > 0 {
             trace!(
@@ -305,10 +383,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
             );
         }
 
-        outputs
-            .into_iter()
-            .map(|o| o.to_db_unblinded_output(&cipher))
-            .collect::<Result<Vec<_>, _>>()
+        self.load_outputs(outputs, &cipher)
     }
 
     fn fetch_invalid_outputs(&self, timestamp: i64) -> Result<Vec<DbUnblindedOutput>, OutputManagerStorageError> {
@@ -316,7 +391,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
         let outputs = OutputSql::index_invalid(&NaiveDateTime::from_timestamp_opt(timestamp, 0).unwrap(), &mut conn)?;
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         if start.elapsed().as_millis() > 0 {
             trace!(
@@ -328,10 +403,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
             );
         }
 
-        outputs
-            .into_iter()
-            .map(|o| o.to_db_unblinded_output(&cipher))
-            .collect::<Result<Vec<_>, _>>()
+        self.load_outputs(outputs, &cipher)
     }
 
     fn fetch_unspent_mined_unconfirmed_outputs(&self) -> Result<Vec<DbUnblindedOutput>, OutputManagerStorageError> {
@@ -339,7 +411,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
         let outputs = OutputSql::index_unconfirmed(&mut conn)?;
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         if start.elapsed().as_millis() > 0 {
             trace!(
@@ -351,17 +423,14 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
             );
         }
 
-        outputs
-            .into_iter()
-            .map(|o| o.to_db_unblinded_output(&cipher))
-            .collect::<Result<Vec<_>, _>>()
+        self.load_outputs(outputs, &cipher)
     }
 
     fn write(&self, op: WriteOperation) -> Result<Option<DbValue>, OutputManagerStorageError> {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         let mut msg = "".to_string();
         let result = match op {
@@ -416,7 +485,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         let outputs = OutputSql::index_status(
             vec![
@@ -653,7 +722,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         let mut commitments = Vec::with_capacity(outputs_to_send.len());
         for output in outputs_to_send {
@@ -786,7 +855,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         let output = OutputSql::first_by_mined_height_desc(&mut conn)?;
         if start.elapsed().as_millis() > 0 {
@@ -808,7 +877,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         let output = OutputSql::first_by_marked_deleted_height_desc(&mut conn)?;
         if start.elapsed().as_millis() > 0 {
@@ -826,6 +895,105 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         }
     }
 
+    fn vacuum(&self) -> Result<u64, OutputManagerStorageError> {
+        // Holding the cipher write lock keeps every other output manager database operation out while vacuuming. The
+        // database file is shared with the other wallet services though, so their connections can still hold a
+        // transaction open, in which case sqlite reports the database as busy once the busy timeout has elapsed.
+        let _cipher = acquire_write_lock!(self.cipher);
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let db_path = self.database_connection.db_path();
+
+        // The WAL is checkpointed before measuring so that the measured file sizes include all committed changes
+        conn.batch_execute("PRAGMA wal_checkpoint(TRUNCATE);")
+            .map_err(map_busy_error)?;
+        let size_before = fs::metadata(db_path)?.len();
+        conn.batch_execute("VACUUM; PRAGMA wal_checkpoint(TRUNCATE);")
+            .map_err(map_busy_error)?;
+        let size_after = fs::metadata(db_path)?.len();
+
+        let reclaimed = size_before.saturating_sub(size_after);
+        debug!(
+            target: LOG_TARGET,
+            "Vacuumed output manager database, reclaimed {} bytes ({} -> {})", reclaimed, size_before, size_after
+        );
+        Ok(reclaimed)
+    }
+
+    fn lock(&self) {
+        let mut cipher = acquire_write_lock!(self.cipher);
+        // Replacing the cipher drops the one holding the main key
+        *cipher = XChaCha20Poly1305::new(&Key::default());
+        self.is_locked.store(true, Ordering::SeqCst);
+    }
+
+    fn unlock(&self, cipher: XChaCha20Poly1305) {
+        let mut current = acquire_write_lock!(self.cipher);
+        *current = cipher;
+        self.is_locked.store(false, Ordering::SeqCst);
+    }
+
+    fn rotate_encryption(
+        &self,
+        old_cipher: &XChaCha20Poly1305,
+        new_cipher: XChaCha20Poly1305,
+    ) -> Result<usize, OutputManagerStorageError> {
+        // Holding the cipher write lock keeps every other output manager database operation out while re-encrypting
+        let mut cipher = acquire_write_lock!(self.cipher);
+        if self.is_locked.load(Ordering::SeqCst) {
+            return Err(OutputManagerStorageError::Locked);
+        }
+        let mut conn = self.database_connection.get_pooled_connection()?;
+
+        let num_outputs = conn.transaction::<_, OutputManagerStorageError, _>(|conn| {
+            let outputs = OutputSql::index(conn)?;
+            for output in &outputs {
+                let spending_key = reencrypt_bytes_integral_nonce(
+                    old_cipher,
+                    &new_cipher,
+                    output.domain("spending_key"),
+                    &output.spending_key,
+                )?;
+                let script_private_key = reencrypt_bytes_integral_nonce(
+                    old_cipher,
+                    &new_cipher,
+                    output.domain("script_private_key"),
+                    &output.script_private_key,
+                )?;
+                diesel::update(outputs::table.filter(outputs::id.eq(output.id)))
+                    .set((
+                        outputs::spending_key.eq(&spending_key),
+                        outputs::script_private_key.eq(&script_private_key),
+                    ))
+                    .execute(conn)
+                    .num_rows_affected_or_not_found(1)?;
+            }
+
+            for script in KnownOneSidedPaymentScriptSql::index(conn)? {
+                let private_key = reencrypt_bytes_integral_nonce(
+                    old_cipher,
+                    &new_cipher,
+                    script.domain("private_key"),
+                    &script.private_key,
+                )?;
+                diesel::update(
+                    known_one_sided_payment_scripts::table
+                        .filter(known_one_sided_payment_scripts::script_hash.eq(&script.script_hash)),
+                )
+                .set(known_one_sided_payment_scripts::private_key.eq(&private_key))
+                .execute(conn)
+                .num_rows_affected_or_not_found(1)?;
+            }
+            Ok(outputs.len())
+        })?;
+
+        *cipher = new_cipher;
+        debug!(
+            target: LOG_TARGET,
+            "Re-encrypted {} output(s) with a new cipher", num_outputs
+        );
+        Ok(num_outputs)
+    }
+
     fn get_balance(
         &self,
         current_tip_for_time_lock_calculation: Option<u64>,
@@ -847,6 +1015,43 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         result
     }
 
+    fn fetch_insufficiently_confirmed_unspent_value(
+        &self,
+        output_type: OutputType,
+        min_confirmations: u64,
+        tip: u64,
+    ) -> Result<MicroTari, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        OutputSql::sum_insufficiently_confirmed_unspent(output_type, min_confirmations, tip, &mut conn)
+    }
+
+    fn has_spendable_outputs(&self, tip: Option<u64>) -> Result<bool, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        OutputSql::exists_spendable(tip, &mut conn)
+    }
+
+    fn fetch_max_unspent_mined_height(&self) -> Result<Option<u64>, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        OutputSql::max_unspent_mined_height(&mut conn)
+    }
+
+    fn fetch_unspent_outputs_skipping_corrupt(
+        &self,
+    ) -> Result<(Vec<DbUnblindedOutput>, Vec<CorruptOutput>), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let outputs = OutputSql::index_status(
+            vec![OutputStatus::Unspent, OutputStatus::UnspentMinedUnconfirmed],
+            &mut conn,
+        )?;
+        let cipher = self.read_cipher()?;
+
+        Ok(load_outputs_skipping_corrupt(outputs, &cipher))
+    }
+
+    fn set_skip_corrupt_outputs(&self, skip: bool) {
+        self.skip_corrupt_outputs.store(skip, Ordering::SeqCst);
+    }
+
     fn cancel_pending_transaction(&self, tx_id: TxId) -> Result<(), OutputManagerStorageError> {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
@@ -985,6 +1190,44 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         Ok(())
     }
 
+    fn mark_output_as_spent_externally(
+        &self,
+        commitment: &Commitment,
+        spent_in_tx_id: Option<TxId>,
+    ) -> Result<(), OutputManagerStorageError> {
+        let start = Instant::now();
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+
+        conn.transaction::<_, _, _>(|conn| {
+            let output = OutputSql::find_by_commitment_and_cancelled(&commitment.to_vec(), false, conn)?;
+
+            if OutputStatus::try_from(output.status)? != OutputStatus::Unspent {
+                return Err(OutputManagerStorageError::OutputAlreadySpent);
+            }
+            output.update(
+                UpdateOutput {
+                    status: Some(OutputStatus::SpentMinedUnconfirmed),
+                    spent_in_tx_id: Some(spent_in_tx_id),
+                    ..Default::default()
+                },
+                conn,
+            )?;
+
+            Ok(())
+        })?;
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - mark_output_as_spent_externally: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        Ok(())
+    }
+
     fn set_coinbase_abandoned(&self, tx_id: TxId, abandoned: bool) -> Result<(), OutputManagerStorageError> {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
@@ -1054,7 +1297,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         if OutputSql::find_by_commitment_and_cancelled(&output.commitment.to_vec(), false, &mut conn).is_ok() {
             return Err(OutputManagerStorageError::DuplicateOutput);
@@ -1084,7 +1327,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         let outputs = OutputSql::fetch_unspent_outputs_for_spending(selection_criteria, amount, tip_height, &mut conn)?;
 
@@ -1095,16 +1338,13 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
             (start.elapsed() - acquire_lock).as_millis(),
             start.elapsed().as_millis()
         );
-        outputs
-            .iter()
-            .map(|o| o.clone().to_db_unblinded_output(&cipher))
-            .collect::<Result<Vec<_>, _>>()
+        self.load_outputs(outputs, &cipher)
     }
 
     fn fetch_outputs_by_tx_id(&self, tx_id: TxId) -> Result<Vec<DbUnblindedOutput>, OutputManagerStorageError> {
         let mut conn = self.database_connection.get_pooled_connection()?;
         let outputs = OutputSql::find_by_tx_id(tx_id, &mut conn)?;
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         outputs
             .iter()
@@ -1114,7 +1354,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
 
     fn fetch_outputs_by(&self, q: OutputBackendQuery) -> Result<Vec<DbUnblindedOutput>, OutputManagerStorageError> {
         let mut conn = self.database_connection.get_pooled_connection()?;
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
         Ok(OutputSql::fetch_outputs_by(q, &mut conn)?
             .into_iter()
             .filter_map(|x| {
@@ -1152,6 +1392,33 @@ fn update_outputs_with_tx_id_and_status_to_new_status(
     Ok(())
 }
 
+/// Decrypts the ciphertext with `old_cipher` and encrypts it again with `new_cipher` for the same domain. The
+/// plaintext is zeroized once it has been re-encrypted.
+fn reencrypt_bytes_integral_nonce(
+    old_cipher: &XChaCha20Poly1305,
+    new_cipher: &XChaCha20Poly1305,
+    domain: Vec<u8>,
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, OutputManagerStorageError> {
+    let plaintext = Zeroizing::new(
+        decrypt_bytes_integral_nonce(old_cipher, domain.clone(), ciphertext)
+            .map_err(OutputManagerStorageError::AeadError)?,
+    );
+    encrypt_bytes_integral_nonce(new_cipher, domain, Hidden::hide(plaintext.to_vec()))
+        .map_err(OutputManagerStorageError::AeadError)
+}
+
+/// Sqlite reports `SQLITE_BUSY` and `SQLITE_LOCKED` as "database is locked" and "database table is locked" errors,
+/// which diesel does not map to a dedicated error kind
+fn map_busy_error(err: DieselError) -> OutputManagerStorageError {
+    match err {
+        DieselError::DatabaseError(_, ref info) if info.message().contains("is locked") => {
+            OutputManagerStorageError::DatabaseBusy
+        },
+        err => err.into(),
+    }
+}
+
 /// These are the fields that can be updated for an Output
 #[derive(Clone, Default)]
 pub struct UpdateOutput {
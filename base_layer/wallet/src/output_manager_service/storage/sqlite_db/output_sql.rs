@@ -209,9 +209,16 @@ impl OutputSql {
 
         // NOTE: Safe mode presets `script_lock_height` and `maturity` filters for all queries
         if selection_criteria.mode == UtxoSelectionMode::Safe {
-            query = query
-                .filter(outputs::script_lock_height.le(i64_tip_height))
-                .filter(outputs::maturity.le(i64_tip_height));
+            query = query.filter(outputs::script_lock_height.le(i64_tip_height));
+            query = if selection_criteria.allow_immature_coinbase {
+                query.filter(
+                    outputs::maturity
+                        .le(i64_tip_height)
+                        .or(outputs::output_type.eq(i32::from(OutputType::Coinbase.as_byte()))),
+                )
+            } else {
+                query.filter(outputs::maturity.le(i64_tip_height))
+            };
         };
 
         match &selection_criteria.filter {
@@ -317,6 +324,69 @@ impl OutputSql {
             .load(conn)?)
     }
 
+    /// Return the `limit` unspent outputs with the highest value, ordered from highest to lowest value
+    pub fn index_largest_unspent(
+        limit: usize,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<OutputSql>, OutputManagerStorageError> {
+        Ok(outputs::table
+            .filter(outputs::status.eq(OutputStatus::Unspent as i32))
+            .order((outputs::value.desc(), outputs::id.asc()))
+            .limit(i64::try_from(limit).unwrap_or(i64::MAX))
+            .load(conn)?)
+    }
+
+    /// Return the total value of unspent outputs of the given type that were mined less than `min_confirmations`
+    /// blocks before `tip`
+    pub fn sum_insufficiently_confirmed_unspent(
+        output_type: OutputType,
+        min_confirmations: u64,
+        tip: u64,
+        conn: &mut SqliteConnection,
+    ) -> Result<MicroTari, OutputManagerStorageError> {
+        let values = outputs::table
+            .filter(outputs::status.eq(OutputStatus::Unspent as i32))
+            .filter(outputs::output_type.eq(i32::from(output_type.as_byte())))
+            .filter(outputs::mined_height.gt(tip as i64 - min_confirmations as i64))
+            .select(outputs::value)
+            .load::<i64>(conn)?;
+        Ok(values
+            .into_iter()
+            .fold(MicroTari::zero(), |acc, value| acc + MicroTari::from(value as u64)))
+    }
+
+    /// Returns true if there is at least one unspent standard or coinbase output whose maturity and script lock height
+    /// have been reached at `tip`, i.e. one that default coin selection could spend. The query stops at the first
+    /// match.
+    pub fn exists_spendable(tip: Option<u64>, conn: &mut SqliteConnection) -> Result<bool, OutputManagerStorageError> {
+        let i64_tip_height = tip.and_then(|h| i64::try_from(h).ok()).unwrap_or(i64::MAX);
+        Ok(diesel::select(diesel::dsl::exists(
+            outputs::table
+                .filter(outputs::status.eq(OutputStatus::Unspent as i32))
+                .filter(
+                    outputs::output_type
+                        .eq(i32::from(OutputType::Standard.as_byte()))
+                        .or(outputs::output_type.eq(i32::from(OutputType::Coinbase.as_byte()))),
+                )
+                .filter(outputs::maturity.le(i64_tip_height))
+                .filter(outputs::script_lock_height.le(i64_tip_height)),
+        ))
+        .get_result(conn)?)
+    }
+
+    /// Returns the highest mined height of all unspent outputs, or `None` if none of them have been mined
+    pub fn max_unspent_mined_height(conn: &mut SqliteConnection) -> Result<Option<u64>, OutputManagerStorageError> {
+        let height = outputs::table
+            .filter(
+                outputs::status
+                    .eq(OutputStatus::Unspent as i32)
+                    .or(outputs::status.eq(OutputStatus::UnspentMinedUnconfirmed as i32)),
+            )
+            .select(diesel::dsl::max(outputs::mined_height))
+            .first::<Option<i64>>(conn)?;
+        Ok(height.map(|h| h as u64))
+    }
+
     pub fn index_marked_deleted_in_block_is_null(
         conn: &mut SqliteConnection,
     ) -> Result<Vec<OutputSql>, OutputManagerStorageError> {
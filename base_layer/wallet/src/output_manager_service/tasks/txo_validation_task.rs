@@ -46,6 +46,7 @@ use crate::{
         storage::{
             database::{OutputManagerBackend, OutputManagerDatabase},
             models::DbUnblindedOutput,
+            OutputStatus,
         },
     },
 };
@@ -289,10 +290,12 @@ where
                     );
                 }
 
+                // Outputs marked as spent externally have no deleted height yet, but still need to be reverted
                 if deleted_bitmap_response
                     .not_deleted_positions
                     .contains(&mined_mmr_position) &&
-                    output.marked_deleted_at_height.is_some()
+                    (output.marked_deleted_at_height.is_some() ||
+                        output.status == OutputStatus::SpentMinedUnconfirmed)
                 {
                     self.db
                         .mark_output_as_unspent(output.hash)
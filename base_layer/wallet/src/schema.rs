@@ -29,6 +29,7 @@ diesel::table! {
         mined_timestamp -> Nullable<Timestamp>,
         transaction_signature_nonce -> Binary,
         transaction_signature_key -> Binary,
+        body_pruned -> Integer,
     }
 }
 
@@ -70,6 +71,7 @@ diesel::table! {
         direct_send_success -> Integer,
         send_count -> Integer,
         last_send_timestamp -> Nullable<Timestamp>,
+        priority -> Integer,
     }
 }
 
@@ -123,6 +125,16 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    send_attempts (id) {
+        id -> Integer,
+        tx_id -> BigInt,
+        timestamp -> Timestamp,
+        mechanism -> Integer,
+        succeeded -> Integer,
+    }
+}
+
 diesel::table! {
     wallet_settings (key) {
         key -> Text,
@@ -147,6 +159,7 @@ diesel::allow_tables_to_appear_in_same_query!(
     outbound_transactions,
     outputs,
     scanned_blocks,
+    send_attempts,
     wallet_settings,
     burnt_proofs,
 );
@@ -25,6 +25,7 @@ use std::{
     sync::Arc,
 };
 
+use chacha20poly1305::XChaCha20Poly1305;
 use chrono::NaiveDateTime;
 use log::*;
 use tari_common_types::chain_metadata::ChainMetadata;
@@ -62,6 +63,10 @@ pub trait WalletBackend: Send + Sync + Clone {
 
     /// Change the passphrase used to encrypt the database
     fn change_passphrase(&self, existing: &SafePassword, new: &SafePassword) -> Result<(), WalletStorageError>;
+    /// Discard the in-memory database cipher. Encrypted values cannot be read or written until the database is unlocked
+    fn lock(&self);
+    /// Restore the database cipher from the passphrase, returning it so that the other wallet backends can be unlocked
+    fn unlock(&self, passphrase: &SafePassword) -> Result<XChaCha20Poly1305, WalletStorageError>;
 
     fn create_burnt_proof(
         &self,
@@ -165,6 +170,14 @@ where T: WalletBackend + 'static
         Ok(())
     }
 
+    pub fn lock(&self) {
+        self.db.lock();
+    }
+
+    pub fn unlock(&self, passphrase: &SafePassword) -> Result<XChaCha20Poly1305, WalletStorageError> {
+        self.db.unlock(passphrase)
+    }
+
     pub fn get_master_seed(&self) -> Result<Option<CipherSeed>, WalletStorageError> {
         let c = match self.db.fetch(&DbKey::MasterSeed) {
             Ok(None) => Ok(None),
@@ -24,7 +24,12 @@ use std::{
     convert::TryFrom,
     mem::size_of,
     str::{from_utf8, FromStr},
-    sync::{Arc, RwLock},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+        RwLock,
+        RwLockReadGuard,
+    },
 };
 
 use argon2::password_hash::{
@@ -228,6 +233,7 @@ impl DatabaseEncryptionFields {
 pub struct WalletSqliteDatabase {
     database_connection: WalletDbConnection,
     cipher: Arc<RwLock<XChaCha20Poly1305>>,
+    is_locked: Arc<AtomicBool>,
 }
 impl WalletSqliteDatabase {
     pub fn new(database_connection: WalletDbConnection, passphrase: SafePassword) -> Result<Self, WalletStorageError> {
@@ -236,11 +242,21 @@ impl WalletSqliteDatabase {
         Ok(Self {
             database_connection,
             cipher: Arc::new(RwLock::new(cipher)),
+            is_locked: Arc::new(AtomicBool::new(false)),
         })
     }
 
-    fn set_master_seed(&self, seed: &CipherSeed, conn: &mut SqliteConnection) -> Result<(), WalletStorageError> {
+    /// Acquires the database cipher, failing if the database has been locked
+    fn read_cipher(&self) -> Result<RwLockReadGuard<'_, XChaCha20Poly1305>, WalletStorageError> {
         let cipher = acquire_read_lock!(self.cipher);
+        if self.is_locked.load(Ordering::SeqCst) {
+            return Err(WalletStorageError::Locked);
+        }
+        Ok(cipher)
+    }
+
+    fn set_master_seed(&self, seed: &CipherSeed, conn: &mut SqliteConnection) -> Result<(), WalletStorageError> {
+        let cipher = self.read_cipher()?;
         if WalletSettingSql::get(&DbKey::WalletBirthday, conn)?.is_none() {
             let birthday = seed.birthday();
             WalletSettingSql::new(DbKey::WalletBirthday, birthday.to_string()).set(conn)?;
@@ -256,7 +272,7 @@ impl WalletSqliteDatabase {
     }
 
     fn get_master_seed(&self, conn: &mut SqliteConnection) -> Result<Option<CipherSeed>, WalletStorageError> {
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
         if let Some(seed_str) = WalletSettingSql::get(&DbKey::MasterSeed, conn)? {
             let seed = {
                 // Decrypted_key_bytes contains sensitive data regarding decrypted
@@ -279,7 +295,7 @@ impl WalletSqliteDatabase {
     }
 
     fn decrypt_value<T: Encryptable<XChaCha20Poly1305>>(&self, o: T) -> Result<T, WalletStorageError> {
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
         let o = o
             .decrypt(&cipher)
             .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e)))?;
@@ -288,7 +304,7 @@ impl WalletSqliteDatabase {
 
     #[allow(dead_code)]
     fn encrypt_value<T: Encryptable<XChaCha20Poly1305>>(&self, o: T) -> Result<T, WalletStorageError> {
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
         o.encrypt(&cipher)
             .map_err(|e| WalletStorageError::AeadError(format!("Encryption Error:{}", e)))
     }
@@ -315,7 +331,7 @@ impl WalletSqliteDatabase {
     }
 
     fn set_tor_id(&self, tor: TorIdentity, conn: &mut SqliteConnection) -> Result<(), WalletStorageError> {
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         let bytes =
             Hidden::hide(bincode::serialize(&tor).map_err(|e| WalletStorageError::ConversionError(e.to_string()))?);
@@ -328,7 +344,7 @@ impl WalletSqliteDatabase {
     }
 
     fn get_tor_id(&self, conn: &mut SqliteConnection) -> Result<Option<DbValue>, WalletStorageError> {
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
         if let Some(key_str) = WalletSettingSql::get(&DbKey::TorId, conn)? {
             let id = {
                 // we must zeroize decrypted_key_bytes, as this contains sensitive data,
@@ -367,7 +383,7 @@ impl WalletSqliteDatabase {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
         let kvp_text;
         match kvp {
             DbKeyValuePair::MasterSeed(seed) => {
@@ -576,6 +592,21 @@ impl WalletBackend for WalletSqliteDatabase {
         ScannedBlockSql::clear_before_height(height, exclude_recovered, &mut conn)
     }
 
+    fn lock(&self) {
+        let mut cipher = acquire_write_lock!(self.cipher);
+        // Replacing the cipher drops the one holding the main key
+        *cipher = XChaCha20Poly1305::new(&Key::default());
+        self.is_locked.store(true, Ordering::SeqCst);
+    }
+
+    fn unlock(&self, passphrase: &SafePassword) -> Result<XChaCha20Poly1305, WalletStorageError> {
+        let unlocked = get_db_cipher(&self.database_connection, passphrase)?;
+        let mut cipher = acquire_write_lock!(self.cipher);
+        *cipher = unlocked.clone();
+        self.is_locked.store(false, Ordering::SeqCst);
+        Ok(unlocked)
+    }
+
     fn change_passphrase(&self, existing: &SafePassword, new: &SafePassword) -> Result<(), WalletStorageError> {
         let mut conn = self.database_connection.get_pooled_connection()?;
 
@@ -635,7 +666,7 @@ impl WalletBackend for WalletSqliteDatabase {
         payload: String,
     ) -> Result<(), WalletStorageError> {
         let mut conn = self.database_connection.get_pooled_connection()?;
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         BurntProofSql::new(
             id,
@@ -44,6 +44,7 @@ use crate::{
 pub(crate) mod wallet_db_connection;
 
 const LOG_TARGET: &str = "wallet::storage:sqlite_utilities";
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations");
 
 pub fn run_migration_and_create_sqlite_connection<P: AsRef<Path>>(
     db_path: P,
@@ -51,22 +52,9 @@ pub fn run_migration_and_create_sqlite_connection<P: AsRef<Path>>(
 ) -> Result<WalletDbConnection, WalletStorageError> {
     let file_lock = acquire_exclusive_file_lock(db_path.as_ref())?;
 
-    let path_str = db_path
-        .as_ref()
-        .to_str()
-        .ok_or(WalletStorageError::InvalidUnicodePath)?;
-
-    let mut pool = SqliteConnectionPool::new(
-        String::from(path_str),
-        sqlite_pool_size,
-        true,
-        true,
-        Duration::from_secs(60),
-    );
-    pool.create_pool()?;
+    let pool = create_connection_pool(db_path.as_ref(), sqlite_pool_size)?;
     let mut connection = pool.get_pooled_connection()?;
 
-    const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations");
     connection
         .run_pending_migrations(MIGRATIONS)
         .map_err(|err| WalletStorageError::DatabaseMigrationError(format!("Database migration failed {}", err)))?;
@@ -74,6 +62,42 @@ pub fn run_migration_and_create_sqlite_connection<P: AsRef<Path>>(
     Ok(WalletDbConnection::new(pool, Some(file_lock)))
 }
 
+/// Runs the pending database migrations one at a time, so that a large database can be migrated before it is opened
+/// while reporting progress. Before each migration is applied `callback` is invoked with the migration name, its
+/// index (starting at 1) and the total number of pending migrations.
+pub fn migrate_with_progress<P: AsRef<Path>, F: Fn(&str, usize, usize)>(
+    db_path: P,
+    callback: F,
+) -> Result<(), WalletStorageError> {
+    let _file_lock = acquire_exclusive_file_lock(db_path.as_ref())?;
+
+    let pool = create_connection_pool(db_path.as_ref(), 1)?;
+    let mut connection = pool.get_pooled_connection()?;
+
+    let pending = connection.pending_migrations(MIGRATIONS).map_err(|err| {
+        WalletStorageError::DatabaseMigrationError(format!("Could not determine pending migrations {}", err))
+    })?;
+    let total = pending.len();
+    for (i, migration) in pending.iter().enumerate() {
+        let name = migration.name().to_string();
+        debug!(target: LOG_TARGET, "Running migration {} ({}/{})", name, i + 1, total);
+        callback(&name, i + 1, total);
+        connection.run_migration(&**migration).map_err(|err| {
+            WalletStorageError::DatabaseMigrationError(format!("Database migration {} failed {}", name, err))
+        })?;
+    }
+
+    Ok(())
+}
+
+fn create_connection_pool(db_path: &Path, pool_size: usize) -> Result<SqliteConnectionPool, WalletStorageError> {
+    let path_str = db_path.to_str().ok_or(WalletStorageError::InvalidUnicodePath)?;
+
+    let mut pool = SqliteConnectionPool::new(String::from(path_str), pool_size, true, true, Duration::from_secs(60));
+    pool.create_pool()?;
+    Ok(pool)
+}
+
 pub fn acquire_exclusive_file_lock(db_path: &Path) -> Result<File, WalletStorageError> {
     let lock_file_path = match db_path.file_name() {
         None => {
@@ -144,25 +168,52 @@ pub fn initialize_sqlite_database_backends<P: AsRef<Path>>(
 }
 
 pub fn get_last_version<P: AsRef<Path>>(db_path: P) -> Result<Option<String>, WalletStorageError> {
-    let path_str = db_path
-        .as_ref()
-        .to_str()
-        .ok_or(WalletStorageError::InvalidUnicodePath)?;
-
-    let mut pool = SqliteConnectionPool::new(String::from(path_str), 1, true, true, Duration::from_secs(60));
-    pool.create_pool()?;
+    let pool = create_connection_pool(db_path.as_ref(), 1)?;
 
     WalletSettingSql::get(&DbKey::LastAccessedVersion, pool.get_pooled_connection()?.deref_mut())
 }
 
 pub fn get_last_network<P: AsRef<Path>>(db_path: P) -> Result<Option<String>, WalletStorageError> {
-    let path_str = db_path
-        .as_ref()
-        .to_str()
-        .ok_or(WalletStorageError::InvalidUnicodePath)?;
-
-    let mut pool = SqliteConnectionPool::new(String::from(path_str), 1, true, true, Duration::from_secs(60));
-    pool.create_pool()?;
+    let pool = create_connection_pool(db_path.as_ref(), 1)?;
 
     WalletSettingSql::get(&DbKey::LastAccessedNetwork, pool.get_pooled_connection()?.deref_mut())
 }
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, fs};
+
+    use tari_test_utils::random::string;
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn it_reports_progress_for_each_pending_migration() {
+        let db_tempdir = tempdir().unwrap();
+        let db_path = db_tempdir.path().join(format!("{}.sqlite3", string(8).as_str()));
+        let num_migrations = fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations"))
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().path().is_dir())
+            .count();
+
+        let calls = RefCell::new(Vec::new());
+        migrate_with_progress(&db_path, |name, index, total| {
+            calls.borrow_mut().push((name.to_string(), index, total))
+        })
+        .unwrap();
+        let calls = calls.into_inner();
+        assert_eq!(calls.len(), num_migrations);
+        for (i, (name, index, total)) in calls.iter().enumerate() {
+            assert!(!name.is_empty());
+            assert_eq!(*index, i + 1);
+            assert_eq!(*total, num_migrations);
+        }
+
+        // Nothing is left to run and the database can be opened as usual
+        let calls = RefCell::new(0);
+        migrate_with_progress(&db_path, |_, _, _| *calls.borrow_mut() += 1).unwrap();
+        assert_eq!(calls.into_inner(), 0);
+        assert!(run_migration_and_create_sqlite_connection(&db_path, 1).is_ok());
+    }
+}
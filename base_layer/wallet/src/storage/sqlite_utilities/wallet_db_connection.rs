@@ -44,6 +44,11 @@ impl WalletDbConnection {
             _file_lock: Arc::new(file_lock),
         }
     }
+
+    /// Returns the path of the wallet database file
+    pub fn db_path(&self) -> &str {
+        self.pool.db_path()
+    }
 }
 
 impl PooledDbConnection for WalletDbConnection {
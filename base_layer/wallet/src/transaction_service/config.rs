@@ -25,6 +25,7 @@ use std::{fmt, time::Duration};
 use log::*;
 use serde::{Deserialize, Serialize};
 use tari_common::configuration::serializers;
+use tari_core::transactions::tari_amount::MicroTari;
 
 const LOG_TARGET: &str = "wallet::transaction_service::config";
 
@@ -70,6 +71,38 @@ pub struct TransactionServiceConfig {
     /// This is the timeout period that will be used to re-submit transactions not found in the mempool
     #[serde(with = "serializers::seconds")]
     pub transaction_mempool_resubmission_window: Duration,
+    /// The maximum number of outbound transactions that can be waiting for a reply from the recipient at the same
+    /// time. New sends are rejected until one of them completes or is cancelled.
+    pub max_pending_outbound: usize,
+    /// When restarting queued transactions, those with a lower priority than the highest pending priority are started
+    /// one after another with this interval between them, after the highest priority transactions have started.
+    #[serde(with = "serializers::seconds")]
+    pub low_priority_restart_interval: Duration,
+    /// Outbound transactions that are still queued this long after they were created, without the recipient ever
+    /// having been reached, are reported as stuck with a `TransactionStuck` event
+    #[serde(with = "serializers::seconds")]
+    pub queued_stuck_after: Duration,
+    /// When set, the stored body of a confirmed transaction is dropped once it has this many confirmations, keeping
+    /// only its metadata
+    pub prune_confirmed_tx_bodies_after: Option<u64>,
+    /// If true, every attempt at sending a transaction to the recipient is recorded with its routing mechanism and
+    /// result, to help diagnose delivery problems
+    pub record_send_attempts: bool,
+    /// If true, all queued and pending outbound transactions are cancelled during a graceful shutdown so that their
+    /// encumbered outputs are released instead of showing as locked funds after a restart
+    pub cancel_pending_on_shutdown: bool,
+    /// If set, inbound transactions with an amount below this value are rejected with a cancellation reply
+    pub min_accept_amount: Option<MicroTari>,
+    /// If set, inbound transactions with an amount above this value are rejected with a cancellation reply
+    pub max_accept_amount: Option<MicroTari>,
+}
+
+impl TransactionServiceConfig {
+    /// Returns true if an inbound transaction of this amount is within the configured acceptance range
+    pub fn accepts_inbound_amount(&self, amount: MicroTari) -> bool {
+        self.min_accept_amount.map_or(true, |min| amount >= min) &&
+            self.max_accept_amount.map_or(true, |max| amount <= max)
+    }
 }
 
 impl Default for TransactionServiceConfig {
@@ -88,6 +121,14 @@ impl Default for TransactionServiceConfig {
             transaction_routing_mechanism: TransactionRoutingMechanism::default(),
             transaction_event_channel_size: 1000,
             transaction_mempool_resubmission_window: Duration::from_secs(600),
+            max_pending_outbound: 10_000,
+            low_priority_restart_interval: Duration::from_secs(1),
+            queued_stuck_after: Duration::from_secs(86_400), // 1 Day
+            prune_confirmed_tx_bodies_after: None,
+            record_send_attempts: false,
+            cancel_pending_on_shutdown: false,
+            min_accept_amount: None,
+            max_accept_amount: None,
         }
     }
 }
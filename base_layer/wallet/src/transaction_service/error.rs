@@ -57,6 +57,8 @@ pub enum TransactionServiceError {
     InvalidStateError,
     #[error("Transaction is sending to a network different than ours")]
     InvalidNetwork,
+    #[error("The maximum number of pending outbound transactions ({0}) has been reached")]
+    TooManyPendingTransactions(usize),
     #[error("One-sided transaction error: `{0}`")]
     OneSidedTransactionError(String),
     #[error("Transaction Protocol Error: `{0}`")]
@@ -97,6 +99,8 @@ pub enum TransactionServiceError {
     InvalidCompletedTransaction,
     #[error("Attempted to broadcast a coinbase transaction. TxId `{0}`")]
     AttemptedToBroadcastCoinbaseTransaction(TxId),
+    #[error("The body of the transaction was pruned after it was confirmed. TxId `{0}`")]
+    TransactionBodyPruned(TxId),
     #[error("No Base Node public keys are provided for Base chain broadcast and monitoring")]
     NoBaseNodeKeysProvided,
     #[error("Base node changed during {task_name}")]
@@ -111,6 +115,8 @@ pub enum TransactionServiceError {
     UnexpectedBaseNodeResponse,
     #[error("The current transaction has been cancelled")]
     TransactionCancelled,
+    #[error("The finalized transaction does not match the transaction that was sent: {0}")]
+    FinalizationMismatch(String),
     #[error("Chain tip has moved beyond this coinbase before it was mined so it must be cancelled")]
     ChainTipHigherThanCoinbaseHeight,
     #[error("DHT outbound error: `{0}`")]
@@ -184,6 +190,8 @@ pub enum TransactionServiceError {
     CommitmentSignatureError(#[from] CommitmentSignatureError),
     #[error("Invalid data: `{0}`")]
     RangeProofError(#[from] RangeProofError),
+    #[error("Transaction is not queued for sending: TxId `{0}`")]
+    TransactionNotQueued(TxId),
 }
 
 #[derive(Debug, Error)]
@@ -238,6 +246,8 @@ pub enum TransactionStorageError {
     AlreadyEncrypted,
     #[error("Aead error: `{0}`")]
     AeadError(String),
+    #[error("The transaction database is locked")]
+    Locked,
     #[error("Transaction (TxId: '{0}') is not mined")]
     TransactionNotMined(TxId),
     #[error("Conversion error: `{0}`")]
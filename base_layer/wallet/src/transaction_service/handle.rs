@@ -25,6 +25,7 @@ use std::{
     fmt,
     fmt::{Display, Formatter},
     sync::Arc,
+    time::Duration,
 };
 
 use chrono::NaiveDateTime;
@@ -32,7 +33,7 @@ use tari_common_types::{
     burnt_proof::BurntProof,
     tari_address::TariAddress,
     transaction::{ImportStatus, TxId},
-    types::{PublicKey, Signature},
+    types::{Commitment, PublicKey, Signature},
 };
 use tari_comms::types::CommsPublicKey;
 use tari_core::{
@@ -44,6 +45,7 @@ use tari_core::{
     },
 };
 use tari_service_framework::reply_channel::SenderService;
+use tari_utilities::hex::Hex;
 use tokio::sync::broadcast;
 use tower::Service;
 
@@ -55,6 +57,8 @@ use crate::{
             CompletedTransaction,
             InboundTransaction,
             OutboundTransaction,
+            PendingImpact,
+            SendAttempt,
             TxCancellationReason,
             WalletTransaction,
         },
@@ -73,6 +77,7 @@ pub enum TransactionServiceRequest {
     GetCancelledPendingOutboundTransactions,
     GetCancelledCompletedTransactions,
     GetCompletedTransaction(TxId),
+    VerifyCompletedTransactionOutputs(TxId),
     GetAnyTransaction(TxId),
     SendTransaction {
         destination: TariAddress,
@@ -138,12 +143,19 @@ pub enum TransactionServiceRequest {
     RestartBroadcastProtocols,
     GetNumConfirmationsRequired,
     SetNumConfirmationsRequired(u64),
+    SetPendingOutboundTransactionPriority(TxId, u8),
     ValidateTransactions,
     ReValidateTransactions,
     /// Returns the fee per gram estimates for the next {count} blocks.
     GetFeePerGramStatsPerBlock {
         count: usize,
     },
+    GetSendAttempts(TxId),
+    GetTransactionsForCommitment(Commitment),
+    GetTransactionsWithAddress(TariAddress),
+    EstimateDeliveryConfidence(TariAddress),
+    GetPendingTransactionImpacts,
+    EstimateSendEta(TxId),
 }
 
 impl fmt::Display for TransactionServiceRequest {
@@ -156,6 +168,7 @@ impl fmt::Display for TransactionServiceRequest {
             Self::GetCancelledPendingOutboundTransactions => write!(f, "GetCancelledPendingOutboundTransactions"),
             Self::GetCancelledCompletedTransactions => write!(f, "GetCancelledCompletedTransactions"),
             Self::GetCompletedTransaction(t) => write!(f, "GetCompletedTransaction({})", t),
+            Self::VerifyCompletedTransactionOutputs(t) => write!(f, "VerifyCompletedTransactionOutputs({})", t),
             Self::SendTransaction {
                 destination,
                 amount,
@@ -223,12 +236,25 @@ impl fmt::Display for TransactionServiceRequest {
             Self::RestartBroadcastProtocols => write!(f, "RestartBroadcastProtocols"),
             Self::GetNumConfirmationsRequired => write!(f, "GetNumConfirmationsRequired"),
             Self::SetNumConfirmationsRequired(_) => write!(f, "SetNumConfirmationsRequired"),
+            Self::SetPendingOutboundTransactionPriority(tx_id, priority) => write!(
+                f,
+                "SetPendingOutboundTransactionPriority(tx_id: {}, priority: {})",
+                tx_id, priority
+            ),
             Self::GetAnyTransaction(t) => write!(f, "GetAnyTransaction({})", t),
             Self::ValidateTransactions => write!(f, "ValidateTransactions"),
             Self::ReValidateTransactions => write!(f, "ReValidateTransactions"),
             Self::GetFeePerGramStatsPerBlock { count } => {
                 write!(f, "GetFeePerGramEstimatesPerBlock(count: {})", count,)
             },
+            Self::GetSendAttempts(tx_id) => write!(f, "GetSendAttempts({})", tx_id),
+            Self::GetTransactionsForCommitment(commitment) => {
+                write!(f, "GetTransactionsForCommitment({})", commitment.to_hex())
+            },
+            Self::GetTransactionsWithAddress(address) => write!(f, "GetTransactionsWithAddress({})", address),
+            Self::EstimateDeliveryConfidence(address) => write!(f, "EstimateDeliveryConfidence({})", address),
+            Self::GetPendingTransactionImpacts => write!(f, "GetPendingTransactionImpacts"),
+            Self::EstimateSendEta(tx_id) => write!(f, "EstimateSendEta({})", tx_id),
         }
     }
 }
@@ -243,6 +269,7 @@ pub enum TransactionServiceResponse {
     PendingOutboundTransactions(HashMap<TxId, OutboundTransaction>),
     CompletedTransactions(HashMap<TxId, CompletedTransaction>),
     CompletedTransaction(Box<CompletedTransaction>),
+    CompletedTransactionOutputsVerified(bool),
     BaseNodePublicKeySet,
     UtxoImported(TxId),
     TransactionSubmitted,
@@ -253,10 +280,17 @@ pub enum TransactionServiceResponse {
     AnyTransaction(Box<Option<WalletTransaction>>),
     NumConfirmationsRequired(u64),
     NumConfirmationsSet,
+    PendingOutboundTransactionPrioritySet,
     ValidationStarted(OperationId),
     CompletedTransactionValidityChanged,
     ShaAtomicSwapTransactionSent(Box<(TxId, PublicKey, TransactionOutput)>),
     FeePerGramStatsPerBlock(FeePerGramStatsResponse),
+    SendAttempts(Vec<SendAttempt>),
+    TransactionsForCommitment(Vec<CompletedTransaction>),
+    TransactionsWithAddress(Vec<CompletedTransaction>),
+    DeliveryConfidence(f64),
+    PendingTransactionImpacts(Vec<PendingImpact>),
+    SendEta(Option<Duration>),
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, Default)]
@@ -284,8 +318,15 @@ pub enum TransactionEvent {
     ReceivedTransaction(TxId),
     ReceivedTransactionReply(TxId),
     ReceivedFinalizedTransaction(TxId),
+    /// An inbound transaction was rejected because its amount is outside of the configured acceptance range
+    InboundRejected {
+        tx_id: TxId,
+        amount: MicroTari,
+    },
     TransactionDiscoveryInProgress(TxId),
     TransactionSendResult(TxId, TransactionSendStatus),
+    /// An outbound transaction has remained queued for longer than the configured `queued_stuck_after` window
+    TransactionStuck(TxId),
     TransactionCompletedImmediately(TxId),
     TransactionCancelled(TxId, TxCancellationReason),
     TransactionBroadcast(TxId),
@@ -330,6 +371,12 @@ impl fmt::Display for TransactionEvent {
             TransactionEvent::ReceivedFinalizedTransaction(tx) => {
                 write!(f, "ReceivedFinalizedTransaction for {tx}")
             },
+            TransactionEvent::InboundRejected { tx_id, amount } => {
+                write!(f, "InboundRejected for {tx_id} with amount {amount}")
+            },
+            TransactionEvent::TransactionStuck(tx) => {
+                write!(f, "TransactionStuck for {tx}")
+            },
             TransactionEvent::TransactionDiscoveryInProgress(tx) => {
                 write!(f, "TransactionDiscoveryInProgress for {tx}")
             },
@@ -669,6 +716,29 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Returns the fee-per-gram actually paid by the completed transaction with the given `tx_id`
+    pub async fn get_transaction_effective_fee_per_gram(
+        &mut self,
+        tx_id: TxId,
+    ) -> Result<MicroTari, TransactionServiceError> {
+        let transaction = self.get_completed_transaction(tx_id).await?;
+        Ok(transaction.effective_fee_per_gram())
+    }
+
+    /// Re-derives the commitments of the wallet's own outputs in the completed transaction from the wallet's keys and
+    /// returns true if they all match outputs in the stored transaction body. Returns false if the transaction has no
+    /// outputs belonging to the wallet.
+    pub async fn verify_completed_transaction_outputs(&mut self, tx_id: TxId) -> Result<bool, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::VerifyCompletedTransactionOutputs(tx_id))
+            .await??
+        {
+            TransactionServiceResponse::CompletedTransactionOutputsVerified(verified) => Ok(verified),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn get_any_transaction(
         &mut self,
         tx_id: TxId,
@@ -784,6 +854,25 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Set the priority of a pending outbound transaction. Queued transactions with a higher priority are restarted
+    /// before those with a lower priority.
+    pub async fn set_pending_outbound_transaction_priority(
+        &mut self,
+        tx_id: TxId,
+        priority: u8,
+    ) -> Result<(), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SetPendingOutboundTransactionPriority(
+                tx_id, priority,
+            ))
+            .await??
+        {
+            TransactionServiceResponse::PendingOutboundTransactionPrioritySet => Ok(()),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn generate_coinbase_transaction(
         &mut self,
         reward: MicroTari,
@@ -880,4 +969,94 @@ impl TransactionServiceHandle {
             _ => Err(TransactionServiceError::UnexpectedApiResponse),
         }
     }
+
+    /// Returns the recorded attempts at sending the transaction to its counterparty, oldest first. Attempts are only
+    /// recorded when `record_send_attempts` is enabled in the transaction service config.
+    pub async fn get_send_attempts(&mut self, tx_id: TxId) -> Result<Vec<SendAttempt>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetSendAttempts(tx_id))
+            .await??
+        {
+            TransactionServiceResponse::SendAttempts(attempts) => Ok(attempts),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the completed transactions that created or spent an output with the given commitment, oldest first.
+    /// Compact inputs don't reference the commitment of the output they spend, so they can't be matched, and neither
+    /// can transactions whose body was pruned after they were confirmed.
+    pub async fn get_transactions_for_commitment(
+        &mut self,
+        commitment: Commitment,
+    ) -> Result<Vec<CompletedTransaction>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetTransactionsForCommitment(commitment))
+            .await??
+        {
+            TransactionServiceResponse::TransactionsForCommitment(transactions) => Ok(transactions),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the completed transactions that were sent to or received from the given address, oldest first
+    pub async fn get_transactions_with_address(
+        &mut self,
+        address: &TariAddress,
+    ) -> Result<Vec<CompletedTransaction>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetTransactionsWithAddress(address.clone()))
+            .await??
+        {
+            TransactionServiceResponse::TransactionsWithAddress(transactions) => Ok(transactions),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns a heuristic between 0 and 1 of how likely a transaction sent to the given address is to be delivered,
+    /// being the share of transactions sent to that address that were delivered directly or replied to. Returns 0.5 if
+    /// there is no send history for the address, which requires `record_send_attempts` to be enabled in the
+    /// transaction service config.
+    pub async fn estimate_delivery_confidence(
+        &mut self,
+        address: &TariAddress,
+    ) -> Result<f64, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::EstimateDeliveryConfidence(address.clone()))
+            .await??
+        {
+            TransactionServiceResponse::DeliveryConfidence(confidence) => Ok(confidence),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the balance impact of each pending inbound and outbound transaction and of each completed transaction
+    /// that is not yet mined but still holds encumbered outputs, ordered by tx_id
+    pub async fn get_pending_transaction_impacts(&mut self) -> Result<Vec<PendingImpact>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetPendingTransactionImpacts)
+            .await??
+        {
+            TransactionServiceResponse::PendingTransactionImpacts(impacts) => Ok(impacts),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Estimates how long until the next send attempt of the given queued outbound transaction, based on the configured
+    /// resend period and when it was last sent. Returns `None` while the wallet is offline, as the next attempt is only
+    /// made once it is back online. Fails if the transaction is not queued.
+    pub async fn estimate_send_eta(&mut self, tx_id: TxId) -> Result<Option<Duration>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::EstimateSendEta(tx_id))
+            .await??
+        {
+            TransactionServiceResponse::SendEta(eta) => Ok(eta),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
 }
@@ -38,7 +38,7 @@ use tari_core::{
     covenants::Covenant,
     transactions::{
         tari_amount::MicroTari,
-        transaction_components::OutputFeatures,
+        transaction_components::{OutputFeatures, Transaction, TransactionKernel, TransactionKernelVersion},
         transaction_protocol::{
             proto::protocol as proto,
             recipient::RecipientSignedMessage,
@@ -65,7 +65,13 @@ use crate::{
         service::{TransactionSendResult, TransactionServiceResources},
         storage::{
             database::TransactionBackend,
-            models::{CompletedTransaction, OutboundTransaction, TxCancellationReason},
+            models::{
+                CompletedTransaction,
+                OutboundTransaction,
+                SendAttempt,
+                SendAttemptMechanism,
+                TxCancellationReason,
+            },
         },
         tasks::{
             send_finalized_transaction::send_finalized_transaction_message,
@@ -537,9 +543,23 @@ where
             TransactionServiceProtocolError::new(self.id, TransactionServiceError::TransactionCancelled)
         })?;
 
+        // The recipient reply carries its own copy of the transaction metadata, so remember what was sent to check
+        // the reply and the finalized transaction against it
+        let sent_message = outbound_tx
+            .sender_protocol
+            .get_single_round_message()
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+        verify_recipient_reply(&sent_message, &recipient_reply).map_err(|e| {
+            error!(
+                target: LOG_TARGET,
+                "Recipient reply for Transaction (TxId: {}) does not match what was sent: {}", self.id, e,
+            );
+            TransactionServiceProtocolError::new(self.id, e)
+        })?;
+
         outbound_tx
             .sender_protocol
-            .add_single_recipient_info(recipient_reply)
+            .add_single_recipient_info(recipient_reply.clone())
             .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
 
         outbound_tx.sender_protocol.finalize().map_err(|e| {
@@ -554,6 +574,13 @@ where
             .sender_protocol
             .get_transaction()
             .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+        verify_finalized_kernel(tx, &sent_message, &recipient_reply).map_err(|e| {
+            error!(
+                target: LOG_TARGET,
+                "Finalized Transaction (TxId: {}) does not match what was sent: {}", self.id, e,
+            );
+            TransactionServiceProtocolError::new(self.id, e)
+        })?;
 
         let completed_transaction = CompletedTransaction::new(
             tx_id,
@@ -638,9 +665,43 @@ where
             },
         };
 
+        if self.resources.config.record_send_attempts {
+            self.record_send_attempts(&result);
+        }
+
         Ok(result)
     }
 
+    /// Persist the outcome of each routing mechanism that was used for a send. A failure to record is logged and does
+    /// not affect the send.
+    fn record_send_attempts(&self, result: &SendResult) {
+        let routing = self.resources.config.transaction_routing_mechanism;
+        let timestamp = Utc::now().naive_utc();
+        let mut attempts = Vec::with_capacity(2);
+        if routing != TransactionRoutingMechanism::StoreAndForwardOnly {
+            attempts.push((SendAttemptMechanism::Direct, result.direct_send_result));
+        }
+        if routing != TransactionRoutingMechanism::DirectOnly {
+            attempts.push((
+                SendAttemptMechanism::StoreAndForward,
+                result.store_and_forward_send_result,
+            ));
+        }
+        for (mechanism, succeeded) in attempts {
+            if let Err(e) = self.resources.db.add_send_attempt(SendAttempt {
+                tx_id: self.id,
+                timestamp,
+                mechanism,
+                succeeded,
+            }) {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not record {} send attempt for TxId {}: {}", mechanism, self.id, e
+                );
+            }
+        }
+    }
+
     /// Attempt to send the transaction to the recipient both directly and via Store-and-forward. If both fail to send
     /// the transaction will be cancelled.
     /// # Argumentswallet_sync_with_base_node
@@ -940,3 +1001,83 @@ struct SendResult {
     store_and_forward_send_result: bool,
     transaction_status: TransactionStatus,
 }
+
+/// Checks that the recipient reply is for the transaction that was sent, carries the same metadata and that the
+/// recipient's partial signature is valid for the combined public excess and nonce over that metadata
+fn verify_recipient_reply(
+    sent: &SingleRoundSenderData,
+    reply: &RecipientSignedMessage,
+) -> Result<(), TransactionServiceError> {
+    if reply.tx_id != sent.tx_id {
+        return Err(TransactionServiceError::FinalizationMismatch(format!(
+            "recipient reply is for TxId {}, expected {}",
+            reply.tx_id, sent.tx_id
+        )));
+    }
+    if reply.tx_metadata != sent.metadata {
+        return Err(TransactionServiceError::FinalizationMismatch(
+            "recipient reply metadata does not match".to_string(),
+        ));
+    }
+    let challenge = TransactionKernel::build_kernel_challenge_from_tx_meta(
+        &TransactionKernelVersion::get_current_version(),
+        &(&sent.public_nonce + reply.partial_signature.get_public_nonce()),
+        &(&sent.public_excess + &reply.public_spend_key),
+        &sent.metadata,
+    );
+    if !reply
+        .partial_signature
+        .verify_challenge(&reply.public_spend_key, &challenge)
+    {
+        return Err(TransactionServiceError::FinalizationMismatch(
+            "recipient partial signature is invalid".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that the finalized transaction has a single kernel built from the sender's and the recipient's public
+/// excess and nonce, committing to the metadata that was sent to the recipient
+fn verify_finalized_kernel(
+    tx: &Transaction,
+    sent: &SingleRoundSenderData,
+    reply: &RecipientSignedMessage,
+) -> Result<(), TransactionServiceError> {
+    let kernels = tx.body().kernels();
+    if kernels.len() != 1 {
+        return Err(TransactionServiceError::FinalizationMismatch(format!(
+            "expected a single kernel, found {}",
+            kernels.len()
+        )));
+    }
+    let kernel = &kernels[0];
+    if *kernel.excess.as_public_key() != &sent.public_excess + &reply.public_spend_key {
+        return Err(TransactionServiceError::FinalizationMismatch(
+            "kernel excess does not match the combined public excess".to_string(),
+        ));
+    }
+    if *kernel.excess_sig.get_public_nonce() != &sent.public_nonce + reply.partial_signature.get_public_nonce() {
+        return Err(TransactionServiceError::FinalizationMismatch(
+            "kernel signature nonce does not match the combined public nonce".to_string(),
+        ));
+    }
+    let expected = &sent.metadata;
+    if kernel.fee != expected.fee {
+        return Err(TransactionServiceError::FinalizationMismatch(format!(
+            "kernel fee is {}, expected {}",
+            kernel.fee, expected.fee
+        )));
+    }
+    if kernel.lock_height != expected.lock_height {
+        return Err(TransactionServiceError::FinalizationMismatch(format!(
+            "kernel lock height is {}, expected {}",
+            kernel.lock_height, expected.lock_height
+        )));
+    }
+    if kernel.features != expected.kernel_features || kernel.burn_commitment != expected.burn_commitment {
+        return Err(TransactionServiceError::FinalizationMismatch(
+            "kernel features do not match".to_string(),
+        ));
+    }
+    Ok(())
+}
@@ -253,6 +253,15 @@ where
                         .unwrap_or_else(|| "{No Kernel found}".to_string()),
                     self.operation_id
                 );
+                if last_mined_transaction.body_pruned {
+                    warn!(
+                        target: LOG_TARGET,
+                        "The body of transaction {} was pruned after it was confirmed, so it can't be rebroadcast if \
+                         it is not mined again (Operation ID: {})",
+                        last_mined_transaction.tx_id,
+                        self.operation_id
+                    );
+                }
                 self.update_transaction_as_unmined(last_mined_transaction.tx_id, &last_mined_transaction.status)
                     .await?;
                 self.publish_event(TransactionEvent::TransactionValidationStateChanged(op_id));
@@ -37,7 +37,7 @@ use tari_common_types::{
     burnt_proof::BurntProof,
     tari_address::TariAddress,
     transaction::{ImportStatus, TransactionDirection, TransactionStatus, TxId},
-    types::{CommitmentFactory, PrivateKey, PublicKey, Signature},
+    types::{Commitment, CommitmentFactory, PrivateKey, PublicKey, Signature},
 };
 use tari_comms::types::{CommsDHKE, CommsPublicKey};
 use tari_comms_dht::outbound::OutboundMessageRequester;
@@ -80,14 +80,16 @@ use tari_shutdown::ShutdownSignal;
 use tokio::{
     sync::{mpsc, mpsc::Sender, oneshot, Mutex},
     task::JoinHandle,
+    time::sleep,
 };
 
 use crate::{
     base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle},
-    connectivity_service::WalletConnectivityInterface,
+    connectivity_service::{OnlineStatus, WalletConnectivityInterface},
     output_manager_service::{
+        error::{OutputManagerError, OutputManagerStorageError},
         handle::{OutputManagerEvent, OutputManagerHandle},
-        storage::models::SpendingPriority,
+        storage::{models::SpendingPriority, OutputStatus},
         UtxoSelectionCriteria,
     },
     storage::database::{WalletBackend, WalletDatabase},
@@ -109,7 +111,7 @@ use crate::{
         },
         storage::{
             database::{TransactionBackend, TransactionDatabase},
-            models::{CompletedTransaction, TxCancellationReason},
+            models::{CompletedTransaction, PendingImpact, SendAttemptMechanism, TxCancellationReason},
         },
         tasks::{
             check_faux_transaction_status::check_faux_transactions,
@@ -135,6 +137,11 @@ use crate::{
 };
 
 const LOG_TARGET: &str = "wallet::transaction_service::service";
+/// Wallet key-value store key holding the ids of transactions that were cancelled on shutdown without releasing their
+/// outputs
+const UNRELEASED_CANCELLED_TXS_KEY: &str = "unreleased_cancelled_txs";
+/// The delivery confidence reported for an address without any recorded send attempts
+const DEFAULT_DELIVERY_CONFIDENCE: f64 = 0.5;
 
 /// TransactionService allows for the management of multiple inbound and outbound transaction protocols
 /// which are uniquely identified by a tx_id. The TransactionService generates and accepts the various protocol
@@ -178,6 +185,7 @@ pub struct TransactionService<
     finalized_transaction_senders: HashMap<TxId, Sender<(TariAddress, TxId, Transaction)>>,
     receiver_transaction_cancellation_senders: HashMap<TxId, oneshot::Sender<()>>,
     active_transaction_broadcast_protocols: HashSet<TxId>,
+    queued_transaction_watchers: HashMap<TxId, JoinHandle<()>>,
     timeout_update_watch: Watch<Duration>,
     wallet_db: WalletDatabase<TWalletBackend>,
     base_node_service: BaseNodeServiceHandle,
@@ -277,6 +285,7 @@ where
             finalized_transaction_senders: HashMap::new(),
             receiver_transaction_cancellation_senders: HashMap::new(),
             active_transaction_broadcast_protocols: HashSet::new(),
+            queued_transaction_watchers: HashMap::new(),
             timeout_update_watch,
             base_node_service,
             wallet_db,
@@ -345,12 +354,15 @@ where
         let mut base_node_service_event_stream = self.base_node_service.get_event_stream();
         let mut output_manager_event_stream = self.output_manager_service.get_event_stream();
 
+        // Also done when the option has since been turned off, so that no outputs stay encumbered
+        self.release_outputs_of_cancelled_pending_outbound_transactions().await;
+
         debug!(target: LOG_TARGET, "Transaction Service started");
         loop {
             tokio::select! {
                 event = output_manager_event_stream.recv() => {
                     match event {
-                        Ok(msg) => self.handle_output_manager_service_event(msg).await,
+                        Ok(msg) => self.handle_output_manager_service_event(msg, &mut transaction_broadcast_protocol_handles).await,
                         Err(e) => debug!(target: LOG_TARGET, "Lagging read on base node event broadcast channel: {}", e),
                     };
                 },
@@ -552,6 +564,9 @@ where
                 }
                  _ = shutdown.wait() => {
                     info!(target: LOG_TARGET, "Transaction service shutting down because it received the shutdown signal");
+                    if self.resources.config.cancel_pending_on_shutdown {
+                        self.cancel_pending_outbound_transactions_on_shutdown().await;
+                    }
                     break;
                 }
             }
@@ -735,6 +750,10 @@ where
             TransactionServiceRequest::GetCompletedTransaction(tx_id) => Ok(
                 TransactionServiceResponse::CompletedTransaction(Box::new(self.db.get_completed_transaction(tx_id)?)),
             ),
+            TransactionServiceRequest::VerifyCompletedTransactionOutputs(tx_id) => self
+                .verify_completed_transaction_outputs(tx_id)
+                .await
+                .map(TransactionServiceResponse::CompletedTransactionOutputsVerified),
             TransactionServiceRequest::GetAnyTransaction(tx_id) => Ok(TransactionServiceResponse::AnyTransaction(
                 Box::new(self.db.get_any_transaction(tx_id)?),
             )),
@@ -797,6 +816,10 @@ where
                 self.resources.config.num_confirmations_required = number;
                 Ok(TransactionServiceResponse::NumConfirmationsSet)
             },
+            TransactionServiceRequest::SetPendingOutboundTransactionPriority(tx_id, priority) => {
+                self.db.set_outbound_transaction_priority(tx_id, priority)?;
+                Ok(TransactionServiceResponse::PendingOutboundTransactionPrioritySet)
+            },
             TransactionServiceRequest::ValidateTransactions => self
                 .start_transaction_validation_protocol(transaction_validation_join_handles)
                 .await
@@ -810,6 +833,28 @@ where
                 self.handle_get_fee_per_gram_stats_per_block_request(count, reply_channel);
                 return Ok(());
             },
+            TransactionServiceRequest::GetSendAttempts(tx_id) => Ok(TransactionServiceResponse::SendAttempts(
+                self.db.fetch_send_attempts(tx_id)?,
+            )),
+            TransactionServiceRequest::GetTransactionsForCommitment(commitment) => {
+                Ok(TransactionServiceResponse::TransactionsForCommitment(
+                    self.fetch_transactions_for_commitment(&commitment)?,
+                ))
+            },
+            TransactionServiceRequest::GetTransactionsWithAddress(address) => {
+                Ok(TransactionServiceResponse::TransactionsWithAddress(
+                    self.db.fetch_completed_transactions_with_address(&address)?,
+                ))
+            },
+            TransactionServiceRequest::EstimateDeliveryConfidence(address) => Ok(
+                TransactionServiceResponse::DeliveryConfidence(self.estimate_delivery_confidence(&address)?),
+            ),
+            TransactionServiceRequest::GetPendingTransactionImpacts => Ok(
+                TransactionServiceResponse::PendingTransactionImpacts(self.get_pending_transaction_impacts().await?),
+            ),
+            TransactionServiceRequest::EstimateSendEta(tx_id) => {
+                Ok(TransactionServiceResponse::SendEta(self.estimate_send_eta(tx_id)?))
+            },
         };
 
         // If the individual handlers did not already send the API response then do it here.
@@ -822,6 +867,177 @@ where
         Ok(())
     }
 
+    fn fetch_transactions_for_commitment(
+        &self,
+        commitment: &Commitment,
+    ) -> Result<Vec<CompletedTransaction>, TransactionServiceError> {
+        let (pruned, completed): (Vec<_>, Vec<_>) = self
+            .db
+            .get_completed_transactions()?
+            .into_values()
+            .partition(|tx| tx.body_pruned);
+        if !pruned.is_empty() {
+            debug!(
+                target: LOG_TARGET,
+                "{} completed transaction(s) with pruned bodies can't be matched against the commitment",
+                pruned.len()
+            );
+        }
+        let mut transactions = completed
+            .into_iter()
+            .filter(|tx| {
+                let body = &tx.transaction.body;
+                body.outputs().iter().any(|output| output.commitment == *commitment) ||
+                    body.inputs()
+                        .iter()
+                        .any(|input| input.commitment().map_or(false, |c| c == commitment))
+            })
+            .collect::<Vec<_>>();
+        transactions.sort_by_key(|tx| tx.timestamp);
+        Ok(transactions)
+    }
+
+    /// The share of outbound transactions to the address that are known to have reached the recipient, out of those
+    /// with recorded send attempts. Each transaction counts once. A transaction reached the recipient if one of its
+    /// direct sends succeeded or the recipient replied to it, i.e. it was completed. A successful store and forward
+    /// send only means the message was stored, so it is not evidence of delivery on its own.
+    fn estimate_delivery_confidence(&self, address: &TariAddress) -> Result<f64, TransactionServiceError> {
+        // Maps each transaction to whether the recipient is known to have replied
+        let mut txs = HashMap::new();
+        for tx in self
+            .db
+            .get_pending_outbound_transactions()?
+            .into_values()
+            .chain(self.db.get_cancelled_pending_outbound_transactions()?.into_values())
+        {
+            if tx.destination_address == *address {
+                txs.insert(tx.tx_id, false);
+            }
+        }
+        for tx in self.db.fetch_completed_transactions_with_address(address)? {
+            if tx.destination_address == *address && tx.direction == TransactionDirection::Outbound {
+                txs.insert(tx.tx_id, true);
+            }
+        }
+
+        let mut attempted = 0usize;
+        let mut delivered = 0usize;
+        for (tx_id, replied) in txs {
+            let attempts = self.db.fetch_send_attempts(tx_id)?;
+            if attempts.is_empty() {
+                continue;
+            }
+            attempted += 1;
+            if replied ||
+                attempts
+                    .iter()
+                    .any(|a| a.succeeded && a.mechanism == SendAttemptMechanism::Direct)
+            {
+                delivered += 1;
+            }
+        }
+
+        if attempted == 0 {
+            return Ok(DEFAULT_DELIVERY_CONFIDENCE);
+        }
+        Ok(delivered as f64 / attempted as f64)
+    }
+
+    /// The effect of every in-flight transaction on the wallet balance: the pending inbound and outbound transactions
+    /// as well as the completed, broadcast and unmined ones that still hold encumbered outputs, sorted by transaction
+    /// id.
+    async fn get_pending_transaction_impacts(&mut self) -> Result<Vec<PendingImpact>, TransactionServiceError> {
+        let mut impacts = Vec::new();
+        for tx in self.db.get_pending_outbound_transactions()?.into_values() {
+            let (encumbered_inputs, encumbered_outputs) = self.fetch_encumbered_commitments(tx.tx_id).await?;
+            impacts.push(PendingImpact {
+                tx_id: tx.tx_id,
+                direction: TransactionDirection::Outbound,
+                amount: tx.amount,
+                fee: tx.fee,
+                encumbered_inputs,
+                encumbered_outputs,
+            });
+        }
+        for tx in self.db.get_pending_inbound_transactions()?.into_values() {
+            let (encumbered_inputs, encumbered_outputs) = self.fetch_encumbered_commitments(tx.tx_id).await?;
+            impacts.push(PendingImpact {
+                tx_id: tx.tx_id,
+                direction: TransactionDirection::Inbound,
+                amount: tx.amount,
+                fee: MicroTari::from(0),
+                encumbered_inputs,
+                encumbered_outputs,
+            });
+        }
+        for info in self.db.fetch_unconfirmed_transactions_info()? {
+            // Once mined the outputs are no longer encumbered, they are only waiting for confirmations
+            if info.status == TransactionStatus::MinedUnconfirmed {
+                continue;
+            }
+            let (encumbered_inputs, encumbered_outputs) = self.fetch_encumbered_commitments(info.tx_id).await?;
+            if encumbered_inputs.is_empty() && encumbered_outputs.is_empty() {
+                continue;
+            }
+            let tx = self.db.get_completed_transaction(info.tx_id)?;
+            let fee = if tx.direction == TransactionDirection::Outbound {
+                tx.fee
+            } else {
+                MicroTari::from(0)
+            };
+            impacts.push(PendingImpact {
+                tx_id: tx.tx_id,
+                direction: tx.direction,
+                amount: tx.amount,
+                fee,
+                encumbered_inputs,
+                encumbered_outputs,
+            });
+        }
+        impacts.sort_by_key(|impact| impact.tx_id.as_u64());
+        Ok(impacts)
+    }
+
+    /// The commitments of the wallet outputs encumbered by the transaction, split into the outputs it spends and the
+    /// outputs it creates
+    async fn fetch_encumbered_commitments(
+        &mut self,
+        tx_id: TxId,
+    ) -> Result<(Vec<Commitment>, Vec<Commitment>), TransactionServiceError> {
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
+        for output in self
+            .output_manager_service
+            .get_encumbered_outputs_by_tx_id(tx_id)
+            .await?
+        {
+            match output.status {
+                OutputStatus::EncumberedToBeSpent | OutputStatus::ShortTermEncumberedToBeSpent => {
+                    inputs.push(output.commitment)
+                },
+                _ => outputs.push(output.commitment),
+            }
+        }
+        Ok((inputs, outputs))
+    }
+
+    fn estimate_send_eta(&mut self, tx_id: TxId) -> Result<Option<Duration>, TransactionServiceError> {
+        let tx = self.db.get_pending_outbound_transaction(tx_id)?;
+        if tx.status != TransactionStatus::Queued {
+            return Err(TransactionServiceError::TransactionNotQueued(tx_id));
+        }
+        // While offline there is no telling when the wallet comes back online and the next attempt is made
+        if self.resources.connectivity.get_connectivity_status() == OnlineStatus::Offline {
+            return Ok(None);
+        }
+        let last_sent = tx.last_send_timestamp.unwrap_or(tx.timestamp);
+        // A send timestamp in the future, e.g. due to clock skew, is treated as having just been sent
+        let elapsed = utc_duration_since(&last_sent).unwrap_or_default();
+        Ok(Some(
+            self.resources.config.transaction_resend_period.saturating_sub(elapsed),
+        ))
+    }
+
     fn handle_get_fee_per_gram_stats_per_block_request(
         &self,
         count: usize,
@@ -885,11 +1101,90 @@ where
                     });
 
                 self.last_seen_tip_height = Some(height);
+                self.prune_confirmed_transaction_bodies(height);
             },
         }
     }
 
-    async fn handle_output_manager_service_event(&mut self, event: Arc<OutputManagerEvent>) {
+    /// Drop the stored bodies of confirmed transactions that are buried deep enough below the tip, if configured
+    fn prune_confirmed_transaction_bodies(&self, tip_height: u64) {
+        let max_mined_height = match self
+            .resources
+            .config
+            .prune_confirmed_tx_bodies_after
+            .and_then(|confirmations| tip_height.checked_sub(confirmations))
+        {
+            Some(height) => height,
+            None => return,
+        };
+        match self.db.prune_confirmed_transaction_bodies(max_mined_height) {
+            Ok(pruned) if !pruned.is_empty() => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Pruned the bodies of {} confirmed transactions mined at or below height {}",
+                    pruned.len(),
+                    max_mined_height
+                );
+            },
+            Ok(_) => {},
+            Err(e) => warn!(
+                target: LOG_TARGET,
+                "Error pruning confirmed transaction bodies: {:?}", e
+            ),
+        }
+    }
+
+    async fn handle_output_manager_service_event(
+        &mut self,
+        event: Arc<OutputManagerEvent>,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) {
+        if let OutputManagerEvent::AutoConsolidationTransactionCreated {
+            tx_id,
+            transaction,
+            amount,
+        } = (*event).clone()
+        {
+            let fee = transaction.body.get_total_fee();
+            if let Err(e) = self.submit_transaction_to_self(
+                transaction_broadcast_join_handles,
+                tx_id,
+                *transaction,
+                fee,
+                amount,
+                "Automatic coin consolidation".to_string(),
+            ) {
+                error!(
+                    target: LOG_TARGET,
+                    "Could not submit auto consolidation transaction (TxId: {}): {:?}", tx_id, e
+                );
+            }
+            return;
+        }
+        if let OutputManagerEvent::CoinbaseSplitTransactionCreated {
+            tx_id,
+            transaction,
+            amount,
+        } = (*event).clone()
+        {
+            let fee = transaction.body.get_total_fee();
+            if let Err(e) = self.submit_transaction_to_self(
+                transaction_broadcast_join_handles,
+                tx_id,
+                *transaction,
+                fee,
+                amount,
+                "Automatic coinbase split".to_string(),
+            ) {
+                error!(
+                    target: LOG_TARGET,
+                    "Could not submit coinbase split transaction (TxId: {}): {:?}", tx_id, e
+                );
+            }
+            return;
+        }
         if let OutputManagerEvent::TxoValidationSuccess(_) = (*event).clone() {
             let db = self.db.clone();
             let output_manager_handle = self.output_manager_service.clone();
@@ -990,6 +1285,25 @@ where
             return Ok(());
         }
 
+        let max_pending_outbound = self.resources.config.max_pending_outbound;
+        if self.pending_transaction_reply_senders.len() >= max_pending_outbound {
+            warn!(
+                target: LOG_TARGET,
+                "Rejecting transaction send, {} outbound transactions are already pending", max_pending_outbound
+            );
+            let _result = reply_channel
+                .send(Err(TransactionServiceError::TooManyPendingTransactions(
+                    max_pending_outbound,
+                )))
+                .map_err(|e| {
+                    warn!(target: LOG_TARGET, "Failed to send service reply");
+                    e
+                });
+            return Err(TransactionServiceError::TooManyPendingTransactions(
+                max_pending_outbound,
+            ));
+        }
+
         let (tx_reply_sender, tx_reply_receiver) = mpsc::channel(100);
         let (cancellation_sender, cancellation_receiver) = oneshot::channel();
         self.pending_transaction_reply_senders.insert(tx_id, tx_reply_sender);
@@ -1752,6 +2066,7 @@ where
                 if val.transaction_status != TransactionStatus::Queued {
                     let _sender = self.pending_transaction_reply_senders.remove(&val.tx_id);
                     let _sender = self.send_transaction_cancellation_senders.remove(&val.tx_id);
+                    self.stop_queued_transaction_watcher(val.tx_id);
                     let completed_tx = match self.db.get_completed_transaction(val.tx_id) {
                         Ok(v) => v,
                         Err(e) => {
@@ -1778,12 +2093,14 @@ where
                         "Send Transaction Protocol for TxId: {} not completed successfully, transaction Queued",
                         val.tx_id
                     );
+                    self.watch_queued_transaction(val.tx_id);
                 } else {
                 }
             },
             Err(TransactionServiceProtocolError { id, error }) => {
                 let _public_key = self.pending_transaction_reply_senders.remove(&id);
                 let _result = self.send_transaction_cancellation_senders.remove(&id);
+                self.stop_queued_transaction_watcher(id);
                 if let TransactionServiceError::Shutdown = error {
                     return;
                 }
@@ -1798,6 +2115,57 @@ where
         }
     }
 
+    /// Spawns a task that publishes a `TransactionStuck` event if the transaction is still queued once the
+    /// `queued_stuck_after` window, measured from when the transaction was created, has passed. Only one watcher is
+    /// kept per transaction, so restarting the send protocol of a queued transaction does not report it again.
+    fn watch_queued_transaction(&mut self, tx_id: TxId) {
+        if self.queued_transaction_watchers.contains_key(&tx_id) {
+            return;
+        }
+        let outbound_tx = match self.db.get_pending_outbound_transaction(tx_id) {
+            Ok(tx) => tx,
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not watch queued Outbound Transaction TxId: {} for being stuck: {:?}", tx_id, e
+                );
+                return;
+            },
+        };
+        // A timestamp in the future is treated as the transaction having just been created
+        let elapsed_time = utc_duration_since(&outbound_tx.timestamp).unwrap_or_default();
+        let remaining = self
+            .resources
+            .config
+            .queued_stuck_after
+            .checked_sub(elapsed_time)
+            .unwrap_or_default();
+        let db = self.db.clone();
+        let event_publisher = self.event_publisher.clone();
+        let mut shutdown = self.resources.shutdown_signal.clone();
+        let watcher = tokio::spawn(async move {
+            tokio::select! {
+                _ = sleep(remaining) => {},
+                _ = shutdown.wait() => return,
+            }
+            // The transaction may have progressed, been cancelled or completed in the meantime
+            if matches!(db.get_pending_outbound_transaction(tx_id), Ok(tx) if tx.status == TransactionStatus::Queued) {
+                warn!(
+                    target: LOG_TARGET,
+                    "Outbound Transaction TxId: {} is still queued, marking it as stuck", tx_id
+                );
+                let _size = event_publisher.send(Arc::new(TransactionEvent::TransactionStuck(tx_id)));
+            }
+        });
+        self.queued_transaction_watchers.insert(tx_id, watcher);
+    }
+
+    fn stop_queued_transaction_watcher(&mut self, tx_id: TxId) {
+        if let Some(watcher) = self.queued_transaction_watchers.remove(&tx_id) {
+            watcher.abort();
+        }
+    }
+
     /// Cancel a pending transaction
     async fn cancel_pending_transaction(&mut self, tx_id: TxId) -> Result<(), TransactionServiceError> {
         self.db.cancel_pending_transaction(tx_id).map_err(|e| {
@@ -1814,6 +2182,7 @@ where
             let _result = cancellation_sender.send(());
         }
         let _public_key = self.pending_transaction_reply_senders.remove(&tx_id);
+        self.stop_queued_transaction_watcher(tx_id);
 
         if let Some(cancellation_sender) = self.receiver_transaction_cancellation_senders.remove(&tx_id) {
             let _result = cancellation_sender.send(());
@@ -1840,6 +2209,136 @@ where
         Ok(())
     }
 
+    /// Cancels all queued and pending outbound transactions when the service shuts down. The Output Manager Service
+    /// shuts down on the same signal and may no longer answer, so the ids of transactions whose outputs could not be
+    /// released are stored and released by `release_outputs_of_cancelled_pending_outbound_transactions` on the next
+    /// startup.
+    async fn cancel_pending_outbound_transactions_on_shutdown(&mut self) {
+        let pending_txs = match self.db.get_pending_outbound_transactions() {
+            Ok(txs) => txs,
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not fetch pending outbound transactions to cancel on shutdown: {:?}", e
+                );
+                return;
+            },
+        };
+        let mut unreleased = Vec::new();
+        for tx_id in pending_txs.into_keys() {
+            match self.cancel_pending_transaction(tx_id).await {
+                Ok(_) => info!(
+                    target: LOG_TARGET,
+                    "Cancelled pending outbound transaction (TxId: {}) on shutdown", tx_id
+                ),
+                Err(TransactionServiceError::OutputManagerError(e)) => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Cancelled pending outbound transaction (TxId: {}) on shutdown, its outputs will be released \
+                         on the next startup: {:?}",
+                        tx_id,
+                        e
+                    );
+                    unreleased.push(tx_id.to_string());
+                },
+                Err(e) => warn!(
+                    target: LOG_TARGET,
+                    "Could not cancel pending outbound transaction (TxId: {}) on shutdown: {:?}", tx_id, e
+                ),
+            }
+        }
+        if unreleased.is_empty() {
+            return;
+        }
+        if let Ok(Some(stored)) = self
+            .wallet_db
+            .get_client_key_value(UNRELEASED_CANCELLED_TXS_KEY.to_string())
+        {
+            unreleased.extend(stored.split(',').map(str::to_string));
+        }
+        if let Err(e) = self
+            .wallet_db
+            .set_client_key_value(UNRELEASED_CANCELLED_TXS_KEY.to_string(), unreleased.join(","))
+        {
+            warn!(
+                target: LOG_TARGET,
+                "Could not store the transactions whose outputs must be released on startup: {:?}", e
+            );
+        }
+    }
+
+    /// Releases the outputs of the transactions that `cancel_pending_outbound_transactions_on_shutdown` cancelled after
+    /// the Output Manager Service had stopped. Only those transactions are visited, and the stored list is cleared
+    /// once they have all been released.
+    async fn release_outputs_of_cancelled_pending_outbound_transactions(&mut self) {
+        let stored = match self
+            .wallet_db
+            .get_client_key_value(UNRELEASED_CANCELLED_TXS_KEY.to_string())
+        {
+            Ok(Some(stored)) => stored,
+            Ok(None) => return,
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not fetch the transactions whose outputs must be released: {:?}", e
+                );
+                return;
+            },
+        };
+        let mut unreleased = Vec::new();
+        for tx_id in stored
+            .split(',')
+            .filter_map(|id| id.parse::<u64>().ok())
+            .map(TxId::from)
+        {
+            if let Err(e) = self.output_manager_service.cancel_transaction(tx_id).await {
+                match e {
+                    // The outputs were already released
+                    OutputManagerError::OutputManagerStorageError(OutputManagerStorageError::ValueNotFound) => {},
+                    e => {
+                        warn!(
+                            target: LOG_TARGET,
+                            "Could not release the outputs of cancelled transaction (TxId: {}): {:?}", tx_id, e
+                        );
+                        unreleased.push(tx_id.to_string());
+                    },
+                }
+            }
+        }
+        let result = if unreleased.is_empty() {
+            self.wallet_db
+                .clear_client_value(UNRELEASED_CANCELLED_TXS_KEY.to_string())
+                .map(|_| ())
+        } else {
+            self.wallet_db
+                .set_client_key_value(UNRELEASED_CANCELLED_TXS_KEY.to_string(), unreleased.join(","))
+        };
+        if let Err(e) = result {
+            warn!(
+                target: LOG_TARGET,
+                "Could not update the transactions whose outputs must be released: {:?}", e
+            );
+        }
+    }
+
+    /// Checks that the wallet's own outputs in a completed transaction, re-derived from the wallet's keys, are present
+    /// in the stored transaction body
+    async fn verify_completed_transaction_outputs(&mut self, tx_id: TxId) -> Result<bool, TransactionServiceError> {
+        let completed_tx = self.db.get_completed_transaction(tx_id)?;
+        let commitments = completed_tx
+            .transaction
+            .body
+            .outputs()
+            .iter()
+            .map(|output| output.commitment.clone())
+            .collect();
+        let verified = self
+            .output_manager_service
+            .verify_transaction_outputs(tx_id, commitments)
+            .await?;
+        Ok(verified)
+    }
+
     /// Handle a Transaction Cancelled message received from the Comms layer
     pub async fn handle_transaction_cancelled_message(
         &mut self,
@@ -1872,7 +2371,16 @@ where
             JoinHandle<Result<TransactionSendResult, TransactionServiceProtocolError<TxId>>>,
         >,
     ) -> Result<(), TransactionServiceError> {
-        let outbound_txs = self.db.get_pending_outbound_transactions()?;
+        // Restart the highest priority transactions first, oldest first within the same priority
+        let mut outbound_txs = self
+            .db
+            .get_pending_outbound_transactions()?
+            .into_iter()
+            .collect::<Vec<_>>();
+        outbound_txs.sort_by(|(_, a), (_, b)| b.priority.cmp(&a.priority).then(a.timestamp.cmp(&b.timestamp)));
+        let highest_priority = outbound_txs.first().map(|(_, tx)| tx.priority).unwrap_or_default();
+        let mut num_throttled = 0u32;
+
         for (tx_id, tx) in outbound_txs {
             let (sender_protocol, stage) = if tx.send_count > 0 {
                 (None, TransactionSendProtocolStage::WaitForReply)
@@ -1900,6 +2408,13 @@ where
             }
 
             if not_yet_pending || queued {
+                // Lower priority transactions are started one at a time after the highest priority ones
+                let restart_delay = if tx.priority < highest_priority {
+                    num_throttled += 1;
+                    self.resources.config.low_priority_restart_interval * num_throttled
+                } else {
+                    Duration::ZERO
+                };
                 let (tx_reply_sender, tx_reply_receiver) = mpsc::channel(100);
                 let (cancellation_sender, cancellation_receiver) = oneshot::channel();
                 self.pending_transaction_reply_senders.insert(tx_id, tx_reply_sender);
@@ -1921,7 +2436,14 @@ where
                     sender_protocol,
                 );
 
-                let join_handle = tokio::spawn(protocol.execute());
+                let join_handle = if restart_delay.is_zero() {
+                    tokio::spawn(protocol.execute())
+                } else {
+                    tokio::spawn(async move {
+                        sleep(restart_delay).await;
+                        protocol.execute().await
+                    })
+                };
                 join_handles.push(join_handle);
             }
         }
@@ -2033,6 +2555,32 @@ where
                 return Err(TransactionServiceError::RepeatedMessageError);
             }
 
+            if !self.resources.config.accepts_inbound_amount(data.amount) {
+                info!(
+                    target: LOG_TARGET,
+                    "Transaction (TxId: {}) from {} rejected, the amount {} is outside of the accepted range",
+                    data.tx_id,
+                    source_pubkey,
+                    data.amount
+                );
+                tokio::spawn(send_transaction_cancelled_message(
+                    data.tx_id,
+                    source_pubkey,
+                    self.resources.outbound_message_service.clone(),
+                ));
+                let _size = self
+                    .event_publisher
+                    .send(Arc::new(TransactionEvent::InboundRejected {
+                        tx_id: data.tx_id,
+                        amount: data.amount,
+                    }))
+                    .map_err(|e| {
+                        trace!(target: LOG_TARGET, "Error sending event due to no subscribers: {:?}", e);
+                        e
+                    });
+                return Ok(());
+            }
+
             let (tx_finalized_sender, tx_finalized_receiver) = mpsc::channel(100);
             let (cancellation_sender, cancellation_receiver) = oneshot::channel();
             self.finalized_transaction_senders
@@ -2412,6 +2960,9 @@ where
         join_handles: &mut FuturesUnordered<JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>>,
     ) -> Result<(), TransactionServiceError> {
         let tx_id = completed_tx.tx_id;
+        if completed_tx.body_pruned {
+            return Err(TransactionServiceError::TransactionBodyPruned(tx_id));
+        }
         if !(completed_tx.status == TransactionStatus::Completed ||
             completed_tx.status == TransactionStatus::Broadcast ||
             completed_tx.status == TransactionStatus::MinedUnconfirmed) ||
@@ -2462,6 +3013,16 @@ where
         );
         let txn_list = self.db.get_transactions_to_be_broadcast()?;
         for completed_txn in txn_list {
+            // A pruned transaction can only be waiting to be broadcast again if it was reorged out after its body was
+            // dropped, in which case there is nothing left to broadcast
+            if completed_txn.body_pruned {
+                warn!(
+                    target: LOG_TARGET,
+                    "Not rebroadcasting transaction {} because its body was pruned after it was confirmed",
+                    completed_txn.tx_id
+                );
+                continue;
+            }
             self.broadcast_completed_transaction(completed_txn, join_handles)?;
         }
 
@@ -28,6 +28,7 @@ use std::{
     sync::Arc,
 };
 
+use chacha20poly1305::XChaCha20Poly1305;
 use chrono::{NaiveDateTime, Utc};
 use log::*;
 use tari_common_types::{
@@ -44,6 +45,7 @@ use crate::transaction_service::{
             CompletedTransaction,
             InboundTransaction,
             OutboundTransaction,
+            SendAttempt,
             TxCancellationReason,
             WalletTransaction,
         },
@@ -58,6 +60,10 @@ const LOG_TARGET: &str = "wallet::transaction_service::database";
 /// required to be supported by the backends then these enums can be updated to reflect this requirement and the trait
 /// will remain the same
 pub trait TransactionBackend: Send + Sync + Clone {
+    /// Discard the in-memory database cipher. Encrypted values cannot be read or written until the database is unlocked
+    fn lock(&self);
+    /// Restore the database cipher after the database was locked
+    fn unlock(&self, cipher: XChaCha20Poly1305);
     /// Retrieve the record associated with the provided DbKey
     fn fetch(&self, key: &DbKey) -> Result<Option<DbValue>, TransactionStorageError>;
 
@@ -115,6 +121,8 @@ pub trait TransactionBackend: Send + Sync + Clone {
     ) -> Result<TariAddress, TransactionStorageError>;
     /// Mark a pending transaction direct send attempt as a success
     fn mark_direct_send_success(&self, tx_id: TxId) -> Result<(), TransactionStorageError>;
+    /// Set the priority with which a pending outbound transaction is restarted
+    fn set_outbound_transaction_priority(&self, tx_id: TxId, priority: u8) -> Result<(), TransactionStorageError>;
     /// Cancel coinbase transactions at a specific block height
     fn cancel_coinbase_transactions_at_block_height(&self, block_height: u64) -> Result<(), TransactionStorageError>;
     /// Find coinbase transaction at a specific block height for a given amount
@@ -155,6 +163,18 @@ pub trait TransactionBackend: Send + Sync + Clone {
         height: u64,
     ) -> Result<Vec<CompletedTransaction>, TransactionStorageError>;
     fn abandon_coinbase_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError>;
+    /// Drop the stored body of confirmed transactions mined at or below `max_mined_height`, keeping their metadata.
+    /// Returns the ids of the transactions that were pruned
+    fn prune_confirmed_transaction_bodies(&self, max_mined_height: u64) -> Result<Vec<TxId>, TransactionStorageError>;
+    /// Record an attempt at sending a transaction message to the counterparty
+    fn add_send_attempt(&self, attempt: SendAttempt) -> Result<(), TransactionStorageError>;
+    /// Fetch the recorded send attempts of a transaction, oldest first
+    fn fetch_send_attempts(&self, tx_id: TxId) -> Result<Vec<SendAttempt>, TransactionStorageError>;
+    /// Fetch the completed transactions that were sent to or received from the given address, oldest first
+    fn fetch_completed_transactions_with_address(
+        &self,
+        address: &TariAddress,
+    ) -> Result<Vec<CompletedTransaction>, TransactionStorageError>;
 }
 
 #[derive(Clone, PartialEq)]
@@ -636,6 +656,10 @@ where T: TransactionBackend + 'static
         self.db.mark_direct_send_success(tx_id)
     }
 
+    pub fn set_outbound_transaction_priority(&self, tx_id: TxId, priority: u8) -> Result<(), TransactionStorageError> {
+        self.db.set_outbound_transaction_priority(tx_id, priority)
+    }
+
     /// Indicated that the specified completed transaction has been broadcast into the mempool
     pub fn broadcast_completed_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
         self.db.broadcast_completed_transaction(tx_id)
@@ -745,6 +769,28 @@ where T: TransactionBackend + 'static
     pub fn abandon_coinbase_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
         self.db.abandon_coinbase_transaction(tx_id)
     }
+
+    pub fn prune_confirmed_transaction_bodies(
+        &self,
+        max_mined_height: u64,
+    ) -> Result<Vec<TxId>, TransactionStorageError> {
+        self.db.prune_confirmed_transaction_bodies(max_mined_height)
+    }
+
+    pub fn add_send_attempt(&self, attempt: SendAttempt) -> Result<(), TransactionStorageError> {
+        self.db.add_send_attempt(attempt)
+    }
+
+    pub fn fetch_send_attempts(&self, tx_id: TxId) -> Result<Vec<SendAttempt>, TransactionStorageError> {
+        self.db.fetch_send_attempts(tx_id)
+    }
+
+    pub fn fetch_completed_transactions_with_address(
+        &self,
+        address: &TariAddress,
+    ) -> Result<Vec<CompletedTransaction>, TransactionStorageError> {
+        self.db.fetch_completed_transactions_with_address(address)
+    }
 }
 
 impl Display for DbKey {
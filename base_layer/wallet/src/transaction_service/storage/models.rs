@@ -30,11 +30,12 @@ use serde::{Deserialize, Serialize};
 use tari_common_types::{
     tari_address::TariAddress,
     transaction::{TransactionConversionError, TransactionDirection, TransactionStatus, TxId},
-    types::{BlockHash, PrivateKey, Signature},
+    types::{BlockHash, Commitment, PrivateKey, Signature},
 };
 use tari_core::transactions::{
     tari_amount::MicroTari,
     transaction_components::Transaction,
+    weight::TransactionWeight,
     ReceiverTransactionProtocol,
     SenderTransactionProtocol,
 };
@@ -94,6 +95,8 @@ pub struct OutboundTransaction {
     pub direct_send_success: bool,
     pub send_count: u32,
     pub last_send_timestamp: Option<NaiveDateTime>,
+    /// Transactions with a higher priority are restarted before those with a lower priority
+    pub priority: u8,
 }
 
 impl OutboundTransaction {
@@ -121,6 +124,7 @@ impl OutboundTransaction {
             direct_send_success,
             send_count: 0,
             last_send_timestamp: None,
+            priority: 0,
         }
     }
 }
@@ -146,6 +150,9 @@ pub struct CompletedTransaction {
     pub mined_height: Option<u64>,
     pub mined_in_block: Option<BlockHash>,
     pub mined_timestamp: Option<NaiveDateTime>,
+    /// Set once the transaction body has been dropped after being deeply confirmed
+    #[serde(default)]
+    pub body_pruned: bool,
 }
 
 impl CompletedTransaction {
@@ -189,6 +196,7 @@ impl CompletedTransaction {
             mined_height,
             mined_in_block: None,
             mined_timestamp,
+            body_pruned: false,
         }
     }
 
@@ -199,6 +207,16 @@ impl CompletedTransaction {
             false
         }
     }
+
+    /// Returns the fee-per-gram actually paid by this transaction, i.e. the fee divided by the weight of the stored
+    /// transaction body. Returns zero if the transaction body is empty.
+    pub fn effective_fee_per_gram(&self) -> MicroTari {
+        let weight = self.transaction.calculate_weight(&TransactionWeight::latest());
+        if weight == 0 {
+            return MicroTari::zero();
+        }
+        self.fee / weight
+    }
 }
 
 impl From<CompletedTransaction> for InboundTransaction {
@@ -234,6 +252,7 @@ impl From<CompletedTransaction> for OutboundTransaction {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            priority: 0,
         }
     }
 }
@@ -277,6 +296,7 @@ impl From<OutboundTransaction> for CompletedTransaction {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            body_pruned: false,
         }
     }
 }
@@ -307,6 +327,7 @@ impl From<InboundTransaction> for CompletedTransaction {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            body_pruned: false,
         }
     }
 }
@@ -376,3 +397,56 @@ impl Display for TxCancellationReason {
         fmt.write_str(response)
     }
 }
+
+/// The message routing mechanism used for a send attempt
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SendAttemptMechanism {
+    Direct,          // 0
+    StoreAndForward, // 1
+}
+
+impl TryFrom<u32> for SendAttemptMechanism {
+    type Error = TransactionConversionError;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(SendAttemptMechanism::Direct),
+            1 => Ok(SendAttemptMechanism::StoreAndForward),
+            code => Err(TransactionConversionError { code: code as i32 }),
+        }
+    }
+}
+
+impl Display for SendAttemptMechanism {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        let response = match self {
+            SendAttemptMechanism::Direct => "Direct",
+            SendAttemptMechanism::StoreAndForward => "Store and Forward",
+        };
+        fmt.write_str(response)
+    }
+}
+
+/// A single attempt at sending a transaction message to the counterparty
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SendAttempt {
+    pub tx_id: TxId,
+    pub timestamp: NaiveDateTime,
+    pub mechanism: SendAttemptMechanism,
+    pub succeeded: bool,
+}
+
+/// The effect a single in-flight transaction has on the wallet balance until it is mined or cancelled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingImpact {
+    pub tx_id: TxId,
+    pub direction: TransactionDirection,
+    pub amount: MicroTari,
+    /// The fee is paid by the sender, so this is zero for inbound transactions
+    pub fee: MicroTari,
+    /// The commitments of the wallet outputs encumbered to be spent by this transaction, i.e. its inputs
+    pub encumbered_inputs: Vec<Commitment>,
+    /// The commitments of the wallet outputs encumbered to be received by this transaction, i.e. the received output
+    /// or the change
+    pub encumbered_outputs: Vec<Commitment>,
+}
@@ -24,10 +24,15 @@ use std::{
     collections::HashMap,
     convert::{TryFrom, TryInto},
     str::from_utf8,
-    sync::{Arc, RwLock},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+        RwLock,
+        RwLockReadGuard,
+    },
 };
 
-use chacha20poly1305::XChaCha20Poly1305;
+use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
 use chrono::{NaiveDateTime, Utc};
 use diesel::{prelude::*, result::Error as DieselError, SqliteConnection};
 use log::*;
@@ -44,7 +49,7 @@ use tari_common_types::{
     },
     types::{BlockHash, PrivateKey, PublicKey, Signature},
 };
-use tari_core::transactions::tari_amount::MicroTari;
+use tari_core::transactions::{tari_amount::MicroTari, transaction_components::Transaction};
 use tari_utilities::{
     hex::{from_hex, Hex},
     ByteArray,
@@ -55,7 +60,7 @@ use tokio::time::Instant;
 use zeroize::Zeroize;
 
 use crate::{
-    schema::{completed_transactions, inbound_transactions, outbound_transactions},
+    schema::{completed_transactions, inbound_transactions, outbound_transactions, send_attempts},
     storage::sqlite_utilities::wallet_db_connection::WalletDbConnection,
     transaction_service::{
         error::{TransactionKeyError, TransactionStorageError},
@@ -65,6 +70,8 @@ use crate::{
                 CompletedTransaction,
                 InboundTransaction,
                 OutboundTransaction,
+                SendAttempt,
+                SendAttemptMechanism,
                 TxCancellationReason,
                 WalletTransaction,
             },
@@ -78,6 +85,7 @@ const LOG_TARGET: &str = "wallet::transaction_service::database::wallet";
 pub struct TransactionServiceSqliteDatabase {
     database_connection: WalletDbConnection,
     cipher: Arc<RwLock<XChaCha20Poly1305>>,
+    is_locked: Arc<AtomicBool>,
 }
 
 impl TransactionServiceSqliteDatabase {
@@ -85,11 +93,21 @@ impl TransactionServiceSqliteDatabase {
         Self {
             database_connection,
             cipher: Arc::new(RwLock::new(cipher)),
+            is_locked: Arc::new(AtomicBool::new(false)),
         }
     }
 
-    fn insert(&self, kvp: DbKeyValuePair, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+    /// Acquires the database cipher, failing if the database has been locked
+    fn read_cipher(&self) -> Result<RwLockReadGuard<'_, XChaCha20Poly1305>, TransactionStorageError> {
         let cipher = acquire_read_lock!(self.cipher);
+        if self.is_locked.load(Ordering::SeqCst) {
+            return Err(TransactionStorageError::Locked);
+        }
+        Ok(cipher)
+    }
+
+    fn insert(&self, kvp: DbKeyValuePair, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        let cipher = self.read_cipher()?;
 
         match kvp {
             DbKeyValuePair::PendingOutboundTransaction(k, v) => {
@@ -120,7 +138,7 @@ impl TransactionServiceSqliteDatabase {
     }
 
     fn remove(&self, key: DbKey, conn: &mut SqliteConnection) -> Result<Option<DbValue>, TransactionStorageError> {
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
         match key {
             DbKey::PendingOutboundTransaction(k) => {
                 conn.transaction::<_, _, _>(|conn| match OutboundTransactionSql::find_by_cancelled(k, false, conn) {
@@ -206,12 +224,25 @@ impl TransactionServiceSqliteDatabase {
 }
 
 impl TransactionBackend for TransactionServiceSqliteDatabase {
+    fn lock(&self) {
+        let mut cipher = acquire_write_lock!(self.cipher);
+        // Replacing the cipher drops the one holding the main key
+        *cipher = XChaCha20Poly1305::new(&Key::default());
+        self.is_locked.store(true, Ordering::SeqCst);
+    }
+
+    fn unlock(&self, cipher: XChaCha20Poly1305) {
+        let mut current = acquire_write_lock!(self.cipher);
+        *current = cipher;
+        self.is_locked.store(false, Ordering::SeqCst);
+    }
+
     #[allow(clippy::too_many_lines)]
     fn fetch(&self, key: &DbKey) -> Result<Option<DbValue>, TransactionStorageError> {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         let result = match key {
             DbKey::PendingOutboundTransaction(t) => {
@@ -470,7 +501,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         if let Ok(outbound_tx_sql) = OutboundTransactionSql::find_by_cancelled(tx_id, false, &mut conn) {
             let outbound_tx = OutboundTransaction::try_from(outbound_tx_sql, &cipher)?;
@@ -509,7 +540,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         tx_id: TxId,
     ) -> Result<Option<WalletTransaction>, TransactionStorageError> {
         let mut conn = self.database_connection.get_pooled_connection()?;
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         match OutboundTransactionSql::find_by_cancelled(tx_id, true, &mut conn) {
             Ok(o) => {
@@ -549,7 +580,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         if CompletedTransactionSql::find_by_cancelled(tx_id, false, &mut conn).is_ok() {
             return Err(TransactionStorageError::TransactionAlreadyExists);
@@ -590,7 +621,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         if CompletedTransactionSql::find_by_cancelled(tx_id, false, &mut conn).is_ok() {
             return Err(TransactionStorageError::TransactionAlreadyExists);
@@ -759,6 +790,31 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         Ok(())
     }
 
+    fn set_outbound_transaction_priority(&self, tx_id: TxId, priority: u8) -> Result<(), TransactionStorageError> {
+        let start = Instant::now();
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+
+        match OutboundTransactionSql::set_priority(tx_id, priority, &mut conn) {
+            Ok(_) => {},
+            Err(TransactionStorageError::DieselError(DieselError::NotFound)) => {
+                return Err(TransactionStorageError::ValuesNotFound);
+            },
+            Err(e) => return Err(e),
+        };
+
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - set_outbound_transaction_priority: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        Ok(())
+    }
+
     fn cancel_coinbase_transactions_at_block_height(&self, block_height: u64) -> Result<(), TransactionStorageError> {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
@@ -790,7 +846,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         let coinbase_txs = CompletedTransactionSql::index_coinbase_at_block_height(block_height as i64, &mut conn)?;
         for c in coinbase_txs {
@@ -896,7 +952,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         let tx = completed_transactions::table
             // Note: Check 'mined_in_block' as well as 'mined_height' is populated for faux transactions before it is confirmed
@@ -953,7 +1009,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         let txs = completed_transactions::table
             .filter(
@@ -1066,7 +1122,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
 
     fn fetch_imported_transactions(&self) -> Result<Vec<CompletedTransaction>, TransactionStorageError> {
         let mut conn = self.database_connection.get_pooled_connection()?;
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         CompletedTransactionSql::index_by_status_and_cancelled(TransactionStatus::Imported, false, &mut conn)?
             .into_iter()
@@ -1078,7 +1134,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
 
     fn fetch_unconfirmed_faux_transactions(&self) -> Result<Vec<CompletedTransaction>, TransactionStorageError> {
         let mut conn = self.database_connection.get_pooled_connection()?;
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         CompletedTransactionSql::index_by_status_and_cancelled(TransactionStatus::FauxUnconfirmed, false, &mut conn)?
             .into_iter()
@@ -1093,7 +1149,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         height: u64,
     ) -> Result<Vec<CompletedTransaction>, TransactionStorageError> {
         let mut conn = self.database_connection.get_pooled_connection()?;
-        let cipher = acquire_read_lock!(self.cipher);
+        let cipher = self.read_cipher()?;
 
         CompletedTransactionSql::index_by_status_and_cancelled_from_block_height(
             TransactionStatus::FauxConfirmed,
@@ -1122,6 +1178,60 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
 
         Ok(())
     }
+
+    fn prune_confirmed_transaction_bodies(&self, max_mined_height: u64) -> Result<Vec<TxId>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = self.read_cipher()?;
+
+        conn.transaction::<_, TransactionStorageError, _>(|conn| {
+            let mut pruned = Vec::new();
+            for tx_sql in CompletedTransactionSql::index_prunable_up_to_block_height(max_mined_height as i64, conn)? {
+                let mut tx = CompletedTransaction::try_from(tx_sql.clone(), &cipher)?;
+                tx.transaction = Transaction::new(vec![], vec![], vec![], PrivateKey::default(), PrivateKey::default());
+                tx.body_pruned = true;
+                let tx_id = tx.tx_id;
+                let pruned_sql = CompletedTransactionSql::try_from(tx, &cipher)?;
+                tx_sql.update(
+                    UpdateCompletedTransactionSql {
+                        transaction_protocol: Some(pruned_sql.transaction_protocol),
+                        body_pruned: Some(1),
+                        ..Default::default()
+                    },
+                    conn,
+                )?;
+                pruned.push(tx_id);
+            }
+            Ok(pruned)
+        })
+    }
+
+    fn add_send_attempt(&self, attempt: SendAttempt) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        NewSendAttemptSql::from(attempt).insert(&mut conn)
+    }
+
+    fn fetch_send_attempts(&self, tx_id: TxId) -> Result<Vec<SendAttempt>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        SendAttemptSql::index_by_tx_id(tx_id, &mut conn)?
+            .into_iter()
+            .map(SendAttempt::try_from)
+            .collect()
+    }
+
+    fn fetch_completed_transactions_with_address(
+        &self,
+        address: &TariAddress,
+    ) -> Result<Vec<CompletedTransaction>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = self.read_cipher()?;
+
+        CompletedTransactionSql::index_by_address(address, &mut conn)?
+            .into_iter()
+            .map(|ct: CompletedTransactionSql| {
+                CompletedTransaction::try_from(ct, &cipher).map_err(TransactionStorageError::from)
+            })
+            .collect::<Result<Vec<CompletedTransaction>, TransactionStorageError>>()
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -1445,6 +1555,7 @@ struct OutboundTransactionSql {
     direct_send_success: i32,
     send_count: i32,
     last_send_timestamp: Option<NaiveDateTime>,
+    priority: i32,
 }
 
 impl OutboundTransactionSql {
@@ -1498,6 +1609,7 @@ impl OutboundTransactionSql {
             sender_protocol: None,
             send_count: None,
             last_send_timestamp: None,
+            priority: None,
         })
         .execute(conn)
         .num_rows_affected_or_not_found(1)?;
@@ -1505,6 +1617,22 @@ impl OutboundTransactionSql {
         Ok(())
     }
 
+    pub fn set_priority(tx_id: TxId, priority: u8, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::update(outbound_transactions::table.filter(outbound_transactions::tx_id.eq(tx_id.as_u64() as i64)))
+            .set(UpdateOutboundTransactionSql {
+                cancelled: None,
+                direct_send_success: None,
+                sender_protocol: None,
+                send_count: None,
+                last_send_timestamp: None,
+                priority: Some(i32::from(priority)),
+            })
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+
+        Ok(())
+    }
+
     pub fn complete_outbound_transaction(
         tx_id: TxId,
         conn: &mut SqliteConnection,
@@ -1539,6 +1667,7 @@ impl OutboundTransactionSql {
                     },
                 ),
                 last_send_timestamp: Some(Some(Utc::now().naive_utc())),
+                priority: None,
             })
             .execute(conn)
             .num_rows_affected_or_not_found(1)?;
@@ -1579,6 +1708,7 @@ impl OutboundTransactionSql {
                 sender_protocol: None,
                 send_count: None,
                 last_send_timestamp: None,
+                priority: None,
             })
             .execute(conn)
             .num_rows_affected_or_not_found(1)?;
@@ -1595,6 +1725,7 @@ impl OutboundTransactionSql {
                 sender_protocol: Some(self.sender_protocol.clone()),
                 send_count: None,
                 last_send_timestamp: None,
+                priority: None,
             },
             conn,
         )
@@ -1613,6 +1744,7 @@ impl OutboundTransactionSql {
             direct_send_success: i32::from(o.direct_send_success),
             send_count: o.send_count as i32,
             last_send_timestamp: o.last_send_timestamp,
+            priority: i32::from(o.priority),
         };
 
         outbound_tx.encrypt(cipher).map_err(TransactionStorageError::AeadError)
@@ -1677,6 +1809,7 @@ impl OutboundTransaction {
             direct_send_success: o.direct_send_success != 0,
             send_count: o.send_count as u32,
             last_send_timestamp: o.last_send_timestamp,
+            priority: o.priority as u8,
         };
 
         // zeroize decrypted data
@@ -1694,6 +1827,7 @@ pub struct UpdateOutboundTransactionSql {
     sender_protocol: Option<String>,
     send_count: Option<i32>,
     last_send_timestamp: Option<Option<NaiveDateTime>>,
+    priority: Option<i32>,
 }
 
 /// A structure to represent a Sql compatible version of the CompletedTransaction struct
@@ -1720,6 +1854,7 @@ pub struct CompletedTransactionSql {
     mined_timestamp: Option<NaiveDateTime>,
     transaction_signature_nonce: Vec<u8>,
     transaction_signature_key: Vec<u8>,
+    body_pruned: i32,
 }
 
 impl CompletedTransactionSql {
@@ -1766,6 +1901,21 @@ impl CompletedTransactionSql {
             .load::<CompletedTransactionSql>(conn)?)
     }
 
+    pub fn index_by_address(
+        address: &TariAddress,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<CompletedTransactionSql>, TransactionStorageError> {
+        let address = address.to_bytes().to_vec();
+        Ok(completed_transactions::table
+            .filter(
+                completed_transactions::source_address
+                    .eq(&address)
+                    .or(completed_transactions::destination_address.eq(&address)),
+            )
+            .order_by(completed_transactions::timestamp)
+            .load::<CompletedTransactionSql>(conn)?)
+    }
+
     pub fn index_by_status_and_cancelled_from_block_height(
         status: TransactionStatus,
         cancelled: bool,
@@ -1785,6 +1935,18 @@ impl CompletedTransactionSql {
             .load::<CompletedTransactionSql>(conn)?)
     }
 
+    pub fn index_prunable_up_to_block_height(
+        block_height: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<CompletedTransactionSql>, TransactionStorageError> {
+        Ok(completed_transactions::table
+            .filter(completed_transactions::status.eq(TransactionStatus::MinedConfirmed as i32))
+            .filter(completed_transactions::cancelled.is_null())
+            .filter(completed_transactions::body_pruned.eq(0))
+            .filter(completed_transactions::mined_height.le(block_height))
+            .load::<CompletedTransactionSql>(conn)?)
+    }
+
     pub fn index_coinbase_at_block_height(
         block_height: i64,
         conn: &mut SqliteConnection,
@@ -2036,6 +2198,7 @@ impl CompletedTransactionSql {
             mined_timestamp: c.mined_timestamp,
             transaction_signature_nonce: c.transaction_signature.get_public_nonce().to_vec(),
             transaction_signature_key: c.transaction_signature.get_signature().to_vec(),
+            body_pruned: i32::from(c.body_pruned),
         };
 
         output.encrypt(cipher).map_err(TransactionStorageError::AeadError)
@@ -2142,6 +2305,7 @@ impl CompletedTransaction {
             mined_height: c.mined_height.map(|ic| ic as u64),
             mined_in_block,
             mined_timestamp: c.mined_timestamp,
+            body_pruned: c.body_pruned != 0,
         };
 
         // zeroize sensitive data
@@ -2167,6 +2331,7 @@ pub struct UpdateCompletedTransactionSql {
     mined_timestamp: Option<NaiveDateTime>,
     transaction_signature_nonce: Option<Vec<u8>>,
     transaction_signature_key: Option<Vec<u8>>,
+    body_pruned: Option<i32>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -2244,6 +2409,69 @@ impl UnconfirmedTransactionInfoSql {
     }
 }
 
+#[derive(Clone, Debug, Insertable)]
+#[diesel(table_name = send_attempts)]
+struct NewSendAttemptSql {
+    tx_id: i64,
+    timestamp: NaiveDateTime,
+    mechanism: i32,
+    succeeded: i32,
+}
+
+impl NewSendAttemptSql {
+    pub fn insert(self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::insert_into(send_attempts::table).values(self).execute(conn)?;
+        Ok(())
+    }
+}
+
+impl From<SendAttempt> for NewSendAttemptSql {
+    fn from(attempt: SendAttempt) -> Self {
+        Self {
+            tx_id: attempt.tx_id.as_u64() as i64,
+            timestamp: attempt.timestamp,
+            mechanism: attempt.mechanism as i32,
+            succeeded: i32::from(attempt.succeeded),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Queryable)]
+struct SendAttemptSql {
+    tx_id: i64,
+    timestamp: NaiveDateTime,
+    mechanism: i32,
+    succeeded: i32,
+}
+
+impl SendAttemptSql {
+    pub fn index_by_tx_id(tx_id: TxId, conn: &mut SqliteConnection) -> Result<Vec<Self>, TransactionStorageError> {
+        Ok(send_attempts::table
+            .select((
+                send_attempts::tx_id,
+                send_attempts::timestamp,
+                send_attempts::mechanism,
+                send_attempts::succeeded,
+            ))
+            .filter(send_attempts::tx_id.eq(tx_id.as_u64() as i64))
+            .order_by(send_attempts::id.asc())
+            .load::<SendAttemptSql>(conn)?)
+    }
+}
+
+impl TryFrom<SendAttemptSql> for SendAttempt {
+    type Error = TransactionStorageError;
+
+    fn try_from(s: SendAttemptSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            tx_id: TxId::from(s.tx_id as u64),
+            timestamp: s.timestamp,
+            mechanism: SendAttemptMechanism::try_from(s.mechanism as u32)?,
+            succeeded: s.succeeded != 0,
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::{mem::size_of, time::Duration};
@@ -2385,6 +2613,7 @@ mod test {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            priority: 0,
         };
         let address = TariAddress::new(
             PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
@@ -2404,6 +2633,7 @@ mod test {
                 direct_send_success: false,
                 send_count: 0,
                 last_send_timestamp: None,
+                priority: 0,
             },
             &cipher,
         )
@@ -2540,6 +2770,7 @@ mod test {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            body_pruned: false,
         };
         let source_address = TariAddress::new(
             PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
@@ -2569,6 +2800,7 @@ mod test {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            body_pruned: false,
         };
 
         CompletedTransactionSql::try_from(completed_tx1.clone(), &cipher)
@@ -2710,6 +2942,7 @@ mod test {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            body_pruned: false,
         };
 
         let source_address = TariAddress::new(
@@ -2740,6 +2973,7 @@ mod test {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            body_pruned: false,
         };
 
         let source_address = TariAddress::new(
@@ -2770,6 +3004,7 @@ mod test {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            body_pruned: false,
         };
 
         CompletedTransactionSql::try_from(coinbase_tx1, &cipher)
@@ -2870,6 +3105,7 @@ mod test {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            priority: 0,
         };
 
         let outbound_tx_sql = OutboundTransactionSql::try_from(outbound_tx.clone(), &cipher).unwrap();
@@ -2915,6 +3151,7 @@ mod test {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            body_pruned: false,
         };
 
         let completed_tx_sql = CompletedTransactionSql::try_from(completed_tx.clone(), &cipher).unwrap();
@@ -3005,6 +3242,7 @@ mod test {
                 direct_send_success: false,
                 send_count: 0,
                 last_send_timestamp: None,
+                priority: 0,
             };
             let outbound_tx_sql = OutboundTransactionSql::try_from(outbound_tx, &cipher).unwrap();
 
@@ -3044,6 +3282,7 @@ mod test {
                 mined_height: None,
                 mined_in_block: None,
                 mined_timestamp: None,
+                body_pruned: false,
             };
             let completed_tx_sql = CompletedTransactionSql::try_from(completed_tx, &cipher).unwrap();
 
@@ -3190,6 +3429,7 @@ mod test {
                 mined_height: None,
                 mined_in_block: None,
                 mined_timestamp: None,
+                body_pruned: false,
             };
             let completed_tx_sql = CompletedTransactionSql::try_from(completed_tx.clone(), &cipher).unwrap();
 
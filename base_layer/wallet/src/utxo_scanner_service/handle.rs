@@ -43,10 +43,11 @@ pub enum UtxoScannerEvent {
         retry_limit: usize,
         error: String,
     },
-    /// Progress of the recovery process (current_block, current_chain_height)
+    /// Progress of the recovery process (current_block, current_chain_height, outputs recovered so far)
     Progress {
         current_height: u64,
         tip_height: u64,
+        outputs_recovered: u64,
     },
     /// Completed Recovery (Number scanned, Num of Recovered outputs, Value of recovered outputs, Time taken)
     Completed {
@@ -31,6 +31,7 @@
 pub mod error;
 pub mod handle;
 pub mod initializer;
+pub mod recovery_estimate;
 pub mod service;
 mod utxo_scanner_task;
 pub mod uxto_scanner_service_builder;
@@ -0,0 +1,68 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::time::Duration;
+
+/// A rough estimate of how long a wallet recovery scan will take, based on the number of blocks between the scan's
+/// starting height and the chain tip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecoveryEstimate {
+    pub from_height: u64,
+    pub tip_height: u64,
+    pub blocks_to_scan: u64,
+    pub estimated_duration: Duration,
+}
+
+impl RecoveryEstimate {
+    pub fn new(from_height: u64, tip_height: u64, scan_time_per_block: Duration) -> Self {
+        let blocks_to_scan = tip_height.saturating_add(1).saturating_sub(from_height);
+        let estimated_duration = scan_time_per_block.saturating_mul(u32::try_from(blocks_to_scan).unwrap_or(u32::MAX));
+        Self {
+            from_height,
+            tip_height,
+            blocks_to_scan,
+            estimated_duration,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_scales_with_the_blocks_to_scan() {
+        let per_block = Duration::from_millis(20);
+        let estimate = RecoveryEstimate::new(1_000, 1_999, per_block);
+        assert_eq!(estimate.blocks_to_scan, 1_000);
+        assert_eq!(estimate.estimated_duration, Duration::from_secs(20));
+
+        let double = RecoveryEstimate::new(0, 1_999, per_block);
+        assert_eq!(double.blocks_to_scan, 2_000);
+        assert_eq!(double.estimated_duration, estimate.estimated_duration * 2);
+
+        // Starting beyond the tip leaves nothing to scan
+        let done = RecoveryEstimate::new(2_500, 1_999, per_block);
+        assert_eq!(done.blocks_to_scan, 0);
+        assert_eq!(done.estimated_duration, Duration::ZERO);
+    }
+}
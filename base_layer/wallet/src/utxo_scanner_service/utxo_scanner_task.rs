@@ -156,6 +156,7 @@ where
         self.publish_event(UtxoScannerEvent::Progress {
             current_height: final_height,
             tip_height: final_height,
+            outputs_recovered: num_outputs_recovered,
         });
         self.publish_event(UtxoScannerEvent::Completed {
             final_height,
@@ -501,19 +502,24 @@ where
                         true,
                     )?;
 
-                    if current_height % PROGRESS_REPORT_INTERVAL == 0 {
+                    num_recovered = num_recovered.saturating_add(count);
+                    total_amount += amount;
+
+                    // Report progress on the interval, and immediately whenever outputs were recovered
+                    if current_height % PROGRESS_REPORT_INTERVAL == 0 || count > 0 {
                         debug!(
                             target: LOG_TARGET,
-                            "Scanned up to block {} with a current tip_height of {}", current_height, tip_height
+                            "Scanned up to block {} with a current tip_height of {} ({} outputs recovered)",
+                            current_height,
+                            tip_height,
+                            num_recovered
                         );
                         self.publish_event(UtxoScannerEvent::Progress {
                             current_height,
                             tip_height,
+                            outputs_recovered: num_recovered,
                         });
                     }
-
-                    num_recovered = num_recovered.saturating_add(count);
-                    total_amount += amount;
                 }
             }
             prev_scanned_block = Some(ScannedBlock {
@@ -20,8 +20,16 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{cmp, marker::PhantomData, sync::Arc};
+use std::{
+    cmp,
+    collections::HashMap,
+    io::{Read, Write},
+    marker::PhantomData,
+    sync::{Arc, Mutex, Weak},
+    time::Duration,
+};
 
+use borsh::{BorshDeserialize, BorshSerialize};
 use log::*;
 use tari_common::configuration::bootstrap::ApplicationType;
 use tari_common_types::{
@@ -72,7 +80,8 @@ use tari_p2p::{
 use tari_script::{one_sided_payment_script, ExecutionStack, TariScript};
 use tari_service_framework::StackBuilder;
 use tari_shutdown::ShutdownSignal;
-use tari_utilities::ByteArray;
+use tari_utilities::{ByteArray, SafePassword};
+use tokio::time::Instant;
 
 use crate::{
     base_node_service::{handle::BaseNodeServiceHandle, BaseNodeServiceInitializer},
@@ -96,7 +105,12 @@ use crate::{
         TransactionServiceInitializer,
     },
     util::wallet_identity::WalletIdentity,
-    utxo_scanner_service::{handle::UtxoScannerHandle, initializer::UtxoScannerServiceInitializer, RECOVERY_KEY},
+    utxo_scanner_service::{
+        handle::UtxoScannerHandle,
+        initializer::UtxoScannerServiceInitializer,
+        recovery_estimate::RecoveryEstimate,
+        RECOVERY_KEY,
+    },
 };
 
 const LOG_TARGET: &str = "wallet";
@@ -128,8 +142,9 @@ pub struct Wallet<T, U, V, W, X> {
     pub db: WalletDatabase<T>,
     pub output_db: OutputManagerDatabase<V>,
     pub factories: CryptoFactories,
-    _u: PhantomData<U>,
-    _v: PhantomData<V>,
+    recovery_scan_time_per_block: Duration,
+    auto_lock: Arc<Mutex<AutoLockState>>,
+    lockable_backends: LockableBackends<T, U, V, X>,
     _w: PhantomData<W>,
 }
 
@@ -177,6 +192,16 @@ where
             config.buffer_rate_limit
         );
         let wallet_identity = WalletIdentity::new(node_identity.clone(), config.network);
+        let lockable_backends = LockableBackends {
+            wallet: wallet_database.clone(),
+            transactions: transaction_backend.clone(),
+            outputs: output_manager_backend.clone(),
+            keys: key_manager_backend.clone(),
+        };
+        let mut key_manager_initializer = KeyManagerInitializer::new(key_manager_backend, master_seed);
+        if config.network_tagged_key_branches {
+            key_manager_initializer = key_manager_initializer.with_network_tag(config.network.as_key_str().to_string());
+        }
         let stack = StackBuilder::new(shutdown_signal)
             .add_initializer(P2pInitializer::new(
                 config.p2p.clone(),
@@ -192,7 +217,7 @@ where
                 config.network.into(),
                 node_identity.clone(),
             ))
-            .add_initializer(KeyManagerInitializer::new(key_manager_backend, master_seed))
+            .add_initializer(key_manager_initializer)
             .add_initializer(TransactionServiceInitializer::new(
                 config.transaction_service_config,
                 peer_message_subscription_factory.clone(),
@@ -309,10 +334,11 @@ where
             db: wallet_database,
             output_db: output_manager_database,
             factories,
+            recovery_scan_time_per_block: Duration::from_millis(config.recovery_scan_ms_per_block),
+            auto_lock: Arc::new(Mutex::new(AutoLockState::new())),
+            lockable_backends,
             #[cfg(feature = "test_harness")]
             transaction_backend: transaction_backend_handle,
-            _u: PhantomData,
-            _v: PhantomData,
             _w: PhantomData,
         })
     }
@@ -440,6 +466,7 @@ where
         encrypted_data: EncryptedData,
         minimum_value_promise: MicroTari,
     ) -> Result<TxId, WalletError> {
+        self.record_activity()?;
         let unblinded_output = UnblindedOutput::new_current_version(
             amount,
             spending_key.clone(),
@@ -467,6 +494,7 @@ where
         source_address: TariAddress,
         message: String,
     ) -> Result<TxId, WalletError> {
+        self.record_activity()?;
         let tx_id = self
             .transaction_service
             .import_utxo_with_status(
@@ -499,6 +527,50 @@ where
         Ok(tx_id)
     }
 
+    /// Write all of the wallet's unspent outputs to `writer` as a borsh encoded list of `UnblindedOutput`s and return
+    /// the number of outputs written. The backup can be restored with `import_outputs_from_reader`.
+    ///
+    /// WARNING: The backup is NOT encrypted. It contains the spending keys of the outputs, so anyone who obtains it can
+    /// spend the funds. It must be stored securely.
+    pub async fn export_unspent_outputs_backup<W: Write>(&self, mut writer: W) -> Result<usize, WalletError> {
+        self.record_activity()?;
+        let outputs = self
+            .output_manager_service
+            .clone()
+            .get_unspent_outputs()
+            .await?
+            .into_iter()
+            .map(|o| o.unblinded_output)
+            .collect::<Vec<_>>();
+        outputs.serialize(&mut writer)?;
+        writer.flush()?;
+        debug!(target: LOG_TARGET, "Exported {} unspent output(s)", outputs.len());
+        Ok(outputs.len())
+    }
+
+    /// Restore the outputs in a backup written by `export_unspent_outputs_backup`. Each output is imported as a
+    /// non-rewindable output, in the same way as `import_unblinded_output_as_non_rewindable`, and becomes spendable
+    /// once it has been validated. The TxIds of the generated faux transactions are returned.
+    pub async fn import_outputs_from_reader<R: Read>(
+        &mut self,
+        mut reader: R,
+        source_address: TariAddress,
+        message: String,
+    ) -> Result<Vec<TxId>, WalletError> {
+        self.record_activity()?;
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf)?;
+        let outputs = Vec::<UnblindedOutput>::try_from_slice(&buf)?;
+        let mut tx_ids = Vec::with_capacity(outputs.len());
+        for output in outputs {
+            let tx_id = self
+                .import_unblinded_output_as_non_rewindable(output, source_address.clone(), message.clone())
+                .await?;
+            tx_ids.push(tx_id);
+        }
+        Ok(tx_ids)
+    }
+
     pub fn sign_message(
         &mut self,
         secret: &PrivateKey,
@@ -523,6 +595,7 @@ where
         split_count: usize,
         fee_per_gram: MicroTari,
     ) -> Result<(Vec<MicroTari>, MicroTari), WalletError> {
+        self.record_activity()?;
         self.output_manager_service
             .preview_coin_split_with_commitments_no_amount(commitments, split_count, fee_per_gram)
             .await
@@ -535,6 +608,7 @@ where
         commitments: Vec<Commitment>,
         fee_per_gram: MicroTari,
     ) -> Result<(Vec<MicroTari>, MicroTari), WalletError> {
+        self.record_activity()?;
         self.output_manager_service
             .preview_coin_join_with_commitments(commitments, fee_per_gram)
             .await
@@ -550,6 +624,7 @@ where
         fee_per_gram: MicroTari,
         message: String,
     ) -> Result<TxId, WalletError> {
+        self.record_activity()?;
         let coin_split_tx = self
             .output_manager_service
             .create_coin_split(commitments, amount_per_split, split_count, fee_per_gram)
@@ -578,6 +653,7 @@ where
         fee_per_gram: MicroTari,
         message: String,
     ) -> Result<TxId, WalletError> {
+        self.record_activity()?;
         let coin_split_tx = self
             .output_manager_service
             .create_coin_split_even(commitments, split_count, fee_per_gram)
@@ -606,6 +682,7 @@ where
         fee_per_gram: MicroTari,
         message: String,
     ) -> Result<TxId, WalletError> {
+        self.record_activity()?;
         let coin_split_tx = self
             .output_manager_service
             .create_coin_split_even(commitments, split_count, fee_per_gram)
@@ -632,6 +709,7 @@ where
         fee_per_gram: MicroTari,
         msg: Option<String>,
     ) -> Result<TxId, WalletError> {
+        self.record_activity()?;
         let coin_join_tx = self
             .output_manager_service
             .create_coin_join(commitments, fee_per_gram)
@@ -653,13 +731,174 @@ where
         }
     }
 
+    /// Find the outputs that are spent by more than one of the wallet's completed transactions, which can happen after
+    /// a botched manual import. Each double spent commitment is returned with the TxIds of the transactions spending
+    /// it. Transactions whose body was pruned after they were confirmed can't be checked.
+    pub async fn find_double_spent_outputs(&self) -> Result<Vec<(Commitment, Vec<TxId>)>, WalletError> {
+        self.record_activity()?;
+        let completed_transactions = self.transaction_service.clone().get_completed_transactions().await?;
+        let mut spent_in: HashMap<Commitment, Vec<TxId>> = HashMap::new();
+        let mut num_pruned = 0usize;
+        for (tx_id, completed_tx) in completed_transactions {
+            if completed_tx.body_pruned {
+                num_pruned += 1;
+                continue;
+            }
+            // Compact inputs don't carry the commitment of the spent output, so they can't be compared
+            for commitment in completed_tx
+                .transaction
+                .body
+                .inputs()
+                .iter()
+                .filter_map(|input| input.commitment().ok())
+            {
+                spent_in.entry(commitment.clone()).or_default().push(tx_id);
+            }
+        }
+
+        let mut double_spent = spent_in
+            .into_iter()
+            .filter(|(_, tx_ids)| tx_ids.len() > 1)
+            .map(|(commitment, mut tx_ids)| {
+                tx_ids.sort_by_key(|tx_id| tx_id.as_u64());
+                (commitment, tx_ids)
+            })
+            .collect::<Vec<_>>();
+        double_spent.sort_by(|(a, _), (b, _)| a.as_bytes().cmp(b.as_bytes()));
+        if num_pruned > 0 {
+            warn!(
+                target: LOG_TARGET,
+                "{} completed transaction(s) were not checked for double spends because their bodies were pruned",
+                num_pruned
+            );
+        }
+        if !double_spent.is_empty() {
+            warn!(
+                target: LOG_TARGET,
+                "Found {} output(s) spent by more than one completed transaction",
+                double_spent.len()
+            );
+        }
+        Ok(double_spent)
+    }
+
+    /// List the commitments of all the outputs the wallet currently owns, e.g. for a proof of reserves. Outputs the
+    /// wallet has already spent are included when `include_spent` is set.
+    pub fn list_all_commitments(&self, include_spent: bool) -> Result<Vec<Commitment>, WalletError> {
+        self.record_activity()?;
+        let mut outputs = self
+            .output_db
+            .fetch_all_unspent_outputs()
+            .map_err(OutputManagerError::from)?;
+        if include_spent {
+            outputs.extend(self.output_db.fetch_spent_outputs().map_err(OutputManagerError::from)?);
+        }
+        Ok(outputs.into_iter().map(|o| o.commitment).collect())
+    }
+
     /// Utility function to find out if there is data in the database indicating that there is an incomplete recovery
     /// process in progress
     pub fn is_recovery_in_progress(&self) -> Result<bool, WalletError> {
+        self.record_activity()?;
         Ok(self.db.get_client_key_value(RECOVERY_KEY.to_string())?.is_some())
     }
 
+    /// Estimate how long a wallet recovery starting at `from_height` would take, using the chain tip last reported by
+    /// the base node and the configured per-block scan time.
+    pub async fn estimate_recovery_duration(&self, from_height: u64) -> Result<RecoveryEstimate, WalletError> {
+        self.record_activity()?;
+        let tip_height = self
+            .base_node_service
+            .clone()
+            .get_chain_metadata()
+            .await?
+            .ok_or(WalletError::ChainTipUnknown)?
+            .height_of_longest_chain();
+        Ok(RecoveryEstimate::new(
+            from_height,
+            tip_height,
+            self.recovery_scan_time_per_block,
+        ))
+    }
+
+    /// Lock the wallet once `timeout` has passed without any activity through the wallet API. Locking discards the
+    /// database ciphers of the wallet, transaction, output manager and key manager backends, after which wallet
+    /// operations return `WalletError::Locked` and service requests needing encrypted data fail until `unlock` is
+    /// called with the wallet passphrase. Only calls made through the wallet API count as activity. Setting a new
+    /// timeout replaces the previous one.
+    pub fn set_auto_lock(&self, timeout: Duration) {
+        let generation = {
+            let mut state = acquire_lock!(self.auto_lock);
+            state.timeout = Some(timeout);
+            state.generation += 1;
+            state.last_activity = Instant::now();
+            state.generation
+        };
+        self.spawn_auto_lock_timer(generation, timeout);
+    }
+
+    /// Unlock a wallet that was locked after inactivity and restart the inactivity timer
+    pub fn unlock(&self, passphrase: &SafePassword) -> Result<(), WalletError> {
+        self.lockable_backends.unlock(passphrase)?;
+        let (generation, timeout) = {
+            let mut state = acquire_lock!(self.auto_lock);
+            state.locked = false;
+            state.generation += 1;
+            state.last_activity = Instant::now();
+            (state.generation, state.timeout)
+        };
+        info!(target: LOG_TARGET, "Wallet unlocked");
+        if let Some(timeout) = timeout {
+            self.spawn_auto_lock_timer(generation, timeout);
+        }
+        Ok(())
+    }
+
+    pub fn is_locked(&self) -> bool {
+        acquire_lock!(self.auto_lock).locked
+    }
+
+    fn spawn_auto_lock_timer(&self, generation: u64, timeout: Duration) {
+        let auto_lock: Weak<Mutex<AutoLockState>> = Arc::downgrade(&self.auto_lock);
+        let backends = self.lockable_backends.clone();
+        tokio::spawn(async move {
+            let mut remaining = timeout;
+            loop {
+                tokio::time::sleep(remaining).await;
+                // The wallet has been dropped
+                let auto_lock = match auto_lock.upgrade() {
+                    Some(auto_lock) => auto_lock,
+                    None => break,
+                };
+                let mut state = acquire_lock!(auto_lock);
+                // The timer has been replaced by a newer one
+                if state.generation != generation || state.locked {
+                    break;
+                }
+                let idle = state.last_activity.elapsed();
+                if idle >= timeout {
+                    backends.lock();
+                    state.locked = true;
+                    info!(target: LOG_TARGET, "Wallet locked after {:.2?} of inactivity", idle);
+                    break;
+                }
+                remaining = timeout - idle;
+            }
+        });
+    }
+
+    /// Reset the inactivity timer, or fail if the wallet has been locked
+    fn record_activity(&self) -> Result<(), WalletError> {
+        let mut state = acquire_lock!(self.auto_lock);
+        if state.locked {
+            return Err(WalletError::Locked);
+        }
+        state.last_activity = Instant::now();
+        Ok(())
+    }
+
     pub fn get_seed_words(&self, language: &MnemonicLanguage) -> Result<SeedWords, WalletError> {
+        self.record_activity()?;
         let master_seed = self.db.get_master_seed()?.ok_or_else(|| {
             WalletError::WalletStorageError(WalletStorageError::RecoverySeedError(
                 "Cipher Seed not found".to_string(),
@@ -671,6 +910,58 @@ where
     }
 }
 
+/// The storage backends that hold a copy of the database cipher, all of which are locked together
+#[derive(Clone)]
+struct LockableBackends<T, U, V, X> {
+    wallet: WalletDatabase<T>,
+    transactions: U,
+    outputs: V,
+    keys: X,
+}
+
+impl<T, U, V, X> LockableBackends<T, U, V, X>
+where
+    T: WalletBackend + 'static,
+    U: TransactionBackend + 'static,
+    V: OutputManagerBackend + 'static,
+    X: KeyManagerBackend + 'static,
+{
+    fn lock(&self) {
+        self.wallet.lock();
+        self.transactions.lock();
+        self.outputs.lock();
+        self.keys.lock();
+    }
+
+    fn unlock(&self, passphrase: &SafePassword) -> Result<(), WalletError> {
+        let cipher = self.wallet.unlock(passphrase)?;
+        self.transactions.unlock(cipher.clone());
+        self.outputs.unlock(cipher.clone());
+        self.keys.unlock(cipher);
+        Ok(())
+    }
+}
+
+/// Tracks wallet activity for the inactivity auto-lock
+struct AutoLockState {
+    timeout: Option<Duration>,
+    last_activity: Instant,
+    locked: bool,
+    /// Incremented whenever the timer is (re)started, so that stale timers exit
+    generation: u64,
+}
+
+impl AutoLockState {
+    fn new() -> Self {
+        Self {
+            timeout: None,
+            last_activity: Instant::now(),
+            locked: false,
+            generation: 0,
+        }
+    }
+}
+
 pub fn read_or_create_master_seed<T: WalletBackend + 'static>(
     recovery_seed: Option<CipherSeed>,
     db: &WalletDatabase<T>,
@@ -24,6 +24,7 @@ use std::mem::size_of;
 
 use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
 use rand::{rngs::OsRng, RngCore};
+use tari_common_types::types::PrivateKey;
 use tari_key_manager::{
     cipher_seed::CipherSeed,
     key_manager_service::{
@@ -190,3 +191,78 @@ async fn key_manager_test_index() {
         key_manager.find_key_index("branch2", &key_2).await.unwrap()
     );
 }
+
+#[tokio::test]
+async fn key_manager_find_highest_used_index() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let cipher = CipherSeed::new();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let db_cipher = XChaCha20Poly1305::new(key_ga);
+
+    let key_manager = KeyManagerHandle::new(
+        cipher.clone(),
+        KeyManagerDatabase::new(KeyManagerSqliteDatabase::init(connection.clone(), db_cipher.clone())),
+    );
+    key_manager.add_new_branch("branch1").await.unwrap();
+
+    // The keys at indices 1 to 3, 6 and 20 are in use, e.g. because outputs with those keys are held by the wallet
+    let mut used_keys = Vec::new();
+    for index in [1, 2, 3, 6, 20] {
+        used_keys.push(key_manager.get_key_at_index("branch1", index).await.unwrap());
+    }
+    let is_used = |k: &PrivateKey| used_keys.contains(k);
+    assert!(key_manager
+        .find_highest_used_index("branch1", 5, |_| false)
+        .await
+        .is_err());
+    assert!(key_manager
+        .find_highest_used_index("branch2", 3, is_used)
+        .await
+        .is_err());
+
+    // Nothing is remembered between restarts, the keys are derived and probed every time
+    drop(key_manager);
+    let key_manager = KeyManagerHandle::new(
+        cipher,
+        KeyManagerDatabase::new(KeyManagerSqliteDatabase::init(connection, db_cipher)),
+    );
+    key_manager.add_new_branch("branch1").await.unwrap();
+    assert_eq!(
+        key_manager
+            .find_highest_used_index("branch1", 2, is_used)
+            .await
+            .unwrap(),
+        3
+    );
+    assert_eq!(
+        key_manager
+            .find_highest_used_index("branch1", 3, is_used)
+            .await
+            .unwrap(),
+        6
+    );
+    assert_eq!(
+        key_manager
+            .find_highest_used_index("branch1", 14, is_used)
+            .await
+            .unwrap(),
+        20
+    );
+
+    // The search itself is bounded by the gap limit past the current index
+    let key = key_manager.get_key_at_index("branch1", 30).await.unwrap();
+    assert!(key_manager
+        .find_key_index_with_gap_limit("branch1", &key, 10)
+        .await
+        .is_err());
+    assert_eq!(
+        key_manager
+            .find_key_index_with_gap_limit("branch1", &key, 31)
+            .await
+            .unwrap(),
+        30
+    );
+}
@@ -23,13 +23,14 @@
 use std::{mem::size_of, panic, path::Path, sync::Arc, time::Duration};
 
 use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
+use chrono::Utc;
 use rand::{rngs::OsRng, RngCore};
 use support::utils::make_non_recoverable_input;
 use tari_common::configuration::{MultiaddrList, StringList};
 use tari_common_types::{
     chain_metadata::ChainMetadata,
     tari_address::TariAddress,
-    transaction::TransactionStatus,
+    transaction::{TransactionDirection, TransactionStatus, TxId},
     types::{FixedHash, PrivateKey, PublicKey},
 };
 use tari_comms::{
@@ -52,15 +53,15 @@ use tari_core::{
     transactions::{
         tari_amount::{uT, MicroTari},
         test_helpers::{create_non_recoverable_unblinded_output, TestParams},
-        transaction_components::OutputFeatures,
+        transaction_components::{OutputFeatures, Transaction},
         CryptoFactories,
     },
 };
 use tari_crypto::keys::{PublicKey as PublicKeyTrait, SecretKey};
 use tari_key_manager::{
     cipher_seed::CipherSeed,
-    key_manager_service::storage::sqlite_db::KeyManagerSqliteDatabase,
-    mnemonic::Mnemonic,
+    key_manager_service::{storage::sqlite_db::KeyManagerSqliteDatabase, KeyManagerInterface},
+    mnemonic::{Mnemonic, MnemonicLanguage},
     SeedWords,
 };
 use tari_p2p::{
@@ -93,7 +94,11 @@ use tari_wallet::{
     transaction_service::{
         config::TransactionServiceConfig,
         handle::TransactionEvent,
-        storage::sqlite_db::TransactionServiceSqliteDatabase,
+        storage::{
+            database::TransactionDatabase,
+            models::CompletedTransaction,
+            sqlite_db::TransactionServiceSqliteDatabase,
+        },
     },
     wallet::read_or_create_master_seed,
     Wallet,
@@ -101,7 +106,7 @@ use tari_wallet::{
     WalletSqlite,
 };
 use tempfile::tempdir;
-use tokio::{sync::mpsc, time::sleep};
+use tokio::{sync::mpsc, time, time::sleep};
 
 use crate::support::utils::make_input;
 
@@ -126,6 +131,30 @@ async fn create_wallet(
     passphrase: SafePassword,
     recovery_seed: Option<CipherSeed>,
 ) -> Result<WalletSqlite, WalletError> {
+    create_wallet_with_transaction_db(
+        data_path,
+        database_name,
+        consensus_manager,
+        factories,
+        shutdown_signal,
+        passphrase,
+        recovery_seed,
+    )
+    .await
+    .map(|(wallet, _)| wallet)
+}
+
+/// Creates a wallet like `create_wallet` and also returns a handle to its transaction database so that tests can
+/// insert transactions directly
+async fn create_wallet_with_transaction_db(
+    data_path: &Path,
+    database_name: &str,
+    consensus_manager: ConsensusManager,
+    factories: CryptoFactories,
+    shutdown_signal: ShutdownSignal,
+    passphrase: SafePassword,
+    recovery_seed: Option<CipherSeed>,
+) -> Result<(WalletSqlite, TransactionDatabase<TransactionServiceSqliteDatabase>), WalletError> {
     const NETWORK: Network = Network::LocalNet;
     let node_identity = NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
     let comms_config = P2pConfig {
@@ -187,8 +216,9 @@ async fn create_wallet(
     let master_seed = read_or_create_master_seed(recovery_seed, &wallet_db)?;
 
     let output_db = OutputManagerDatabase::new(output_manager_backend.clone());
+    let transaction_db = TransactionDatabase::new(transaction_backend.clone());
 
-    Wallet::start(
+    let wallet = Wallet::start(
         config,
         PeerSeedsConfig::default(),
         AutoUpdateConfig::default(),
@@ -204,7 +234,8 @@ async fn create_wallet(
         shutdown_signal,
         master_seed,
     )
-    .await
+    .await?;
+    Ok((wallet, transaction_db))
 }
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
@@ -782,6 +813,182 @@ async fn test_import_utxo() {
     assert!(outputs.iter().any(|o| { o.hash == expected_output_hash }));
 }
 
+#[tokio::test]
+async fn test_export_and_import_unspent_outputs_backup() {
+    let consensus_manager = ConsensusManager::builder(Network::LocalNet).build();
+    let factories = CryptoFactories::default();
+    let shutdown = Shutdown::new();
+    let dir = tempdir().unwrap();
+    // The backup is restored into a fresh wallet with the same seed
+    let seed = CipherSeed::new();
+
+    let mut alice_wallet = create_wallet(
+        dir.path(),
+        "alice_db",
+        consensus_manager.clone(),
+        factories.clone(),
+        shutdown.to_signal(),
+        "alice passphrase".to_string().into(),
+        Some(seed.clone()),
+    )
+    .await
+    .unwrap();
+    for value in [1000, 2000, 3000] {
+        let p = TestParams::new();
+        let utxo =
+            create_non_recoverable_unblinded_output(script!(Nop), OutputFeatures::default(), &p, value * uT).unwrap();
+        alice_wallet
+            .output_manager_service
+            .add_output(utxo, None)
+            .await
+            .unwrap();
+    }
+    let alice_balance = alice_wallet.output_manager_service.get_balance().await.unwrap();
+    assert_eq!(alice_balance.available_balance, 6000 * uT);
+
+    let mut backup = Vec::new();
+    let num_exported = alice_wallet.export_unspent_outputs_backup(&mut backup).await.unwrap();
+    assert_eq!(num_exported, 3);
+
+    let mut restored_wallet = create_wallet(
+        dir.path(),
+        "restored_db",
+        consensus_manager,
+        factories,
+        shutdown.to_signal(),
+        "restored passphrase".to_string().into(),
+        Some(seed),
+    )
+    .await
+    .unwrap();
+    let source_address = TariAddress::new(
+        alice_wallet.comms.node_identity().public_key().clone(),
+        Network::LocalNet,
+    );
+    let tx_ids = restored_wallet
+        .import_outputs_from_reader(backup.as_slice(), source_address, "Restored from backup".to_string())
+        .await
+        .unwrap();
+    assert_eq!(tx_ids.len(), 3);
+
+    // Imported outputs only become available once they have been validated against the base node
+    let restored_balance = restored_wallet.output_manager_service.get_balance().await.unwrap();
+    assert_eq!(
+        restored_balance.pending_incoming_balance,
+        alice_balance.available_balance
+    );
+}
+
+#[tokio::test]
+async fn test_find_double_spent_outputs() {
+    let network = Network::LocalNet;
+    let consensus_manager = ConsensusManager::builder(network).build();
+    let factories = CryptoFactories::default();
+    let shutdown = Shutdown::new();
+    let temp_dir = tempdir().unwrap();
+    let (alice_wallet, ts_db) = create_wallet_with_transaction_db(
+        temp_dir.path(),
+        "alice_db",
+        consensus_manager,
+        factories.clone(),
+        shutdown.to_signal(),
+        SafePassword::from("a new passphrase"),
+        None,
+    )
+    .await
+    .unwrap();
+    assert!(alice_wallet.find_double_spent_outputs().await.unwrap().is_empty());
+
+    // Two transactions spend the same output, a third spends a different one
+    let double_spent =
+        create_non_recoverable_unblinded_output(script!(Nop), OutputFeatures::default(), &TestParams::new(), 1000 * uT)
+            .unwrap()
+            .as_transaction_input(&factories.commitment)
+            .unwrap();
+    let single_spent =
+        create_non_recoverable_unblinded_output(script!(Nop), OutputFeatures::default(), &TestParams::new(), 2000 * uT)
+            .unwrap()
+            .as_transaction_input(&factories.commitment)
+            .unwrap();
+    let address = TariAddress::new(alice_wallet.comms.node_identity().public_key().clone(), network);
+    for (tx_id, input) in [(1u64, &double_spent), (2, &single_spent), (3, &double_spent)] {
+        let transaction = Transaction::new(
+            vec![input.clone()],
+            vec![],
+            vec![],
+            PrivateKey::default(),
+            PrivateKey::default(),
+        );
+        let completed_tx = CompletedTransaction::new(
+            tx_id.into(),
+            address.clone(),
+            address.clone(),
+            1000 * uT,
+            MicroTari::zero(),
+            transaction,
+            TransactionStatus::MinedConfirmed,
+            "Yo!".to_string(),
+            Utc::now().naive_utc(),
+            TransactionDirection::Outbound,
+            None,
+            None,
+            None,
+        );
+        ts_db.insert_completed_transaction(tx_id.into(), completed_tx).unwrap();
+    }
+
+    let reported = alice_wallet.find_double_spent_outputs().await.unwrap();
+    assert_eq!(reported.len(), 1);
+    assert_eq!(&reported[0].0, double_spent.commitment().unwrap());
+    assert_eq!(reported[0].1, vec![TxId::from(1u64), TxId::from(3u64)]);
+}
+
+#[tokio::test]
+async fn test_list_all_commitments() {
+    let consensus_manager = ConsensusManager::builder(Network::LocalNet).build();
+    let factories = CryptoFactories::default();
+    let shutdown = Shutdown::new();
+    let temp_dir = tempdir().unwrap();
+    let alice_wallet = create_wallet(
+        temp_dir.path(),
+        "alice_db",
+        consensus_manager,
+        factories.clone(),
+        shutdown.to_signal(),
+        SafePassword::from("a new passphrase"),
+        None,
+    )
+    .await
+    .unwrap();
+    assert!(alice_wallet.list_all_commitments(true).unwrap().is_empty());
+
+    let mut oms = alice_wallet.output_manager_service.clone();
+    for value in [1000 * uT, 2000 * uT, 3000 * uT] {
+        let (_ti, uo) = make_non_recoverable_input(&mut OsRng, value, &factories.commitment).await;
+        oms.add_output(uo, None).await.unwrap();
+    }
+    let outputs = alice_wallet.output_db.fetch_all_unspent_outputs().unwrap();
+    assert_eq!(outputs.len(), 3);
+    let spent = &outputs[0];
+    alice_wallet
+        .output_db
+        .set_received_output_mined_height_and_status(spent.hash, 1, FixedHash::zero(), 1, true, 0)
+        .unwrap();
+    alice_wallet
+        .output_db
+        .mark_output_as_spent(spent.hash, 2, FixedHash::zero(), true)
+        .unwrap();
+
+    let unspent_commitments = alice_wallet.list_all_commitments(false).unwrap();
+    assert_eq!(unspent_commitments.len(), 2);
+    assert!(!unspent_commitments.contains(&spent.commitment));
+    assert!(outputs[1..].iter().all(|o| unspent_commitments.contains(&o.commitment)));
+
+    let all_commitments = alice_wallet.list_all_commitments(true).unwrap();
+    assert_eq!(all_commitments.len(), 3);
+    assert!(outputs.iter().all(|o| all_commitments.contains(&o.commitment)));
+}
+
 #[test]
 fn test_db_file_locking() {
     let db_tempdir = tempdir().unwrap();
@@ -967,3 +1174,102 @@ async fn test_contacts_service_liveness() {
     alice_wallet.wait_until_shutdown().await;
     bob_wallet.wait_until_shutdown().await;
 }
+
+#[tokio::test]
+async fn test_estimate_recovery_duration() {
+    let dir = tempdir().unwrap();
+    let consensus_manager = ConsensusManager::builder(Network::LocalNet).build();
+    let factories = CryptoFactories::default();
+    let mut shutdown = Shutdown::new();
+
+    let wallet = create_wallet(
+        dir.path(),
+        "wallet_db",
+        consensus_manager,
+        factories,
+        shutdown.to_signal(),
+        "my wallet passphrase".to_string().into(),
+        None,
+    )
+    .await
+    .unwrap();
+
+    // Without a connected base node the tip last stored in the wallet database is used
+    wallet
+        .db
+        .set_chain_metadata(ChainMetadata::new(1_999, FixedHash::zero(), 0, 0, 0, 0))
+        .unwrap();
+
+    let estimate = wallet.estimate_recovery_duration(1_000).await.unwrap();
+    assert_eq!(estimate.tip_height, 1_999);
+    assert_eq!(estimate.blocks_to_scan, 1_000);
+    let per_block = Duration::from_millis(WalletConfig::default().recovery_scan_ms_per_block);
+    assert_eq!(estimate.estimated_duration, per_block * 1_000);
+
+    let double = wallet.estimate_recovery_duration(0).await.unwrap();
+    assert_eq!(double.blocks_to_scan, 2_000);
+    assert_eq!(double.estimated_duration, estimate.estimated_duration * 2);
+
+    shutdown.trigger();
+    wallet.wait_until_shutdown().await;
+}
+
+#[tokio::test]
+async fn test_wallet_auto_lock() {
+    let factories = CryptoFactories::default();
+    let consensus_manager = ConsensusManager::builder(Network::LocalNet).build();
+    let db_tempdir = tempdir().unwrap();
+    let passphrase = SafePassword::from("auto lock passphrase");
+    let shutdown = Shutdown::new();
+    let wallet = create_wallet(
+        db_tempdir.path(),
+        "auto_lock_db",
+        consensus_manager,
+        factories,
+        shutdown.to_signal(),
+        passphrase.clone(),
+        None,
+    )
+    .await
+    .unwrap();
+
+    wallet.key_manager_service.add_new_branch("auto_lock").await.unwrap();
+    // With the clock paused, sleeping advances the time straight to the next timer, so the inactivity timer always
+    // runs before a sleep that ends after it
+    time::pause();
+    wallet.set_auto_lock(Duration::from_secs(60));
+    assert!(!wallet.is_locked());
+    // Activity keeps the wallet unlocked
+    sleep(Duration::from_secs(40)).await;
+    assert!(wallet.list_all_commitments(false).unwrap().is_empty());
+    sleep(Duration::from_secs(50)).await;
+    assert!(!wallet.is_locked());
+
+    sleep(Duration::from_secs(15)).await;
+    assert!(wallet.is_locked());
+    assert!(matches!(wallet.list_all_commitments(false), Err(WalletError::Locked)));
+    assert!(matches!(
+        wallet.get_seed_words(&MnemonicLanguage::English),
+        Err(WalletError::Locked)
+    ));
+    // The database ciphers have been discarded as well, so the services can't touch encrypted data either
+    assert!(matches!(wallet.db.get_master_seed(), Err(WalletStorageError::Locked)));
+    assert!(wallet.key_manager_service.get_next_key("auto_lock").await.is_err());
+
+    assert!(matches!(
+        wallet.unlock(&SafePassword::from("wrong passphrase")),
+        Err(WalletError::WalletStorageError(WalletStorageError::InvalidPassphrase))
+    ));
+    assert!(wallet.is_locked());
+
+    wallet.unlock(&passphrase).unwrap();
+    assert!(!wallet.is_locked());
+    assert!(wallet.get_seed_words(&MnemonicLanguage::English).is_ok());
+    assert!(wallet.key_manager_service.get_next_key("auto_lock").await.is_ok());
+
+    // The timer is re-armed after unlocking
+    sleep(Duration::from_secs(59)).await;
+    assert!(!wallet.is_locked());
+    sleep(Duration::from_secs(2)).await;
+    assert!(wallet.is_locked());
+}
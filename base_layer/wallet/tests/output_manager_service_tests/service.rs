@@ -23,15 +23,18 @@
 use std::{collections::HashMap, convert::TryInto, mem::size_of, sync::Arc, time::Duration};
 
 use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
+use diesel::RunQueryDsl;
 use rand::{rngs::OsRng, RngCore};
+use tari_common_sqlite::sqlite_connection_pool::PooledDbConnection;
 use tari_common_types::{
     transaction::TxId,
-    types::{ComAndPubSignature, PrivateKey, PublicKey},
+    types::{ComAndPubSignature, FixedHash, PrivateKey, PublicKey},
 };
 use tari_comms::{
     peer_manager::{NodeIdentity, PeerFeatures},
-    protocol::rpc::{mock::MockRpcServer, NamedProtocolService},
+    protocol::rpc::{mock::MockRpcServer, NamedProtocolService, RpcStatus},
     test_utils::node_identity::build_node_identity,
+    types::CommsDHKE,
 };
 use tari_core::{
     base_node::rpc::BaseNodeWalletRpcServer,
@@ -42,7 +45,7 @@ use tari_core::{
     transactions::{
         fee::Fee,
         tari_amount::{uT, MicroTari},
-        test_helpers::{create_non_recoverable_unblinded_output, TestParams as TestParamsHelpers},
+        test_helpers::{create_non_recoverable_unblinded_output, TestParams as TestParamsHelpers, UtxoTestParams},
         transaction_components::{EncryptedData, OutputFeatures, OutputType, TransactionOutput, UnblindedOutput},
         transaction_protocol::{sender::TransactionSenderMessage, RecoveryData, TransactionMetadata},
         weight::TransactionWeight,
@@ -52,6 +55,7 @@ use tari_core::{
 };
 use tari_crypto::{
     commitment::HomomorphicCommitmentFactory,
+    hash::blake2::Blake256,
     keys::{PublicKey as PublicKeyTrait, SecretKey},
 };
 use tari_key_manager::{
@@ -68,10 +72,10 @@ use tari_key_manager::{
     mnemonic::Mnemonic,
     SeedWords,
 };
-use tari_script::{inputs, script, TariScript};
+use tari_script::{inputs, one_sided_payment_script, script, ExecutionStack, TariScript};
 use tari_service_framework::reply_channel;
 use tari_shutdown::Shutdown;
-use tari_utilities::Hidden;
+use tari_utilities::{hex::Hex, Hidden};
 use tari_wallet::{
     base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle},
     connectivity_service::{create_wallet_connectivity_mock, WalletConnectivityMock},
@@ -83,14 +87,16 @@ use tari_wallet::{
         service::OutputManagerService,
         storage::{
             database::{OutputManagerBackend, OutputManagerDatabase},
-            models::SpendingPriority,
+            models::{DbUnblindedOutput, KnownOneSidedPaymentScript, SpendingPriority},
             sqlite_db::OutputManagerSqliteDatabase,
+            OutputSource,
             OutputStatus,
         },
         UtxoSelectionCriteria,
     },
     test_utils::create_consensus_constants,
     transaction_service::handle::TransactionServiceHandle,
+    util::one_sided::shared_secret_to_output_encryption_key,
 };
 use tokio::{
     sync::{broadcast, broadcast::channel},
@@ -124,12 +130,21 @@ struct TestOmsService<U> {
     pub recovery_data: RecoveryData,
 }
 
+async fn setup_output_manager_service<T: OutputManagerBackend + 'static, U: KeyManagerBackend + 'static>(
+    backend: T,
+    ks_backend: U,
+    with_connection: bool,
+) -> TestOmsService<U> {
+    setup_output_manager_service_with_config(backend, ks_backend, with_connection, Default::default()).await
+}
+
 #[allow(clippy::type_complexity)]
 #[allow(clippy::too_many_lines)]
-async fn setup_output_manager_service<T: OutputManagerBackend + 'static, U: KeyManagerBackend + 'static>(
+async fn setup_output_manager_service_with_config<T: OutputManagerBackend + 'static, U: KeyManagerBackend + 'static>(
     backend: T,
     ks_backend: U,
     with_connection: bool,
+    config: OutputManagerServiceConfig,
 ) -> TestOmsService<U> {
     let shutdown = Shutdown::new();
     let factories = CryptoFactories::default();
@@ -186,7 +201,7 @@ async fn setup_output_manager_service<T: OutputManagerBackend + 'static, U: KeyM
     let key_manager = KeyManagerHandle::new(cipher_seed.clone(), KeyManagerDatabase::new(ks_backend));
 
     let output_manager_service = OutputManagerService::new(
-        OutputManagerServiceConfig { ..Default::default() },
+        config,
         oms_request_receiver,
         OutputManagerDatabase::new(backend),
         oms_event_publisher.clone(),
@@ -666,6 +681,57 @@ async fn test_utxo_selection_with_chain_metadata() {
     }
 }
 
+#[tokio::test]
+async fn test_estimate_sweep() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let server_node_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    // setup with chain metadata at a height of 6
+    let (mut oms, _shutdown, _, _, _) = setup_oms_with_bn_state(
+        OutputManagerSqliteDatabase::new(connection, cipher),
+        Some(6),
+        server_node_identity,
+    )
+    .await;
+    let fee_calc = Fee::new(*create_consensus_constants(0).transaction_weight());
+    let fee_per_gram = MicroTari::from(2);
+
+    // no utxos - not enough funds
+    let err = oms.estimate_sweep(fee_per_gram).await.unwrap_err();
+    assert!(matches!(err, OutputManagerError::NotEnoughFunds));
+
+    // create 10 utxos with maturity at heights from 1 to 10, only the first 6 are spendable
+    let amount = MicroTari::from(1000);
+    for i in 1..=10 {
+        let (_, uo) = make_input_with_features(
+            &mut OsRng.clone(),
+            i * amount,
+            &factories.commitment,
+            Some(OutputFeatures {
+                maturity: i,
+                ..Default::default()
+            }),
+        )
+        .await;
+        oms.add_output(uo.clone(), None).await.unwrap();
+    }
+
+    let (sweep_amount, num_inputs) = oms.estimate_sweep(fee_per_gram).await.unwrap();
+    assert_eq!(num_inputs, 6);
+    let expected_fee = fee_calc.calculate(fee_per_gram, 1, 6, 1, default_features_and_scripts_size_byte_size());
+
+    let balance = oms.get_balance().await.unwrap();
+    let spendable_balance = balance.available_balance - balance.time_locked_balance.unwrap();
+    assert_eq!(spendable_balance, (1..=6).map(|i| i * amount).sum::<MicroTari>());
+    assert_eq!(sweep_amount + expected_fee, spendable_balance);
+}
+
 #[tokio::test]
 async fn test_utxo_selection_with_tx_priority() {
     let factories = CryptoFactories::default();
@@ -738,6 +804,343 @@ async fn test_utxo_selection_with_tx_priority() {
     assert_ne!(utxos[0].unblinded_output.features.output_type, OutputType::Coinbase);
 }
 
+#[tokio::test]
+async fn test_utxo_selection_allowing_immature_coinbase() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let server_node_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    // setup with chain metadata at a height of 6
+    let (mut oms, _shutdown, _, _, _) = setup_oms_with_bn_state(
+        OutputManagerSqliteDatabase::new(connection, cipher),
+        Some(6),
+        server_node_identity,
+    )
+    .await;
+
+    let amount = MicroTari::from(2000);
+    let fee_per_gram = MicroTari::from(2);
+
+    // a coinbase that only matures at height 10
+    let (_, uo) = make_input_with_features(
+        &mut OsRng.clone(),
+        amount,
+        &factories.commitment,
+        Some(OutputFeatures::create_coinbase(10, None)),
+    )
+    .await;
+    oms.add_output(uo, None).await.unwrap();
+
+    // the default criteria excludes the immature coinbase
+    let err = oms
+        .prepare_transaction_to_send(
+            TxId::new_random(),
+            MicroTari::from(1000),
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            fee_per_gram,
+            TransactionMetadata::default(),
+            "".to_string(),
+            script!(Nop),
+            Covenant::default(),
+            MicroTari::zero(),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        OutputManagerError::NotEnoughFunds | OutputManagerError::FundsPending
+    ));
+    assert_eq!(oms.get_unspent_outputs().await.unwrap().len(), 1);
+
+    let stp = oms
+        .prepare_transaction_to_send(
+            TxId::new_random(),
+            MicroTari::from(1000),
+            UtxoSelectionCriteria {
+                allow_immature_coinbase: true,
+                ..Default::default()
+            },
+            OutputFeatures::default(),
+            fee_per_gram,
+            TransactionMetadata::default(),
+            "".to_string(),
+            script!(Nop),
+            Covenant::default(),
+            MicroTari::zero(),
+        )
+        .await
+        .unwrap();
+    assert!(stp.get_tx_id().is_ok());
+
+    // the immature coinbase was selected
+    assert!(oms.get_unspent_outputs().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_utxo_selection_preferring_confirmed_outputs() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let db = OutputManagerDatabase::new(OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone()));
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let config = OutputManagerServiceConfig {
+        num_confirmations_required: 3,
+        ..Default::default()
+    };
+    let mut oms = setup_output_manager_service_with_config(backend, ks_backend, true, config).await;
+
+    let fresh_values = [1_000 * uT, 2_000 * uT];
+    let confirmed_value = 5_000 * uT;
+    for value in fresh_values.iter().chain(Some(&confirmed_value)) {
+        let (_ti, uo) = make_non_recoverable_input(&mut OsRng, *value, &factories.commitment).await;
+        oms.output_manager_handle.add_output(uo, None).await.unwrap();
+    }
+
+    // The mocked base node tip is at i64::MAX, the fresh outputs only have a single confirmation
+    let tip = i64::MAX as u64;
+    for (mmr_pos, output) in db.fetch_all_unspent_outputs().unwrap().into_iter().enumerate() {
+        let mined_height = if output.unblinded_output.value == confirmed_value {
+            tip - 10
+        } else {
+            tip - 1
+        };
+        db.set_received_output_mined_height_and_status(
+            output.hash,
+            mined_height,
+            FixedHash::zero(),
+            mmr_pos as u64,
+            true,
+            0,
+        )
+        .unwrap();
+    }
+
+    // Smallest first would select a fresh output, but the confirmed one is preferred
+    oms.output_manager_handle
+        .prepare_transaction_to_send(
+            TxId::new_random(),
+            MicroTari::from(500),
+            UtxoSelectionCriteria {
+                prefer_confirmed: true,
+                ..UtxoSelectionCriteria::smallest_first()
+            },
+            OutputFeatures::default(),
+            MicroTari::from(2),
+            TransactionMetadata::default(),
+            "".to_string(),
+            script!(Nop),
+            Covenant::default(),
+            MicroTari::zero(),
+        )
+        .await
+        .unwrap();
+
+    let mut unspent = oms
+        .output_manager_handle
+        .get_unspent_outputs()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|o| o.unblinded_output.value)
+        .collect::<Vec<_>>();
+    unspent.sort();
+    assert_eq!(unspent, fresh_values.to_vec());
+}
+
+#[tokio::test]
+async fn test_has_spendable_funds() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let server_node_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    // setup with chain metadata at a height of 6
+    let (mut oms, _shutdown, _, _, _) = setup_oms_with_bn_state(
+        OutputManagerSqliteDatabase::new(connection, cipher),
+        Some(6),
+        server_node_identity,
+    )
+    .await;
+    assert!(!oms.has_spendable_funds().await.unwrap());
+
+    // a coinbase that only matures at height 10 cannot be spent yet
+    let (_, uo) = make_input_with_features(
+        &mut OsRng.clone(),
+        MicroTari::from(2000),
+        &factories.commitment,
+        Some(OutputFeatures::create_coinbase(10, None)),
+    )
+    .await;
+    oms.add_output(uo, None).await.unwrap();
+    assert!(!oms.has_spendable_funds().await.unwrap());
+
+    // coin selection never spends outputs other than standard and coinbase outputs
+    let (_, uo) = make_input_with_features(
+        &mut OsRng.clone(),
+        MicroTari::from(2000),
+        &factories.commitment,
+        Some(OutputFeatures {
+            output_type: OutputType::ValidatorNodeRegistration,
+            ..Default::default()
+        }),
+    )
+    .await;
+    oms.add_output(uo, None).await.unwrap();
+    assert!(!oms.has_spendable_funds().await.unwrap());
+
+    let (_, uo) = make_non_recoverable_input(&mut OsRng.clone(), MicroTari::from(2000), &factories.commitment).await;
+    oms.add_output(uo, None).await.unwrap();
+    assert!(oms.has_spendable_funds().await.unwrap());
+}
+
+#[tokio::test]
+async fn test_min_unspent_confirmations() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let server_node_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let db = OutputManagerDatabase::new(OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone()));
+    // setup with chain metadata at a height of 100
+    let (mut oms, _shutdown, _, _, _) = setup_oms_with_bn_state(
+        OutputManagerSqliteDatabase::new(connection, cipher),
+        Some(100),
+        server_node_identity,
+    )
+    .await;
+    assert_eq!(oms.min_unspent_confirmations().await.unwrap(), None);
+
+    let values = [1_000 * uT, 2_000 * uT, 3_000 * uT];
+    for value in values {
+        let (_ti, uo) = make_non_recoverable_input(&mut OsRng, value, &factories.commitment).await;
+        oms.add_output(uo, None).await.unwrap();
+    }
+    // None of the outputs have been mined yet
+    assert_eq!(oms.min_unspent_confirmations().await.unwrap(), None);
+
+    // The third output stays unmined and is not considered
+    for (mmr_pos, output) in db.fetch_all_unspent_outputs().unwrap().into_iter().enumerate() {
+        let mined_height = if output.unblinded_output.value == values[0] {
+            80
+        } else if output.unblinded_output.value == values[1] {
+            95
+        } else {
+            continue;
+        };
+        db.set_received_output_mined_height_and_status(
+            output.hash,
+            mined_height,
+            FixedHash::zero(),
+            mmr_pos as u64,
+            true,
+            0,
+        )
+        .unwrap();
+    }
+    assert_eq!(oms.min_unspent_confirmations().await.unwrap(), Some(5));
+}
+
+#[tokio::test]
+async fn test_unspent_outputs_skipping_corrupt() {
+    let factories = CryptoFactories::default();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection.clone(), cipher);
+
+    let mut oms = setup_output_manager_service_with_config(backend, ks_backend, true, OutputManagerServiceConfig {
+        skip_corrupt_outputs: true,
+        ..Default::default()
+    })
+    .await;
+
+    let mut commitments = Vec::new();
+    for value in [100_000, 200_000, 300_000] {
+        let (_, uo) = make_non_recoverable_input(&mut OsRng, MicroTari::from(value), &factories.commitment).await;
+        commitments.push(
+            uo.as_transaction_input(&factories.commitment)
+                .unwrap()
+                .commitment()
+                .unwrap()
+                .clone(),
+        );
+        oms.output_manager_handle.add_output(uo, None).await.unwrap();
+    }
+
+    let mut conn = connection.get_pooled_connection().unwrap();
+    let num_updated = diesel::sql_query(format!(
+        "UPDATE outputs SET encrypted_data = x'00' WHERE commitment = x'{}'",
+        commitments[1].to_hex()
+    ))
+    .execute(&mut conn)
+    .unwrap();
+    assert_eq!(num_updated, 1);
+    // A row whose commitment can't be decoded is reported by its id
+    let num_updated = diesel::sql_query(format!(
+        "UPDATE outputs SET commitment = x'00' WHERE commitment = x'{}'",
+        commitments[2].to_hex()
+    ))
+    .execute(&mut conn)
+    .unwrap();
+    assert_eq!(num_updated, 1);
+
+    let (outputs, mut corrupt) = oms
+        .output_manager_handle
+        .get_unspent_outputs_skipping_corrupt()
+        .await
+        .unwrap();
+    assert_eq!(outputs.len(), 1);
+    assert_eq!(outputs[0].commitment, commitments[0]);
+    corrupt.sort_by_key(|c| c.id);
+    assert_eq!(corrupt.len(), 2);
+    assert_eq!(corrupt[0].commitment, Some(commitments[1].clone()));
+    assert_eq!(corrupt[1].commitment, None);
+    assert_ne!(corrupt[0].id, corrupt[1].id);
+
+    // With `skip_corrupt_outputs` set the regular fetch skips the corrupt outputs as well
+    let outputs = oms.output_manager_handle.get_unspent_outputs().await.unwrap();
+    assert_eq!(outputs.len(), 1);
+    // So does the selection of outputs to spend
+    oms.output_manager_handle
+        .fee_estimate(
+            MicroTari::from(100),
+            UtxoSelectionCriteria::default(),
+            MicroTari::from(5),
+            1,
+            1,
+        )
+        .await
+        .unwrap();
+}
+
 #[tokio::test]
 async fn send_not_enough_funds() {
     let factories = CryptoFactories::default();
@@ -1032,9 +1435,235 @@ async fn cancel_transaction_and_reinstate_inbound_tx() {
 }
 
 #[tokio::test]
-async fn test_get_balance() {
-    let factories = CryptoFactories::default();
-
+async fn test_duplicate_sender_message_is_idempotent() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let oms_db = backend.clone();
+
+    let mut oms = setup_output_manager_service(backend, ks_backend, true).await;
+
+    let value = MicroTari::from(5000);
+    let (tx_id, sender_message) = generate_sender_transaction_message(value).await;
+    let rtp1 = oms
+        .output_manager_handle
+        .get_recipient_transaction(sender_message.clone())
+        .await
+        .unwrap();
+    let rtp2 = oms
+        .output_manager_handle
+        .get_recipient_transaction(sender_message)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        rtp1.get_signed_data().unwrap().output.commitment,
+        rtp2.get_signed_data().unwrap().output.commitment
+    );
+    let pending_incoming = oms_db.fetch_pending_incoming_outputs().unwrap();
+    assert_eq!(pending_incoming.len(), 1);
+    assert_eq!(pending_incoming[0].received_in_tx_id, Some(tx_id));
+
+    let balance = oms.output_manager_handle.get_balance().await.unwrap();
+    assert_eq!(balance.pending_incoming_balance, value);
+}
+
+#[tokio::test]
+async fn test_get_largest_unspent_outputs() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let mut oms = setup_output_manager_service(backend, ks_backend, true).await;
+
+    assert!(oms
+        .output_manager_handle
+        .get_largest_unspent_outputs(3)
+        .await
+        .unwrap()
+        .is_empty());
+
+    for value in [5000, 1000, 8000, 3000, 2000] {
+        let (_ti, uo) =
+            make_non_recoverable_input(&mut OsRng.clone(), MicroTari::from(value), &factories.commitment).await;
+        oms.output_manager_handle.add_output(uo, None).await.unwrap();
+    }
+    // An encumbered output must not be returned, even though it has the highest value
+    let (_ti, uo) = make_non_recoverable_input(&mut OsRng.clone(), MicroTari::from(10000), &factories.commitment).await;
+    oms.output_manager_handle
+        .add_unvalidated_output(TxId::new_random(), uo, None)
+        .await
+        .unwrap();
+
+    let largest = oms.output_manager_handle.get_largest_unspent_outputs(3).await.unwrap();
+    let values = largest.iter().map(|o| o.unblinded_output.value).collect::<Vec<_>>();
+    assert_eq!(values, vec![
+        MicroTari::from(8000),
+        MicroTari::from(5000),
+        MicroTari::from(3000)
+    ]);
+
+    let all = oms.output_manager_handle.get_largest_unspent_outputs(10).await.unwrap();
+    assert_eq!(all.len(), 5);
+}
+
+#[tokio::test]
+async fn test_get_value_histogram() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let mut oms = setup_output_manager_service(backend, ks_backend, true).await;
+
+    for value in [500, 1000, 1500, 2500, 9000, 10000, 50000] {
+        let (_ti, uo) =
+            make_non_recoverable_input(&mut OsRng.clone(), MicroTari::from(value), &factories.commitment).await;
+        oms.output_manager_handle.add_output(uo, None).await.unwrap();
+    }
+    // An encumbered output is not unspent and must not be counted
+    let (_ti, uo) = make_non_recoverable_input(&mut OsRng.clone(), MicroTari::from(1200), &factories.commitment).await;
+    oms.output_manager_handle
+        .add_unvalidated_output(TxId::new_random(), uo, None)
+        .await
+        .unwrap();
+
+    // The boundaries are given out of order and with a duplicate
+    let histogram = oms
+        .output_manager_handle
+        .get_value_histogram(vec![
+            MicroTari::from(10000),
+            MicroTari::from(1000),
+            MicroTari::from(3000),
+            MicroTari::from(1000),
+        ])
+        .await
+        .unwrap();
+    assert_eq!(histogram, vec![
+        (MicroTari::from(1000), 3),
+        (MicroTari::from(3000), 1),
+        (MicroTari::from(10000), 2),
+    ]);
+
+    let histogram = oms.output_manager_handle.get_value_histogram(vec![]).await.unwrap();
+    assert!(histogram.is_empty());
+}
+
+#[tokio::test]
+async fn test_vacuum_database() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let db = OutputManagerDatabase::new(OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone()));
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection.clone(), cipher);
+    let mut oms = setup_output_manager_service(backend, ks_backend, true).await;
+
+    let mut commitments = Vec::new();
+    for i in 1..=500u64 {
+        let (_ti, uo) = make_non_recoverable_input(&mut OsRng, MicroTari::from(i * 100), &factories.commitment).await;
+        let uo =
+            DbUnblindedOutput::from_unblinded_output(uo, &factories, None, OutputSource::Unknown, None, None).unwrap();
+        commitments.push(uo.commitment.clone());
+        db.add_unspent_output(uo).unwrap();
+    }
+    // Make sure that the inserted outputs are written to the database file before measuring it
+    oms.output_manager_handle.vacuum_database().await.unwrap();
+    let size_with_outputs = std::fs::metadata(connection.db_path()).unwrap().len();
+
+    for commitment in commitments {
+        db.remove_output_by_commitment(commitment).unwrap();
+    }
+    assert!(db.fetch_all_unspent_outputs().unwrap().is_empty());
+
+    let reclaimed = oms.output_manager_handle.vacuum_database().await.unwrap();
+    let size_after_vacuum = std::fs::metadata(connection.db_path()).unwrap().len();
+    assert!(reclaimed > 0);
+    assert!(size_after_vacuum < size_with_outputs);
+}
+
+#[tokio::test]
+async fn test_rotate_encryption() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+    OsRng.fill_bytes(&mut key);
+    let new_key_ga = Key::from_slice(&key);
+    let new_cipher = XChaCha20Poly1305::new(new_key_ga);
+
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection.clone(), cipher.clone());
+    let mut oms = setup_output_manager_service(backend, ks_backend, true).await;
+
+    let mut spending_keys = Vec::new();
+    for value in [1000 * uT, 2000 * uT, 3000 * uT] {
+        let (_ti, uo) = make_non_recoverable_input(&mut OsRng, value, &factories.commitment).await;
+        spending_keys.push(uo.spending_key.clone());
+        oms.output_manager_handle.add_output(uo, None).await.unwrap();
+    }
+
+    // Rotating with the wrong cipher fails and leaves the outputs encrypted with the current cipher
+    assert!(oms
+        .output_manager_handle
+        .rotate_encryption(new_cipher.clone(), cipher.clone())
+        .await
+        .is_err());
+    assert_eq!(oms.output_manager_handle.get_unspent_outputs().await.unwrap().len(), 3);
+
+    let num_rotated = oms
+        .output_manager_handle
+        .rotate_encryption(cipher.clone(), new_cipher.clone())
+        .await
+        .unwrap();
+    assert_eq!(num_rotated, 3);
+
+    // The service keeps working with the new cipher
+    assert_eq!(oms.output_manager_handle.get_unspent_outputs().await.unwrap().len(), 3);
+
+    // The stored outputs now only decrypt with the new cipher
+    let new_db = OutputManagerDatabase::new(OutputManagerSqliteDatabase::new(connection.clone(), new_cipher));
+    let decrypted_keys = new_db
+        .fetch_all_unspent_outputs()
+        .unwrap()
+        .into_iter()
+        .map(|o| o.unblinded_output.spending_key)
+        .collect::<Vec<_>>();
+    assert_eq!(decrypted_keys.len(), spending_keys.len());
+    assert!(spending_keys.iter().all(|k| decrypted_keys.contains(k)));
+    let old_db = OutputManagerDatabase::new(OutputManagerSqliteDatabase::new(connection, cipher));
+    assert!(old_db.fetch_all_unspent_outputs().is_err());
+}
+
+#[tokio::test]
+async fn test_get_balance() {
+    let factories = CryptoFactories::default();
+
     let (connection, _tempdir) = get_temp_sqlite_database_connection();
 
     let mut key = [0u8; size_of::<Key>()];
@@ -1283,7 +1912,8 @@ async fn coin_split_no_change() {
 }
 
 #[tokio::test]
-async fn handle_coinbase_with_bulletproofs_rewinding() {
+async fn split_specific_output() {
+    let factories = CryptoFactories::default();
     let (connection, _tempdir) = get_temp_sqlite_database_connection();
 
     let mut key = [0u8; size_of::<Key>()];
@@ -1295,75 +1925,93 @@ async fn handle_coinbase_with_bulletproofs_rewinding() {
     let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
     let mut oms = setup_output_manager_service(backend, ks_backend, true).await;
 
-    let reward1 = MicroTari::from(1000);
-    let fees1 = MicroTari::from(500);
-    let reward2 = MicroTari::from(2000);
-    let fees2 = MicroTari::from(500);
-    let reward3 = MicroTari::from(3000);
-    let fees3 = MicroTari::from(500);
-    let value3 = reward3 + fees3;
+    let val1 = 6_000 * uT;
+    let val2 = 7_000 * uT;
+    let val3 = 8_000 * uT;
+    let (_ti, uo1) = make_non_recoverable_input(&mut OsRng, val1, &factories.commitment).await;
+    let (_ti, uo2) = make_non_recoverable_input(&mut OsRng, val2, &factories.commitment).await;
+    let (_ti, uo3) = make_non_recoverable_input(&mut OsRng, val3, &factories.commitment).await;
+    let commitment = factories.commitment.commit_value(&uo2.spending_key, uo2.value.as_u64());
+    assert!(oms.output_manager_handle.add_output(uo1, None).await.is_ok());
+    assert!(oms.output_manager_handle.add_output(uo2, None).await.is_ok());
+    assert!(oms.output_manager_handle.add_output(uo3, None).await.is_ok());
 
-    let _transaction = oms
+    let fee_per_gram = MicroTari::from(5);
+    let split_count = 4;
+    let (_tx_id, coin_split_tx, amount) = oms
         .output_manager_handle
-        .get_coinbase_transaction(1u64.into(), reward1, fees1, 1, b"test".to_vec())
+        .split_specific_output(commitment.clone(), split_count, fee_per_gram)
         .await
         .unwrap();
-    assert_eq!(oms.output_manager_handle.get_unspent_outputs().await.unwrap().len(), 0);
-    // pending coinbases should not show up as pending incoming
-    assert_eq!(
-        oms.output_manager_handle
-            .get_balance()
-            .await
-            .unwrap()
-            .pending_incoming_balance,
-        MicroTari::from(0)
-    );
+    assert_eq!(coin_split_tx.body.inputs().len(), 1);
+    assert_eq!(coin_split_tx.body.inputs()[0].commitment().unwrap(), &commitment);
+    assert_eq!(coin_split_tx.body.outputs().len(), split_count);
+    assert_eq!(amount, val2);
 
-    let _tx2 = oms
+    // The output is now encumbered and cannot be split again
+    let err = oms
         .output_manager_handle
-        .get_coinbase_transaction(2u64.into(), reward2, fees2, 1, b"test".to_vec())
+        .split_specific_output(commitment, split_count, fee_per_gram)
         .await
-        .unwrap();
-    assert_eq!(oms.output_manager_handle.get_unspent_outputs().await.unwrap().len(), 0);
-    assert_eq!(
-        oms.output_manager_handle
-            .get_balance()
-            .await
-            .unwrap()
-            .pending_incoming_balance,
-        MicroTari::from(0)
-    );
-    let tx3 = oms
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        OutputManagerError::OutputManagerStorageError(OutputManagerStorageError::OutputAlreadyEncumbered)
+    ));
+
+    // An unknown output cannot be split
+    let (_ti, unknown) = make_non_recoverable_input(&mut OsRng, val1, &factories.commitment).await;
+    let unknown_commitment = factories
+        .commitment
+        .commit_value(&unknown.spending_key, unknown.value.as_u64());
+    let err = oms
         .output_manager_handle
-        .get_coinbase_transaction(3u64.into(), reward3, fees3, 2, b"test".to_vec())
+        .split_specific_output(unknown_commitment, split_count, fee_per_gram)
         .await
-        .unwrap();
-    assert_eq!(oms.output_manager_handle.get_unspent_outputs().await.unwrap().len(), 0);
-    assert_eq!(
-        oms.output_manager_handle
-            .get_balance()
-            .await
-            .unwrap()
-            .pending_incoming_balance,
-        MicroTari::from(0)
-    );
-
-    let output = tx3.body.outputs()[0].clone();
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        OutputManagerError::OutputManagerStorageError(OutputManagerStorageError::ValueNotFound)
+    ));
 
-    let (decrypted_value, _) = EncryptedData::decrypt_data(
-        &oms.recovery_data.encryption_key,
-        &output.commitment,
-        &output.encrypted_data,
+    // Outputs that are still to be received cannot be split
+    let pending_commitment = coin_split_tx.body.outputs()[0].commitment.clone();
+    let err = oms
+        .output_manager_handle
+        .split_specific_output(pending_commitment, split_count, fee_per_gram)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        OutputManagerError::OutputNotSpendable(OutputStatus::EncumberedToBeReceived)
+    ));
+
+    // Outputs that have not reached their maturity cannot be split
+    let (_ti, immature) = make_input_with_features(
+        &mut OsRng,
+        val1,
+        &factories.commitment,
+        Some(OutputFeatures {
+            maturity: u64::MAX,
+            ..Default::default()
+        }),
     )
-    .unwrap();
-    assert_eq!(decrypted_value, value3);
+    .await;
+    let immature_commitment = factories
+        .commitment
+        .commit_value(&immature.spending_key, immature.value.as_u64());
+    oms.output_manager_handle.add_output(immature, None).await.unwrap();
+    let err = oms
+        .output_manager_handle
+        .split_specific_output(immature_commitment, split_count, fee_per_gram)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, OutputManagerError::OutputNotMatured { maturity, .. } if maturity == u64::MAX));
 }
 
 #[tokio::test]
-#[allow(clippy::too_many_lines)]
-async fn test_txo_validation() {
+async fn auto_consolidation_joins_small_outputs() {
     let factories = CryptoFactories::default();
-
     let (connection, _tempdir) = get_temp_sqlite_database_connection();
 
     let mut key = [0u8; size_of::<Key>()];
@@ -1373,41 +2021,1008 @@ async fn test_txo_validation() {
 
     let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
     let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
-    let oms_db = backend.clone();
-
-    let mut oms = setup_output_manager_service(backend, ks_backend, true).await;
-
-    // Now we add the connection
-    let mut connection = oms
-        .mock_rpc_service
-        .create_connection(oms.node_id.to_peer(), "t/bnwallet/1".into())
-        .await;
-    oms.wallet_connectivity_mock
-        .set_base_node_wallet_rpc_client(connect_rpc_client(&mut connection).await);
-
-    let output1_value = 1_000_000;
-    let (_, output1) =
-        make_non_recoverable_input(&mut OsRng, MicroTari::from(output1_value), &factories.commitment).await;
-    let output1_tx_output = output1.as_transaction_output(&factories).unwrap();
+    let fee_per_gram = MicroTari::from(5);
+    let config = OutputManagerServiceConfig {
+        auto_consolidate_value_threshold: MicroTari::from(10_000),
+        auto_consolidate_max_outputs: 5,
+        auto_consolidate_fee_per_gram: fee_per_gram,
+        ..Default::default()
+    };
+    let mut oms = setup_output_manager_service_with_config(backend, ks_backend, true, config).await;
 
-    oms.output_manager_handle
-        .add_output_with_tx_id(TxId::from(1u64), output1.clone(), None)
+    // Not enough small outputs to trigger a consolidation
+    let small_value = 2_000 * uT;
+    let mut small_total = MicroTari::zero();
+    for _ in 0..5 {
+        let (_ti, uo) = make_non_recoverable_input(&mut OsRng, small_value, &factories.commitment).await;
+        oms.output_manager_handle.add_output(uo, None).await.unwrap();
+        small_total += small_value;
+    }
+    let large_value = 50_000 * uT;
+    let (_ti, uo) = make_non_recoverable_input(&mut OsRng, large_value, &factories.commitment).await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+    assert!(oms
+        .output_manager_handle
+        .run_auto_consolidation()
         .await
-        .unwrap();
+        .unwrap()
+        .is_none());
 
-    let output2_value = 2_000_000;
-    let (_, output2) =
-        make_non_recoverable_input(&mut OsRng, MicroTari::from(output2_value), &factories.commitment).await;
-    let output2_tx_output = output2.as_transaction_output(&factories).unwrap();
+    for _ in 0..5 {
+        let (_ti, uo) = make_non_recoverable_input(&mut OsRng, small_value, &factories.commitment).await;
+        oms.output_manager_handle.add_output(uo, None).await.unwrap();
+        small_total += small_value;
+    }
+    assert_eq!(oms.output_manager_handle.get_unspent_outputs().await.unwrap().len(), 11);
 
-    oms.output_manager_handle
-        .add_output_with_tx_id(TxId::from(2u64), output2.clone(), None)
+    let (_tx_id, tx, amount) = oms
+        .output_manager_handle
+        .run_auto_consolidation()
         .await
+        .unwrap()
         .unwrap();
+    assert_eq!(tx.body.inputs().len(), 10);
+    assert_eq!(tx.body.outputs().len(), 1);
+    assert_eq!(amount, small_total);
 
-    let output3_value = 4_000_000;
-    let (_, output3) =
-        make_non_recoverable_input(&mut OsRng, MicroTari::from(output3_value), &factories.commitment).await;
+    let fee_calc = Fee::new(*create_consensus_constants(0).transaction_weight());
+    let expected_fee = fee_calc.calculate(fee_per_gram, 1, 10, 1, default_features_and_scripts_size_byte_size());
+    assert_eq!(tx.body.get_total_fee(), expected_fee);
+
+    // Only the large output is left unspent, the small ones are now being joined into a single pending output
+    let unspent = oms.output_manager_handle.get_unspent_outputs().await.unwrap();
+    assert_eq!(unspent.len(), 1);
+    assert_eq!(unspent[0].unblinded_output.value, large_value);
+    let balance = oms.output_manager_handle.get_balance().await.unwrap();
+    assert_eq!(balance.available_balance, large_value);
+    assert_eq!(balance.pending_outgoing_balance, small_total);
+    assert_eq!(balance.pending_incoming_balance, small_total - expected_fee);
+}
+
+#[tokio::test]
+async fn auto_split_coinbase_splits_large_matured_coinbases() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let db = OutputManagerDatabase::new(OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone()));
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let fee_per_gram = MicroTari::from(5);
+    let split_count = 4;
+    let config = OutputManagerServiceConfig {
+        auto_split_coinbase_threshold: Some(MicroTari::from(1_000_000)),
+        auto_split_coinbase_count: split_count,
+        auto_split_coinbase_fee_per_gram: fee_per_gram,
+        ..Default::default()
+    };
+    let mut oms = setup_output_manager_service_with_config(backend, ks_backend, true, config).await;
+
+    let coinbase_value = 10_000_000 * uT;
+    let (_ti, uo) = make_input_with_features(
+        &mut OsRng,
+        coinbase_value,
+        &factories.commitment,
+        Some(OutputFeatures::create_coinbase(10, None)),
+    )
+    .await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+    // Neither a small coinbase nor a large regular output is split
+    let small_coinbase_value = 500_000 * uT;
+    let (_ti, uo) = make_input_with_features(
+        &mut OsRng,
+        small_coinbase_value,
+        &factories.commitment,
+        Some(OutputFeatures::create_coinbase(10, None)),
+    )
+    .await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+    let regular_value = 20_000_000 * uT;
+    let (_ti, uo) = make_non_recoverable_input(&mut OsRng, regular_value, &factories.commitment).await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+
+    let splits = oms.output_manager_handle.run_coinbase_auto_split().await.unwrap();
+    assert_eq!(splits.len(), 1);
+    let (tx_id, tx, amount) = &splits[0];
+    assert_eq!(*amount, coinbase_value);
+    assert_eq!(tx.body.inputs().len(), 1);
+    assert_eq!(tx.body.outputs().len(), split_count);
+
+    let fee_calc = Fee::new(*create_consensus_constants(0).transaction_weight());
+    let expected_fee = fee_calc.calculate(
+        fee_per_gram,
+        1,
+        1,
+        split_count,
+        default_features_and_scripts_size_byte_size() * split_count,
+    );
+    assert_eq!(tx.body.get_total_fee(), expected_fee);
+
+    let mut split_values = db
+        .fetch_outputs_by_tx_id(*tx_id)
+        .unwrap()
+        .into_iter()
+        .filter(|o| o.status == OutputStatus::EncumberedToBeReceived)
+        .map(|o| o.unblinded_output.value)
+        .collect::<Vec<_>>();
+    split_values.sort();
+    assert_eq!(split_values.len(), split_count);
+    let denomination = MicroTari((coinbase_value - expected_fee).as_u64() / split_count as u64);
+    assert!(split_values[..split_count - 1].iter().all(|v| *v == denomination));
+    assert_eq!(
+        split_values.iter().fold(MicroTari::zero(), |acc, v| acc + *v),
+        coinbase_value - expected_fee
+    );
+
+    let unspent = oms.output_manager_handle.get_unspent_outputs().await.unwrap();
+    assert_eq!(unspent.len(), 2);
+    assert!(unspent.iter().all(|o| o.unblinded_output.value != coinbase_value));
+
+    // The encumbered coinbase is not split a second time
+    assert!(oms
+        .output_manager_handle
+        .run_coinbase_auto_split()
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn auto_split_coinbase_continues_after_a_failed_split() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let split_count = 4;
+    let config = OutputManagerServiceConfig {
+        auto_split_coinbase_threshold: Some(MicroTari::from(10)),
+        auto_split_coinbase_count: split_count,
+        auto_split_coinbase_fee_per_gram: MicroTari::from(5),
+        ..Default::default()
+    };
+    let mut oms = setup_output_manager_service_with_config(backend, ks_backend, true, config).await;
+
+    // The first coinbase is above the threshold, but cannot pay the fee of its split
+    let unsplittable_value = 100 * uT;
+    let (_ti, uo) = make_input_with_features(
+        &mut OsRng,
+        unsplittable_value,
+        &factories.commitment,
+        Some(OutputFeatures::create_coinbase(10, None)),
+    )
+    .await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+    let coinbase_value = 10_000_000 * uT;
+    let (_ti, uo) = make_input_with_features(
+        &mut OsRng,
+        coinbase_value,
+        &factories.commitment,
+        Some(OutputFeatures::create_coinbase(10, None)),
+    )
+    .await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+
+    let splits = oms.output_manager_handle.run_coinbase_auto_split().await.unwrap();
+    assert_eq!(splits.len(), 1);
+    assert_eq!(splits[0].2, coinbase_value);
+
+    let unspent = oms.output_manager_handle.get_unspent_outputs().await.unwrap();
+    assert_eq!(unspent.len(), 1);
+    assert_eq!(unspent[0].unblinded_output.value, unsplittable_value);
+}
+
+#[tokio::test]
+async fn available_balance_applies_confirmation_requirements_by_output_type() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let db = OutputManagerDatabase::new(OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone()));
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let config = OutputManagerServiceConfig {
+        min_confirmations_by_output_type: [(OutputType::Coinbase, 10), (OutputType::Standard, 1)]
+            .into_iter()
+            .collect(),
+        ..Default::default()
+    };
+    let mut oms = setup_output_manager_service_with_config(backend, ks_backend, true, config).await;
+
+    let coinbase_value = 5_000 * uT;
+    let (_ti, uo) = make_input_with_features(
+        &mut OsRng,
+        coinbase_value,
+        &factories.commitment,
+        Some(OutputFeatures::create_coinbase(1, None)),
+    )
+    .await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+    let regular_value = 3_000 * uT;
+    let (_ti, uo) = make_non_recoverable_input(&mut OsRng, regular_value, &factories.commitment).await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+
+    // The mocked base node tip is at i64::MAX, so both outputs have 2 confirmations
+    let mined_height = i64::MAX as u64 - 2;
+    for (mmr_pos, output) in db.fetch_all_unspent_outputs().unwrap().into_iter().enumerate() {
+        db.set_received_output_mined_height_and_status(
+            output.hash,
+            mined_height,
+            FixedHash::zero(),
+            mmr_pos as u64,
+            true,
+            0,
+        )
+        .unwrap();
+    }
+
+    let balance = oms.output_manager_handle.get_balance().await.unwrap();
+    assert_eq!(balance.available_balance, regular_value);
+    assert_eq!(balance.pending_incoming_balance, coinbase_value);
+}
+
+#[tokio::test]
+async fn handle_coinbase_with_bulletproofs_rewinding() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let mut oms = setup_output_manager_service(backend, ks_backend, true).await;
+
+    let reward1 = MicroTari::from(1000);
+    let fees1 = MicroTari::from(500);
+    let reward2 = MicroTari::from(2000);
+    let fees2 = MicroTari::from(500);
+    let reward3 = MicroTari::from(3000);
+    let fees3 = MicroTari::from(500);
+    let value3 = reward3 + fees3;
+
+    let _transaction = oms
+        .output_manager_handle
+        .get_coinbase_transaction(1u64.into(), reward1, fees1, 1, b"test".to_vec())
+        .await
+        .unwrap();
+    assert_eq!(oms.output_manager_handle.get_unspent_outputs().await.unwrap().len(), 0);
+    // pending coinbases should not show up as pending incoming
+    assert_eq!(
+        oms.output_manager_handle
+            .get_balance()
+            .await
+            .unwrap()
+            .pending_incoming_balance,
+        MicroTari::from(0)
+    );
+
+    let _tx2 = oms
+        .output_manager_handle
+        .get_coinbase_transaction(2u64.into(), reward2, fees2, 1, b"test".to_vec())
+        .await
+        .unwrap();
+    assert_eq!(oms.output_manager_handle.get_unspent_outputs().await.unwrap().len(), 0);
+    assert_eq!(
+        oms.output_manager_handle
+            .get_balance()
+            .await
+            .unwrap()
+            .pending_incoming_balance,
+        MicroTari::from(0)
+    );
+    let tx3 = oms
+        .output_manager_handle
+        .get_coinbase_transaction(3u64.into(), reward3, fees3, 2, b"test".to_vec())
+        .await
+        .unwrap();
+    assert_eq!(oms.output_manager_handle.get_unspent_outputs().await.unwrap().len(), 0);
+    assert_eq!(
+        oms.output_manager_handle
+            .get_balance()
+            .await
+            .unwrap()
+            .pending_incoming_balance,
+        MicroTari::from(0)
+    );
+
+    let output = tx3.body.outputs()[0].clone();
+
+    let (decrypted_value, _) = EncryptedData::decrypt_data(
+        &oms.recovery_data.encryption_key,
+        &output.commitment,
+        &output.encrypted_data,
+    )
+    .unwrap();
+    assert_eq!(decrypted_value, value3);
+}
+
+#[tokio::test]
+async fn test_manual_validation_only() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let config = OutputManagerServiceConfig {
+        manual_validation_only: true,
+        ..Default::default()
+    };
+    let mut oms = setup_output_manager_service_with_config(backend, ks_backend, true, config).await;
+
+    let (_, output) = make_non_recoverable_input(&mut OsRng, MicroTari::from(1_000_000), &factories.commitment).await;
+    oms.output_manager_handle
+        .add_output_with_tx_id(TxId::from(1u64), output, None)
+        .await
+        .unwrap();
+
+    let mut block_header = BlockHeader::new(1);
+    block_header.height = 1;
+    oms.node_event
+        .send(Arc::new(BaseNodeEvent::NewBlockDetected(
+            (*block_header.hash()).into(),
+            1,
+        )))
+        .unwrap();
+
+    // The new block must not trigger a validation
+    assert!(oms
+        .base_node_wallet_rpc_mock_state
+        .wait_pop_utxo_query_calls(1, Duration::from_secs(3))
+        .await
+        .is_err());
+
+    oms.output_manager_handle.validate_txos().await.unwrap();
+    let _utxo_query_calls = oms
+        .base_node_wallet_rpc_mock_state
+        .wait_pop_utxo_query_calls(1, Duration::from_secs(60))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_find_highest_used_spend_key_index() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let mut oms = setup_output_manager_service(backend, ks_backend, true).await;
+
+    assert!(oms
+        .output_manager_handle
+        .find_highest_used_spend_key_index(5)
+        .await
+        .is_err());
+
+    // Outputs are held for the spend keys at indices 1, 2 and 6
+    for index in [1, 2, 6] {
+        let mut test_params = TestParamsHelpers::new();
+        test_params.spend_key = oms
+            .key_manager_handler
+            .get_key_at_index(OutputManagerKeyManagerBranch::Spend.get_branch_key(), index)
+            .await
+            .unwrap();
+        let uo = create_non_recoverable_unblinded_output(
+            script!(Nop),
+            OutputFeatures::default(),
+            &test_params,
+            MicroTari::from(10_000),
+        )
+        .unwrap();
+        oms.output_manager_handle.add_output(uo, None).await.unwrap();
+    }
+    // An output with a key that wasn't derived from the branch doesn't count
+    let (_ti, uo) = make_non_recoverable_input(&mut OsRng, MicroTari::from(10_000), &factories.commitment).await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+
+    assert_eq!(
+        oms.output_manager_handle
+            .find_highest_used_spend_key_index(3)
+            .await
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        oms.output_manager_handle
+            .find_highest_used_spend_key_index(4)
+            .await
+            .unwrap(),
+        6
+    );
+}
+
+#[tokio::test]
+async fn test_custom_key_manager_branches() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let config = OutputManagerServiceConfig {
+        script_key_branch: "custom_script".to_string(),
+        sender_offset_key_branch: "custom_sender_offset".to_string(),
+        ..Default::default()
+    };
+    let mut oms = setup_output_manager_service_with_config(backend, ks_backend, true, config).await;
+
+    let (spend_key, script_key) = oms
+        .output_manager_handle
+        .get_next_spend_and_script_keys()
+        .await
+        .unwrap();
+    let index = oms
+        .key_manager_handler
+        .find_key_index(OutputManagerKeyManagerBranch::Spend.get_branch_key(), &spend_key)
+        .await
+        .unwrap();
+    let custom_script_key = oms
+        .key_manager_handler
+        .get_key_at_index("custom_script", index)
+        .await
+        .unwrap();
+    let default_script_key = oms
+        .key_manager_handler
+        .get_key_at_index(OutputManagerKeyManagerBranch::SpendScript.get_branch_key(), index)
+        .await
+        .unwrap();
+    assert_eq!(script_key, custom_script_key);
+    assert_ne!(script_key, default_script_key);
+
+    let custom_sender_offset_key = oms
+        .key_manager_handler
+        .get_key_at_index("custom_sender_offset", 0)
+        .await
+        .unwrap();
+    let default_sender_offset_key = oms
+        .key_manager_handler
+        .get_key_at_index(OutputManagerKeyManagerBranch::SenderOffset.get_branch_key(), 0)
+        .await
+        .unwrap();
+    assert_ne!(custom_sender_offset_key, default_sender_offset_key);
+
+    let invalid_configs = [
+        ("", "custom_sender_offset"),
+        ("custom_script", ""),
+        ("custom", "custom"),
+        ("coinbase", "custom_sender_offset"),
+        ("custom_script", "script"),
+    ];
+    for (script_key_branch, sender_offset_key_branch) in invalid_configs {
+        let config = OutputManagerServiceConfig {
+            script_key_branch: script_key_branch.to_string(),
+            sender_offset_key_branch: sender_offset_key_branch.to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate_key_branches(),
+            Err(OutputManagerError::InvalidKeyManagerBranch(_))
+        ));
+    }
+    assert!(OutputManagerServiceConfig::default().validate_key_branches().is_ok());
+}
+
+#[tokio::test]
+async fn test_max_fee_per_transaction() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let max_fee = MicroTari::from(100);
+    let config = OutputManagerServiceConfig {
+        max_fee_per_transaction: Some(max_fee),
+        ..Default::default()
+    };
+    let mut oms = setup_output_manager_service_with_config(backend, ks_backend, true, config).await;
+
+    let (_ti, uo) = make_non_recoverable_input(&mut OsRng.clone(), 20_000 * uT, &factories.commitment).await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+
+    let err = oms
+        .output_manager_handle
+        .prepare_transaction_to_send(
+            TxId::new_random(),
+            MicroTari::from(1000),
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            MicroTari::from(20),
+            TransactionMetadata::default(),
+            "".to_string(),
+            script!(Nop),
+            Covenant::default(),
+            MicroTari::zero(),
+        )
+        .await
+        .unwrap_err();
+    match err {
+        OutputManagerError::FeeExceedsMaximum { fee, max } => {
+            assert!(fee > max);
+            assert_eq!(max, max_fee);
+        },
+        _ => panic!("Expected FeeExceedsMaximum, got {:?}", err),
+    }
+    // Nothing should have been encumbered by the rejected transaction
+    let balance = oms.output_manager_handle.get_balance().await.unwrap();
+    assert_eq!(balance.pending_outgoing_balance, MicroTari::from(0));
+
+    let stp = oms
+        .output_manager_handle
+        .prepare_transaction_to_send_with_fee_override(
+            TxId::new_random(),
+            MicroTari::from(1000),
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            MicroTari::from(20),
+            TransactionMetadata::default(),
+            "".to_string(),
+            script!(Nop),
+            Covenant::default(),
+            MicroTari::zero(),
+        )
+        .await
+        .unwrap();
+    assert!(stp.get_fee_amount().unwrap() > max_fee);
+}
+
+#[tokio::test]
+async fn test_max_fee_per_transaction_includes_fee_rounding() {
+    let factories = CryptoFactories::default();
+    let max_fee = MicroTari::from(999);
+
+    for (fee_rounding, should_succeed) in [(None, true), (Some(MicroTari::from(1000)), false)] {
+        let (connection, _tempdir) = get_temp_sqlite_database_connection();
+        let mut key = [0u8; size_of::<Key>()];
+        OsRng.fill_bytes(&mut key);
+        let key_ga = Key::from_slice(&key);
+        let cipher = XChaCha20Poly1305::new(key_ga);
+
+        let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+        let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+        let config = OutputManagerServiceConfig {
+            max_fee_per_transaction: Some(max_fee),
+            fee_rounding,
+            ..Default::default()
+        };
+        let mut oms = setup_output_manager_service_with_config(backend, ks_backend, true, config).await;
+
+        let (_ti, uo) = make_non_recoverable_input(&mut OsRng.clone(), 20_000 * uT, &factories.commitment).await;
+        oms.output_manager_handle.add_output(uo, None).await.unwrap();
+
+        // The unrounded fee is well below the maximum, but rounding it up pushes it over
+        let result = oms
+            .output_manager_handle
+            .prepare_transaction_to_send(
+                TxId::new_random(),
+                MicroTari::from(1000),
+                UtxoSelectionCriteria::default(),
+                OutputFeatures::default(),
+                MicroTari::from(1),
+                TransactionMetadata::default(),
+                "".to_string(),
+                script!(Nop),
+                Covenant::default(),
+                MicroTari::zero(),
+            )
+            .await;
+        if should_succeed {
+            assert!(result.unwrap().get_fee_amount().unwrap() <= max_fee);
+        } else {
+            match result.unwrap_err() {
+                OutputManagerError::FeeExceedsMaximum { fee, max } => {
+                    assert_eq!(fee, MicroTari::from(1000));
+                    assert_eq!(max, max_fee);
+                },
+                err => panic!("Expected FeeExceedsMaximum, got {:?}", err),
+            }
+            let balance = oms.output_manager_handle.get_balance().await.unwrap();
+            assert_eq!(balance.pending_outgoing_balance, MicroTari::from(0));
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_max_inputs_per_transaction() {
+    let factories = CryptoFactories::default();
+
+    for (max_inputs, should_succeed) in [(3, false), (20, true)] {
+        let (connection, _tempdir) = get_temp_sqlite_database_connection();
+        let mut key = [0u8; size_of::<Key>()];
+        OsRng.fill_bytes(&mut key);
+        let key_ga = Key::from_slice(&key);
+        let cipher = XChaCha20Poly1305::new(key_ga);
+
+        let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+        let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+        let config = OutputManagerServiceConfig {
+            max_inputs_per_transaction: max_inputs,
+            ..Default::default()
+        };
+        let mut oms = setup_output_manager_service_with_config(backend, ks_backend, true, config).await;
+
+        for _ in 0..10 {
+            let (_ti, uo) = make_non_recoverable_input(&mut OsRng.clone(), 2_000 * uT, &factories.commitment).await;
+            oms.output_manager_handle.add_output(uo, None).await.unwrap();
+        }
+
+        let result = oms
+            .output_manager_handle
+            .prepare_transaction_to_send(
+                TxId::new_random(),
+                MicroTari::from(10_000),
+                UtxoSelectionCriteria::default(),
+                OutputFeatures::default(),
+                MicroTari::from(1),
+                TransactionMetadata::default(),
+                "".to_string(),
+                script!(Nop),
+                Covenant::default(),
+                MicroTari::zero(),
+            )
+            .await;
+        if should_succeed {
+            let stp = result.unwrap();
+            assert!(stp.get_fee_amount().unwrap() > MicroTari::zero());
+        } else {
+            match result.unwrap_err() {
+                OutputManagerError::TooManyInputsRequired { max } => assert_eq!(max, max_inputs),
+                err => panic!("Expected TooManyInputsRequired, got {:?}", err),
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_ignore_received_dust() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let config = OutputManagerServiceConfig {
+        ignore_received_dust: true,
+        dust_fee_per_gram: MicroTari::from(5),
+        ..Default::default()
+    };
+    let mut oms = setup_output_manager_service_with_config(backend, ks_backend, true, config).await;
+
+    let (receiver_sk, receiver_pk) = PublicKey::random_keypair(&mut OsRng);
+    let script = one_sided_payment_script(&receiver_pk);
+    oms.output_manager_handle
+        .add_known_script(KnownOneSidedPaymentScript {
+            script_hash: script.as_hash::<Blake256>().unwrap().to_vec(),
+            private_key: receiver_sk,
+            script: script.clone(),
+            input: ExecutionStack::default(),
+            script_lock_height: 0,
+        })
+        .await
+        .unwrap();
+
+    let one_sided_output = |value: MicroTari| {
+        let mut test_params = TestParamsHelpers::new();
+        let shared_secret = CommsDHKE::new(&test_params.sender_offset_private_key, &receiver_pk);
+        test_params.recovery_data = RecoveryData {
+            encryption_key: shared_secret_to_output_encryption_key(&shared_secret).unwrap(),
+        };
+        test_params
+            .create_unblinded_output_with_recovery_data(UtxoTestParams {
+                value,
+                script: script.clone(),
+                ..Default::default()
+            })
+            .unwrap()
+            .as_transaction_output(&factories)
+            .unwrap()
+    };
+    // Spending a single input at 5 uT per gram costs 40 uT
+    let dust = one_sided_output(MicroTari::from(30));
+    let payment = one_sided_output(MicroTari::from(5_000));
+
+    let recovered = oms
+        .output_manager_handle
+        .scan_outputs_for_one_sided_payments(vec![dust, payment])
+        .await
+        .unwrap();
+    assert_eq!(recovered.len(), 1);
+    assert_eq!(recovered[0].output.value, MicroTari::from(5_000));
+
+    let balance = oms.output_manager_handle.get_balance().await.unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(5_000));
+    let unspent = oms.output_manager_handle.get_unspent_outputs().await.unwrap();
+    assert_eq!(unspent.len(), 1);
+}
+
+#[tokio::test]
+async fn test_fee_rounding() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let fee_rounding = MicroTari::from(1000);
+    let config = OutputManagerServiceConfig {
+        fee_rounding: Some(fee_rounding),
+        ..Default::default()
+    };
+    let mut oms = setup_output_manager_service_with_config(backend, ks_backend, true, config).await;
+
+    let input_value = 20_000 * uT;
+    let (_ti, uo) = make_non_recoverable_input(&mut OsRng.clone(), input_value, &factories.commitment).await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+
+    let amount = MicroTari::from(1000);
+    let stp = oms
+        .output_manager_handle
+        .prepare_transaction_to_send(
+            TxId::new_random(),
+            amount,
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            MicroTari::from(3),
+            TransactionMetadata::default(),
+            "".to_string(),
+            script!(Nop),
+            Covenant::default(),
+            MicroTari::zero(),
+        )
+        .await
+        .unwrap();
+    let fee = stp.get_fee_amount().unwrap();
+    assert_eq!(fee.as_u64() % fee_rounding.as_u64(), 0);
+    // The extra fee is taken from the change
+    let change = stp.get_change_amount().unwrap();
+    assert!(change > MicroTari::zero());
+    assert_eq!(amount + fee + change, input_value);
+}
+
+#[tokio::test]
+async fn test_min_change_value() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let config = OutputManagerServiceConfig {
+        min_change_value: Some(MicroTari::from(1000)),
+        ..Default::default()
+    };
+    let mut oms = setup_output_manager_service_with_config(backend, ks_backend, true, config).await;
+
+    let input_value = 20_000 * uT;
+    let (_ti, uo) = make_non_recoverable_input(&mut OsRng.clone(), input_value, &factories.commitment).await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+
+    // Sending all but 1000 uT leaves less than 1000 uT of change once the fee has been paid
+    let amount = MicroTari::from(19_000);
+    let stp = oms
+        .output_manager_handle
+        .prepare_transaction_to_send(
+            TxId::new_random(),
+            amount,
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            MicroTari::from(3),
+            TransactionMetadata::default(),
+            "".to_string(),
+            script!(Nop),
+            Covenant::default(),
+            MicroTari::zero(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(stp.get_change_amount().unwrap(), MicroTari::zero());
+    assert!(stp.get_change_unblinded_output().unwrap().is_none());
+    // The would-be change is absorbed by the fee
+    assert_eq!(stp.get_fee_amount().unwrap(), input_value - amount);
+
+    let balance = oms.output_manager_handle.get_balance().await.unwrap();
+    assert_eq!(balance.pending_incoming_balance, MicroTari::zero());
+}
+
+#[tokio::test]
+async fn test_auto_absorb_small_change() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let config = OutputManagerServiceConfig {
+        auto_absorb_small_change: true,
+        auto_consolidate_value_threshold: MicroTari::from(10_000),
+        ..Default::default()
+    };
+    let mut oms = setup_output_manager_service_with_config(backend, ks_backend, true, config).await;
+
+    let large_value = 200_000 * uT;
+    let (_ti, uo) = make_non_recoverable_input(&mut OsRng.clone(), large_value, &factories.commitment).await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+    let small_value = 5_000 * uT;
+    let (_ti, uo) = make_non_recoverable_input(&mut OsRng.clone(), small_value, &factories.commitment).await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+
+    // The large output alone covers the amount and leaves change
+    let amount = MicroTari::from(50_000);
+    let stp = oms
+        .output_manager_handle
+        .prepare_transaction_to_send(
+            TxId::new_random(),
+            amount,
+            UtxoSelectionCriteria::largest_first(),
+            OutputFeatures::default(),
+            MicroTari::from(4),
+            TransactionMetadata::default(),
+            "".to_string(),
+            script!(Nop),
+            Covenant::default(),
+            MicroTari::zero(),
+        )
+        .await
+        .unwrap();
+
+    // The small output is spent as well and the change combines both
+    assert!(oms
+        .output_manager_handle
+        .get_unspent_outputs()
+        .await
+        .unwrap()
+        .is_empty());
+    let fee = stp.get_fee_amount().unwrap();
+    let change = stp.get_change_amount().unwrap();
+    assert!(change > small_value);
+    assert_eq!(amount + fee + change, large_value + small_value);
+}
+
+#[tokio::test]
+async fn test_change_outputs_are_flagged() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let mut oms = setup_output_manager_service(backend, ks_backend, true).await;
+
+    let (_ti, uo) = make_non_recoverable_input(&mut OsRng.clone(), 20_000 * uT, &factories.commitment).await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+
+    let _stp = oms
+        .output_manager_handle
+        .prepare_transaction_to_send(
+            TxId::new_random(),
+            MicroTari::from(1000),
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            MicroTari::from(4),
+            TransactionMetadata::default(),
+            "".to_string(),
+            script!(Nop),
+            Covenant::default(),
+            MicroTari::zero(),
+        )
+        .await
+        .unwrap();
+
+    let (_ti, received) = make_non_recoverable_input(&mut OsRng.clone(), 5_000 * uT, &factories.commitment).await;
+    let received_commitment = received.as_transaction_output(&factories).unwrap().commitment;
+    oms.output_manager_handle.add_output(received, None).await.unwrap();
+
+    let outputs = oms.output_manager_handle.get_received_outputs(false).await.unwrap();
+    assert_eq!(outputs.len(), 2);
+    assert_eq!(outputs.iter().filter(|o| o.is_change()).count(), 1);
+
+    let outputs = oms.output_manager_handle.get_received_outputs(true).await.unwrap();
+    assert_eq!(outputs.len(), 1);
+    assert!(!outputs[0].is_change());
+    assert_eq!(outputs[0].commitment, received_commitment);
+}
+
+#[tokio::test]
+#[allow(clippy::too_many_lines)]
+async fn test_txo_validation() {
+    let factories = CryptoFactories::default();
+
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let oms_db = backend.clone();
+
+    let mut oms = setup_output_manager_service(backend, ks_backend, true).await;
+
+    // Now we add the connection
+    let mut connection = oms
+        .mock_rpc_service
+        .create_connection(oms.node_id.to_peer(), "t/bnwallet/1".into())
+        .await;
+    oms.wallet_connectivity_mock
+        .set_base_node_wallet_rpc_client(connect_rpc_client(&mut connection).await);
+
+    let output1_value = 1_000_000;
+    let (_, output1) =
+        make_non_recoverable_input(&mut OsRng, MicroTari::from(output1_value), &factories.commitment).await;
+    let output1_tx_output = output1.as_transaction_output(&factories).unwrap();
+
+    oms.output_manager_handle
+        .add_output_with_tx_id(TxId::from(1u64), output1.clone(), None)
+        .await
+        .unwrap();
+
+    let output2_value = 2_000_000;
+    let (_, output2) =
+        make_non_recoverable_input(&mut OsRng, MicroTari::from(output2_value), &factories.commitment).await;
+    let output2_tx_output = output2.as_transaction_output(&factories).unwrap();
+
+    oms.output_manager_handle
+        .add_output_with_tx_id(TxId::from(2u64), output2.clone(), None)
+        .await
+        .unwrap();
+
+    let output3_value = 4_000_000;
+    let (_, output3) =
+        make_non_recoverable_input(&mut OsRng, MicroTari::from(output3_value), &factories.commitment).await;
 
     oms.output_manager_handle
         .add_output_with_tx_id(TxId::from(3u64), output3.clone(), None)
@@ -2006,75 +3621,420 @@ async fn test_txo_revalidation() {
     };
 
     oms.base_node_wallet_rpc_mock_state
-        .set_query_deleted_response(query_deleted_response.clone());
+        .set_query_deleted_response(query_deleted_response.clone());
+    oms.output_manager_handle.validate_txos().await.unwrap();
+    let _utxo_query_calls = oms
+        .base_node_wallet_rpc_mock_state
+        .wait_pop_utxo_query_calls(1, Duration::from_secs(60))
+        .await
+        .unwrap();
+    let _query_deleted_calls = oms
+        .base_node_wallet_rpc_mock_state
+        .wait_pop_query_deleted(1, Duration::from_secs(60))
+        .await
+        .unwrap();
+
+    let unspent_txos = oms.output_manager_handle.get_unspent_outputs().await.unwrap();
+    assert_eq!(unspent_txos.len(), 2);
+
+    // This response sets output1 as spent
+    let query_deleted_response = QueryDeletedResponse {
+        best_block: block4_header.hash().to_vec(),
+        height_of_longest_chain: 4,
+        deleted_positions: vec![1],
+        not_deleted_positions: vec![2],
+        heights_deleted_at: vec![4],
+        blocks_deleted_in: vec![block4_header.hash().to_vec()],
+    };
+
+    oms.base_node_wallet_rpc_mock_state
+        .set_query_deleted_response(query_deleted_response.clone());
+    oms.output_manager_handle.revalidate_all_outputs().await.unwrap();
+    let _utxo_query_calls = oms
+        .base_node_wallet_rpc_mock_state
+        .wait_pop_utxo_query_calls(1, Duration::from_secs(60))
+        .await
+        .unwrap();
+    let _query_deleted_calls = oms
+        .base_node_wallet_rpc_mock_state
+        .wait_pop_query_deleted(1, Duration::from_secs(60))
+        .await
+        .unwrap();
+
+    let unspent_txos = oms.output_manager_handle.get_unspent_outputs().await.unwrap();
+    assert_eq!(unspent_txos.len(), 1);
+
+    // This response sets output1 and 2 as spent
+    let query_deleted_response = QueryDeletedResponse {
+        best_block: block4_header.hash().to_vec(),
+        height_of_longest_chain: 4,
+        deleted_positions: vec![1, 2],
+        not_deleted_positions: vec![],
+        heights_deleted_at: vec![4, 4],
+        blocks_deleted_in: vec![block4_header.hash().to_vec(), block4_header.hash().to_vec()],
+    };
+
+    oms.base_node_wallet_rpc_mock_state
+        .set_query_deleted_response(query_deleted_response.clone());
+    oms.output_manager_handle.revalidate_all_outputs().await.unwrap();
+    let _utxo_query_calls = oms
+        .base_node_wallet_rpc_mock_state
+        .wait_pop_utxo_query_calls(1, Duration::from_secs(60))
+        .await
+        .unwrap();
+    let _query_deleted_calls = oms
+        .base_node_wallet_rpc_mock_state
+        .wait_pop_query_deleted(1, Duration::from_secs(60))
+        .await
+        .unwrap();
+
+    let unspent_txos = oms.output_manager_handle.get_unspent_outputs().await.unwrap();
+    assert_eq!(unspent_txos.len(), 0);
+}
+
+#[tokio::test]
+async fn test_mark_output_spent_external() {
+    let factories = CryptoFactories::default();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+
+    let mut oms = setup_output_manager_service(backend, ks_backend, true).await;
+
+    let mut connection = oms
+        .mock_rpc_service
+        .create_connection(oms.node_id.to_peer(), "t/bnwallet/1".into())
+        .await;
+    oms.wallet_connectivity_mock
+        .set_base_node_wallet_rpc_client(connect_rpc_client(&mut connection).await);
+
+    let mut block1_header = BlockHeader::new(1);
+    block1_header.height = 1;
+    let mut block4_header = BlockHeader::new(1);
+    block4_header.height = 4;
+    let mut block_headers = HashMap::new();
+    block_headers.insert(1, block1_header.clone());
+    block_headers.insert(4, block4_header.clone());
+    oms.base_node_wallet_rpc_mock_state.set_blocks(block_headers);
+
+    let mut responses = Vec::new();
+    let mut commitments = Vec::new();
+    for (i, value) in [1_000_000u64, 2_000_000].into_iter().enumerate() {
+        let output = create_non_recoverable_unblinded_output(
+            script!(Nop),
+            OutputFeatures::default(),
+            &TestParamsHelpers::new(),
+            MicroTari::from(value),
+        )
+        .unwrap();
+        let tx_output = output.as_transaction_output(&factories).unwrap();
+        oms.output_manager_handle
+            .add_output_with_tx_id(TxId::from(i as u64 + 1), output, None)
+            .await
+            .unwrap();
+        responses.push(UtxoQueryResponse {
+            output: Some(tx_output.clone().try_into().unwrap()),
+            mmr_position: i as u64 + 1,
+            mined_height: 1,
+            mined_in_block: block1_header.hash().to_vec(),
+            output_hash: tx_output.hash().to_vec(),
+            mined_timestamp: 0,
+        });
+        commitments.push(tx_output.commitment);
+    }
+
+    oms.base_node_wallet_rpc_mock_state
+        .set_utxo_query_response(UtxoQueryResponses {
+            best_block: block4_header.hash().to_vec(),
+            height_of_longest_chain: 4,
+            responses,
+        });
+    // The base node never sees either output spent
+    oms.base_node_wallet_rpc_mock_state
+        .set_query_deleted_response(QueryDeletedResponse {
+            best_block: block4_header.hash().to_vec(),
+            height_of_longest_chain: 4,
+            deleted_positions: vec![],
+            not_deleted_positions: vec![1, 2],
+            heights_deleted_at: vec![],
+            blocks_deleted_in: vec![],
+        });
+
+    async fn wait_for_validation_success(event_stream: &mut broadcast::Receiver<Arc<OutputManagerEvent>>) -> bool {
+        let delay = sleep(Duration::from_secs(30));
+        tokio::pin!(delay);
+        loop {
+            tokio::select! {
+                event = event_stream.recv() => {
+                    if let OutputManagerEvent::TxoValidationSuccess(_) = &*event.unwrap() {
+                        return true;
+                    }
+                },
+                () = &mut delay => return false,
+            }
+        }
+    }
+
+    let mut event_stream = oms.output_manager_handle.get_event_stream();
+    oms.output_manager_handle.validate_txos().await.unwrap();
+    assert!(wait_for_validation_success(&mut event_stream).await);
+    let balance = oms.output_manager_handle.get_balance().await.unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(3_000_000));
+
+    oms.output_manager_handle
+        .mark_output_spent_external(commitments[0].clone(), Some(TxId::from(3u64)))
+        .await
+        .unwrap();
+    let balance = oms.output_manager_handle.get_balance().await.unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(2_000_000));
+    let unspent_txos = oms.output_manager_handle.get_unspent_outputs().await.unwrap();
+    assert_eq!(unspent_txos.len(), 1);
+    assert_eq!(unspent_txos[0].commitment, commitments[1]);
+
+    // An output that is no longer unspent cannot be marked again
+    assert!(matches!(
+        oms.output_manager_handle
+            .mark_output_spent_external(commitments[0].clone(), None)
+            .await,
+        Err(OutputManagerError::OutputManagerStorageError(
+            OutputManagerStorageError::OutputAlreadySpent
+        ))
+    ));
+
+    // The base node still reports the output as unspent, so validation reverts the external spend
+    oms.output_manager_handle.validate_txos().await.unwrap();
+    assert!(wait_for_validation_success(&mut event_stream).await);
+    let balance = oms.output_manager_handle.get_balance().await.unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(3_000_000));
+    let unspent_txos = oms.output_manager_handle.get_unspent_outputs().await.unwrap();
+    assert_eq!(unspent_txos.len(), 2);
+}
+
+#[tokio::test]
+async fn test_txo_validation_queries_base_node_in_chunks() {
+    let factories = CryptoFactories::default();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let config = OutputManagerServiceConfig {
+        tx_validator_batch_size: 2,
+        ..Default::default()
+    };
+    let mut oms = setup_output_manager_service_with_config(backend, ks_backend, true, config).await;
+
+    let mut connection = oms
+        .mock_rpc_service
+        .create_connection(oms.node_id.to_peer(), "t/bnwallet/1".into())
+        .await;
+    oms.wallet_connectivity_mock
+        .set_base_node_wallet_rpc_client(connect_rpc_client(&mut connection).await);
+
+    let mut block1_header = BlockHeader::new(1);
+    block1_header.height = 1;
+    let mut block4_header = BlockHeader::new(1);
+    block4_header.height = 4;
+    let mut block_headers = HashMap::new();
+    block_headers.insert(1, block1_header.clone());
+    block_headers.insert(4, block4_header.clone());
+    oms.base_node_wallet_rpc_mock_state.set_blocks(block_headers);
+
+    let num_outputs = 5;
+    let mut responses = Vec::with_capacity(num_outputs);
+    for i in 0..num_outputs {
+        let output = create_non_recoverable_unblinded_output(
+            script!(Nop),
+            OutputFeatures::default(),
+            &TestParamsHelpers::new(),
+            MicroTari::from(1_000_000 * (i as u64 + 1)),
+        )
+        .unwrap();
+        let tx_output = output.as_transaction_output(&factories).unwrap();
+        oms.output_manager_handle
+            .add_output_with_tx_id(TxId::from(i as u64 + 1), output, None)
+            .await
+            .unwrap();
+        responses.push(UtxoQueryResponse {
+            output: Some(tx_output.clone().try_into().unwrap()),
+            mmr_position: i as u64 + 1,
+            mined_height: 1,
+            mined_in_block: block1_header.hash().to_vec(),
+            output_hash: tx_output.hash().to_vec(),
+            mined_timestamp: 0,
+        });
+    }
+
+    oms.base_node_wallet_rpc_mock_state
+        .set_utxo_query_response(UtxoQueryResponses {
+            best_block: block4_header.hash().to_vec(),
+            height_of_longest_chain: 4,
+            responses,
+        });
+    oms.base_node_wallet_rpc_mock_state
+        .set_query_deleted_response(QueryDeletedResponse {
+            best_block: block4_header.hash().to_vec(),
+            height_of_longest_chain: 4,
+            deleted_positions: vec![],
+            not_deleted_positions: (1..=num_outputs as u64).collect(),
+            heights_deleted_at: vec![],
+            blocks_deleted_in: vec![],
+        });
+
+    let mut event_stream = oms.output_manager_handle.get_event_stream();
     oms.output_manager_handle.validate_txos().await.unwrap();
-    let _utxo_query_calls = oms
-        .base_node_wallet_rpc_mock_state
-        .wait_pop_utxo_query_calls(1, Duration::from_secs(60))
-        .await
-        .unwrap();
-    let _query_deleted_calls = oms
+
+    let utxo_query_calls = oms
         .base_node_wallet_rpc_mock_state
-        .wait_pop_query_deleted(1, Duration::from_secs(60))
+        .wait_pop_utxo_query_calls(3, Duration::from_secs(60))
         .await
         .unwrap();
+    assert_eq!(utxo_query_calls.iter().map(|c| c.len()).collect::<Vec<_>>(), vec![
+        2, 2, 1
+    ]);
+
+    let delay = sleep(Duration::from_secs(30));
+    tokio::pin!(delay);
+    let mut validated = false;
+    loop {
+        tokio::select! {
+            event = event_stream.recv() => {
+                if let OutputManagerEvent::TxoValidationSuccess(_) = &*event.unwrap() {
+                    validated = true;
+                    break;
+                }
+            },
+            () = &mut delay => {
+                break;
+            },
+        }
+    }
+    assert!(validated, "Validation should have succeeded");
 
     let unspent_txos = oms.output_manager_handle.get_unspent_outputs().await.unwrap();
-    assert_eq!(unspent_txos.len(), 2);
+    assert_eq!(unspent_txos.len(), num_outputs);
+    assert!(unspent_txos.iter().all(|o| o.mined_height == Some(1)));
+}
 
-    // This response sets output1 as spent
-    let query_deleted_response = QueryDeletedResponse {
-        best_block: block4_header.hash().to_vec(),
-        height_of_longest_chain: 4,
-        deleted_positions: vec![1],
-        not_deleted_positions: vec![2],
-        heights_deleted_at: vec![4],
-        blocks_deleted_in: vec![block4_header.hash().to_vec()],
+#[tokio::test]
+async fn test_failed_txo_validation_is_retried_automatically() {
+    let factories = CryptoFactories::default();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let config = OutputManagerServiceConfig {
+        auto_retry_failed_validation: true,
+        txo_validation_retry_delay: Duration::from_secs(1),
+        ..Default::default()
     };
+    let mut oms = setup_output_manager_service_with_config(backend, ks_backend, true, config).await;
 
-    oms.base_node_wallet_rpc_mock_state
-        .set_query_deleted_response(query_deleted_response.clone());
-    oms.output_manager_handle.revalidate_all_outputs().await.unwrap();
-    let _utxo_query_calls = oms
-        .base_node_wallet_rpc_mock_state
-        .wait_pop_utxo_query_calls(1, Duration::from_secs(60))
-        .await
-        .unwrap();
-    let _query_deleted_calls = oms
-        .base_node_wallet_rpc_mock_state
-        .wait_pop_query_deleted(1, Duration::from_secs(60))
+    let mut connection = oms
+        .mock_rpc_service
+        .create_connection(oms.node_id.to_peer(), "t/bnwallet/1".into())
+        .await;
+    oms.wallet_connectivity_mock
+        .set_base_node_wallet_rpc_client(connect_rpc_client(&mut connection).await);
+
+    let mut block1_header = BlockHeader::new(1);
+    block1_header.height = 1;
+    let mut block4_header = BlockHeader::new(1);
+    block4_header.height = 4;
+    let mut block_headers = HashMap::new();
+    block_headers.insert(1, block1_header.clone());
+    block_headers.insert(4, block4_header.clone());
+    oms.base_node_wallet_rpc_mock_state.set_blocks(block_headers);
+
+    let output = create_non_recoverable_unblinded_output(
+        script!(Nop),
+        OutputFeatures::default(),
+        &TestParamsHelpers::new(),
+        MicroTari::from(1_000_000),
+    )
+    .unwrap();
+    let tx_output = output.as_transaction_output(&factories).unwrap();
+    oms.output_manager_handle
+        .add_output_with_tx_id(TxId::from(1u64), output, None)
         .await
         .unwrap();
 
-    let unspent_txos = oms.output_manager_handle.get_unspent_outputs().await.unwrap();
-    assert_eq!(unspent_txos.len(), 1);
+    oms.base_node_wallet_rpc_mock_state
+        .set_utxo_query_response(UtxoQueryResponses {
+            best_block: block4_header.hash().to_vec(),
+            height_of_longest_chain: 4,
+            responses: vec![UtxoQueryResponse {
+                output: Some(tx_output.clone().try_into().unwrap()),
+                mmr_position: 1,
+                mined_height: 1,
+                mined_in_block: block1_header.hash().to_vec(),
+                output_hash: tx_output.hash().to_vec(),
+                mined_timestamp: 0,
+            }],
+        });
+    oms.base_node_wallet_rpc_mock_state
+        .set_query_deleted_response(QueryDeletedResponse {
+            best_block: block4_header.hash().to_vec(),
+            height_of_longest_chain: 4,
+            deleted_positions: vec![],
+            not_deleted_positions: vec![1],
+            heights_deleted_at: vec![],
+            blocks_deleted_in: vec![],
+        });
+    // The first attempt fails
+    oms.base_node_wallet_rpc_mock_state
+        .set_rpc_status_error(Some(RpcStatus::general("Unavailable")));
 
-    // This response sets output1 and 2 as spent
-    let query_deleted_response = QueryDeletedResponse {
-        best_block: block4_header.hash().to_vec(),
-        height_of_longest_chain: 4,
-        deleted_positions: vec![1, 2],
-        not_deleted_positions: vec![],
-        heights_deleted_at: vec![4, 4],
-        blocks_deleted_in: vec![block4_header.hash().to_vec(), block4_header.hash().to_vec()],
-    };
+    let mut event_stream = oms.output_manager_handle.get_event_stream();
+    oms.output_manager_handle.validate_txos().await.unwrap();
 
-    oms.base_node_wallet_rpc_mock_state
-        .set_query_deleted_response(query_deleted_response.clone());
-    oms.output_manager_handle.revalidate_all_outputs().await.unwrap();
-    let _utxo_query_calls = oms
-        .base_node_wallet_rpc_mock_state
-        .wait_pop_utxo_query_calls(1, Duration::from_secs(60))
-        .await
-        .unwrap();
-    let _query_deleted_calls = oms
-        .base_node_wallet_rpc_mock_state
-        .wait_pop_query_deleted(1, Duration::from_secs(60))
-        .await
-        .unwrap();
+    let delay = sleep(Duration::from_secs(30));
+    tokio::pin!(delay);
+    let mut retries = 0;
+    let mut validated = false;
+    loop {
+        tokio::select! {
+            event = event_stream.recv() => {
+                match &*event.unwrap() {
+                    OutputManagerEvent::TxoValidationRetrying { attempt, .. } => {
+                        retries = *attempt;
+                        // Let the retry succeed
+                        oms.base_node_wallet_rpc_mock_state.set_rpc_status_error(None);
+                    },
+                    OutputManagerEvent::TxoValidationSuccess(_) => {
+                        validated = true;
+                        break;
+                    },
+                    OutputManagerEvent::TxoValidationCommunicationFailure(_) => break,
+                    _ => (),
+                }
+            },
+            () = &mut delay => {
+                break;
+            },
+        }
+    }
+    assert_eq!(retries, 1);
+    assert!(validated, "Validation should have succeeded after a retry");
 
     let unspent_txos = oms.output_manager_handle.get_unspent_outputs().await.unwrap();
-    assert_eq!(unspent_txos.len(), 0);
+    assert_eq!(unspent_txos.len(), 1);
+    assert_eq!(unspent_txos[0].mined_height, Some(1));
 }
 
 #[tokio::test]
@@ -2231,6 +4191,285 @@ async fn scan_for_recovery_test() {
     }
 }
 
+#[tokio::test]
+async fn test_parallel_scan_for_recovery_matches_sequential_scan() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let mut oms = setup_output_manager_service(backend, ks_backend, true).await;
+
+    const NUM_OUTPUTS: usize = 40;
+
+    let encryption_key = oms
+        .key_manager_handler
+        .get_key_at_index(OutputManagerKeyManagerBranch::OpeningsEncryption.get_branch_key(), 0)
+        .await
+        .unwrap();
+    let mut outputs = Vec::with_capacity(NUM_OUTPUTS);
+    for i in 1..=NUM_OUTPUTS {
+        let amount = MicroTari::from(1_000 * i as u64);
+        // Interleave recoverable and non-recoverable outputs so that every thread gets a mix of both
+        let uo = if i % 2 == 0 {
+            let spending_key_result = oms
+                .key_manager_handler
+                .get_next_key(OutputManagerKeyManagerBranch::Spend.get_branch_key())
+                .await
+                .unwrap();
+            let script_key = oms
+                .key_manager_handler
+                .get_key_at_index(
+                    OutputManagerKeyManagerBranch::SpendScript.get_branch_key(),
+                    spending_key_result.index,
+                )
+                .await
+                .unwrap();
+            let commitment = factories
+                .commitment
+                .commit_value(&spending_key_result.key, amount.as_u64());
+            let encrypted_data =
+                EncryptedData::encrypt_data(&encryption_key, &commitment, amount, &spending_key_result.key).unwrap();
+            UnblindedOutput::new_current_version(
+                amount,
+                spending_key_result.key,
+                OutputFeatures::default(),
+                script!(Nop),
+                inputs!(PublicKey::from_secret_key(&script_key)),
+                script_key,
+                PublicKey::default(),
+                ComAndPubSignature::default(),
+                0,
+                Covenant::new(),
+                encrypted_data,
+                MicroTari::zero(),
+            )
+        } else {
+            make_non_recoverable_input(&mut OsRng, amount, &factories.commitment)
+                .await
+                .1
+        };
+        outputs.push(uo.as_transaction_output(&factories).unwrap());
+    }
+
+    let sequential = oms
+        .output_manager_handle
+        .scan_for_recoverable_outputs(outputs.clone())
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|ro| (ro.output.spending_key, ro.output.value))
+        .collect::<Vec<_>>();
+    assert_eq!(sequential.len(), NUM_OUTPUTS / 2);
+
+    for scan_parallelism in [2, 3, 8, 64] {
+        let (connection, _tempdir) = get_temp_sqlite_database_connection();
+        let backend = OutputManagerSqliteDatabase::new(connection.clone(), XChaCha20Poly1305::new(key_ga));
+        let ks_backend = KeyManagerSqliteDatabase::init(connection, XChaCha20Poly1305::new(key_ga));
+        let config = OutputManagerServiceConfig {
+            scan_parallelism,
+            ..Default::default()
+        };
+        let mut oms = setup_output_manager_service_with_config(backend, ks_backend, true, config).await;
+
+        let parallel = oms
+            .output_manager_handle
+            .scan_for_recoverable_outputs(outputs.clone())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|ro| (ro.output.spending_key, ro.output.value))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parallel, sequential,
+            "scan_parallelism = {} should recover the same outputs",
+            scan_parallelism
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_repeated_scan_for_recovery_uses_trial_decryption_cache() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let mut oms = setup_output_manager_service(backend, ks_backend, true).await;
+
+    const NUM_OUTPUTS: usize = 10;
+
+    let encryption_key = oms
+        .key_manager_handler
+        .get_key_at_index(OutputManagerKeyManagerBranch::OpeningsEncryption.get_branch_key(), 0)
+        .await
+        .unwrap();
+    let mut outputs = Vec::with_capacity(NUM_OUTPUTS);
+    for i in 1..=NUM_OUTPUTS {
+        let amount = MicroTari::from(1_000 * i as u64);
+        let uo = if i % 2 == 0 {
+            let spending_key_result = oms
+                .key_manager_handler
+                .get_next_key(OutputManagerKeyManagerBranch::Spend.get_branch_key())
+                .await
+                .unwrap();
+            let script_key = oms
+                .key_manager_handler
+                .get_key_at_index(
+                    OutputManagerKeyManagerBranch::SpendScript.get_branch_key(),
+                    spending_key_result.index,
+                )
+                .await
+                .unwrap();
+            let commitment = factories
+                .commitment
+                .commit_value(&spending_key_result.key, amount.as_u64());
+            let encrypted_data =
+                EncryptedData::encrypt_data(&encryption_key, &commitment, amount, &spending_key_result.key).unwrap();
+            UnblindedOutput::new_current_version(
+                amount,
+                spending_key_result.key,
+                OutputFeatures::default(),
+                script!(Nop),
+                inputs!(PublicKey::from_secret_key(&script_key)),
+                script_key,
+                PublicKey::default(),
+                ComAndPubSignature::default(),
+                0,
+                Covenant::new(),
+                encrypted_data,
+                MicroTari::zero(),
+            )
+        } else {
+            make_non_recoverable_input(&mut OsRng, amount, &factories.commitment)
+                .await
+                .1
+        };
+        outputs.push(uo.as_transaction_output(&factories).unwrap());
+    }
+
+    let recovered = oms
+        .output_manager_handle
+        .scan_for_recoverable_outputs(outputs.clone())
+        .await
+        .unwrap();
+    assert_eq!(recovered.len(), NUM_OUTPUTS / 2);
+    let first_pass = oms
+        .output_manager_handle
+        .get_trial_decryption_cache_stats()
+        .await
+        .unwrap();
+    assert_eq!(first_pass.hits, 0);
+    assert!(first_pass.misses > 0);
+    assert_eq!(first_pass.size as u64, first_pass.misses);
+
+    // Scanning the same range again only hits the cache. The recovered outputs are already known, so none are returned.
+    let recovered = oms
+        .output_manager_handle
+        .scan_for_recoverable_outputs(outputs)
+        .await
+        .unwrap();
+    assert!(recovered.is_empty());
+    let second_pass = oms
+        .output_manager_handle
+        .get_trial_decryption_cache_stats()
+        .await
+        .unwrap();
+    assert_eq!(second_pass.hits, first_pass.misses);
+    assert_eq!(second_pass.misses, first_pass.misses);
+    assert_eq!(second_pass.size, first_pass.size);
+}
+
+#[tokio::test]
+async fn test_scan_for_recovery_skips_excluded_output_types() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let ks_backend = KeyManagerSqliteDatabase::init(connection, cipher);
+    let config = OutputManagerServiceConfig {
+        scan_output_types: Some([OutputType::Standard].into_iter().collect()),
+        ..Default::default()
+    };
+    let mut oms = setup_output_manager_service_with_config(backend, ks_backend, true, config).await;
+
+    const NUM_OUTPUTS: usize = 6;
+
+    let encryption_key = oms
+        .key_manager_handler
+        .get_key_at_index(OutputManagerKeyManagerBranch::OpeningsEncryption.get_branch_key(), 0)
+        .await
+        .unwrap();
+    let mut outputs = Vec::with_capacity(NUM_OUTPUTS);
+    for i in 1..=NUM_OUTPUTS {
+        let amount = MicroTari::from(1_000 * i as u64);
+        // Every output is recoverable, but only every second one is a standard output
+        let features = if i % 2 == 0 {
+            OutputFeatures::default()
+        } else {
+            OutputFeatures::create_coinbase(1, None)
+        };
+        let spending_key_result = oms
+            .key_manager_handler
+            .get_next_key(OutputManagerKeyManagerBranch::Spend.get_branch_key())
+            .await
+            .unwrap();
+        let script_key = oms
+            .key_manager_handler
+            .get_key_at_index(
+                OutputManagerKeyManagerBranch::SpendScript.get_branch_key(),
+                spending_key_result.index,
+            )
+            .await
+            .unwrap();
+        let commitment = factories
+            .commitment
+            .commit_value(&spending_key_result.key, amount.as_u64());
+        let encrypted_data =
+            EncryptedData::encrypt_data(&encryption_key, &commitment, amount, &spending_key_result.key).unwrap();
+        let uo = UnblindedOutput::new_current_version(
+            amount,
+            spending_key_result.key,
+            features,
+            script!(Nop),
+            inputs!(PublicKey::from_secret_key(&script_key)),
+            script_key,
+            PublicKey::default(),
+            ComAndPubSignature::default(),
+            0,
+            Covenant::new(),
+            encrypted_data,
+            MicroTari::zero(),
+        );
+        outputs.push(uo.as_transaction_output(&factories).unwrap());
+    }
+
+    let recovered = oms
+        .output_manager_handle
+        .scan_for_recoverable_outputs(outputs)
+        .await
+        .unwrap();
+    assert_eq!(recovered.len(), NUM_OUTPUTS / 2);
+    assert!(recovered
+        .iter()
+        .all(|ro| ro.output.features.output_type == OutputType::Standard));
+}
+
 #[tokio::test]
 async fn recovered_output_key_not_in_keychain() {
     let factories = CryptoFactories::default();
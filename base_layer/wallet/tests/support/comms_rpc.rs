@@ -708,6 +708,11 @@ impl BaseNodeWalletService for BaseNodeWalletRpcMockService {
         let mut utxo_query_lock = acquire_lock!(self.state.utxo_query_calls);
         (*utxo_query_lock).push(message.output_hashes);
 
+        let status_lock = acquire_lock!(self.state.rpc_status_error);
+        if let Some(status) = (*status_lock).clone() {
+            return Err(status);
+        }
+
         let lock = acquire_lock!(self.state.utxo_query_response);
         Ok(Response::new(lock.clone()))
     }
@@ -31,6 +31,7 @@ use std::{
 
 use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
 use chrono::{Duration as ChronoDuration, Utc};
+use diesel::RunQueryDsl;
 use futures::{
     channel::{mpsc, mpsc::Sender},
     FutureExt,
@@ -38,11 +39,12 @@ use futures::{
 };
 use prost::Message;
 use rand::{rngs::OsRng, RngCore};
+use tari_common_sqlite::sqlite_connection_pool::PooledDbConnection;
 use tari_common_types::{
     chain_metadata::ChainMetadata,
     tari_address::TariAddress,
     transaction::{ImportStatus, TransactionDirection, TransactionStatus, TxId},
-    types::{FixedHash, PrivateKey, PublicKey, Signature},
+    types::{Commitment, FixedHash, PrivateKey, PublicKey, Signature},
 };
 use tari_comms::{
     message::EnvelopeBody,
@@ -110,6 +112,7 @@ use tari_wallet::{
     base_node_service::{config::BaseNodeServiceConfig, handle::BaseNodeServiceHandle, BaseNodeServiceInitializer},
     connectivity_service::{
         create_wallet_connectivity_mock,
+        OnlineStatus,
         WalletConnectivityHandle,
         WalletConnectivityInitializer,
         WalletConnectivityInterface,
@@ -140,7 +143,14 @@ use tari_wallet::{
         service::TransactionService,
         storage::{
             database::{DbKeyValuePair, TransactionBackend, TransactionDatabase, WriteOperation},
-            models::{CompletedTransaction, InboundTransaction, OutboundTransaction, WalletTransaction},
+            models::{
+                CompletedTransaction,
+                InboundTransaction,
+                OutboundTransaction,
+                SendAttempt,
+                SendAttemptMechanism,
+                WalletTransaction,
+            },
             sqlite_db::TransactionServiceSqliteDatabase,
         },
         TransactionServiceInitializer,
@@ -799,6 +809,78 @@ async fn send_one_sided_transaction_to_other() {
     assert!(found, "'TransactionCompletedImmediately(_)' event not found");
 }
 
+#[tokio::test]
+async fn test_verify_completed_transaction_outputs() {
+    let network = Network::LocalNet;
+    let consensus_manager = ConsensusManager::builder(network).build();
+    let factories = CryptoFactories::default();
+    let alice_node_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
+    let bob_node_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
+
+    let temp_dir = tempdir().unwrap();
+    let database_path = temp_dir.path().to_str().unwrap().to_string();
+    let (db_connection, _tempdir) = make_wallet_database_connection(Some(database_path.clone()));
+
+    let shutdown = Shutdown::new();
+    let (mut alice_ts, mut alice_oms, _alice_comms, _alice_connectivity) = setup_transaction_service(
+        alice_node_identity,
+        vec![],
+        consensus_manager,
+        factories.clone(),
+        db_connection.clone(),
+        database_path,
+        Duration::from_secs(0),
+        shutdown.to_signal(),
+    )
+    .await;
+
+    for _ in 0..2 {
+        let (_utxo, uo) = make_non_recoverable_input(&mut OsRng, 25000.into(), &factories.commitment).await;
+        alice_oms.add_output(uo, None).await.unwrap();
+    }
+
+    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), Network::LocalNet);
+    let mut tx_ids = Vec::new();
+    for _ in 0..2 {
+        let tx_id = alice_ts
+            .send_one_sided_transaction(
+                bob_address.clone(),
+                10000.into(),
+                UtxoSelectionCriteria::default(),
+                OutputFeatures::default(),
+                20.into(),
+                "verify me".to_string(),
+            )
+            .await
+            .unwrap();
+        assert!(alice_ts.verify_completed_transaction_outputs(tx_id).await.unwrap());
+        tx_ids.push(tx_id);
+    }
+
+    // Give the first change output the spending key of the second one, so that the stored spending key belongs to a
+    // key index the first change output's script key was not derived at
+    let mut conn = db_connection.get_pooled_connection().unwrap();
+    let num_updated = diesel::sql_query(format!(
+        "UPDATE outputs SET spending_key = (SELECT spending_key FROM outputs WHERE received_in_tx_id = {}) WHERE \
+         received_in_tx_id = {}",
+        tx_ids[1].as_i64_wrapped(),
+        tx_ids[0].as_i64_wrapped()
+    ))
+    .execute(&mut conn)
+    .unwrap();
+    assert_eq!(num_updated, 1);
+    assert!(!alice_ts.verify_completed_transaction_outputs(tx_ids[0]).await.unwrap());
+    assert!(alice_ts.verify_completed_transaction_outputs(tx_ids[1]).await.unwrap());
+}
+
 #[tokio::test]
 async fn recover_one_sided_transaction() {
     let network = Network::LocalNet;
@@ -1465,6 +1547,140 @@ async fn test_accepting_unknown_tx_id_and_malformed_reply() {
         .unwrap();
 }
 
+#[tokio::test]
+async fn test_max_pending_outbound_transactions() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = make_wallet_database_connection(None);
+
+    let mut alice_ts_interface = setup_transaction_service_no_comms(
+        factories.clone(),
+        connection,
+        Some(TransactionServiceConfig {
+            max_pending_outbound: 2,
+            ..Default::default()
+        }),
+    )
+    .await;
+
+    for _ in 0..4 {
+        let (_utxo, uo) = make_non_recoverable_input(&mut OsRng, MicroTari(250000), &factories.commitment).await;
+        alice_ts_interface
+            .output_manager_service_handle
+            .add_output(uo, None)
+            .await
+            .unwrap();
+    }
+
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), Network::LocalNet);
+
+    let tx_id = alice_ts_interface
+        .transaction_service_handle
+        .send_transaction(
+            bob_address.clone(),
+            MicroTari::from(5000),
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            MicroTari::from(20),
+            "".to_string(),
+        )
+        .await
+        .unwrap();
+    alice_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(1, Duration::from_secs(60))
+        .await
+        .unwrap();
+    let (_, body) = alice_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+    let envelope_body = EnvelopeBody::decode(body.to_vec().as_slice()).unwrap();
+    let sender_message = envelope_body
+        .decode_part::<proto::TransactionSenderMessage>(1)
+        .unwrap()
+        .unwrap();
+
+    alice_ts_interface
+        .transaction_service_handle
+        .send_transaction(
+            bob_address.clone(),
+            MicroTari::from(5000),
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            MicroTari::from(20),
+            "".to_string(),
+        )
+        .await
+        .unwrap();
+
+    // The cap has been reached
+    let result = alice_ts_interface
+        .transaction_service_handle
+        .send_transaction(
+            bob_address.clone(),
+            MicroTari::from(5000),
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            MicroTari::from(20),
+            "".to_string(),
+        )
+        .await;
+    assert!(matches!(
+        result,
+        Err(TransactionServiceError::TooManyPendingTransactions(2))
+    ));
+
+    // Complete the first transaction by replying to it
+    let params = TestParams::new(&mut OsRng);
+    let rtp = ReceiverTransactionProtocol::new(
+        sender_message.try_into().unwrap(),
+        params.nonce,
+        params.spend_key,
+        &factories,
+    );
+    let tx_reply = rtp.get_signed_data().unwrap().clone();
+    assert_eq!(tx_reply.tx_id, tx_id);
+    alice_ts_interface
+        .transaction_ack_message_channel
+        .send(create_dummy_message(
+            tx_reply.try_into().unwrap(),
+            bob_node_identity.public_key(),
+        ))
+        .await
+        .unwrap();
+
+    // Once the reply has been processed there is room for a new send
+    let mut sent = false;
+    for _ in 0..30 {
+        let result = alice_ts_interface
+            .transaction_service_handle
+            .send_transaction(
+                bob_address.clone(),
+                MicroTari::from(5000),
+                UtxoSelectionCriteria::default(),
+                OutputFeatures::default(),
+                MicroTari::from(20),
+                "".to_string(),
+            )
+            .await;
+        match result {
+            Ok(_) => {
+                sent = true;
+                break;
+            },
+            Err(TransactionServiceError::TooManyPendingTransactions(_)) => sleep(Duration::from_secs(1)).await,
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+    assert!(sent, "New send should be accepted once a pending transaction completed");
+
+    let completed_tx = alice_ts_interface
+        .transaction_service_handle
+        .get_completed_transaction(tx_id)
+        .await
+        .unwrap();
+    assert_eq!(completed_tx.tx_id, tx_id);
+}
+
 #[tokio::test]
 async fn finalize_tx_with_incorrect_pubkey() {
     let factories = CryptoFactories::default();
@@ -1578,7 +1794,7 @@ async fn finalize_tx_with_incorrect_pubkey() {
 }
 
 #[tokio::test]
-async fn finalize_tx_with_missing_output() {
+async fn finalizing_inbound_tx_publishes_event() {
     let factories = CryptoFactories::default();
 
     let temp_dir = tempdir().unwrap();
@@ -1599,13 +1815,11 @@ async fn finalize_tx_with_missing_output() {
     let mut bob_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection_bob, None).await;
 
     let (_utxo, uo) = make_non_recoverable_input(&mut OsRng, MicroTari(250000), &factories.commitment).await;
-
     bob_ts_interface
         .output_manager_service_handle
         .add_output(uo, None)
         .await
         .unwrap();
-
     let mut stp = bob_ts_interface
         .output_manager_service_handle
         .prepare_transaction_to_send(
@@ -1613,7 +1827,7 @@ async fn finalize_tx_with_missing_output() {
             MicroTari::from(5000),
             UtxoSelectionCriteria::default(),
             OutputFeatures::default(),
-            MicroTari::from(20),
+            MicroTari::from(25),
             TransactionMetadata::default(),
             "".to_string(),
             script!(Nop),
@@ -1649,22 +1863,25 @@ async fn finalize_tx_with_missing_output() {
         .try_into()
         .unwrap();
 
+    // Alice has replied, so the transaction awaits finalization by Bob
+    let pending_inbound = alice_ts_interface
+        .transaction_service_handle
+        .get_pending_inbound_transactions()
+        .await
+        .unwrap();
+    assert_eq!(pending_inbound.len(), 1);
+    assert_eq!(
+        pending_inbound.get(&recipient_reply.tx_id).unwrap().status,
+        TransactionStatus::Pending
+    );
+
     stp.add_single_recipient_info(recipient_reply.clone()).unwrap();
     stp.finalize().unwrap();
+    let tx = stp.get_transaction().unwrap();
 
     let finalized_transaction_message = proto::TransactionFinalizedMessage {
         tx_id: recipient_reply.tx_id.as_u64(),
-        transaction: Some(
-            Transaction::new(
-                vec![],
-                vec![],
-                vec![],
-                PrivateKey::random(&mut OsRng),
-                PrivateKey::random(&mut OsRng),
-            )
-            .try_into()
-            .unwrap(),
-        ),
+        transaction: Some(tx.clone().try_into().unwrap()),
     };
 
     alice_ts_interface
@@ -1678,13 +1895,13 @@ async fn finalize_tx_with_missing_output() {
 
     let delay = sleep(Duration::from_secs(15));
     tokio::pin!(delay);
-
-    tokio::pin!(delay);
+    let mut finalized_tx_id = None;
     loop {
         tokio::select! {
             event = alice_event_stream.recv() => {
-                 if let TransactionEvent::ReceivedFinalizedTransaction(_) = (*event.unwrap()).clone() {
-                    panic!("Should not have received finalized event");
+                 if let TransactionEvent::ReceivedFinalizedTransaction(tx_id) = (*event.unwrap()).clone() {
+                     finalized_tx_id = Some(tx_id);
+                     break;
                 }
             },
             () = &mut delay => {
@@ -1692,212 +1909,433 @@ async fn finalize_tx_with_missing_output() {
             },
         }
     }
+    assert_eq!(finalized_tx_id, Some(recipient_reply.tx_id));
 
-    assert!(alice_ts_interface
+    let completed_tx = alice_ts_interface
         .transaction_service_handle
         .get_completed_transaction(recipient_reply.tx_id)
         .await
-        .is_err());
+        .unwrap();
+    assert_eq!(completed_tx.status, TransactionStatus::Completed);
+    assert_eq!(completed_tx.direction, TransactionDirection::Inbound);
+    assert!(alice_ts_interface
+        .transaction_service_handle
+        .get_pending_inbound_transactions()
+        .await
+        .unwrap()
+        .is_empty());
 }
 
-#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
-async fn discovery_async_return_test() {
-    let db_tempdir = tempdir().unwrap();
-    let db_folder = db_tempdir.path();
-    let network = Network::LocalNet;
-    let consensus_manager = ConsensusManager::builder(network).build();
+#[tokio::test]
+async fn inbound_tx_outside_accepted_range_is_rejected() {
     let factories = CryptoFactories::default();
 
-    // Alice's parameters
-    let alice_node_identity = Arc::new(NodeIdentity::random(
-        &mut OsRng,
-        get_next_memory_address(),
-        PeerFeatures::COMMUNICATION_NODE,
-    ));
-
-    // Bob's parameters
-    let bob_node_identity = Arc::new(NodeIdentity::random(
-        &mut OsRng,
-        get_next_memory_address(),
-        PeerFeatures::COMMUNICATION_NODE,
-    ));
-
-    // Carols's parameters
-    let carol_node_identity = Arc::new(NodeIdentity::random(
-        &mut OsRng,
-        get_next_memory_address(),
-        PeerFeatures::COMMUNICATION_NODE,
-    ));
-
-    log::info!(
-        "discovery_async_return_test: Alice: '{}', Bob: '{}', Carol: '{}'",
-        alice_node_identity.node_id().short_str(),
-        bob_node_identity.node_id().short_str(),
-        carol_node_identity.node_id().short_str(),
-    );
-    let mut shutdown = Shutdown::new();
-
-    let (carol_connection, _temp_dir1) = make_wallet_database_connection(None);
-
-    let (_carol_ts, _carol_oms, carol_comms, _carol_connectivity) = setup_transaction_service(
-        carol_node_identity.clone(),
-        vec![],
-        consensus_manager.clone(),
-        factories.clone(),
-        carol_connection,
-        db_folder.join("carol"),
-        Duration::from_secs(1),
-        shutdown.to_signal(),
-    )
-    .await;
-
-    let (alice_connection, _temp_dir2) = make_wallet_database_connection(None);
-
-    let (mut alice_ts, mut alice_oms, alice_comms, _alice_connectivity) = setup_transaction_service(
-        alice_node_identity,
-        vec![carol_node_identity.clone()],
-        consensus_manager,
+    let (alice_connection, _tempdir) = make_wallet_database_connection(None);
+    let mut alice_ts_interface = setup_transaction_service_no_comms(
         factories.clone(),
         alice_connection,
-        db_folder.join("alice"),
-        Duration::from_secs(20),
-        shutdown.to_signal(),
+        Some(TransactionServiceConfig {
+            min_accept_amount: Some(MicroTari::from(10_000)),
+            ..Default::default()
+        }),
     )
     .await;
-    let mut alice_event_stream = alice_ts.get_event_stream();
+    let mut alice_event_stream = alice_ts_interface.transaction_service_handle.get_event_stream();
 
-    let (_utxo, uo1a) = make_non_recoverable_input(&mut OsRng, MicroTari(55000), &factories.commitment).await;
-    alice_oms.add_output(uo1a, None).await.unwrap();
-    let (_utxo, uo1b) = make_non_recoverable_input(&mut OsRng, MicroTari(30000), &factories.commitment).await;
-    alice_oms.add_output(uo1b, None).await.unwrap();
-    let (_utxo, uo1c) = make_non_recoverable_input(&mut OsRng, MicroTari(30000), &factories.commitment).await;
-    alice_oms.add_output(uo1c, None).await.unwrap();
-
-    let initial_balance = alice_oms.get_balance().await.unwrap();
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let (bob_connection, _tempdir) = make_wallet_database_connection(None);
+    let mut bob_ts_interface = setup_transaction_service_no_comms(factories.clone(), bob_connection, None).await;
 
-    let value_a_to_c_1 = MicroTari::from(14000);
-    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), network);
-    let tx_id = alice_ts
-        .send_transaction(
-            bob_address,
-            value_a_to_c_1,
+    let (_utxo, uo) = make_non_recoverable_input(&mut OsRng, MicroTari(250000), &factories.commitment).await;
+    bob_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+    let mut stp = bob_ts_interface
+        .output_manager_service_handle
+        .prepare_transaction_to_send(
+            TxId::new_random(),
+            MicroTari::from(5000),
             UtxoSelectionCriteria::default(),
             OutputFeatures::default(),
-            MicroTari::from(20),
-            "Discovery Tx!".to_string(),
+            MicroTari::from(25),
+            TransactionMetadata::default(),
+            "".to_string(),
+            script!(Nop),
+            Covenant::default(),
+            MicroTari::zero(),
         )
         .await
         .unwrap();
+    let msg = stp.build_single_round_message().unwrap();
+    let tx_id = msg.tx_id;
+    alice_ts_interface
+        .transaction_send_message_channel
+        .send(create_dummy_message(
+            TransactionSenderMessage::Single(Box::new(msg)).try_into().unwrap(),
+            bob_node_identity.public_key(),
+        ))
+        .await
+        .unwrap();
 
-    assert_ne!(initial_balance, alice_oms.get_balance().await.unwrap());
-
-    #[allow(unused_assignments)]
-    let mut found_txid = TxId::from(0u64);
-    #[allow(unused_assignments)]
-    let mut is_direct_send = true;
-    let delay = sleep(Duration::from_secs(60));
+    let delay = sleep(Duration::from_secs(15));
     tokio::pin!(delay);
+    let mut rejected = None;
     loop {
         tokio::select! {
             event = alice_event_stream.recv() => {
-                if let TransactionEvent::TransactionSendResult(tx_id, status) = (*event.unwrap()).clone() {
-                    found_txid = tx_id;
-                    is_direct_send = status.direct_send_result;
-                    break;
+                 if let TransactionEvent::InboundRejected { tx_id, amount } = (*event.unwrap()).clone() {
+                     rejected = Some((tx_id, amount));
+                     break;
                 }
             },
             () = &mut delay => {
-                panic!("Timeout while waiting for transaction to fail sending");
+                break;
             },
         }
     }
-    assert_eq!(found_txid, tx_id);
-    assert!(!is_direct_send);
+    assert_eq!(rejected, Some((tx_id, MicroTari::from(5000))));
 
-    let carol_address = TariAddress::new(carol_node_identity.public_key().clone(), network);
-    let tx_id2 = alice_ts
-        .send_transaction(
-            carol_address,
-            value_a_to_c_1,
+    // Instead of a reply, the sender is told that the transaction was cancelled
+    alice_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(1, Duration::from_secs(10))
+        .await
+        .unwrap();
+    let call = alice_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+    let cancelled_message = try_decode_transaction_cancelled_message(call.1.to_vec()).unwrap();
+    assert_eq!(cancelled_message.tx_id, tx_id.as_u64());
+    assert!(alice_ts_interface
+        .transaction_service_handle
+        .get_pending_inbound_transactions()
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn finalize_tx_with_missing_output() {
+    let factories = CryptoFactories::default();
+
+    let temp_dir = tempdir().unwrap();
+    let path_string = temp_dir.path().to_str().unwrap().to_string();
+
+    let alice_db_name = format!("{}.sqlite3", random::string(8).as_str());
+    let alice_db_path = format!("{}/{}", path_string, alice_db_name);
+    let bob_db_name = format!("{}.sqlite3", random::string(8).as_str());
+    let bob_db_path = format!("{}/{}", path_string, bob_db_name);
+    let connection_alice = run_migration_and_create_sqlite_connection(&alice_db_path, 16).unwrap();
+    let connection_bob = run_migration_and_create_sqlite_connection(&bob_db_path, 16).unwrap();
+
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection_alice, None).await;
+    let mut alice_event_stream = alice_ts_interface.transaction_service_handle.get_event_stream();
+
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let mut bob_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection_bob, None).await;
+
+    let (_utxo, uo) = make_non_recoverable_input(&mut OsRng, MicroTari(250000), &factories.commitment).await;
+
+    bob_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+
+    let mut stp = bob_ts_interface
+        .output_manager_service_handle
+        .prepare_transaction_to_send(
+            TxId::new_random(),
+            MicroTari::from(5000),
             UtxoSelectionCriteria::default(),
             OutputFeatures::default(),
             MicroTari::from(20),
-            "Discovery Tx2!".to_string(),
+            TransactionMetadata::default(),
+            "".to_string(),
+            script!(Nop),
+            Covenant::default(),
+            MicroTari::zero(),
         )
         .await
         .unwrap();
+    let msg = stp.build_single_round_message().unwrap();
+    let tx_message = create_dummy_message(
+        TransactionSenderMessage::Single(Box::new(msg)).try_into().unwrap(),
+        bob_node_identity.public_key(),
+    );
 
-    #[allow(unused_assignments)]
-    let mut success_result = false;
-    #[allow(unused_assignments)]
-    let mut success_tx_id = TxId::from(0u64);
-    let delay = sleep(Duration::from_secs(60));
-    tokio::pin!(delay);
+    alice_ts_interface
+        .transaction_send_message_channel
+        .send(tx_message)
+        .await
+        .unwrap();
 
-    loop {
-        tokio::select! {
-            event = alice_event_stream.recv() => {
-                if let TransactionEvent::TransactionSendResult(tx_id, status) = &*event.unwrap() {
-                    success_result = status.direct_send_result;
-                    success_tx_id = *tx_id;
-                    break;
-                }
-            },
-            () = &mut delay => {
-                panic!("Timeout while waiting for transaction to successfully be sent");
-            },
-        }
-    }
+    alice_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(1, Duration::from_secs(10))
+        .await
+        .unwrap();
+    let (_, body) = alice_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
 
-    assert_eq!(success_tx_id, tx_id2);
-    assert!(success_result);
+    let envelope_body = EnvelopeBody::decode(body.to_vec().as_slice()).unwrap();
+    let recipient_reply: RecipientSignedMessage = envelope_body
+        .decode_part::<proto::RecipientSignedMessage>(1)
+        .unwrap()
+        .unwrap()
+        .try_into()
+        .unwrap();
 
-    let delay = sleep(Duration::from_secs(60));
+    stp.add_single_recipient_info(recipient_reply.clone()).unwrap();
+    stp.finalize().unwrap();
+
+    let finalized_transaction_message = proto::TransactionFinalizedMessage {
+        tx_id: recipient_reply.tx_id.as_u64(),
+        transaction: Some(
+            Transaction::new(
+                vec![],
+                vec![],
+                vec![],
+                PrivateKey::random(&mut OsRng),
+                PrivateKey::random(&mut OsRng),
+            )
+            .try_into()
+            .unwrap(),
+        ),
+    };
+
+    alice_ts_interface
+        .transaction_finalize_message_channel
+        .send(create_dummy_message(
+            finalized_transaction_message,
+            bob_node_identity.public_key(),
+        ))
+        .await
+        .unwrap();
+
+    let delay = sleep(Duration::from_secs(15));
     tokio::pin!(delay);
+
     tokio::pin!(delay);
     loop {
         tokio::select! {
             event = alice_event_stream.recv() => {
-                if let TransactionEvent::ReceivedTransactionReply(tx_id) = &*event.unwrap() {
-                    if tx_id == &tx_id2 {
-                        break;
-                    }
+                 if let TransactionEvent::ReceivedFinalizedTransaction(_) = (*event.unwrap()).clone() {
+                    panic!("Should not have received finalized event");
                 }
             },
             () = &mut delay => {
-                panic!("Timeout while Alice was waiting for a transaction reply");
+                break;
             },
         }
     }
 
-    shutdown.trigger();
-    alice_comms.wait_until_shutdown().await;
-    carol_comms.wait_until_shutdown().await;
+    assert!(alice_ts_interface
+        .transaction_service_handle
+        .get_completed_transaction(recipient_reply.tx_id)
+        .await
+        .is_err());
 }
 
-#[tokio::test]
-async fn test_power_mode_updates() {
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn discovery_async_return_test() {
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path();
+    let network = Network::LocalNet;
+    let consensus_manager = ConsensusManager::builder(network).build();
     let factories = CryptoFactories::default();
-    let (connection, _temp_dir) = make_wallet_database_connection(None);
 
-    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, None).await;
-    let tx_backend = alice_ts_interface.ts_db;
+    // Alice's parameters
+    let alice_node_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
 
-    let kernel = KernelBuilder::new()
-        .with_excess(&factories.commitment.zero())
-        .with_signature(&Signature::default())
-        .build()
-        .unwrap();
-    let tx = Transaction::new(
-        vec![],
-        vec![],
-        vec![kernel],
-        PrivateKey::random(&mut OsRng),
-        PrivateKey::random(&mut OsRng),
-    );
-    let source_address = TariAddress::new(
-        PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
-        Network::LocalNet,
+    // Bob's parameters
+    let bob_node_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
+
+    // Carols's parameters
+    let carol_node_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
+
+    log::info!(
+        "discovery_async_return_test: Alice: '{}', Bob: '{}', Carol: '{}'",
+        alice_node_identity.node_id().short_str(),
+        bob_node_identity.node_id().short_str(),
+        carol_node_identity.node_id().short_str(),
+    );
+    let mut shutdown = Shutdown::new();
+
+    let (carol_connection, _temp_dir1) = make_wallet_database_connection(None);
+
+    let (_carol_ts, _carol_oms, carol_comms, _carol_connectivity) = setup_transaction_service(
+        carol_node_identity.clone(),
+        vec![],
+        consensus_manager.clone(),
+        factories.clone(),
+        carol_connection,
+        db_folder.join("carol"),
+        Duration::from_secs(1),
+        shutdown.to_signal(),
+    )
+    .await;
+
+    let (alice_connection, _temp_dir2) = make_wallet_database_connection(None);
+
+    let (mut alice_ts, mut alice_oms, alice_comms, _alice_connectivity) = setup_transaction_service(
+        alice_node_identity,
+        vec![carol_node_identity.clone()],
+        consensus_manager,
+        factories.clone(),
+        alice_connection,
+        db_folder.join("alice"),
+        Duration::from_secs(20),
+        shutdown.to_signal(),
+    )
+    .await;
+    let mut alice_event_stream = alice_ts.get_event_stream();
+
+    let (_utxo, uo1a) = make_non_recoverable_input(&mut OsRng, MicroTari(55000), &factories.commitment).await;
+    alice_oms.add_output(uo1a, None).await.unwrap();
+    let (_utxo, uo1b) = make_non_recoverable_input(&mut OsRng, MicroTari(30000), &factories.commitment).await;
+    alice_oms.add_output(uo1b, None).await.unwrap();
+    let (_utxo, uo1c) = make_non_recoverable_input(&mut OsRng, MicroTari(30000), &factories.commitment).await;
+    alice_oms.add_output(uo1c, None).await.unwrap();
+
+    let initial_balance = alice_oms.get_balance().await.unwrap();
+
+    let value_a_to_c_1 = MicroTari::from(14000);
+    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), network);
+    let tx_id = alice_ts
+        .send_transaction(
+            bob_address,
+            value_a_to_c_1,
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            MicroTari::from(20),
+            "Discovery Tx!".to_string(),
+        )
+        .await
+        .unwrap();
+
+    assert_ne!(initial_balance, alice_oms.get_balance().await.unwrap());
+
+    #[allow(unused_assignments)]
+    let mut found_txid = TxId::from(0u64);
+    #[allow(unused_assignments)]
+    let mut is_direct_send = true;
+    let delay = sleep(Duration::from_secs(60));
+    tokio::pin!(delay);
+    loop {
+        tokio::select! {
+            event = alice_event_stream.recv() => {
+                if let TransactionEvent::TransactionSendResult(tx_id, status) = (*event.unwrap()).clone() {
+                    found_txid = tx_id;
+                    is_direct_send = status.direct_send_result;
+                    break;
+                }
+            },
+            () = &mut delay => {
+                panic!("Timeout while waiting for transaction to fail sending");
+            },
+        }
+    }
+    assert_eq!(found_txid, tx_id);
+    assert!(!is_direct_send);
+
+    let carol_address = TariAddress::new(carol_node_identity.public_key().clone(), network);
+    let tx_id2 = alice_ts
+        .send_transaction(
+            carol_address,
+            value_a_to_c_1,
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            MicroTari::from(20),
+            "Discovery Tx2!".to_string(),
+        )
+        .await
+        .unwrap();
+
+    #[allow(unused_assignments)]
+    let mut success_result = false;
+    #[allow(unused_assignments)]
+    let mut success_tx_id = TxId::from(0u64);
+    let delay = sleep(Duration::from_secs(60));
+    tokio::pin!(delay);
+
+    loop {
+        tokio::select! {
+            event = alice_event_stream.recv() => {
+                if let TransactionEvent::TransactionSendResult(tx_id, status) = &*event.unwrap() {
+                    success_result = status.direct_send_result;
+                    success_tx_id = *tx_id;
+                    break;
+                }
+            },
+            () = &mut delay => {
+                panic!("Timeout while waiting for transaction to successfully be sent");
+            },
+        }
+    }
+
+    assert_eq!(success_tx_id, tx_id2);
+    assert!(success_result);
+
+    let delay = sleep(Duration::from_secs(60));
+    tokio::pin!(delay);
+    tokio::pin!(delay);
+    loop {
+        tokio::select! {
+            event = alice_event_stream.recv() => {
+                if let TransactionEvent::ReceivedTransactionReply(tx_id) = &*event.unwrap() {
+                    if tx_id == &tx_id2 {
+                        break;
+                    }
+                }
+            },
+            () = &mut delay => {
+                panic!("Timeout while Alice was waiting for a transaction reply");
+            },
+        }
+    }
+
+    shutdown.trigger();
+    alice_comms.wait_until_shutdown().await;
+    carol_comms.wait_until_shutdown().await;
+}
+
+#[tokio::test]
+async fn test_power_mode_updates() {
+    let factories = CryptoFactories::default();
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, None).await;
+    let tx_backend = alice_ts_interface.ts_db;
+
+    let kernel = KernelBuilder::new()
+        .with_excess(&factories.commitment.zero())
+        .with_signature(&Signature::default())
+        .build()
+        .unwrap();
+    let tx = Transaction::new(
+        vec![],
+        vec![],
+        vec![kernel],
+        PrivateKey::random(&mut OsRng),
+        PrivateKey::random(&mut OsRng),
+    );
+    let source_address = TariAddress::new(
+        PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        Network::LocalNet,
     );
     let destination_address = TariAddress::new(
         PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
@@ -1923,6 +2361,7 @@ async fn test_power_mode_updates() {
         mined_height: None,
         mined_in_block: None,
         mined_timestamp: None,
+        body_pruned: false,
     };
 
     let source_address = TariAddress::new(
@@ -1953,6 +2392,7 @@ async fn test_power_mode_updates() {
         mined_height: None,
         mined_in_block: None,
         mined_timestamp: None,
+        body_pruned: false,
     };
 
     tx_backend
@@ -2401,6 +2841,100 @@ async fn test_transaction_cancellation() {
         .remove(&tx_id3)
         .is_none());
 }
+
+#[tokio::test]
+async fn test_cancel_pending_on_shutdown() {
+    let factories = CryptoFactories::default();
+
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+    let config = TransactionServiceConfig {
+        cancel_pending_on_shutdown: true,
+        ..Default::default()
+    };
+
+    let mut alice_ts_interface =
+        setup_transaction_service_no_comms(factories.clone(), connection.clone(), Some(config.clone())).await;
+    let mut alice_event_stream = alice_ts_interface.transaction_service_handle.get_event_stream();
+
+    let alice_total_available = 2500000 * uT;
+    let (_utxo, uo) = make_non_recoverable_input(&mut OsRng, alice_total_available, &factories.commitment).await;
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+
+    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), Network::LocalNet);
+    let tx_id = alice_ts_interface
+        .transaction_service_handle
+        .send_transaction(
+            bob_address,
+            100000 * uT,
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            100 * uT,
+            "Testing Message".to_string(),
+        )
+        .await
+        .unwrap();
+
+    let delay = sleep(Duration::from_secs(60));
+    tokio::pin!(delay);
+    loop {
+        tokio::select! {
+            event = alice_event_stream.recv() => {
+                if let TransactionEvent::TransactionSendResult(_,_) = &*event.unwrap() {
+                    break;
+                }
+            },
+            () = &mut delay => {
+                panic!("Transaction should have been sent by now");
+            },
+        }
+    }
+
+    let balance = alice_ts_interface
+        .output_manager_service_handle
+        .get_balance()
+        .await
+        .unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(0));
+
+    alice_ts_interface._shutdown.trigger();
+    sleep(Duration::from_secs(2)).await;
+
+    let ts_db = TransactionDatabase::new(alice_ts_interface.ts_db.clone());
+    assert!(ts_db.get_pending_outbound_transactions().unwrap().is_empty());
+    assert!(ts_db
+        .get_cancelled_pending_outbound_transactions()
+        .unwrap()
+        .contains_key(&tx_id));
+
+    // Whether or not the outputs were released during shutdown, they must be available again after a restart
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories, connection, Some(config)).await;
+    let mut balance = alice_ts_interface
+        .output_manager_service_handle
+        .get_balance()
+        .await
+        .unwrap();
+    for _ in 0..10 {
+        if balance.available_balance == alice_total_available {
+            break;
+        }
+        sleep(Duration::from_millis(500)).await;
+        balance = alice_ts_interface
+            .output_manager_service_handle
+            .get_balance()
+            .await
+            .unwrap();
+    }
+    assert_eq!(balance.available_balance, alice_total_available);
+    assert_eq!(balance.pending_outgoing_balance, MicroTari::from(0));
+}
+
 #[tokio::test]
 async fn test_direct_vs_saf_send_of_tx_reply_and_finalize() {
     let factories = CryptoFactories::default();
@@ -2929,19 +3463,100 @@ async fn test_tx_direct_send_behaviour() {
 }
 
 #[tokio::test]
-async fn test_restarting_transaction_protocols() {
-    let network = Network::LocalNet;
+async fn test_queued_transaction_is_reported_as_stuck() {
     let factories = CryptoFactories::default();
-    let (alice_connection, _temp_dir) = make_wallet_database_connection(None);
-
-    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), alice_connection, None).await;
 
-    let alice_backend = alice_ts_interface.ts_db;
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
 
-    let (bob_connection, _temp_dir2) = make_wallet_database_connection(None);
-    let mut bob_ts_interface = setup_transaction_service_no_comms(factories.clone(), bob_connection, None).await;
+    let config = TransactionServiceConfig {
+        queued_stuck_after: Duration::from_secs(3),
+        ..Default::default()
+    };
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, Some(config)).await;
+    let mut alice_event_stream = alice_ts_interface.transaction_service_handle.get_event_stream();
 
-    let bob_backend = bob_ts_interface.ts_db;
+    let (_utxo, uo) = make_non_recoverable_input(&mut OsRng, 1000000 * uT, &factories.commitment).await;
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+
+    // Neither direct nor store and forward sending succeeds, so the transaction stays queued
+    alice_ts_interface
+        .outbound_service_mock_state
+        .set_behaviour(MockBehaviour {
+            direct: ResponseType::Failed,
+            broadcast: ResponseType::Failed,
+        })
+        .await;
+
+    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), Network::LocalNet);
+    let tx_id = alice_ts_interface
+        .transaction_service_handle
+        .send_transaction(
+            bob_address,
+            100000 * uT,
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            100 * uT,
+            "Testing Message".to_string(),
+        )
+        .await
+        .unwrap();
+
+    let mut queued = false;
+    let mut stuck = false;
+    let delay = sleep(Duration::from_secs(60));
+    tokio::pin!(delay);
+    loop {
+        tokio::select! {
+            event = alice_event_stream.recv() => {
+                match &*event.unwrap() {
+                    TransactionEvent::TransactionSendResult(id, status) if *id == tx_id => {
+                        queued = status.queued_for_retry;
+                    },
+                    TransactionEvent::TransactionStuck(id) if *id == tx_id => {
+                        stuck = true;
+                        break;
+                    },
+                    _ => (),
+                }
+            },
+            () = &mut delay => {
+                break;
+            },
+        }
+    }
+    assert!(queued, "Transaction should have been queued");
+    assert!(stuck, "Transaction should have been reported as stuck");
+
+    let outbound_tx = alice_ts_interface
+        .transaction_service_handle
+        .get_pending_outbound_transactions()
+        .await
+        .unwrap()
+        .remove(&tx_id)
+        .unwrap();
+    assert_eq!(outbound_tx.status, TransactionStatus::Queued);
+}
+
+#[tokio::test]
+async fn test_restarting_transaction_protocols() {
+    let network = Network::LocalNet;
+    let factories = CryptoFactories::default();
+    let (alice_connection, _temp_dir) = make_wallet_database_connection(None);
+
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), alice_connection, None).await;
+
+    let alice_backend = alice_ts_interface.ts_db;
+
+    let (bob_connection, _temp_dir2) = make_wallet_database_connection(None);
+    let mut bob_ts_interface = setup_transaction_service_no_comms(factories.clone(), bob_connection, None).await;
+
+    let bob_backend = bob_ts_interface.ts_db;
 
     let base_node_identity = Arc::new(NodeIdentity::random(
         &mut OsRng,
@@ -3047,6 +3662,7 @@ async fn test_restarting_transaction_protocols() {
         direct_send_success: false,
         send_count: 0,
         last_send_timestamp: None,
+        priority: 0,
     };
     bob_backend
         .write(WriteOperation::Insert(DbKeyValuePair::PendingOutboundTransaction(
@@ -4287,6 +4903,80 @@ async fn test_transaction_resending() {
     assert_eq!(alice_finalize_message.tx_id, tx_id);
 }
 
+#[tokio::test]
+async fn test_send_attempts_are_recorded() {
+    let factories = CryptoFactories::default();
+
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let (connection, _tempdir) = make_wallet_database_connection(None);
+
+    let mut alice_ts_interface = setup_transaction_service_no_comms(
+        factories.clone(),
+        connection,
+        Some(TransactionServiceConfig {
+            transaction_resend_period: Duration::from_secs(2),
+            record_send_attempts: true,
+            ..Default::default()
+        }),
+    )
+    .await;
+
+    // Direct sends fail so every attempt falls back to store and forward
+    alice_ts_interface
+        .outbound_service_mock_state
+        .set_behaviour(MockBehaviour {
+            direct: ResponseType::Failed,
+            broadcast: ResponseType::Queued,
+        })
+        .await;
+
+    let (_utxo, uo) = make_non_recoverable_input(&mut OsRng, 250000 * uT, &factories.commitment).await;
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+
+    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), Network::LocalNet);
+    let tx_id = alice_ts_interface
+        .transaction_service_handle
+        .send_transaction(
+            bob_address,
+            100000 * uT,
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            100 * uT,
+            "Testing Message".to_string(),
+        )
+        .await
+        .unwrap();
+
+    // Wait for the initial send and two resends
+    let mut attempts = Vec::new();
+    for _ in 0..30 {
+        attempts = alice_ts_interface
+            .transaction_service_handle
+            .get_send_attempts(tx_id)
+            .await
+            .unwrap();
+        if attempts.len() >= 6 {
+            break;
+        }
+        sleep(Duration::from_secs(1)).await;
+    }
+    assert!(attempts.len() >= 6, "only {} send attempts recorded", attempts.len());
+
+    for pair in attempts.chunks(2) {
+        assert_eq!(pair[0].tx_id, tx_id);
+        assert_eq!(pair[0].mechanism, SendAttemptMechanism::Direct);
+        assert!(!pair[0].succeeded);
+        assert_eq!(pair[1].mechanism, SendAttemptMechanism::StoreAndForward);
+        assert!(pair[1].succeeded);
+    }
+    assert!(attempts.windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+}
+
 #[tokio::test]
 async fn test_resend_on_startup() {
     // Test that messages are resent on startup if enough time has passed
@@ -4353,6 +5043,7 @@ async fn test_resend_on_startup() {
         direct_send_success: false,
         send_count: 1,
         last_send_timestamp: Some(Utc::now().naive_utc()),
+        priority: 0,
     };
     let (connection, _temp_dir) = make_wallet_database_connection(None);
 
@@ -4583,6 +5274,124 @@ async fn test_resend_on_startup() {
     assert_eq!(reply.tx_id, tx_id);
 }
 
+#[tokio::test]
+async fn test_restart_queued_transactions_by_priority() {
+    let factories = CryptoFactories::default();
+    let base_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+    let mut alice_ts_interface = setup_transaction_service_no_comms(
+        factories.clone(),
+        connection,
+        Some(TransactionServiceConfig {
+            low_priority_restart_interval: Duration::from_secs(3),
+            ..Default::default()
+        }),
+    )
+    .await;
+
+    // Queue a low priority transaction before a high priority one
+    let mut tx_ids = Vec::new();
+    for (i, priority) in [0u8, 10].into_iter().enumerate() {
+        let input = create_non_recoverable_unblinded_output(
+            script!(Nop),
+            OutputFeatures::default(),
+            &TestParamsHelpers::new(),
+            MicroTari::from(100_000),
+        )
+        .unwrap();
+        let constants = create_consensus_constants(0);
+        let mut builder = SenderTransactionProtocol::builder(1, constants);
+        let amount = MicroTari::from(10_000);
+        builder
+            .with_lock_height(0)
+            .with_fee_per_gram(MicroTari::from(177 / 5))
+            .with_offset(PrivateKey::random(&mut OsRng))
+            .with_private_nonce(PrivateKey::random(&mut OsRng))
+            .with_amount(0, amount)
+            .with_message("Yo!".to_string())
+            .with_input(
+                input
+                    .as_transaction_input(&factories.commitment)
+                    .expect("Should be able to make transaction input"),
+                input,
+            )
+            .with_change_secret(PrivateKey::random(&mut OsRng))
+            .with_recipient_data(
+                0,
+                script!(Nop),
+                PrivateKey::random(&mut OsRng),
+                Default::default(),
+                PrivateKey::random(&mut OsRng),
+                Covenant::default(),
+                MicroTari::zero(),
+            )
+            .with_change_script(script!(Nop), ExecutionStack::default(), PrivateKey::random(&mut OsRng));
+        let stp = builder.build(&factories, None, u64::MAX).unwrap();
+        let tx_id = stp.get_tx_id().unwrap();
+        let address = TariAddress::new(
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            Network::LocalNet,
+        );
+        let outbound_tx = OutboundTransaction {
+            tx_id,
+            destination_address: address,
+            amount,
+            fee: stp.get_fee_amount().unwrap(),
+            sender_protocol: stp,
+            status: TransactionStatus::Queued,
+            message: "Yo!".to_string(),
+            timestamp: Utc::now().naive_utc() - ChronoDuration::seconds(60 - i as i64),
+            cancelled: false,
+            direct_send_success: false,
+            send_count: 0,
+            last_send_timestamp: None,
+            priority,
+        };
+        alice_ts_interface
+            .ts_db
+            .write(WriteOperation::Insert(DbKeyValuePair::PendingOutboundTransaction(
+                tx_id,
+                Box::new(outbound_tx),
+            )))
+            .unwrap();
+        tx_ids.push(tx_id);
+    }
+    let (low_priority_tx_id, high_priority_tx_id) = (tx_ids[0], tx_ids[1]);
+
+    alice_ts_interface
+        .wallet_connectivity_service_mock
+        .set_base_node(base_node_identity.to_peer());
+    alice_ts_interface
+        .transaction_service_handle
+        .restart_transaction_protocols()
+        .await
+        .unwrap();
+
+    // The high priority transaction must be sent first, the low priority one is only started after the interval
+    let mut sent_tx_ids = Vec::new();
+    while !sent_tx_ids.contains(&low_priority_tx_id) {
+        alice_ts_interface
+            .outbound_service_mock_state
+            .wait_call_count(1, Duration::from_secs(30))
+            .await
+            .expect("Alice call wait");
+        sent_tx_ids.extend(
+            alice_ts_interface
+                .outbound_service_mock_state
+                .take_calls()
+                .await
+                .into_iter()
+                .filter_map(|(_, body)| match try_decode_sender_message(body.to_vec()) {
+                    Some(TransactionSenderMessage::Single(data)) => Some(data.tx_id),
+                    _ => None,
+                }),
+        );
+    }
+    assert_eq!(sent_tx_ids.first(), Some(&high_priority_tx_id));
+}
+
 #[tokio::test]
 async fn test_replying_to_cancelled_tx() {
     let factories = CryptoFactories::default();
@@ -4708,6 +5517,119 @@ async fn test_replying_to_cancelled_tx() {
     assert_eq!(alice_cancelled_message.tx_id, tx_id.as_u64());
 }
 
+async fn assert_tampered_recipient_reply_is_not_finalized(tamper: impl FnOnce(&mut RecipientSignedMessage)) {
+    let factories = CryptoFactories::default();
+
+    let alice_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let (alice_connection, _tempdir) = make_wallet_database_connection(None);
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), alice_connection, None).await;
+    let mut alice_event_stream = alice_ts_interface.transaction_service_handle.get_event_stream();
+
+    let (_utxo, uo) = make_non_recoverable_input(&mut OsRng, 2500000 * uT, &factories.commitment).await;
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+
+    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), Network::LocalNet);
+    let tx_id = alice_ts_interface
+        .transaction_service_handle
+        .send_transaction(
+            bob_address,
+            100000 * uT,
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            100 * uT,
+            "Testing Message".to_string(),
+        )
+        .await
+        .unwrap();
+    alice_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(1, Duration::from_secs(30))
+        .await
+        .expect("Alice call wait 1");
+    let call = alice_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+    let alice_sender_message = try_decode_sender_message(call.1.to_vec()).unwrap();
+
+    let (bob_connection, _tempdir) = make_wallet_database_connection(None);
+    let mut bob_ts_interface = setup_transaction_service_no_comms(factories, bob_connection, None).await;
+    bob_ts_interface
+        .transaction_send_message_channel
+        .send(create_dummy_message(
+            alice_sender_message.try_into().unwrap(),
+            alice_node_identity.public_key(),
+        ))
+        .await
+        .unwrap();
+    bob_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(1, Duration::from_secs(30))
+        .await
+        .expect("Bob call wait 1");
+    let call = bob_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+    let mut bob_reply_message = try_decode_transaction_reply_message(call.1.to_vec()).unwrap();
+    assert_eq!(bob_reply_message.tx_id, tx_id);
+
+    tamper(&mut bob_reply_message);
+    alice_ts_interface
+        .transaction_ack_message_channel
+        .send(create_dummy_message(
+            bob_reply_message.try_into().unwrap(),
+            bob_node_identity.public_key(),
+        ))
+        .await
+        .unwrap();
+
+    let delay = sleep(Duration::from_secs(30));
+    tokio::pin!(delay);
+    let mut mismatch_reported = false;
+    loop {
+        tokio::select! {
+            event = alice_event_stream.recv() => {
+                 if let TransactionEvent::Error(e) = (*event.unwrap()).clone() {
+                     if e.contains("FinalizationMismatch") {
+                         mismatch_reported = true;
+                         break;
+                     }
+                }
+            },
+            () = &mut delay => {
+                break;
+            },
+        }
+    }
+    assert!(mismatch_reported);
+
+    // The malformed transaction was not stored as completed
+    assert!(alice_ts_interface
+        .transaction_service_handle
+        .get_completed_transaction(tx_id)
+        .await
+        .is_err());
+    assert!(alice_ts_interface
+        .transaction_service_handle
+        .get_pending_outbound_transactions()
+        .await
+        .unwrap()
+        .contains_key(&tx_id));
+}
+
+#[tokio::test]
+async fn test_tampered_recipient_reply_is_not_finalized() {
+    // The reply's copy of the metadata ends up in the kernel
+    assert_tampered_recipient_reply_is_not_finalized(|reply| reply.tx_metadata.lock_height += 1000).await;
+    // The recipient's public spend key is added to the kernel excess and must match its partial signature
+    assert_tampered_recipient_reply_is_not_finalized(|reply| {
+        reply.public_spend_key = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+    })
+    .await;
+}
+
 #[tokio::test]
 async fn test_transaction_timeout_cancellation() {
     let factories = CryptoFactories::default();
@@ -4846,6 +5768,7 @@ async fn test_transaction_timeout_cancellation() {
         direct_send_success: false,
         send_count: 1,
         last_send_timestamp: Some(Utc::now().naive_utc()),
+        priority: 0,
     };
     let (bob_connection, _temp_dir) = make_wallet_database_connection(None);
 
@@ -5363,6 +6286,7 @@ async fn broadcast_all_completed_transactions_on_startup() {
         mined_height: None,
         mined_in_block: None,
         mined_timestamp: None,
+        body_pruned: false,
     };
 
     let completed_tx2 = CompletedTransaction {
@@ -5616,3 +6540,547 @@ async fn test_get_fee_per_gram_per_block_basic() {
     assert_eq!(estimates.stats, stats.into_iter().map(Into::into).collect::<Vec<_>>());
     assert_eq!(estimates.stats.len(), 1)
 }
+
+#[tokio::test]
+async fn test_get_transactions_for_commitment() {
+    let network = Network::LocalNet;
+    let consensus_manager = ConsensusManager::builder(network).build();
+    let factories = CryptoFactories::default();
+    let alice_node_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
+
+    let temp_dir = tempdir().unwrap();
+    let database_path = temp_dir.path().to_str().unwrap().to_string();
+    let (db_connection, _tempdir) = make_wallet_database_connection(Some(database_path.clone()));
+
+    let shutdown = Shutdown::new();
+    let (mut alice_ts, mut alice_oms, _alice_comms, _alice_connectivity) = setup_transaction_service(
+        alice_node_identity.clone(),
+        vec![],
+        consensus_manager,
+        factories.clone(),
+        db_connection,
+        database_path,
+        Duration::from_secs(0),
+        shutdown.to_signal(),
+    )
+    .await;
+
+    let (_utxo, uo1) = make_non_recoverable_input(&mut OsRng, 25000.into(), &factories.commitment).await;
+    let spent_commitment = uo1
+        .as_transaction_input(&factories.commitment)
+        .unwrap()
+        .commitment()
+        .unwrap()
+        .clone();
+    alice_oms.add_output(uo1, None).await.unwrap();
+    assert!(alice_ts
+        .get_transactions_for_commitment(spent_commitment.clone())
+        .await
+        .unwrap()
+        .is_empty());
+
+    let alice_address = TariAddress::new(alice_node_identity.public_key().clone(), network);
+    let tx_id = alice_ts
+        .send_transaction(
+            alice_address,
+            10000.into(),
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            20.into(),
+            "Spending a known output".to_string(),
+        )
+        .await
+        .unwrap();
+
+    let transactions = alice_ts
+        .get_transactions_for_commitment(spent_commitment)
+        .await
+        .unwrap();
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].tx_id, tx_id);
+
+    // The outputs created by the transaction are matched as well
+    let completed_tx = alice_ts.get_completed_transaction(tx_id).await.unwrap();
+    let created_commitment = completed_tx.transaction.body.outputs()[0].commitment.clone();
+    let transactions = alice_ts
+        .get_transactions_for_commitment(created_commitment)
+        .await
+        .unwrap();
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].tx_id, tx_id);
+
+    assert!(alice_ts
+        .get_transactions_for_commitment(Commitment::default())
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_get_transactions_with_address() {
+    let factories = CryptoFactories::default();
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, None).await;
+    let db = alice_ts_interface.ts_db.clone();
+
+    let random_address = || {
+        TariAddress::new(
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            Network::LocalNet,
+        )
+    };
+    let alice_address = random_address();
+    let bob_address = random_address();
+    let carol_address = random_address();
+
+    let kernel = KernelBuilder::new()
+        .with_excess(&factories.commitment.zero())
+        .with_signature(&Signature::default())
+        .build()
+        .unwrap();
+    let tx = Transaction::new(
+        vec![],
+        vec![],
+        vec![kernel],
+        PrivateKey::random(&mut OsRng),
+        PrivateKey::random(&mut OsRng),
+    );
+    let outbound_to_bob = CompletedTransaction {
+        tx_id: 1u64.into(),
+        source_address: alice_address.clone(),
+        destination_address: bob_address.clone(),
+        amount: 5000 * uT,
+        fee: MicroTari::from(20),
+        transaction: tx.clone(),
+        status: TransactionStatus::Completed,
+        message: "To Bob".to_string(),
+        timestamp: Utc::now().naive_utc(),
+        cancelled: None,
+        direction: TransactionDirection::Outbound,
+        coinbase_block_height: None,
+        send_count: 0,
+        last_send_timestamp: None,
+        transaction_signature: tx.first_kernel_excess_sig().unwrap_or(&Signature::default()).clone(),
+        confirmations: None,
+        mined_height: None,
+        mined_in_block: None,
+        mined_timestamp: None,
+        body_pruned: false,
+    };
+    let inbound_from_bob = CompletedTransaction {
+        tx_id: 2u64.into(),
+        source_address: bob_address.clone(),
+        destination_address: alice_address.clone(),
+        message: "From Bob".to_string(),
+        timestamp: outbound_to_bob.timestamp + ChronoDuration::seconds(1),
+        direction: TransactionDirection::Inbound,
+        ..outbound_to_bob.clone()
+    };
+    let outbound_to_carol = CompletedTransaction {
+        tx_id: 3u64.into(),
+        destination_address: carol_address.clone(),
+        message: "To Carol".to_string(),
+        ..outbound_to_bob.clone()
+    };
+    for tx in [outbound_to_bob, inbound_from_bob, outbound_to_carol] {
+        db.write(WriteOperation::Insert(DbKeyValuePair::CompletedTransaction(
+            tx.tx_id,
+            Box::new(tx),
+        )))
+        .unwrap();
+    }
+
+    let transactions = alice_ts_interface
+        .transaction_service_handle
+        .get_transactions_with_address(&bob_address)
+        .await
+        .unwrap();
+    let tx_ids = transactions.iter().map(|tx| tx.tx_id).collect::<Vec<_>>();
+    assert_eq!(tx_ids, vec![TxId::from(1u64), TxId::from(2u64)]);
+
+    let transactions = alice_ts_interface
+        .transaction_service_handle
+        .get_transactions_with_address(&carol_address)
+        .await
+        .unwrap();
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].tx_id, TxId::from(3u64));
+
+    assert!(alice_ts_interface
+        .transaction_service_handle
+        .get_transactions_with_address(&random_address())
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_estimate_delivery_confidence() {
+    let factories = CryptoFactories::default();
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, None).await;
+    let db = alice_ts_interface.ts_db.clone();
+
+    let random_address = || {
+        TariAddress::new(
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            Network::LocalNet,
+        )
+    };
+    let alice_address = random_address();
+    let bob_address = random_address();
+    let carol_address = random_address();
+
+    let kernel = KernelBuilder::new()
+        .with_excess(&factories.commitment.zero())
+        .with_signature(&Signature::default())
+        .build()
+        .unwrap();
+    let tx = Transaction::new(
+        vec![],
+        vec![],
+        vec![kernel],
+        PrivateKey::random(&mut OsRng),
+        PrivateKey::random(&mut OsRng),
+    );
+    let first_to_bob = CompletedTransaction {
+        tx_id: 1u64.into(),
+        source_address: alice_address.clone(),
+        destination_address: bob_address.clone(),
+        amount: 5000 * uT,
+        fee: MicroTari::from(20),
+        transaction: tx.clone(),
+        status: TransactionStatus::Completed,
+        message: "To Bob".to_string(),
+        timestamp: Utc::now().naive_utc(),
+        cancelled: None,
+        direction: TransactionDirection::Outbound,
+        coinbase_block_height: None,
+        send_count: 0,
+        last_send_timestamp: None,
+        transaction_signature: tx.first_kernel_excess_sig().unwrap_or(&Signature::default()).clone(),
+        confirmations: None,
+        mined_height: None,
+        mined_in_block: None,
+        mined_timestamp: None,
+        body_pruned: false,
+    };
+    let from_bob = CompletedTransaction {
+        tx_id: 4u64.into(),
+        source_address: bob_address.clone(),
+        destination_address: alice_address.clone(),
+        message: "From Bob".to_string(),
+        direction: TransactionDirection::Inbound,
+        ..first_to_bob.clone()
+    };
+    for tx in [first_to_bob, from_bob] {
+        db.write(WriteOperation::Insert(DbKeyValuePair::CompletedTransaction(
+            tx.tx_id,
+            Box::new(tx),
+        )))
+        .unwrap();
+    }
+    let pending_to_bob = |tx_id: u64| OutboundTransaction {
+        tx_id: tx_id.into(),
+        destination_address: bob_address.clone(),
+        amount: 5000 * uT,
+        fee: MicroTari::from(20),
+        sender_protocol: SenderTransactionProtocol::new_placeholder(),
+        status: TransactionStatus::Pending,
+        message: "To Bob".to_string(),
+        timestamp: Utc::now().naive_utc(),
+        cancelled: false,
+        direct_send_success: false,
+        send_count: 0,
+        last_send_timestamp: None,
+        priority: 0,
+    };
+    let to_carol = OutboundTransaction {
+        destination_address: carol_address.clone(),
+        ..pending_to_bob(5)
+    };
+    for tx in [pending_to_bob(2), pending_to_bob(3), to_carol] {
+        db.write(WriteOperation::Insert(DbKeyValuePair::PendingOutboundTransaction(
+            tx.tx_id,
+            Box::new(tx),
+        )))
+        .unwrap();
+    }
+
+    let attempts = [
+        // Bob replied to the completed transaction, so it was delivered even though the direct send failed
+        (1u64, SendAttemptMechanism::Direct, false),
+        (1, SendAttemptMechanism::StoreAndForward, true),
+        (2, SendAttemptMechanism::Direct, true),
+        (2, SendAttemptMechanism::StoreAndForward, true),
+        // Stored for an offline Bob, but never delivered, however many times it was sent
+        (3, SendAttemptMechanism::Direct, false),
+        (3, SendAttemptMechanism::StoreAndForward, true),
+        (3, SendAttemptMechanism::Direct, false),
+        (3, SendAttemptMechanism::StoreAndForward, true),
+        // Attempts on inbound transactions are replies to Bob and don't count towards sends to Bob
+        (4, SendAttemptMechanism::Direct, false),
+        (5, SendAttemptMechanism::Direct, false),
+        (5, SendAttemptMechanism::StoreAndForward, true),
+    ];
+    for (tx_id, mechanism, succeeded) in attempts {
+        db.add_send_attempt(SendAttempt {
+            tx_id: tx_id.into(),
+            timestamp: Utc::now().naive_utc(),
+            mechanism,
+            succeeded,
+        })
+        .unwrap();
+    }
+
+    let confidence = alice_ts_interface
+        .transaction_service_handle
+        .estimate_delivery_confidence(&bob_address)
+        .await
+        .unwrap();
+    assert!(
+        (confidence - 2.0 / 3.0).abs() < f64::EPSILON,
+        "confidence was {}",
+        confidence
+    );
+
+    let confidence = alice_ts_interface
+        .transaction_service_handle
+        .estimate_delivery_confidence(&carol_address)
+        .await
+        .unwrap();
+    assert!(confidence.abs() < f64::EPSILON, "confidence was {}", confidence);
+
+    // Without any history the estimate is neutral
+    let confidence = alice_ts_interface
+        .transaction_service_handle
+        .estimate_delivery_confidence(&random_address())
+        .await
+        .unwrap();
+    assert!((confidence - 0.5).abs() < f64::EPSILON, "confidence was {}", confidence);
+}
+
+#[tokio::test]
+async fn test_get_pending_transaction_impacts() {
+    let factories = CryptoFactories::default();
+
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let (connection_alice, _alice_tempdir) = make_wallet_database_connection(None);
+    let (connection_bob, _bob_tempdir) = make_wallet_database_connection(None);
+
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection_alice, None).await;
+    let mut bob_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection_bob, None).await;
+
+    // Alice receives a transaction from Bob and replies, leaving it pending inbound
+    let (_utxo, uo) = make_non_recoverable_input(&mut OsRng, MicroTari(250000), &factories.commitment).await;
+    bob_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+    let mut stp = bob_ts_interface
+        .output_manager_service_handle
+        .prepare_transaction_to_send(
+            TxId::new_random(),
+            MicroTari::from(5000),
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            MicroTari::from(25),
+            TransactionMetadata::default(),
+            "".to_string(),
+            script!(Nop),
+            Covenant::default(),
+            MicroTari::zero(),
+        )
+        .await
+        .unwrap();
+    let msg = stp.build_single_round_message().unwrap();
+    alice_ts_interface
+        .transaction_send_message_channel
+        .send(create_dummy_message(
+            TransactionSenderMessage::Single(Box::new(msg)).try_into().unwrap(),
+            bob_node_identity.public_key(),
+        ))
+        .await
+        .unwrap();
+    alice_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(1, Duration::from_secs(10))
+        .await
+        .unwrap();
+    let (_, body) = alice_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+    let envelope_body = EnvelopeBody::decode(body.to_vec().as_slice()).unwrap();
+    let recipient_reply: RecipientSignedMessage = envelope_body
+        .decode_part::<proto::RecipientSignedMessage>(1)
+        .unwrap()
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+    // Alice sends a transaction to Bob, leaving it pending outbound
+    let (_utxo, uo) = make_non_recoverable_input(&mut OsRng, MicroTari(250000), &factories.commitment).await;
+    let spent_commitment = uo
+        .as_transaction_input(&factories.commitment)
+        .unwrap()
+        .commitment()
+        .unwrap()
+        .clone();
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), Network::LocalNet);
+    let outbound_tx_id = alice_ts_interface
+        .transaction_service_handle
+        .send_transaction(
+            bob_address,
+            MicroTari::from(100000),
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            MicroTari::from(100),
+            "Testing Message".to_string(),
+        )
+        .await
+        .unwrap();
+
+    let mut pending_outbound = HashMap::new();
+    for _ in 0..20 {
+        pending_outbound = alice_ts_interface
+            .transaction_service_handle
+            .get_pending_outbound_transactions()
+            .await
+            .unwrap();
+        if pending_outbound.contains_key(&outbound_tx_id) {
+            break;
+        }
+        sleep(Duration::from_millis(500)).await;
+    }
+    let outbound_fee = pending_outbound.get(&outbound_tx_id).unwrap().fee;
+
+    let impacts = alice_ts_interface
+        .transaction_service_handle
+        .get_pending_transaction_impacts()
+        .await
+        .unwrap();
+    assert_eq!(impacts.len(), 2);
+
+    let inbound = impacts.iter().find(|i| i.tx_id == recipient_reply.tx_id).unwrap();
+    assert_eq!(inbound.direction, TransactionDirection::Inbound);
+    assert_eq!(inbound.amount, MicroTari::from(5000));
+    assert_eq!(inbound.fee, MicroTari::zero());
+    assert!(inbound.encumbered_inputs.is_empty());
+    assert_eq!(inbound.encumbered_outputs, vec![recipient_reply
+        .output
+        .commitment
+        .clone()]);
+
+    let outbound = impacts.iter().find(|i| i.tx_id == outbound_tx_id).unwrap();
+    assert_eq!(outbound.direction, TransactionDirection::Outbound);
+    assert_eq!(outbound.amount, MicroTari::from(100000));
+    assert_eq!(outbound.fee, outbound_fee);
+    assert_eq!(outbound.encumbered_inputs, vec![spent_commitment]);
+    // The change output
+    assert_eq!(outbound.encumbered_outputs.len(), 1);
+}
+
+#[tokio::test]
+async fn test_estimate_send_eta_of_queued_transaction() {
+    let factories = CryptoFactories::default();
+
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let (connection, _tempdir) = make_wallet_database_connection(None);
+    let resend_period = Duration::from_secs(60);
+    let mut alice_ts_interface = setup_transaction_service_no_comms(
+        factories.clone(),
+        connection,
+        Some(TransactionServiceConfig {
+            transaction_resend_period: resend_period,
+            ..Default::default()
+        }),
+    )
+    .await;
+
+    let (_utxo, uo) = make_non_recoverable_input(&mut OsRng, MicroTari(250000), &factories.commitment).await;
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+
+    // Neither direct nor store and forward sending succeeds, so the transaction stays queued
+    alice_ts_interface
+        .outbound_service_mock_state
+        .set_behaviour(MockBehaviour {
+            direct: ResponseType::Failed,
+            broadcast: ResponseType::Failed,
+        })
+        .await;
+
+    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), Network::LocalNet);
+    let tx_id = alice_ts_interface
+        .transaction_service_handle
+        .send_transaction(
+            bob_address,
+            MicroTari::from(100000),
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            MicroTari::from(100),
+            "Testing Message".to_string(),
+        )
+        .await
+        .unwrap();
+
+    let mut status = None;
+    for _ in 0..20 {
+        status = alice_ts_interface
+            .transaction_service_handle
+            .get_pending_outbound_transactions()
+            .await
+            .unwrap()
+            .remove(&tx_id)
+            .map(|tx| tx.status);
+        if status == Some(TransactionStatus::Queued) {
+            break;
+        }
+        sleep(Duration::from_millis(500)).await;
+    }
+    assert_eq!(status, Some(TransactionStatus::Queued));
+
+    // There is no next attempt to estimate while the wallet is offline
+    alice_ts_interface
+        .wallet_connectivity_service_mock
+        .set_connectivity_status(OnlineStatus::Offline);
+    assert!(alice_ts_interface
+        .transaction_service_handle
+        .estimate_send_eta(tx_id)
+        .await
+        .unwrap()
+        .is_none());
+
+    alice_ts_interface
+        .wallet_connectivity_service_mock
+        .set_connectivity_status(OnlineStatus::Online);
+    let eta = alice_ts_interface
+        .transaction_service_handle
+        .estimate_send_eta(tx_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(eta <= resend_period);
+    assert!(eta > resend_period - Duration::from_secs(15));
+
+    assert!(alice_ts_interface
+        .transaction_service_handle
+        .estimate_send_eta(TxId::new_random())
+        .await
+        .is_err());
+}
@@ -35,7 +35,7 @@ use tari_core::{
     covenants::Covenant,
     transactions::{
         tari_amount::{uT, MicroTari},
-        test_helpers::{create_non_recoverable_unblinded_output, TestParams},
+        test_helpers::{create_non_recoverable_unblinded_output, create_tx, TestParams},
         transaction_components::{OutputFeatures, Transaction},
         transaction_protocol::sender::TransactionSenderMessage,
         CryptoFactories,
@@ -127,6 +127,7 @@ pub fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            priority: 0,
         });
         assert!(!db.transaction_exists(tx_id).unwrap(), "TxId should not exist");
 
@@ -271,6 +272,7 @@ pub fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            body_pruned: false,
         });
         db.complete_outbound_transaction(outbound_txs[i].tx_id, completed_txs[i].clone())
             .unwrap();
@@ -600,3 +602,139 @@ async fn import_tx_and_read_it_from_db() {
     assert_eq!(db_tx.first().unwrap().tx_id, TxId::from(3u64));
     assert_eq!(db_tx.first().unwrap().mined_height, Some(7));
 }
+
+#[test]
+fn completed_transaction_reports_effective_fee_per_gram() {
+    let db_name = format!("{}.sqlite3", random::string(8));
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(db_path, 16).unwrap();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+    let db = TransactionDatabase::new(TransactionServiceSqliteDatabase::new(connection, cipher));
+
+    let fee_per_gram = MicroTari::from(25);
+    let (tx, _, _) = create_tx(
+        MicroTari::from(100_000),
+        fee_per_gram,
+        0,
+        1,
+        0,
+        1,
+        OutputFeatures::default(),
+    );
+    let fee = tx.body.get_total_fee();
+    let tx_id = TxId::from(1u64);
+    let completed_tx = CompletedTransaction::new(
+        tx_id,
+        TariAddress::default(),
+        TariAddress::default(),
+        MicroTari::from(100_000) - fee,
+        fee,
+        tx,
+        TransactionStatus::Completed,
+        "message".to_string(),
+        Utc::now().naive_utc(),
+        TransactionDirection::Outbound,
+        None,
+        None,
+        None,
+    );
+    db.insert_completed_transaction(tx_id, completed_tx).unwrap();
+
+    let retrieved_tx = db.get_completed_transaction(tx_id).unwrap();
+    assert_eq!(retrieved_tx.effective_fee_per_gram(), fee_per_gram);
+
+    let empty_tx = CompletedTransaction::new(
+        TxId::from(2u64),
+        TariAddress::default(),
+        TariAddress::default(),
+        MicroTari::from(100_000),
+        MicroTari::from(0),
+        Transaction::new(
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            PrivateKey::random(&mut OsRng),
+            PrivateKey::random(&mut OsRng),
+        ),
+        TransactionStatus::Imported,
+        "message".to_string(),
+        Utc::now().naive_utc(),
+        TransactionDirection::Inbound,
+        None,
+        None,
+        None,
+    );
+    assert_eq!(empty_tx.effective_fee_per_gram(), MicroTari::zero());
+}
+
+#[test]
+fn prune_confirmed_transaction_bodies_keeps_metadata() {
+    let db_name = format!("{}.sqlite3", random::string(8));
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(db_path, 16).unwrap();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+    let db = TransactionDatabase::new(TransactionServiceSqliteDatabase::new(connection, cipher));
+
+    for (tx_id, mined_height) in [(1u64, 5u64), (2u64, 20u64)] {
+        let (tx, _, _) = create_tx(
+            MicroTari::from(100_000),
+            MicroTari::from(25),
+            0,
+            1,
+            0,
+            1,
+            OutputFeatures::default(),
+        );
+        let fee = tx.body.get_total_fee();
+        let completed_tx = CompletedTransaction::new(
+            TxId::from(tx_id),
+            TariAddress::default(),
+            TariAddress::default(),
+            MicroTari::from(100_000) - fee,
+            fee,
+            tx,
+            TransactionStatus::MinedConfirmed,
+            "message".to_string(),
+            Utc::now().naive_utc(),
+            TransactionDirection::Outbound,
+            None,
+            Some(mined_height),
+            Some(NaiveDateTime::from_timestamp_opt(0, 0).unwrap()),
+        );
+        db.insert_completed_transaction(TxId::from(tx_id), completed_tx)
+            .unwrap();
+    }
+    let deep_tx = db.get_completed_transaction(TxId::from(1u64)).unwrap();
+    let shallow_tx = db.get_completed_transaction(TxId::from(2u64)).unwrap();
+
+    let pruned = db.prune_confirmed_transaction_bodies(10).unwrap();
+    assert_eq!(pruned, vec![TxId::from(1u64)]);
+
+    let pruned_tx = db.get_completed_transaction(TxId::from(1u64)).unwrap();
+    assert!(pruned_tx.body_pruned);
+    assert!(pruned_tx.transaction.body.kernels().is_empty());
+    assert!(pruned_tx.transaction.body.outputs().is_empty());
+    assert_eq!(pruned_tx.tx_id, deep_tx.tx_id);
+    assert_eq!(pruned_tx.amount, deep_tx.amount);
+    assert_eq!(pruned_tx.fee, deep_tx.fee);
+    assert_eq!(pruned_tx.status, TransactionStatus::MinedConfirmed);
+    assert_eq!(pruned_tx.transaction_signature, deep_tx.transaction_signature);
+
+    let kept_tx = db.get_completed_transaction(TxId::from(2u64)).unwrap();
+    assert!(!kept_tx.body_pruned);
+    assert_eq!(kept_tx.transaction, shallow_tx.transaction);
+
+    assert!(db.prune_confirmed_transaction_bodies(10).unwrap().is_empty());
+}
@@ -365,6 +365,98 @@ async fn test_utxo_scanner_recovery() {
         }
     }
 }
+
+#[tokio::test]
+async fn test_utxo_scanner_progress_events() {
+    let factories = CryptoFactories::default();
+    let mut test_interface = setup(UtxoScannerMode::Recovery, None, None, None).await;
+
+    let cipher_seed = CipherSeed::new();
+    let birthday_epoch_time = get_birthday_from_unix_epoch_in_seconds(cipher_seed.birthday(), 14u16);
+    test_interface.wallet_db.set_master_seed(cipher_seed).unwrap();
+
+    const NUM_BLOCKS: u64 = 11;
+    const BIRTHDAY_OFFSET: u64 = 5;
+
+    let TestBlockData {
+        block_headers,
+        unblinded_outputs,
+        utxos_by_block,
+    } = generate_block_headers_and_utxos(0, NUM_BLOCKS, birthday_epoch_time, BIRTHDAY_OFFSET, false).await;
+
+    test_interface.rpc_service_state.set_utxos_by_block(utxos_by_block);
+    test_interface.rpc_service_state.set_blocks(block_headers.clone());
+
+    let chain_metadata = ChainMetadata {
+        height_of_longest_chain: Some(NUM_BLOCKS - 1),
+        best_block: Some(block_headers.get(&(NUM_BLOCKS - 1)).unwrap().clone().hash().to_vec()),
+        accumulated_difficulty: Vec::new(),
+        pruned_height: 0,
+        timestamp: Some(0),
+    };
+    test_interface.rpc_service_state.set_tip_info_response(TipInfoResponse {
+        metadata: Some(chain_metadata),
+        is_synced: true,
+    });
+
+    let db_unblinded_outputs = unblinded_outputs
+        .values()
+        .flat_map(|outputs| outputs.iter().skip(outputs.len() / 2))
+        .map(|output| {
+            DbUnblindedOutput::from_unblinded_output(
+                output.clone(),
+                &factories,
+                None,
+                OutputSource::Unknown,
+                None,
+                None,
+            )
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+    test_interface
+        .oms_mock_state
+        .set_recoverable_outputs(db_unblinded_outputs);
+
+    let mut scanner_event_stream = test_interface.scanner_handle.get_event_receiver();
+
+    tokio::spawn(test_interface.scanner_service.take().unwrap().run());
+
+    let mut progress_events = Vec::new();
+    let delay = time::sleep(Duration::from_secs(60));
+    tokio::pin!(delay);
+    loop {
+        tokio::select! {
+            _ = &mut delay => {
+                panic!("Completed event should have arrived by now.");
+            }
+            event = scanner_event_stream.recv() => {
+                match event.unwrap() {
+                    UtxoScannerEvent::Progress {
+                        current_height,
+                        tip_height,
+                        outputs_recovered,
+                    } => {
+                        assert_eq!(tip_height, NUM_BLOCKS - 1);
+                        progress_events.push((current_height, outputs_recovered));
+                    },
+                    UtxoScannerEvent::Completed {
+                        final_height,
+                        num_recovered,
+                        ..
+                    } => {
+                        assert!(progress_events.len() > 1);
+                        assert!(progress_events.windows(2).all(|w| w[0].0 <= w[1].0 && w[0].1 <= w[1].1));
+                        assert_eq!(*progress_events.last().unwrap(), (final_height, num_recovered));
+                        break;
+                    },
+                    _ => {},
+                }
+            }
+        }
+    }
+}
+
 #[tokio::test]
 #[allow(clippy::too_many_lines)]
 async fn test_utxo_scanner_recovery_with_restart() {
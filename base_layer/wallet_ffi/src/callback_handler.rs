@@ -328,6 +328,12 @@ where TBackend: TransactionBackend + 'static
                                 OutputManagerEvent::TxoValidationCommunicationFailure(request_key) => {
                                     self.output_validation_complete_event(request_key,  3);
                                 },
+                                // The validation has not completed yet, its outcome is reported once the retry finishes
+                                OutputManagerEvent::TxoValidationRetrying { .. } => {},
+                                OutputManagerEvent::AutoConsolidationTransactionCreated { .. } |
+                                OutputManagerEvent::CoinbaseSplitTransactionCreated { .. } => {
+                                    self.trigger_balance_refresh().await;
+                                },
                             }
                         },
                         Err(_e) => error!(target: LOG_TARGET, "Error reading from Output Manager Service event broadcast channel"),
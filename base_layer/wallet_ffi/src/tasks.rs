@@ -90,6 +90,7 @@ pub async fn recovery_event_monitoring(
             Ok(UtxoScannerEvent::Progress {
                 current_height: current,
                 tip_height: total,
+                ..
             }) => {
                 unsafe {
                     (recovery_progress_callback)(RecoveryEvent::Progress as u8, current, total);
@@ -64,6 +64,11 @@ impl SqliteConnectionPool {
         }
     }
 
+    /// Returns the path of the sqlite database file
+    pub fn db_path(&self) -> &str {
+        &self.db_path
+    }
+
     /// Create an sqlite connection pool managed by the pool connection manager
     pub fn create_pool(&mut self) -> Result<(), SqliteStorageError> {
         if self.pool.is_none() {
@@ -42,6 +42,8 @@ use crate::{
 
 const LOG_TARGET: &str = "lmdb";
 const BYTES_PER_MB: usize = 1024 * 1024;
+/// The default cap on a single resize increment when the increment grows geometrically
+const DEFAULT_MAX_GROW_SIZE_BYTES: usize = 1024 * BYTES_PER_MB;
 
 /// An atomic pointer to an LMDB database instance
 pub type DatabaseRef = Arc<Database<'static>>;
@@ -52,6 +54,39 @@ pub struct LMDBConfig {
     init_size_bytes: usize,
     grow_size_bytes: usize,
     resize_threshold_bytes: usize,
+    /// Log the duration of each operation in a database transaction. This is intended for debugging performance
+    /// issues and should be left disabled otherwise.
+    #[serde(default)]
+    log_db_txn_timing: bool,
+    /// Compress blocks stored in the orphan database. This reduces the storage used during orphan storms at the cost
+    /// of compressing and decompressing orphans when they are stored and fetched.
+    #[serde(default)]
+    compress_orphans: bool,
+    /// Keep the deleted bitmap in memory between database operations instead of deserializing it from the database
+    /// each time it is needed. This speeds up bulk block processing at the cost of holding the bitmap in memory.
+    #[serde(default)]
+    cache_deleted_bitmap: bool,
+    /// Log a warning whenever a secondary index of the database holds more than this many entries. Indexes such as
+    /// the unique id and contract indexes grow with side-chain activity, so this helps to spot unexpected growth.
+    #[serde(default)]
+    index_size_warning_threshold: Option<usize>,
+    /// The number of threads used to calculate the output and witness hashes of a block body before they are added to
+    /// the MMRs in order. A value of 0 or 1 hashes the outputs serially.
+    #[serde(default)]
+    body_hashing_parallelism: usize,
+    /// Each resize grows the environment by this multiple of the previous increment, starting at `grow_size_bytes`,
+    /// so that fewer resizes are needed while the database grows quickly. A value of 0 or 1 grows the environment by
+    /// `grow_size_bytes` every time.
+    #[serde(default)]
+    grow_size_multiplier: usize,
+    /// The largest increment a single resize may grow the environment by when `grow_size_multiplier` is used. An
+    /// increment never falls below `grow_size_bytes`.
+    #[serde(default = "default_max_grow_size_bytes")]
+    max_grow_size_bytes: usize,
+}
+
+fn default_max_grow_size_bytes() -> usize {
+    DEFAULT_MAX_GROW_SIZE_BYTES
 }
 
 impl LMDBConfig {
@@ -61,6 +96,13 @@ impl LMDBConfig {
             init_size_bytes,
             grow_size_bytes,
             resize_threshold_bytes,
+            log_db_txn_timing: false,
+            compress_orphans: false,
+            cache_deleted_bitmap: false,
+            index_size_warning_threshold: None,
+            body_hashing_parallelism: 0,
+            grow_size_multiplier: 0,
+            max_grow_size_bytes: DEFAULT_MAX_GROW_SIZE_BYTES,
         }
     }
 
@@ -70,9 +112,54 @@ impl LMDBConfig {
             init_size_bytes: init_size_mb * BYTES_PER_MB,
             grow_size_bytes: grow_size_mb * BYTES_PER_MB,
             resize_threshold_bytes: resize_threshold_mb * BYTES_PER_MB,
+            log_db_txn_timing: false,
+            compress_orphans: false,
+            cache_deleted_bitmap: false,
+            index_size_warning_threshold: None,
+            body_hashing_parallelism: 0,
+            grow_size_multiplier: 0,
+            max_grow_size_bytes: DEFAULT_MAX_GROW_SIZE_BYTES,
         }
     }
 
+    /// Enable or disable logging of per-operation timings for database transactions.
+    pub fn with_log_db_txn_timing(mut self, enabled: bool) -> Self {
+        self.log_db_txn_timing = enabled;
+        self
+    }
+
+    /// Enable or disable compression of blocks stored in the orphan database.
+    pub fn with_compress_orphans(mut self, enabled: bool) -> Self {
+        self.compress_orphans = enabled;
+        self
+    }
+
+    /// Enable or disable caching of the deleted bitmap in memory.
+    pub fn with_cache_deleted_bitmap(mut self, enabled: bool) -> Self {
+        self.cache_deleted_bitmap = enabled;
+        self
+    }
+
+    /// Set the number of entries above which a warning is logged for a secondary index, or `None` to disable the
+    /// warning.
+    pub fn with_index_size_warning_threshold(mut self, threshold: Option<usize>) -> Self {
+        self.index_size_warning_threshold = threshold;
+        self
+    }
+
+    /// Set the number of threads used to hash the outputs of a block body when it is inserted.
+    pub fn with_body_hashing_parallelism(mut self, parallelism: usize) -> Self {
+        self.body_hashing_parallelism = parallelism;
+        self
+    }
+
+    /// Set the multiple by which each resize increment grows over the previous one, and the largest increment allowed.
+    pub fn with_geometric_growth(mut self, multiplier: usize, max_grow_size_bytes: usize) -> Self {
+        self.grow_size_multiplier = multiplier;
+        self.max_grow_size_bytes = max_grow_size_bytes;
+        self
+    }
+
     /// Get the initial size of the LMDB environment in bytes.
     pub fn init_size_bytes(&self) -> usize {
         self.init_size_bytes
@@ -84,10 +171,59 @@ impl LMDBConfig {
         self.grow_size_bytes
     }
 
+    /// Returns the multiple by which each resize increment grows over the previous one.
+    pub fn grow_size_multiplier(&self) -> usize {
+        self.grow_size_multiplier
+    }
+
+    /// Returns the largest increment a single resize may grow the environment by.
+    pub fn max_grow_size_bytes(&self) -> usize {
+        self.max_grow_size_bytes
+    }
+
+    /// Returns the number of bytes the next resize of an environment with the given mapsize grows it by. With a
+    /// multiplier `r`, an environment that has grown by `g, g*r, .., g*r^(k-1)` from its initial size has grown by
+    /// `(g*r^k - g) / (r - 1)`, so the next increment `g*r^k` follows from the mapsize alone.
+    pub fn next_grow_size_bytes(&self, current_mapsize: usize) -> usize {
+        if self.grow_size_multiplier <= 1 {
+            return self.grow_size_bytes;
+        }
+        let grown = current_mapsize.saturating_sub(self.init_size_bytes);
+        let increment = self
+            .grow_size_bytes
+            .saturating_add(grown.saturating_mul(self.grow_size_multiplier - 1));
+        increment.min(self.max_grow_size_bytes).max(self.grow_size_bytes)
+    }
+
     /// Get the resize threshold in bytes. The LMDB environment will be resized when this much free space is left.
     pub fn resize_threshold_bytes(&self) -> usize {
         self.resize_threshold_bytes
     }
+
+    /// Returns true if the duration of each operation in a database transaction should be logged.
+    pub fn log_db_txn_timing(&self) -> bool {
+        self.log_db_txn_timing
+    }
+
+    /// Returns true if blocks stored in the orphan database should be compressed.
+    pub fn compress_orphans(&self) -> bool {
+        self.compress_orphans
+    }
+
+    /// Returns true if the deleted bitmap should be cached in memory.
+    pub fn cache_deleted_bitmap(&self) -> bool {
+        self.cache_deleted_bitmap
+    }
+
+    /// Returns the number of entries above which a warning is logged for a secondary index, if any.
+    pub fn index_size_warning_threshold(&self) -> Option<usize> {
+        self.index_size_warning_threshold
+    }
+
+    /// Returns the number of threads used to hash the outputs of a block body when it is inserted.
+    pub fn body_hashing_parallelism(&self) -> usize {
+        self.body_hashing_parallelism
+    }
 }
 
 impl Default for LMDBConfig {
@@ -432,6 +568,7 @@ impl LMDBStore {
         );
 
         if size_left_bytes <= config.resize_threshold_bytes {
+            let grow_size_bytes = config.next_grow_size_bytes(env_info.mapsize);
             Self::resize(env, config)?;
             debug!(
                 target: LOG_TARGET,
@@ -439,13 +576,13 @@ impl LMDBStore {
                 env.path()?.to_str()?,
                 size_used_bytes / BYTES_PER_MB,
                 size_left_bytes / BYTES_PER_MB,
-                config.grow_size_bytes / BYTES_PER_MB,
+                grow_size_bytes / BYTES_PER_MB,
             );
         }
         Ok(())
     }
 
-    /// Grows the LMDB environment by the configured amount
+    /// Grows the LMDB environment by the configured amount, see [LMDBConfig::next_grow_size_bytes]
     ///
     /// # Safety
     /// This may only be called if no write transactions are active in the current process. Note that the library does
@@ -455,7 +592,8 @@ impl LMDBStore {
     pub unsafe fn resize(env: &Environment, config: &LMDBConfig) -> Result<(), LMDBError> {
         let env_info = env.info()?;
         let current_mapsize = env_info.mapsize;
-        env.set_mapsize(current_mapsize + config.grow_size_bytes)?;
+        let grow_size_bytes = config.next_grow_size_bytes(current_mapsize);
+        env.set_mapsize(current_mapsize + grow_size_bytes)?;
         let env_info = env.info()?;
         let new_mapsize = env_info.mapsize;
         debug!(
@@ -464,7 +602,7 @@ impl LMDBStore {
             env.path()?.to_str()?,
             current_mapsize / BYTES_PER_MB,
             new_mapsize / BYTES_PER_MB,
-            config.grow_size_bytes / BYTES_PER_MB,
+            grow_size_bytes / BYTES_PER_MB,
         );
 
         Ok(())
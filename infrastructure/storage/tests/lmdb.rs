@@ -364,3 +364,33 @@ fn test_lmdb_resize_before_full() {
     }
     clean_up(db_env_name); // In Windows file handles must be released before files can be deleted
 }
+
+#[test]
+fn test_lmdb_geometric_resize() {
+    let db_env_name = "resize_geometric";
+    {
+        let path = get_path(db_env_name);
+        std::fs::create_dir_all(&path).unwrap_or_default();
+        const MB: usize = 1024 * 1024;
+        let config = LMDBConfig::new(MB, MB, 100 * 1024).with_geometric_growth(2, 8 * MB);
+        let store = LMDBBuilder::new()
+            .set_path(&path)
+            .set_env_config(config.clone())
+            .set_max_number_of_databases(1)
+            .add_database("test", db::CREATE)
+            .build()
+            .unwrap();
+
+        let mut increments = vec![];
+        for _ in 0..6 {
+            let mapsize_before = store.env().info().unwrap().mapsize;
+            // SAFETY: no transactions are open on this environment
+            unsafe {
+                LMDBStore::resize(&store.env(), &config).unwrap();
+            }
+            increments.push((store.env().info().unwrap().mapsize - mapsize_before) / MB);
+        }
+        assert_eq!(increments, vec![1, 2, 4, 8, 8, 8]);
+    }
+    clean_up(db_env_name); // In Windows file handles must be released before files can be deleted
+}